@@ -1,3 +1,5 @@
 fn main() {
+    // Recompilar cuando cambien las migraciones embebidas con sqlx::migrate!
+    println!("cargo:rerun-if-changed=migrations");
     tauri_build::build()
 }
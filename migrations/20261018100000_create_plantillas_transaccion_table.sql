@@ -0,0 +1,24 @@
+-- Plantillas de transacción para gastos e ingresos que se repiten en cada retiro
+CREATE TABLE plantillas_transaccion (
+    id TEXT PRIMARY KEY NOT NULL,
+    nombre TEXT NOT NULL UNIQUE,
+    categoria_id TEXT NOT NULL,
+    tipo TEXT NOT NULL CHECK (tipo IN ('Ingreso', 'Gasto')),
+    monto REAL NOT NULL CHECK (monto > 0),
+    descripcion TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+    -- Claves foráneas
+    FOREIGN KEY (categoria_id) REFERENCES categorias(id) ON DELETE RESTRICT
+);
+
+CREATE INDEX idx_plantillas_transaccion_categoria_id ON plantillas_transaccion(categoria_id);
+
+-- Trigger para actualizar updated_at automáticamente
+CREATE TRIGGER update_plantillas_transaccion_updated_at
+    AFTER UPDATE ON plantillas_transaccion
+    FOR EACH ROW
+BEGIN
+    UPDATE plantillas_transaccion SET updated_at = datetime('now') WHERE id = NEW.id;
+END;
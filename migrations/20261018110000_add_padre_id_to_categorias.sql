@@ -0,0 +1,6 @@
+-- Subcategorías: referencia opcional a la categoría padre
+-- Sin acción ON DELETE: no se puede borrar una categoría con subcategorías, pero un
+-- DELETE de todas las categorías a la vez (importación en modo reemplazar) sí funciona
+ALTER TABLE categorias ADD COLUMN padre_id TEXT REFERENCES categorias(id);
+
+CREATE INDEX idx_categorias_padre_id ON categorias(padre_id);
@@ -0,0 +1,15 @@
+-- Nombres de categoría únicos por tipo (sin distinguir mayúsculas) y colores #RRGGBB válidos
+
+-- Renombrar los duplicados existentes conservando la categoría más antigua
+UPDATE categorias
+SET nombre = nombre || ' (' || substr(id, 1, 8) || ')'
+WHERE rowid NOT IN (
+    SELECT MIN(rowid) FROM categorias GROUP BY nombre COLLATE NOCASE, tipo
+);
+
+-- Sustituir los colores que no son hexadecimales por un gris neutro
+UPDATE categorias
+SET color = '#808080'
+WHERE color NOT GLOB '#[0-9A-Fa-f][0-9A-Fa-f][0-9A-Fa-f][0-9A-Fa-f][0-9A-Fa-f][0-9A-Fa-f]';
+
+CREATE UNIQUE INDEX idx_categorias_nombre_tipo ON categorias(nombre COLLATE NOCASE, tipo);
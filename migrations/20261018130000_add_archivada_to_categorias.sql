@@ -0,0 +1,2 @@
+-- Categorías archivadas: se conservan para el histórico pero no se ofrecen al crear transacciones
+ALTER TABLE categorias ADD COLUMN archivada BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,14 @@
+-- Borrado lógico: los elementos eliminados pasan a la papelera hasta que se vacía
+-- Al eliminar un retiro sus transacciones reciben el mismo deleted_at, para restaurarlas juntas
+ALTER TABLE categorias ADD COLUMN deleted_at TEXT;
+ALTER TABLE retiros ADD COLUMN deleted_at TEXT;
+ALTER TABLE transacciones ADD COLUMN deleted_at TEXT;
+
+CREATE INDEX idx_categorias_deleted_at ON categorias(deleted_at);
+CREATE INDEX idx_retiros_deleted_at ON retiros(deleted_at);
+CREATE INDEX idx_transacciones_deleted_at ON transacciones(deleted_at);
+
+-- Una categoría en la papelera no impide crear otra con el mismo nombre
+DROP INDEX idx_categorias_nombre_tipo;
+CREATE UNIQUE INDEX idx_categorias_nombre_tipo ON categorias(nombre COLLATE NOCASE, tipo)
+    WHERE deleted_at IS NULL;
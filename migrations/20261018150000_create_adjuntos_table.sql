@@ -0,0 +1,20 @@
+-- Comprobantes (imagen o PDF) de las transacciones
+-- El archivo se guarda en disco junto a la base de datos, con el SHA-256 de su contenido como nombre
+CREATE TABLE adjuntos (
+    id TEXT PRIMARY KEY NOT NULL,
+    transaccion_id TEXT NOT NULL,
+    nombre TEXT NOT NULL,
+    tipo_mime TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    tamano INTEGER NOT NULL CHECK (tamano > 0),
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+    -- Claves foráneas
+    FOREIGN KEY (transaccion_id) REFERENCES transacciones(id) ON DELETE CASCADE,
+
+    -- El mismo archivo no se adjunta dos veces a una transacción
+    UNIQUE (transaccion_id, hash)
+);
+
+CREATE INDEX idx_adjuntos_transaccion_id ON adjuntos(transaccion_id);
+CREATE INDEX idx_adjuntos_hash ON adjuntos(hash);
@@ -0,0 +1,20 @@
+-- Etiquetas libres de las transacciones ("equipo de cocina", "reembolsable"...)
+-- El nombre no distingue mayúsculas: "Cocina" y "cocina" son la misma etiqueta
+CREATE TABLE etiquetas (
+    id TEXT PRIMARY KEY NOT NULL,
+    nombre TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE transaccion_etiquetas (
+    transaccion_id TEXT NOT NULL,
+    etiqueta_id TEXT NOT NULL,
+
+    PRIMARY KEY (transaccion_id, etiqueta_id),
+
+    -- Claves foráneas
+    FOREIGN KEY (transaccion_id) REFERENCES transacciones(id) ON DELETE CASCADE,
+    FOREIGN KEY (etiqueta_id) REFERENCES etiquetas(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_transaccion_etiquetas_etiqueta_id ON transaccion_etiquetas(etiqueta_id);
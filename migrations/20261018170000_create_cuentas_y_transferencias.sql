@@ -0,0 +1,40 @@
+-- Cuentas donde está el dinero (caja, banco, tarjeta...) y método de pago de cada transacción
+CREATE TABLE cuentas (
+    id TEXT PRIMARY KEY NOT NULL,
+    nombre TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    tipo TEXT NOT NULL CHECK (tipo IN ('Caja', 'Banco', 'Tarjeta', 'Otra')),
+    -- Dinero que había en la cuenta antes del primer movimiento registrado
+    saldo_inicial REAL NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+-- Las transacciones anteriores quedan sin cuenta ni método de pago
+ALTER TABLE transacciones ADD COLUMN cuenta_id TEXT REFERENCES cuentas(id) ON DELETE RESTRICT;
+ALTER TABLE transacciones ADD COLUMN metodo_pago TEXT
+    CHECK (metodo_pago IN ('Efectivo', 'Transferencia', 'Tarjeta', 'Bizum'));
+
+CREATE INDEX idx_transacciones_cuenta_id ON transacciones(cuenta_id);
+
+-- Movimientos de dinero entre cuentas dentro de un retiro (p. ej. ingresar la caja en el banco)
+-- No son ingresos ni gastos: solo cambian el saldo de cada cuenta
+CREATE TABLE transferencias (
+    id TEXT PRIMARY KEY NOT NULL,
+    retiro_id TEXT NOT NULL,
+    cuenta_origen_id TEXT NOT NULL,
+    cuenta_destino_id TEXT NOT NULL,
+    monto REAL NOT NULL CHECK (monto > 0),
+    descripcion TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+    -- Claves foráneas
+    FOREIGN KEY (retiro_id) REFERENCES retiros(id) ON DELETE CASCADE,
+    FOREIGN KEY (cuenta_origen_id) REFERENCES cuentas(id) ON DELETE RESTRICT,
+    FOREIGN KEY (cuenta_destino_id) REFERENCES cuentas(id) ON DELETE RESTRICT,
+
+    CHECK (cuenta_origen_id <> cuenta_destino_id)
+);
+
+CREATE INDEX idx_transferencias_retiro_id ON transferencias(retiro_id);
+CREATE INDEX idx_transferencias_cuenta_origen_id ON transferencias(cuenta_origen_id);
+CREATE INDEX idx_transferencias_cuenta_destino_id ON transferencias(cuenta_destino_id);
@@ -0,0 +1,24 @@
+-- Arqueos de caja: recuentos del dinero de una cuenta comparados con su saldo calculado
+CREATE TABLE arqueos (
+    id TEXT PRIMARY KEY NOT NULL,
+    retiro_id TEXT NOT NULL,
+    cuenta_id TEXT NOT NULL,
+    -- Saldo total de la cuenta según los movimientos registrados al hacer el arqueo
+    saldo_calculado REAL NOT NULL,
+    -- Dinero contado físicamente
+    contado REAL NOT NULL CHECK (contado >= 0),
+    -- Recuento por billetes y monedas en JSON ([{"valor": 20.0, "cantidad": 3}, ...]); NULL si solo se dio el total
+    denominaciones TEXT,
+    notas TEXT,
+    -- Transacción que corrige el descuadre, si se pidió
+    transaccion_ajuste_id TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+    -- Claves foráneas
+    FOREIGN KEY (retiro_id) REFERENCES retiros(id) ON DELETE CASCADE,
+    FOREIGN KEY (cuenta_id) REFERENCES cuentas(id) ON DELETE RESTRICT,
+    FOREIGN KEY (transaccion_ajuste_id) REFERENCES transacciones(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_arqueos_retiro_id ON arqueos(retiro_id);
+CREATE INDEX idx_arqueos_cuenta_id ON arqueos(cuenta_id);
@@ -0,0 +1,26 @@
+-- Líneas de extractos bancarios importadas (Norma 43, CAMT.053, OFX)
+-- Cada línea queda enlazada a la transacción que se creó o con la que coincidía,
+-- y su huella evita importar dos veces el mismo movimiento
+CREATE TABLE lineas_extracto (
+    id TEXT PRIMARY KEY NOT NULL,
+    retiro_id TEXT NOT NULL,
+    cuenta_id TEXT,
+    formato TEXT NOT NULL CHECK (formato IN ('Norma43', 'Camt053', 'Ofx')),
+    -- Fecha del movimiento en el banco (YYYY-MM-DD)
+    fecha TEXT NOT NULL,
+    -- Positivo para abonos, negativo para cargos
+    monto REAL NOT NULL,
+    descripcion TEXT NOT NULL,
+    referencia TEXT,
+    huella TEXT NOT NULL UNIQUE,
+    transaccion_id TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+    -- Claves foráneas
+    FOREIGN KEY (retiro_id) REFERENCES retiros(id) ON DELETE CASCADE,
+    FOREIGN KEY (cuenta_id) REFERENCES cuentas(id) ON DELETE SET NULL,
+    FOREIGN KEY (transaccion_id) REFERENCES transacciones(id) ON DELETE SET NULL
+);
+
+CREATE INDEX idx_lineas_extracto_retiro_id ON lineas_extracto(retiro_id);
+CREATE INDEX idx_lineas_extracto_transaccion_id ON lineas_extracto(transaccion_id);
@@ -0,0 +1,10 @@
+-- Estado de conciliación bancaria de cada transacción:
+-- Pendiente (sin comprobar), Punteada (vista en el extracto) o Conciliada (cerrada)
+ALTER TABLE transacciones ADD COLUMN conciliacion TEXT NOT NULL DEFAULT 'Pendiente'
+    CHECK (conciliacion IN ('Pendiente', 'Punteada', 'Conciliada'));
+
+-- Las transacciones ya enlazadas con una línea de extracto se dan por punteadas
+UPDATE transacciones SET conciliacion = 'Punteada'
+WHERE id IN (SELECT transaccion_id FROM lineas_extracto WHERE transaccion_id IS NOT NULL);
+
+CREATE INDEX idx_transacciones_conciliacion ON transacciones(retiro_id, conciliacion);
@@ -0,0 +1,31 @@
+-- Reglas para categorizar automáticamente las transacciones según su descripción y monto
+CREATE TABLE reglas_categoria (
+    id TEXT PRIMARY KEY NOT NULL,
+    nombre TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    -- Expresión regular que se busca en la descripción (sin distinguir mayúsculas)
+    patron TEXT NOT NULL,
+    -- Rango del monto en valor absoluto, ambos extremos incluidos
+    monto_min REAL CHECK (monto_min IS NULL OR monto_min >= 0),
+    monto_max REAL CHECK (monto_max IS NULL OR monto_max >= 0),
+    tipo TEXT NOT NULL CHECK (tipo IN ('Ingreso', 'Gasto')),
+    categoria_id TEXT NOT NULL,
+    -- Etiquetas que se añaden a la transacción, separadas por comas
+    etiquetas TEXT NOT NULL DEFAULT '',
+    -- Si varias reglas coinciden se aplica la de mayor prioridad
+    prioridad INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+
+    -- Claves foráneas
+    FOREIGN KEY (categoria_id) REFERENCES categorias(id) ON DELETE RESTRICT
+);
+
+CREATE INDEX idx_reglas_categoria_categoria_id ON reglas_categoria(categoria_id);
+
+-- Trigger para actualizar updated_at automáticamente
+CREATE TRIGGER update_reglas_categoria_updated_at
+    AFTER UPDATE ON reglas_categoria
+    FOR EACH ROW
+BEGIN
+    UPDATE reglas_categoria SET updated_at = datetime('now') WHERE id = NEW.id;
+END;
@@ -0,0 +1,13 @@
+-- Una transacción corresponde como mucho a una línea de extracto.
+-- Si ya hay varias enlazadas con la misma, se conserva el enlace de la primera importada
+UPDATE lineas_extracto SET transaccion_id = NULL
+WHERE transaccion_id IS NOT NULL
+    AND rowid NOT IN (
+        SELECT MIN(rowid) FROM lineas_extracto
+        WHERE transaccion_id IS NOT NULL
+        GROUP BY transaccion_id
+    );
+
+DROP INDEX idx_lineas_extracto_transaccion_id;
+CREATE UNIQUE INDEX idx_lineas_extracto_transaccion_id
+    ON lineas_extracto(transaccion_id) WHERE transaccion_id IS NOT NULL;
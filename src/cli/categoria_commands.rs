@@ -1,11 +1,15 @@
 use clap::{Args, Subcommand};
+use clap_complete::ArgValueCandidates;
 use colored::*;
-use uuid::Uuid;
 use validator::Validate;
 
+use super::completions;
+use super::output::{self, OutputFormat};
+use super::prompts;
+use super::resolver;
 use crate::database::Database;
 use crate::models::{CreateCategoria, TipoCategoria};
-use crate::repositories::CategoriaRepository;
+use crate::repositories::{CategoriaRepository, RetiroRepository, TransaccionRepository};
 use crate::{AppError, Result};
 
 #[derive(Subcommand)]
@@ -20,21 +24,29 @@ pub enum CategoriaCommands {
     Actualizar(ActualizarArgs),
     /// Eliminar una categoría
     Eliminar(EliminarArgs),
+    /// Archivar una categoría y sus subcategorías (dejan de ofrecerse, pero se conserva el histórico)
+    Archivar(ArchivarArgs),
+    /// Volver a activar una categoría archivada
+    Desarchivar(ArchivarArgs),
 }
 
 #[derive(Args)]
 pub struct CrearArgs {
-    /// Nombre de la categoría
+    /// Nombre de la categoría (se pregunta si no se indica)
     #[arg(short, long)]
-    pub nombre: String,
+    pub nombre: Option<String>,
 
     /// Tipo de categoría (ingreso/gasto)
     #[arg(short, long, value_enum)]
-    pub tipo: CliTipoCategoria,
+    pub tipo: Option<CliTipoCategoria>,
 
-    /// Color en formato hexadecimal (ej: #FF5733)
+    /// Color en formato hexadecimal (ej: #FF5733, por defecto uno de la paleta sin usar)
     #[arg(short, long)]
-    pub color: String,
+    pub color: Option<String>,
+
+    /// Categoría padre, para crear una subcategoría (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_categorias))]
+    pub padre: Option<String>,
 }
 
 #[derive(Args)]
@@ -42,18 +54,32 @@ pub struct ListarArgs {
     /// Filtrar por tipo de categoría
     #[arg(short, long, value_enum)]
     pub tipo: Option<CliTipoCategoria>,
+
+    /// Mostrar las subcategorías en árbol con los totales acumulados
+    #[arg(short, long)]
+    pub arbol: bool,
+
+    /// Incluir las categorías archivadas
+    #[arg(long)]
+    pub archivadas: bool,
+
+    /// Calcular los totales del árbol solo para un retiro (ID, prefijo del ID o nombre)
+    #[arg(long, requires = "arbol", add = ArgValueCandidates::new(completions::completar_retiros))]
+    pub retiro_id: Option<String>,
 }
 
 #[derive(Args)]
 pub struct MostrarArgs {
-    /// ID de la categoría
+    /// Categoría (ID, prefijo del ID o nombre)
+    #[arg(add = ArgValueCandidates::new(completions::completar_categorias))]
     pub id: String,
 }
 
 #[derive(Args)]
 pub struct ActualizarArgs {
-    /// ID de la categoría a actualizar
-    pub id: String,
+    /// Categoría a actualizar: ID, prefijo del ID o nombre (se elige de una lista si no se indica)
+    #[arg(add = ArgValueCandidates::new(completions::completar_categorias))]
+    pub id: Option<String>,
 
     /// Nuevo nombre de la categoría
     #[arg(short, long)]
@@ -66,11 +92,24 @@ pub struct ActualizarArgs {
     /// Nuevo color en formato hexadecimal
     #[arg(short, long)]
     pub color: Option<String>,
+
+    /// Nueva categoría padre (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_categorias))]
+    pub padre: Option<String>,
+
+    /// Convertir en categoría de primer nivel
+    #[arg(long, conflicts_with = "padre")]
+    pub sin_padre: bool,
+
+    /// Preguntar todos los campos mostrando los valores actuales
+    #[arg(short, long)]
+    pub interactive: bool,
 }
 
 #[derive(Args)]
 pub struct EliminarArgs {
-    /// ID de la categoría a eliminar
+    /// Categoría a eliminar (ID, prefijo del ID o nombre)
+    #[arg(add = ArgValueCandidates::new(completions::completar_categorias))]
     pub id: String,
 
     /// Confirmar eliminación sin preguntar
@@ -78,6 +117,13 @@ pub struct EliminarArgs {
     pub force: bool,
 }
 
+#[derive(Args)]
+pub struct ArchivarArgs {
+    /// Categoría (ID, prefijo del ID o nombre)
+    #[arg(add = ArgValueCandidates::new(completions::completar_categorias))]
+    pub id: String,
+}
+
 #[derive(clap::ValueEnum, Clone)]
 pub enum CliTipoCategoria {
     Ingreso,
@@ -93,70 +139,135 @@ impl From<CliTipoCategoria> for TipoCategoria {
     }
 }
 
-pub async fn handle_categoria_command(command: CategoriaCommands) -> Result<()> {
+pub async fn handle_categoria_command(
+    command: CategoriaCommands,
+    database_url: &str,
+    output: OutputFormat,
+) -> Result<()> {
     // Conectar a la base de datos
-    let database_url =
-        std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite:./retiros.db".to_string());
-
-    let db = Database::new(&database_url).await?;
+    let db = Database::new(database_url).await?;
     let repo = CategoriaRepository::new(db.pool().clone());
 
     match command {
         CategoriaCommands::Crear(args) => crear_categoria(repo, args).await,
-        CategoriaCommands::Listar(args) => listar_categorias(repo, args).await,
-        CategoriaCommands::Mostrar(args) => mostrar_categoria(repo, args).await,
+        CategoriaCommands::Listar(args) => listar_categorias(&db, repo, args, output).await,
+        CategoriaCommands::Mostrar(args) => mostrar_categoria(repo, args, output).await,
         CategoriaCommands::Actualizar(args) => actualizar_categoria(repo, args).await,
         CategoriaCommands::Eliminar(args) => eliminar_categoria(repo, args).await,
+        CategoriaCommands::Archivar(args) => archivar_categoria(repo, args, true).await,
+        CategoriaCommands::Desarchivar(args) => archivar_categoria(repo, args, false).await,
     }
 }
 
 async fn crear_categoria(repo: CategoriaRepository, args: CrearArgs) -> Result<()> {
+    // Los campos que falten se preguntan, validándolos con las reglas del modelo
+    let borrador = CreateCategoria {
+        nombre: String::new(),
+        tipo: TipoCategoria::Gasto,
+        color: String::new(),
+        padre_id: None,
+    };
+    let nombre = prompts::requerido(args.nombre, "--nombre", || {
+        prompts::pedir_campo("Nombre", None, &borrador, "nombre", |c, v| c.nombre = v)
+    })?;
+    // Una subcategoría toma por defecto el tipo de su padre
+    let padre = match &args.padre {
+        Some(referencia) => {
+            let id = resolver::resolver_categoria(&repo, referencia).await?;
+            Some(
+                repo.get_by_id(id)
+                    .await?
+                    .ok_or_else(|| AppError::NotFound("Categoría padre".to_string()))?,
+            )
+        }
+        None => None,
+    };
+    let tipo = match (args.tipo, &padre) {
+        (Some(tipo), _) => tipo.into(),
+        (None, Some(padre)) => padre.tipo.clone(),
+        (None, None) => prompts::requerido(None, "--tipo", || prompts::seleccionar_tipo(None))?,
+    };
+    // Sin --color se propone uno de la paleta que no usen otras categorías
+    let color = match args.color {
+        Some(color) => color,
+        None => {
+            let sugerido = repo.sugerir_color().await?;
+            if prompts::is_interactive() {
+                prompts::pedir_campo("Color (#RRGGBB)", Some(sugerido), &borrador, "color", |c, v| {
+                    c.color = v
+                })?
+            } else {
+                sugerido
+            }
+        }
+    };
+
     println!("{}", "🆕 Creando nueva categoría...".cyan().bold());
 
     let create_data = CreateCategoria {
-        nombre: args.nombre.clone(),
-        tipo: args.tipo.into(),
-        color: args.color.clone(),
+        nombre,
+        tipo,
+        color,
+        padre_id: padre.as_ref().map(|p| p.id),
     };
 
     // Validar datos antes de crear
-    if let Err(e) = create_data.validate() {
-        println!("{} {}", "❌ Error de validación:".red().bold(), e);
-        return Err(AppError::Validation(e.to_string()));
-    }
+    create_data
+        .validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
 
-    match repo.create(create_data).await {
-        Ok(categoria) => {
-            println!("{}", "✅ Categoría creada exitosamente!".green().bold());
-            println!();
-            println!("📋 {}", "Detalles:".bold());
-            println!("   ID: {}", categoria.id.to_string().bright_blue());
-            println!("   Nombre: {}", categoria.nombre.bright_white());
-            println!("   Tipo: {}", format!("{}", categoria.tipo).bright_yellow());
-            println!(
-                "   Color: {} {}",
-                categoria.color.bright_magenta(),
-                "●".color(categoria.color.as_str())
-            );
-        }
-        Err(e) => {
-            println!("{} {}", "❌ Error creando categoría:".red().bold(), e);
-            return Err(e);
-        }
+    let categoria = repo.create(create_data).await?;
+    println!("{}", "✅ Categoría creada exitosamente!".green().bold());
+    println!();
+    println!("📋 {}", "Detalles:".bold());
+    println!("   ID: {}", categoria.id.to_string().bright_blue());
+    println!("   Nombre: {}", categoria.nombre.bright_white());
+    println!("   Tipo: {}", format!("{}", categoria.tipo).bright_yellow());
+    println!(
+        "   Color: {} {}",
+        categoria.color.bright_magenta(),
+        "●".color(categoria.color.as_str())
+    );
+    if let Some(padre) = &padre {
+        println!("   Categoría padre: {}", padre.nombre.bright_white());
     }
 
     Ok(())
 }
 
-async fn listar_categorias(repo: CategoriaRepository, args: ListarArgs) -> Result<()> {
-    println!("{}", "📋 Listando categorías...".cyan().bold());
-    println!();
+async fn listar_categorias(
+    db: &Database,
+    repo: CategoriaRepository,
+    args: ListarArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    if args.arbol {
+        return listar_arbol(db, args, output).await;
+    }
 
-    let categorias = match args.tipo {
-        Some(tipo) => repo.get_by_tipo(tipo.into()).await?,
-        None => repo.get_all().await?,
+    let categorias = match (args.tipo, args.archivadas) {
+        (Some(tipo), false) => repo.get_by_tipo(tipo.into()).await?,
+        (None, false) => repo.get_all().await?,
+        (tipo, true) => {
+            let tipo: Option<TipoCategoria> = tipo.map(Into::into);
+            repo.get_all_incluyendo_archivadas()
+                .await?
+                .into_iter()
+                .filter(|c| match &tipo {
+                    Some(tipo) => c.tipo.to_string() == tipo.to_string(),
+                    None => true,
+                })
+                .collect()
+        }
     };
 
+    if output.is_structured() {
+        return output::print_list(output, &categorias);
+    }
+
+    println!("{}", "📋 Listando categorías...".cyan().bold());
+    println!();
+
     if categorias.is_empty() {
         println!("{}", "📭 No se encontraron categorías.".yellow());
         return Ok(());
@@ -180,12 +291,13 @@ async fn listar_categorias(repo: CategoriaRepository, args: ListarArgs) -> Resul
         };
 
         println!(
-            "{:<38} {:<20} {:<10} {} {}",
+            "{:<38} {:<20} {:<10} {} {} {}",
             categoria.id.to_string().bright_blue(),
             categoria.nombre.bright_white(),
             tipo_color,
             categoria.color.bright_magenta(),
-            "●".color(categoria.color.as_str())
+            "●".color(categoria.color.as_str()),
+            if categoria.archivada { "📦 archivada" } else { "" }.bright_black()
         );
     }
 
@@ -199,11 +311,22 @@ async fn listar_categorias(repo: CategoriaRepository, args: ListarArgs) -> Resul
     Ok(())
 }
 
-async fn mostrar_categoria(repo: CategoriaRepository, args: MostrarArgs) -> Result<()> {
-    println!("{}", "🔍 Buscando categoría...".cyan().bold());
+async fn mostrar_categoria(
+    repo: CategoriaRepository,
+    args: MostrarArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let id = resolver::resolver_categoria(&repo, &args.id).await?;
+
+    if output.is_structured() {
+        let categoria = repo
+            .get_by_id(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Categoría".to_string()))?;
+        return output::print_one(output, &categoria);
+    }
 
-    let id =
-        Uuid::parse_str(&args.id).map_err(|_| AppError::Validation("ID inválido".to_string()))?;
+    println!("{}", "🔍 Buscando categoría...".cyan().bold());
 
     match repo.get_by_id(id).await? {
         Some(categoria) => {
@@ -218,9 +341,25 @@ async fn mostrar_categoria(repo: CategoriaRepository, args: MostrarArgs) -> Resu
                 categoria.color.bright_magenta(),
                 "●".color(categoria.color.as_str())
             );
+
+            if categoria.archivada {
+                println!("   Estado: {}", "📦 Archivada".bright_black());
+            }
+
+            let categorias = repo.get_all_incluyendo_archivadas().await?;
+            if let Some(padre) = categorias.iter().find(|c| Some(c.id) == categoria.padre_id) {
+                println!("   Categoría padre: {}", padre.nombre.bright_white());
+            }
+            let subcategorias: Vec<&str> = categorias
+                .iter()
+                .filter(|c| c.padre_id == Some(categoria.id))
+                .map(|c| c.nombre.as_str())
+                .collect();
+            if !subcategorias.is_empty() {
+                println!("   Subcategorías: {}", subcategorias.join(", ").bright_white());
+            }
         }
         None => {
-            println!("{}", "❌ Categoría no encontrada.".red().bold());
             return Err(AppError::NotFound("Categoría".to_string()));
         }
     }
@@ -229,33 +368,69 @@ async fn mostrar_categoria(repo: CategoriaRepository, args: MostrarArgs) -> Resu
 }
 
 async fn actualizar_categoria(repo: CategoriaRepository, args: ActualizarArgs) -> Result<()> {
-    println!("{}", "✏️  Actualizando categoría...".cyan().bold());
+    let interactivo = prompts::modo_interactivo(args.interactive)?;
 
-    let id =
-        Uuid::parse_str(&args.id).map_err(|_| AppError::Validation("ID inválido".to_string()))?;
+    let id = match &args.id {
+        Some(referencia) => resolver::resolver_categoria(&repo, referencia).await?,
+        None if prompts::is_interactive() => prompts::seleccionar_categoria(&repo, None).await?,
+        None => return Err(prompts::falta_argumento("<ID>")),
+    };
 
     // Obtener categoría actual
     let categoria_actual = match repo.get_by_id(id).await? {
         Some(cat) => cat,
         None => {
-            println!("{}", "❌ Categoría no encontrada.".red().bold());
             return Err(AppError::NotFound("Categoría".to_string()));
         }
     };
 
+    let sin_cambios = args.nombre.is_none()
+        && args.tipo.is_none()
+        && args.color.is_none()
+        && args.padre.is_none()
+        && !args.sin_padre;
+
+    let padre_id = match &args.padre {
+        Some(referencia) => Some(resolver::resolver_categoria(&repo, referencia).await?),
+        None if args.sin_padre => None,
+        None => categoria_actual.padre_id,
+    };
+
     // Crear datos de actualización usando valores actuales como default
-    let update_data = CreateCategoria {
+    let mut update_data = CreateCategoria {
         nombre: args.nombre.unwrap_or(categoria_actual.nombre),
         tipo: args.tipo.map(|t| t.into()).unwrap_or(categoria_actual.tipo),
         color: args.color.unwrap_or(categoria_actual.color),
+        padre_id,
     };
 
-    // Validar datos
-    if let Err(e) = update_data.validate() {
-        println!("{} {}", "❌ Error de validación:".red().bold(), e);
-        return Err(AppError::Validation(e.to_string()));
+    // Sin cambios indicados en una terminal se preguntan todos los campos
+    if interactivo || (sin_cambios && prompts::is_interactive()) {
+        let borrador = update_data.clone();
+        update_data.nombre = prompts::pedir_campo(
+            "Nombre",
+            Some(borrador.nombre.clone()),
+            &borrador,
+            "nombre",
+            |c, v| c.nombre = v,
+        )?;
+        update_data.tipo = prompts::seleccionar_tipo(Some(&borrador.tipo))?;
+        update_data.color = prompts::pedir_campo(
+            "Color (#RRGGBB)",
+            Some(borrador.color.clone()),
+            &borrador,
+            "color",
+            |c, v| c.color = v,
+        )?;
     }
 
+    println!("{}", "✏️  Actualizando categoría...".cyan().bold());
+
+    // Validar datos
+    update_data
+        .validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
+
     match repo.update(id, update_data).await? {
         Some(categoria) => {
             println!(
@@ -274,12 +449,6 @@ async fn actualizar_categoria(repo: CategoriaRepository, args: ActualizarArgs) -
             );
         }
         None => {
-            println!(
-                "{}",
-                "❌ Error: Categoría no encontrada durante la actualización."
-                    .red()
-                    .bold()
-            );
             return Err(AppError::NotFound("Categoría".to_string()));
         }
     }
@@ -288,14 +457,12 @@ async fn actualizar_categoria(repo: CategoriaRepository, args: ActualizarArgs) -
 }
 
 async fn eliminar_categoria(repo: CategoriaRepository, args: EliminarArgs) -> Result<()> {
-    let id =
-        Uuid::parse_str(&args.id).map_err(|_| AppError::Validation("ID inválido".to_string()))?;
+    let id = resolver::resolver_categoria(&repo, &args.id).await?;
 
     // Verificar que la categoría existe
     let categoria = match repo.get_by_id(id).await? {
         Some(cat) => cat,
         None => {
-            println!("{}", "❌ Categoría no encontrada.".red().bold());
             return Err(AppError::NotFound("Categoría".to_string()));
         }
     };
@@ -321,16 +488,149 @@ async fn eliminar_categoria(repo: CategoriaRepository, args: EliminarArgs) -> Re
 
     match repo.delete(id).await? {
         true => {
-            println!("{}", "✅ Categoría eliminada exitosamente!".green().bold());
-        }
-        false => {
+            println!("{}", "✅ Categoría enviada a la papelera!".green().bold());
             println!(
-                "{}",
-                "❌ Error: No se pudo eliminar la categoría.".red().bold()
+                "   {}",
+                format!("Restaurable con: papelera restaurar {}", id).bright_black()
             );
+        }
+        false => {
             return Err(AppError::Internal("Error eliminando categoría".to_string()));
         }
     }
 
     Ok(())
 }
+
+async fn archivar_categoria(
+    repo: CategoriaRepository,
+    args: ArchivarArgs,
+    archivada: bool,
+) -> Result<()> {
+    let id = resolver::resolver_categoria(&repo, &args.id).await?;
+
+    if archivada {
+        println!("{}", "📦 Archivando categoría...".cyan().bold());
+    } else {
+        println!("{}", "📤 Desarchivando categoría...".cyan().bold());
+    }
+
+    match repo.set_archivada(id, archivada).await? {
+        Some(categoria) => {
+            let mensaje = if archivada {
+                "✅ Categoría archivada: ya no se ofrecerá al crear transacciones."
+            } else {
+                "✅ Categoría desarchivada."
+            };
+            println!("{}", mensaje.green().bold());
+            println!("   Nombre: {}", categoria.nombre.bright_white());
+            println!("   Tipo: {}", format!("{}", categoria.tipo).bright_yellow());
+        }
+        None => {
+            return Err(AppError::NotFound("Categoría".to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Listar las categorías como árbol, con el total propio y el acumulado con sus subcategorías
+async fn listar_arbol(db: &Database, args: ListarArgs, output: OutputFormat) -> Result<()> {
+    let retiro_id = match &args.retiro_id {
+        Some(referencia) => {
+            let retiro_repo = RetiroRepository::new(db.pool().clone());
+            Some(resolver::resolver_retiro(&retiro_repo, referencia).await?)
+        }
+        None => None,
+    };
+
+    let tipo: Option<TipoCategoria> = args.tipo.map(Into::into);
+    let totales: Vec<_> = TransaccionRepository::new(db.pool().clone())
+        .get_totales_por_categoria(retiro_id)
+        .await?
+        .into_iter()
+        .filter(|t| match &tipo {
+            Some(tipo) => t.tipo.to_string() == tipo.to_string(),
+            None => true,
+        })
+        // Los importes de las archivadas siguen sumando en el total de sus antecesores
+        .filter(|t| args.archivadas || !t.archivada)
+        .collect();
+
+    if output.is_structured() {
+        return output::print_list(output, &totales);
+    }
+
+    println!("{}", "🌳 Árbol de categorías...".cyan().bold());
+    println!();
+
+    if totales.is_empty() {
+        println!("{}", "📭 No se encontraron categorías.".yellow());
+        return Ok(());
+    }
+
+    println!(
+        "{:<40} {:<10} {:<8} {:>12} {:>12}",
+        "NOMBRE".bold(),
+        "TIPO".bold(),
+        "COLOR".bold(),
+        "PROPIO".bold(),
+        "TOTAL".bold()
+    );
+    println!("{}", "─".repeat(86).bright_black());
+
+    let niveles: Vec<usize> = totales.iter().map(|t| t.nivel).collect();
+    for (total, prefijo) in totales.iter().zip(prefijos_arbol(&niveles)) {
+        let tipo_color = match total.tipo {
+            TipoCategoria::Ingreso => total.tipo.to_string().green(),
+            TipoCategoria::Gasto => total.tipo.to_string().red(),
+        };
+        let nombre = format!("{}{}", prefijo, total.nombre);
+
+        println!(
+            "{:<40} {:<10} {} {}  {:>12} {:>12} {}",
+            nombre.bright_white(),
+            tipo_color,
+            total.color.bright_magenta(),
+            "●".color(total.color.as_str()),
+            format!("€{:.2}", total.total_propio).bright_black(),
+            format!("€{:.2}", total.total_acumulado).bright_green(),
+            if total.archivada { "📦" } else { "" }
+        );
+    }
+
+    println!();
+    println!(
+        "{} {}",
+        "📊 Total:".bold(),
+        totales.len().to_string().bright_green()
+    );
+
+    Ok(())
+}
+
+/// Líneas de árbol (├─, └─, │) para una lista de niveles en orden de árbol
+fn prefijos_arbol(niveles: &[usize]) -> Vec<String> {
+    // Indica si después de la posición `i` hay otro elemento en `nivel` antes de subir por encima
+    let continua = |i: usize, nivel: usize| {
+        niveles[i + 1..]
+            .iter()
+            .take_while(|n| **n >= nivel)
+            .any(|n| *n == nivel)
+    };
+
+    niveles
+        .iter()
+        .enumerate()
+        .map(|(i, &nivel)| {
+            let mut prefijo = String::new();
+            for antecesor in 1..nivel {
+                prefijo.push_str(if continua(i, antecesor) { "│  " } else { "   " });
+            }
+            if nivel > 0 {
+                prefijo.push_str(if continua(i, nivel) { "├─ " } else { "└─ " });
+            }
+            prefijo
+        })
+        .collect()
+}
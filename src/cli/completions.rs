@@ -0,0 +1,243 @@
+// Autocompletado de la shell
+// Los scripts se registran con `completions <shell>` y llaman de vuelta al binario
+// (COMPLETE=<shell>), que sugiere también retiros, categorías, plantillas y reglas de la base de datos
+
+use std::future::Future;
+use std::str::FromStr;
+
+use clap::{Args, CommandFactory};
+use clap_complete::env::{Bash, EnvCompleter, Fish, Zsh};
+use clap_complete::{CompleteEnv, CompletionCandidate};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+use super::Cli;
+use crate::config;
+use crate::repositories::{
+    CategoriaRepository, CuentaRepository, EtiquetaRepository, PapeleraRepository,
+    PlantillaRepository, ReglaRepository, RetiroRepository,
+};
+use crate::{AppError, Result};
+
+/// Variable de entorno con la que la shell pide las sugerencias
+const COMPLETE_VAR: &str = "COMPLETE";
+
+#[derive(Args)]
+pub struct CompletionsArgs {
+    /// Shell para la que generar el script
+    #[arg(value_enum)]
+    pub shell: CliShell,
+}
+
+#[derive(clap::ValueEnum, Clone)]
+pub enum CliShell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl CliShell {
+    fn completer(&self) -> &'static dyn EnvCompleter {
+        match self {
+            CliShell::Bash => &Bash,
+            CliShell::Zsh => &Zsh,
+            CliShell::Fish => &Fish,
+        }
+    }
+}
+
+/// Responder a una petición de autocompletado de la shell y terminar el proceso
+///
+/// No hace nada si el binario no se ha lanzado desde un script de autocompletado.
+pub fn complete() {
+    CompleteEnv::with_factory(Cli::command)
+        .var(COMPLETE_VAR)
+        .complete();
+}
+
+/// Imprimir el script de registro del autocompletado para la shell indicada
+pub fn handle_completions_command(args: CompletionsArgs) -> Result<()> {
+    let exe = std::env::current_exe()
+        .map_err(|e| AppError::Internal(format!("No se pudo localizar el ejecutable: {}", e)))?;
+    let bin = exe
+        .file_name()
+        .map(|nombre| nombre.to_string_lossy().into_owned())
+        .unwrap_or_else(|| Cli::command().get_name().to_string());
+
+    let mut stdout = std::io::stdout();
+    args.shell
+        .completer()
+        .write_registration(
+            COMPLETE_VAR,
+            Cli::command().get_name(),
+            &bin,
+            &exe.to_string_lossy(),
+            &mut stdout,
+        )
+        .map_err(|e| AppError::Internal(format!("Error escribiendo el script: {}", e)))
+}
+
+/// Sugerir retiros existentes (por nombre, o por ID si el nombre está repetido)
+pub fn completar_retiros() -> Vec<CompletionCandidate> {
+    consultar(|pool| async move {
+        let retiros = RetiroRepository::new(pool).get_all().await?;
+        Ok(retiros
+            .iter()
+            .map(|r| {
+                let repetido = retiros
+                    .iter()
+                    .filter(|otro| otro.nombre.eq_ignore_ascii_case(&r.nombre))
+                    .count()
+                    > 1;
+                candidato(
+                    &r.nombre,
+                    r.id,
+                    repetido,
+                    format!("{} · {}", r.estado, r.fecha_inicio.format("%d/%m/%Y")),
+                )
+            })
+            .collect())
+    })
+}
+
+/// Sugerir categorías existentes (por nombre, o por ID si el nombre está repetido)
+pub fn completar_categorias() -> Vec<CompletionCandidate> {
+    consultar(|pool| async move {
+        let categorias = CategoriaRepository::new(pool).get_all().await?;
+        Ok(categorias
+            .iter()
+            .map(|c| {
+                let repetido = categorias
+                    .iter()
+                    .filter(|otra| otra.nombre.eq_ignore_ascii_case(&c.nombre))
+                    .count()
+                    > 1;
+                candidato(&c.nombre, c.id, repetido, c.tipo.to_string())
+            })
+            .collect())
+    })
+}
+
+/// Sugerir plantillas de transacción (sus nombres son únicos)
+pub fn completar_plantillas() -> Vec<CompletionCandidate> {
+    consultar(|pool| async move {
+        let plantillas = PlantillaRepository::new(pool).get_all().await?;
+        Ok(plantillas
+            .iter()
+            .map(|p| candidato(&p.nombre, p.id, false, format!("€{:.2}", p.monto)))
+            .collect())
+    })
+}
+
+/// Sugerir reglas de categorización (sus nombres son únicos)
+pub fn completar_reglas() -> Vec<CompletionCandidate> {
+    consultar(|pool| async move {
+        let reglas = ReglaRepository::new(pool).get_all().await?;
+        Ok(reglas
+            .iter()
+            .map(|r| candidato(&r.nombre, r.id, false, format!("/{}/", r.patron)))
+            .collect())
+    })
+}
+
+/// Sugerir cuentas (sus nombres son únicos)
+pub fn completar_cuentas() -> Vec<CompletionCandidate> {
+    consultar(|pool| async move {
+        let cuentas = CuentaRepository::new(pool).get_all().await?;
+        Ok(cuentas
+            .iter()
+            .map(|c| candidato(&c.nombre, c.id, false, c.tipo.to_string()))
+            .collect())
+    })
+}
+
+/// Sugerir etiquetas existentes con el número de transacciones que las usan
+pub fn completar_etiquetas() -> Vec<CompletionCandidate> {
+    consultar(|pool| async move {
+        let etiquetas = EtiquetaRepository::new(pool).get_all().await?;
+        Ok(etiquetas
+            .iter()
+            .map(|e| {
+                CompletionCandidate::new(&e.nombre)
+                    .help(Some(format!("{} transacciones", e.transacciones).into()))
+            })
+            .collect())
+    })
+}
+
+/// Sugerir elementos de la papelera por ID
+pub fn completar_papelera() -> Vec<CompletionCandidate> {
+    consultar(|pool| async move {
+        let elementos = PapeleraRepository::new(pool).listar().await?;
+        Ok(elementos
+            .iter()
+            .map(|e| candidato(&e.descripcion, e.id, true, e.tipo.to_string()))
+            .collect())
+    })
+}
+
+fn candidato(nombre: &str, id: uuid::Uuid, repetido: bool, detalle: String) -> CompletionCandidate {
+    let id = id.to_string();
+    if repetido {
+        CompletionCandidate::new(&id).help(Some(format!("{} · {}", nombre, detalle).into()))
+    } else {
+        CompletionCandidate::new(nombre).help(Some(format!("{} · {}", &id[..8], detalle).into()))
+    }
+}
+
+/// Ejecutar una consulta de solo lectura para las sugerencias
+///
+/// Se usa la misma base de datos que usaría el comando: `--database` o `--workspace` si ya
+/// están escritos en la línea, y si no la de por defecto (DATABASE_URL, workspace activo o
+/// config.toml). Cualquier error simplemente deja la lista vacía.
+fn consultar<F, Fut>(consulta: F) -> Vec<CompletionCandidate>
+where
+    F: FnOnce(sqlx::SqlitePool) -> Fut,
+    Fut: Future<Output = Result<Vec<CompletionCandidate>>>,
+{
+    let resultado = bloquear(async {
+        let url = config::resolve_database_url(
+            opcion_escrita("database").as_deref(),
+            opcion_escrita("workspace").as_deref(),
+        )?;
+        // Sin create_if_missing ni migraciones: completar nunca debe modificar la base de datos
+        let opciones = SqliteConnectOptions::from_str(&url)?.read_only(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(opciones)
+            .await?;
+        consulta(pool).await
+    });
+
+    resultado.and_then(|r| r.ok()).unwrap_or_default()
+}
+
+/// Valor de una opción global escrita en la línea que se está completando.
+/// La shell pasa las palabras tras `--`; la última es la que se completa y no cuenta.
+fn opcion_escrita(nombre: &str) -> Option<String> {
+    let palabras: Vec<String> = std::env::args().skip_while(|a| a != "--").skip(1).collect();
+    let escritas = &palabras[..palabras.len().saturating_sub(1)];
+
+    let opcion = format!("--{}", nombre);
+    let con_valor = format!("--{}=", nombre);
+    let mut valor = None;
+    let mut palabras = escritas.iter();
+    while let Some(palabra) = palabras.next() {
+        if *palabra == opcion {
+            valor = palabras.next().cloned();
+        } else if let Some(v) = palabra.strip_prefix(&con_valor) {
+            valor = Some(v.to_string());
+        }
+    }
+    valor
+}
+
+fn bloquear<Fut: Future>(futuro: Fut) -> Option<Fut::Output> {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => Some(tokio::task::block_in_place(|| handle.block_on(futuro))),
+        Err(_) => tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .ok()
+            .map(|runtime| runtime.block_on(futuro)),
+    }
+}
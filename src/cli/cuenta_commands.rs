@@ -0,0 +1,838 @@
+use chrono::{DateTime, Utc};
+use clap::{Args, Subcommand};
+use clap_complete::ArgValueCandidates;
+use colored::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::completions;
+use super::output::{self, OutputFormat};
+use super::resolver;
+use crate::database::Database;
+use crate::models::{
+    parse_denominacion, Arqueo, CreateArqueo, CreateCuenta, CreateTransferencia, TipoCuenta,
+};
+use crate::repositories::{ArqueoRepository, CuentaRepository, RetiroRepository};
+use crate::{AppError, Result};
+
+#[derive(Subcommand)]
+pub enum CuentaCommands {
+    /// Crear una nueva cuenta (caja, banco, tarjeta...)
+    Crear(CrearCuentaArgs),
+    /// Listar cuentas
+    Listar,
+    /// Eliminar una cuenta sin movimientos
+    Eliminar(EliminarCuentaArgs),
+    /// Saldo de cada cuenta, de un retiro o total
+    Saldos(SaldosArgs),
+    /// Registrar una transferencia entre dos cuentas
+    Transferir(TransferirArgs),
+    /// Listar transferencias entre cuentas
+    Transferencias(TransferenciasArgs),
+    /// Eliminar una transferencia
+    EliminarTransferencia(EliminarTransferenciaArgs),
+    /// Registrar un arqueo: contar el dinero de una cuenta y compararlo con su saldo
+    Arqueo(ArqueoArgs),
+    /// Historial de arqueos
+    Arqueos(ArqueosArgs),
+    /// Eliminar un arqueo del historial
+    EliminarArqueo(EliminarArqueoArgs),
+}
+
+#[derive(Args)]
+pub struct CrearCuentaArgs {
+    /// Nombre de la cuenta
+    #[arg(short, long)]
+    pub nombre: String,
+
+    /// Tipo de cuenta
+    #[arg(short, long, value_enum, default_value = "caja")]
+    pub tipo: CliTipoCuenta,
+
+    /// Dinero que hay en la cuenta antes de registrar movimientos
+    #[arg(long, default_value = "0")]
+    pub saldo_inicial: f64,
+}
+
+#[derive(Args)]
+pub struct EliminarCuentaArgs {
+    /// Cuenta a eliminar (ID, prefijo del ID o nombre)
+    #[arg(add = ArgValueCandidates::new(completions::completar_cuentas))]
+    pub id: String,
+
+    /// Confirmar eliminación sin preguntar
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Args)]
+pub struct SaldosArgs {
+    /// Solo los movimientos de este retiro (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_retiros))]
+    pub retiro_id: Option<String>,
+}
+
+#[derive(Args)]
+pub struct TransferirArgs {
+    /// Retiro en el que se hace la transferencia (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_retiros))]
+    pub retiro_id: String,
+
+    /// Cuenta de la que sale el dinero (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_cuentas))]
+    pub origen: String,
+
+    /// Cuenta a la que llega el dinero (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_cuentas))]
+    pub destino: String,
+
+    /// Monto transferido
+    #[arg(short, long)]
+    pub monto: f64,
+
+    /// Descripción de la transferencia
+    #[arg(short, long, default_value = "Transferencia entre cuentas")]
+    pub descripcion: String,
+}
+
+#[derive(Args)]
+pub struct TransferenciasArgs {
+    /// Filtrar por retiro (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_retiros))]
+    pub retiro_id: Option<String>,
+}
+
+#[derive(Args)]
+pub struct EliminarTransferenciaArgs {
+    /// Transferencia a eliminar (ID o prefijo del ID)
+    pub id: String,
+
+    /// Confirmar eliminación sin preguntar
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Args)]
+pub struct ArqueoArgs {
+    /// Retiro en el que se hace el arqueo (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_retiros))]
+    pub retiro_id: String,
+
+    /// Cuenta contada (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_cuentas))]
+    pub cuenta: String,
+
+    /// Total contado
+    #[arg(long)]
+    pub total: Option<f64>,
+
+    /// Billetes o monedas contados como VALORxCANTIDAD (p. ej. 20x3, 0.50x4); se puede repetir
+    #[arg(short = 'D', long = "denominacion", value_delimiter = ',')]
+    pub denominaciones: Vec<String>,
+
+    /// Notas del arqueo
+    #[arg(long)]
+    pub notas: Option<String>,
+
+    /// Registrar una transacción en "Ajustes de caja" que corrija el descuadre
+    #[arg(long)]
+    pub ajustar: bool,
+}
+
+#[derive(Args)]
+pub struct ArqueosArgs {
+    /// Filtrar por retiro (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_retiros))]
+    pub retiro_id: Option<String>,
+
+    /// Filtrar por cuenta (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_cuentas))]
+    pub cuenta: Option<String>,
+}
+
+#[derive(Args)]
+pub struct EliminarArqueoArgs {
+    /// Arqueo a eliminar (ID o prefijo del ID)
+    pub id: String,
+
+    /// Confirmar eliminación sin preguntar
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(clap::ValueEnum, Clone)]
+pub enum CliTipoCuenta {
+    Caja,
+    Banco,
+    Tarjeta,
+    Otra,
+}
+
+impl From<CliTipoCuenta> for TipoCuenta {
+    fn from(cli_tipo: CliTipoCuenta) -> Self {
+        match cli_tipo {
+            CliTipoCuenta::Caja => TipoCuenta::Caja,
+            CliTipoCuenta::Banco => TipoCuenta::Banco,
+            CliTipoCuenta::Tarjeta => TipoCuenta::Tarjeta,
+            CliTipoCuenta::Otra => TipoCuenta::Otra,
+        }
+    }
+}
+
+pub async fn handle_cuenta_command(
+    command: CuentaCommands,
+    database_url: &str,
+    output: OutputFormat,
+) -> Result<()> {
+    // Conectar a la base de datos
+    let db = Database::new(database_url).await?;
+    let repo = CuentaRepository::new(db.pool().clone());
+    // Para resolver referencias a retiros por nombre o prefijo de ID
+    let retiro_repo = RetiroRepository::new(db.pool().clone());
+    let arqueo_repo = ArqueoRepository::new(db.pool().clone());
+
+    match command {
+        CuentaCommands::Crear(args) => crear_cuenta(repo, args).await,
+        CuentaCommands::Listar => listar_cuentas(repo, output).await,
+        CuentaCommands::Eliminar(args) => eliminar_cuenta(repo, args).await,
+        CuentaCommands::Saldos(args) => mostrar_saldos(repo, &retiro_repo, args, output).await,
+        CuentaCommands::Transferir(args) => transferir(repo, &retiro_repo, args).await,
+        CuentaCommands::Transferencias(args) => {
+            listar_transferencias(repo, &retiro_repo, args, output).await
+        }
+        CuentaCommands::EliminarTransferencia(args) => eliminar_transferencia(repo, args).await,
+        CuentaCommands::Arqueo(args) => {
+            registrar_arqueo(arqueo_repo, &repo, &retiro_repo, args, output).await
+        }
+        CuentaCommands::Arqueos(args) => {
+            listar_arqueos(arqueo_repo, &repo, &retiro_repo, args, output).await
+        }
+        CuentaCommands::EliminarArqueo(args) => eliminar_arqueo(arqueo_repo, args).await,
+    }
+}
+
+async fn crear_cuenta(repo: CuentaRepository, args: CrearCuentaArgs) -> Result<()> {
+    println!("{}", "🏦 Creando nueva cuenta...".cyan().bold());
+
+    let create_data = CreateCuenta {
+        nombre: args.nombre,
+        tipo: args.tipo.into(),
+        saldo_inicial: args.saldo_inicial,
+    };
+
+    let cuenta = repo.create(create_data).await?;
+    println!("{}", "✅ Cuenta creada exitosamente!".green().bold());
+    println!();
+    println!("📋 {}", "Detalles:".bold());
+    println!("   ID: {}", cuenta.id.to_string().bright_blue());
+    println!("   Nombre: {}", cuenta.nombre.bright_white());
+    println!("   Tipo: {}", cuenta.tipo.to_string().bright_yellow());
+    println!(
+        "   Saldo inicial: {}",
+        format!("€{:.2}", cuenta.saldo_inicial).bright_green()
+    );
+
+    Ok(())
+}
+
+async fn listar_cuentas(repo: CuentaRepository, output: OutputFormat) -> Result<()> {
+    let cuentas = repo.get_all().await?;
+
+    if output.is_structured() {
+        return output::print_list(output, &cuentas);
+    }
+
+    println!("{}", "📋 Listando cuentas...".cyan().bold());
+    println!();
+
+    if cuentas.is_empty() {
+        println!("{}", "📭 No se encontraron cuentas.".yellow());
+        return Ok(());
+    }
+
+    println!(
+        "{:<10} {:<30} {:<10} {:>14}",
+        "ID".bold(),
+        "NOMBRE".bold(),
+        "TIPO".bold(),
+        "SALDO INICIAL".bold()
+    );
+    println!("{}", "─".repeat(67).bright_black());
+
+    for cuenta in &cuentas {
+        println!(
+            "{:<10} {:<30} {:<10} {:>14}",
+            cuenta.id.to_string()[..8].bright_blue(),
+            cuenta.nombre.bright_white(),
+            cuenta.tipo.to_string().bright_yellow(),
+            format!("€{:.2}", cuenta.saldo_inicial).bright_green()
+        );
+    }
+
+    println!();
+    println!("   Cuentas: {}", cuentas.len());
+
+    Ok(())
+}
+
+async fn eliminar_cuenta(repo: CuentaRepository, args: EliminarCuentaArgs) -> Result<()> {
+    let id = resolver::resolver_cuenta(&repo, &args.id).await?;
+
+    let cuenta = match repo.get_by_id(id).await? {
+        Some(c) => c,
+        None => {
+            return Err(AppError::NotFound("Cuenta".to_string()));
+        }
+    };
+
+    if !args.force {
+        println!(
+            "{}",
+            "⚠️  ¿Estás seguro de que quieres eliminar esta cuenta?"
+                .yellow()
+                .bold()
+        );
+        println!("   Nombre: {}", cuenta.nombre.bright_white());
+        println!("   Tipo: {}", cuenta.tipo.to_string().bright_yellow());
+        println!();
+        println!(
+            "{}",
+            "Usa --force para confirmar la eliminación.".bright_black()
+        );
+        return Ok(());
+    }
+
+    println!("{}", "🗑️  Eliminando cuenta...".cyan().bold());
+
+    match repo.delete(id).await? {
+        true => {
+            println!("{}", "✅ Cuenta eliminada exitosamente!".green().bold());
+        }
+        false => {
+            return Err(AppError::Internal("Error eliminando cuenta".to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+async fn mostrar_saldos(
+    repo: CuentaRepository,
+    retiro_repo: &RetiroRepository,
+    args: SaldosArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let retiro = match &args.retiro_id {
+        Some(referencia) => {
+            let id = resolver::resolver_retiro(retiro_repo, referencia).await?;
+            Some(
+                retiro_repo
+                    .get_by_id(id)
+                    .await?
+                    .ok_or_else(|| AppError::NotFound("Retiro".to_string()))?,
+            )
+        }
+        None => None,
+    };
+    let saldos = repo.get_saldos(retiro.as_ref().map(|r| r.id)).await?;
+
+    if output.is_structured() {
+        return output::print_list(output, &saldos);
+    }
+
+    match &retiro {
+        Some(retiro) => println!(
+            "{} {}",
+            "💶 Saldos por cuenta en".cyan().bold(),
+            retiro.nombre.bright_white()
+        ),
+        None => println!("{}", "💶 Saldos por cuenta...".cyan().bold()),
+    }
+    println!();
+
+    if saldos.is_empty() {
+        println!("{}", "📭 No hay cuentas ni movimientos.".yellow());
+        return Ok(());
+    }
+
+    println!(
+        "{:<25} {:>12} {:>12} {:>12} {:>14} {:>12}",
+        "CUENTA".bold(),
+        "INICIAL".bold(),
+        "INGRESOS".bold(),
+        "GASTOS".bold(),
+        "TRANSFER.".bold(),
+        "SALDO".bold()
+    );
+    println!("{}", "─".repeat(92).bright_black());
+
+    for saldo in &saldos {
+        let transferencias = saldo.transferencias_entrada - saldo.transferencias_salida;
+        let cuenta = if saldo.cuenta_id.is_some() {
+            saldo.cuenta.bright_white()
+        } else {
+            saldo.cuenta.bright_black()
+        };
+        println!(
+            "{:<25} {:>12} {:>12} {:>12} {:>14} {:>12}",
+            cuenta,
+            format!("€{:.2}", saldo.saldo_inicial),
+            format!("€{:.2}", saldo.ingresos).green(),
+            format!("€{:.2}", saldo.gastos).red(),
+            format!("{:+.2}", transferencias).bright_cyan(),
+            format!("€{:.2}", saldo.saldo).bright_yellow().bold()
+        );
+    }
+
+    println!();
+    println!(
+        "{}",
+        "   Las transferencias mueven dinero entre cuentas: no son ingresos ni gastos."
+            .bright_black()
+    );
+
+    Ok(())
+}
+
+async fn transferir(
+    repo: CuentaRepository,
+    retiro_repo: &RetiroRepository,
+    args: TransferirArgs,
+) -> Result<()> {
+    let retiro_id = resolver::resolver_retiro(retiro_repo, &args.retiro_id).await?;
+    let origen = resolver::resolver_cuenta(&repo, &args.origen).await?;
+    let destino = resolver::resolver_cuenta(&repo, &args.destino).await?;
+
+    println!("{}", "🔁 Registrando transferencia...".cyan().bold());
+
+    let create_data = CreateTransferencia {
+        retiro_id,
+        cuenta_origen_id: origen,
+        cuenta_destino_id: destino,
+        monto: args.monto,
+        descripcion: args.descripcion,
+    };
+
+    let transferencia = repo.create_transferencia(create_data).await?;
+    println!("{}", "✅ Transferencia registrada!".green().bold());
+    println!();
+    println!("📋 {}", "Detalles:".bold());
+    println!("   ID: {}", transferencia.id.to_string().bright_blue());
+    for (etiqueta, id) in [("Origen", origen), ("Destino", destino)] {
+        let nombre = repo.get_by_id(id).await?.map(|c| c.nombre).unwrap_or_default();
+        println!("   {}: {}", etiqueta, nombre.bright_magenta());
+    }
+    println!(
+        "   Monto: {}",
+        format!("€{:.2}", transferencia.monto).bright_green()
+    );
+    println!("   Descripción: {}", transferencia.descripcion.bright_white());
+
+    Ok(())
+}
+
+async fn listar_transferencias(
+    repo: CuentaRepository,
+    retiro_repo: &RetiroRepository,
+    args: TransferenciasArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let retiro_id = match &args.retiro_id {
+        Some(referencia) => Some(resolver::resolver_retiro(retiro_repo, referencia).await?),
+        None => None,
+    };
+    let transferencias = repo.get_transferencias(retiro_id).await?;
+
+    if output.is_structured() {
+        return output::print_list(output, &transferencias);
+    }
+
+    println!("{}", "🔁 Listando transferencias...".cyan().bold());
+    println!();
+
+    if transferencias.is_empty() {
+        println!("{}", "📭 No se encontraron transferencias.".yellow());
+        return Ok(());
+    }
+
+    let cuentas = repo.get_all().await?;
+    let nombre_cuenta = |id| {
+        cuentas
+            .iter()
+            .find(|c| c.id == id)
+            .map(|c| c.nombre.clone())
+            .unwrap_or_default()
+    };
+
+    println!(
+        "{:<10} {:<20} {:<20} {:>12} {:<30} {:<12}",
+        "ID".bold(),
+        "ORIGEN".bold(),
+        "DESTINO".bold(),
+        "MONTO".bold(),
+        "DESCRIPCIÓN".bold(),
+        "FECHA".bold()
+    );
+    println!("{}", "─".repeat(110).bright_black());
+
+    for transferencia in &transferencias {
+        println!(
+            "{:<10} {:<20} {:<20} {:>12} {:<30} {:<12}",
+            transferencia.id.to_string()[..8].bright_blue(),
+            nombre_cuenta(transferencia.cuenta_origen_id).bright_magenta(),
+            nombre_cuenta(transferencia.cuenta_destino_id).bright_magenta(),
+            format!("€{:.2}", transferencia.monto).bright_green(),
+            transferencia.descripcion.bright_white(),
+            transferencia
+                .created_at
+                .format("%Y-%m-%d")
+                .to_string()
+                .bright_cyan()
+        );
+    }
+
+    println!();
+    println!("   Transferencias: {}", transferencias.len());
+
+    Ok(())
+}
+
+async fn eliminar_transferencia(
+    repo: CuentaRepository,
+    args: EliminarTransferenciaArgs,
+) -> Result<()> {
+    let id = resolver::resolver_transferencia(&repo, &args.id).await?;
+
+    let transferencia = match repo
+        .get_transferencias(None)
+        .await?
+        .into_iter()
+        .find(|t| t.id == id)
+    {
+        Some(t) => t,
+        None => {
+            return Err(AppError::NotFound("Transferencia".to_string()));
+        }
+    };
+
+    if !args.force {
+        println!(
+            "{}",
+            "⚠️  ¿Estás seguro de que quieres eliminar esta transferencia?"
+                .yellow()
+                .bold()
+        );
+        println!("   Descripción: {}", transferencia.descripcion.bright_white());
+        println!(
+            "   Monto: {}",
+            format!("€{:.2}", transferencia.monto).bright_green()
+        );
+        println!();
+        println!(
+            "{}",
+            "Usa --force para confirmar la eliminación.".bright_black()
+        );
+        return Ok(());
+    }
+
+    println!("{}", "🗑️  Eliminando transferencia...".cyan().bold());
+
+    match repo.delete_transferencia(id).await? {
+        true => {
+            println!("{}", "✅ Transferencia eliminada exitosamente!".green().bold());
+        }
+        false => {
+            return Err(AppError::Internal("Error eliminando transferencia".to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+async fn registrar_arqueo(
+    repo: ArqueoRepository,
+    cuenta_repo: &CuentaRepository,
+    retiro_repo: &RetiroRepository,
+    args: ArqueoArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let retiro_id = resolver::resolver_retiro(retiro_repo, &args.retiro_id).await?;
+    let cuenta_id = resolver::resolver_cuenta(cuenta_repo, &args.cuenta).await?;
+    let denominaciones = args
+        .denominaciones
+        .iter()
+        .map(|d| parse_denominacion(d))
+        .collect::<Result<Vec<_>>>()?;
+
+    let create_data = CreateArqueo {
+        retiro_id,
+        cuenta_id,
+        total: args.total,
+        denominaciones,
+        notas: args.notas,
+        ajustar: args.ajustar,
+    };
+
+    if output.is_structured() {
+        let arqueo = repo.create(create_data).await?;
+        return print_arqueos(output, std::slice::from_ref(&arqueo));
+    }
+
+    println!("{}", "🧮 Registrando arqueo...".cyan().bold());
+
+    let arqueo = repo.create(create_data).await?;
+    let cuenta = cuenta_repo
+        .get_by_id(cuenta_id)
+        .await?
+        .map(|c| c.nombre)
+        .unwrap_or_default();
+    println!("{}", "✅ Arqueo registrado!".green().bold());
+    println!();
+    println!("📋 {}", "Detalles:".bold());
+    println!("   ID: {}", arqueo.id.to_string().bright_blue());
+    println!("   Cuenta: {}", cuenta.bright_magenta());
+    for denominacion in &arqueo.denominaciones {
+        println!(
+            "     {:>8} x {:<4} = {}",
+            format!("€{:.2}", denominacion.valor),
+            denominacion.cantidad,
+            format!("€{:.2}", denominacion.valor * f64::from(denominacion.cantidad))
+                .bright_white()
+        );
+    }
+    println!(
+        "   Contado: {}",
+        format!("€{:.2}", arqueo.contado).bright_green()
+    );
+    println!(
+        "   Saldo calculado: {}",
+        format!("€{:.2}", arqueo.saldo_calculado).bright_yellow()
+    );
+    println!();
+    imprimir_descuadre(&arqueo);
+    if let Some(ajuste_id) = arqueo.transaccion_ajuste_id {
+        println!(
+            "   Transacción de ajuste: {}",
+            ajuste_id.to_string().bright_blue()
+        );
+    } else if !arqueo.cuadra() {
+        println!(
+            "{}",
+            "   Usa --ajustar para registrar una transacción que corrija el descuadre."
+                .bright_black()
+        );
+    }
+
+    Ok(())
+}
+
+/// Arqueo como fila de CSV, con las denominaciones en una columna ("20x3 0.5x4")
+#[derive(Serialize)]
+struct FilaArqueo<'a> {
+    id: Uuid,
+    retiro_id: Uuid,
+    cuenta_id: Uuid,
+    saldo_calculado: f64,
+    contado: f64,
+    diferencia: f64,
+    denominaciones: String,
+    notas: &'a Option<String>,
+    transaccion_ajuste_id: Option<Uuid>,
+    created_at: DateTime<Utc>,
+}
+
+fn print_arqueos(output: OutputFormat, arqueos: &[Arqueo]) -> Result<()> {
+    if output != OutputFormat::Csv {
+        return output::print_list(output, arqueos);
+    }
+
+    let filas: Vec<FilaArqueo> = arqueos
+        .iter()
+        .map(|a| FilaArqueo {
+            id: a.id,
+            retiro_id: a.retiro_id,
+            cuenta_id: a.cuenta_id,
+            saldo_calculado: a.saldo_calculado,
+            contado: a.contado,
+            diferencia: a.diferencia,
+            denominaciones: a
+                .denominaciones
+                .iter()
+                .map(|d| format!("{}x{}", d.valor, d.cantidad))
+                .collect::<Vec<_>>()
+                .join(" "),
+            notas: &a.notas,
+            transaccion_ajuste_id: a.transaccion_ajuste_id,
+            created_at: a.created_at,
+        })
+        .collect();
+    output::print_list(output, &filas)
+}
+
+fn imprimir_descuadre(arqueo: &Arqueo) {
+    if arqueo.cuadra() {
+        println!("{}", "✅ La cuenta cuadra.".green().bold());
+    } else if arqueo.diferencia > 0.0 {
+        println!(
+            "{} {}",
+            "⚠️  Descuadre: sobran".yellow().bold(),
+            format!("€{:.2}", arqueo.diferencia).yellow().bold()
+        );
+    } else {
+        println!(
+            "{} {}",
+            "⚠️  Descuadre: faltan".red().bold(),
+            format!("€{:.2}", -arqueo.diferencia).red().bold()
+        );
+    }
+}
+
+async fn listar_arqueos(
+    repo: ArqueoRepository,
+    cuenta_repo: &CuentaRepository,
+    retiro_repo: &RetiroRepository,
+    args: ArqueosArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let retiro_id = match &args.retiro_id {
+        Some(referencia) => Some(resolver::resolver_retiro(retiro_repo, referencia).await?),
+        None => None,
+    };
+    let cuenta_id = match &args.cuenta {
+        Some(referencia) => Some(resolver::resolver_cuenta(cuenta_repo, referencia).await?),
+        None => None,
+    };
+    let arqueos = repo.get_all(retiro_id, cuenta_id).await?;
+
+    if output.is_structured() {
+        return print_arqueos(output, &arqueos);
+    }
+
+    println!("{}", "🧮 Listando arqueos...".cyan().bold());
+    println!();
+
+    if arqueos.is_empty() {
+        println!("{}", "📭 No se encontraron arqueos.".yellow());
+        return Ok(());
+    }
+
+    let cuentas = cuenta_repo.get_all().await?;
+    let nombre_cuenta = |id| {
+        cuentas
+            .iter()
+            .find(|c| c.id == id)
+            .map(|c| c.nombre.clone())
+            .unwrap_or_default()
+    };
+
+    println!(
+        "{:<10} {:<17} {:<20} {:>12} {:>12} {:>12} {:<8}",
+        "ID".bold(),
+        "FECHA".bold(),
+        "CUENTA".bold(),
+        "CALCULADO".bold(),
+        "CONTADO".bold(),
+        "DIFERENCIA".bold(),
+        "AJUSTE".bold()
+    );
+    println!("{}", "─".repeat(97).bright_black());
+
+    for arqueo in &arqueos {
+        let diferencia = format!("{:+.2}", arqueo.diferencia);
+        let diferencia = if arqueo.cuadra() {
+            diferencia.green()
+        } else if arqueo.diferencia > 0.0 {
+            diferencia.yellow().bold()
+        } else {
+            diferencia.red().bold()
+        };
+        println!(
+            "{:<10} {:<17} {:<20} {:>12} {:>12} {:>12} {:<8}",
+            arqueo.id.to_string()[..8].bright_blue(),
+            arqueo
+                .created_at
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+                .bright_cyan(),
+            nombre_cuenta(arqueo.cuenta_id).bright_magenta(),
+            format!("€{:.2}", arqueo.saldo_calculado),
+            format!("€{:.2}", arqueo.contado).bright_green(),
+            diferencia,
+            if arqueo.transaccion_ajuste_id.is_some() { "Sí" } else { "" }
+        );
+    }
+
+    let descuadres = arqueos.iter().filter(|a| !a.cuadra()).count();
+    println!();
+    println!("   Arqueos: {}", arqueos.len());
+    if descuadres > 0 {
+        println!(
+            "   Con descuadre: {}",
+            descuadres.to_string().red().bold()
+        );
+    }
+
+    Ok(())
+}
+
+async fn eliminar_arqueo(repo: ArqueoRepository, args: EliminarArqueoArgs) -> Result<()> {
+    let id = resolver::resolver_arqueo(&repo, &args.id).await?;
+
+    let arqueo = match repo
+        .get_all(None, None)
+        .await?
+        .into_iter()
+        .find(|a| a.id == id)
+    {
+        Some(a) => a,
+        None => {
+            return Err(AppError::NotFound("Arqueo".to_string()));
+        }
+    };
+
+    if !args.force {
+        println!(
+            "{}",
+            "⚠️  ¿Estás seguro de que quieres eliminar este arqueo?"
+                .yellow()
+                .bold()
+        );
+        println!(
+            "   Fecha: {}",
+            arqueo
+                .created_at
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+                .bright_white()
+        );
+        println!(
+            "   Contado: {}",
+            format!("€{:.2}", arqueo.contado).bright_green()
+        );
+        if arqueo.transaccion_ajuste_id.is_some() {
+            println!(
+                "{}",
+                "   La transacción de ajuste irá a la papelera.".bright_black()
+            );
+        }
+        println!();
+        println!(
+            "{}",
+            "Usa --force para confirmar la eliminación.".bright_black()
+        );
+        return Ok(());
+    }
+
+    println!("{}", "🗑️  Eliminando arqueo...".cyan().bold());
+
+    match repo.delete(id).await? {
+        true => {
+            println!("{}", "✅ Arqueo eliminado exitosamente!".green().bold());
+        }
+        false => {
+            return Err(AppError::Internal("Error eliminando arqueo".to_string()));
+        }
+    }
+
+    Ok(())
+}
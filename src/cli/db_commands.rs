@@ -0,0 +1,427 @@
+use clap::{Args, Subcommand};
+use colored::*;
+
+use crate::database::{backup, AlmacenAdjuntos, Backups, Database};
+use crate::models::Dataset;
+use crate::repositories::{DatasetRepository, ModoImportacion, ResolucionConflicto, ResumenEntidad};
+use crate::{AppError, Result};
+
+#[derive(Subcommand)]
+pub enum DbCommands {
+    /// Crear una copia de seguridad de la base de datos
+    Backup(BackupArgs),
+    /// Restaurar una copia de seguridad
+    Restore(RestoreArgs),
+    /// Listar las copias de seguridad disponibles
+    Listar,
+    /// Verificar la integridad de una copia de seguridad
+    Verificar(VerificarArgs),
+    /// Exportar todos los datos a JSON
+    ///
+    /// Los comprobantes se exportan sin sus archivos: al importar el JSON en otra base de
+    /// datos solo se enlazan aquellos cuyo archivo ya esté en su almacén de adjuntos.
+    Exportar(ExportarArgs),
+    /// Importar datos desde un JSON exportado
+    Importar(ImportarArgs),
+    /// Borrar los archivos de comprobantes que no usa la base de datos ni sus copias
+    ///
+    /// Eliminar comprobantes o vaciar la papelera conserva sus archivos para que las copias de
+    /// seguridad sigan completas; este comando los borra cuando ya no los referencia ninguna.
+    PurgarAdjuntos,
+}
+
+#[derive(Args)]
+pub struct BackupArgs {
+    /// Etiqueta añadida al nombre del archivo
+    #[arg(short, long, default_value = "manual")]
+    pub etiqueta: String,
+}
+
+#[derive(Args)]
+pub struct RestoreArgs {
+    /// Ruta o nombre del archivo de copia
+    pub archivo: String,
+
+    /// Confirmar la restauración sin preguntar
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Args)]
+pub struct VerificarArgs {
+    /// Ruta o nombre del archivo de copia
+    pub archivo: String,
+}
+
+#[derive(Args)]
+pub struct ExportarArgs {
+    /// Archivo de salida (por defecto la salida estándar)
+    #[arg(short, long)]
+    pub archivo: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ImportarArgs {
+    /// Archivo JSON generado con `db exportar`
+    pub archivo: String,
+
+    /// Fusionar con los datos existentes o reemplazarlos
+    #[arg(short, long, value_enum, default_value = "fusionar")]
+    pub modo: CliModoImportacion,
+
+    /// Qué hacer si un ID ya existe con datos distintos
+    #[arg(short, long, value_enum, default_value = "abortar")]
+    pub conflictos: CliResolucionConflicto,
+
+    /// Confirmar el reemplazo sin preguntar
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(clap::ValueEnum, Clone)]
+pub enum CliModoImportacion {
+    Fusionar,
+    Reemplazar,
+}
+
+impl From<CliModoImportacion> for ModoImportacion {
+    fn from(cli_modo: CliModoImportacion) -> Self {
+        match cli_modo {
+            CliModoImportacion::Fusionar => ModoImportacion::Fusionar,
+            CliModoImportacion::Reemplazar => ModoImportacion::Reemplazar,
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone)]
+pub enum CliResolucionConflicto {
+    Abortar,
+    Mantener,
+    Sobrescribir,
+}
+
+impl From<CliResolucionConflicto> for ResolucionConflicto {
+    fn from(cli_resolucion: CliResolucionConflicto) -> Self {
+        match cli_resolucion {
+            CliResolucionConflicto::Abortar => ResolucionConflicto::Abortar,
+            CliResolucionConflicto::Mantener => ResolucionConflicto::Mantener,
+            CliResolucionConflicto::Sobrescribir => ResolucionConflicto::Sobrescribir,
+        }
+    }
+}
+
+pub async fn handle_db_command(command: DbCommands, database_url: &str) -> Result<()> {
+    // Solo las copias de seguridad requieren una base de datos en archivo
+    match command {
+        DbCommands::Backup(args) => {
+            crear_backup(Backups::new(database_url)?, database_url, args).await
+        }
+        DbCommands::Restore(args) => restaurar_backup(Backups::new(database_url)?, args).await,
+        DbCommands::Listar => listar_backups(Backups::new(database_url)?).await,
+        DbCommands::Verificar(args) => verificar_backup(Backups::new(database_url)?, args).await,
+        DbCommands::Exportar(args) => exportar_datos(database_url, args).await,
+        DbCommands::Importar(args) => importar_datos(database_url, args).await,
+        DbCommands::PurgarAdjuntos => {
+            purgar_adjuntos(Backups::new(database_url)?, database_url).await
+        }
+    }
+}
+
+async fn crear_backup(backups: Backups, database_url: &str, args: BackupArgs) -> Result<()> {
+    println!("{}", "💾 Creando copia de seguridad...".cyan().bold());
+
+    let db = Database::new(database_url).await?;
+
+    let path = backups.create(db.pool(), &args.etiqueta).await?;
+    println!("{}", "✅ Copia creada exitosamente!".green().bold());
+    println!();
+    println!("   Archivo: {}", path.display().to_string().bright_magenta());
+
+    Ok(())
+}
+
+async fn restaurar_backup(backups: Backups, args: RestoreArgs) -> Result<()> {
+    let path = backups.find(&args.archivo)?;
+
+    println!("{}", "🔍 Verificando copia...".cyan().bold());
+    backup::verify(&path).await?;
+    println!("{}", "✅ Integridad verificada.".green());
+
+    if !args.force {
+        println!();
+        println!(
+            "{}",
+            "⚠️  ¿Estás seguro de que quieres restaurar esta copia?"
+                .yellow()
+                .bold()
+        );
+        println!("   Archivo: {}", path.display().to_string().bright_magenta());
+        println!();
+        println!(
+            "{}",
+            "⚠️  ADVERTENCIA: Se reemplazarán todos los datos actuales.".red()
+        );
+        println!(
+            "{}",
+            "Usa --force para confirmar la restauración.".bright_black()
+        );
+        return Ok(());
+    }
+
+    println!("{}", "♻️  Restaurando copia...".cyan().bold());
+
+    let copia_previa = backups.restore(&path).await?;
+    println!("{}", "✅ Copia restaurada exitosamente!".green().bold());
+    if let Some(copia_previa) = copia_previa {
+        println!(
+            "   Estado anterior guardado en: {}",
+            copia_previa.display().to_string().bright_black()
+        );
+    }
+
+    Ok(())
+}
+
+async fn listar_backups(backups: Backups) -> Result<()> {
+    println!("{}", "📋 Listando copias de seguridad...".cyan().bold());
+    println!();
+
+    let lista = backups.list()?;
+
+    if lista.is_empty() {
+        println!("{}", "📭 No se encontraron copias de seguridad.".yellow());
+        return Ok(());
+    }
+
+    println!(
+        "{:<20} {:<12} {:<60}",
+        "FECHA".bold(),
+        "TAMAÑO".bold(),
+        "ARCHIVO".bold()
+    );
+    println!("{}", "─".repeat(95).bright_black());
+
+    for info in &lista {
+        let nombre = info
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        println!(
+            "{:<20} {:<12} {:<60}",
+            info.fecha.format("%Y-%m-%d %H:%M:%S").to_string().bright_cyan(),
+            format!("{:.1} KB", info.bytes as f64 / 1024.0).bright_green(),
+            nombre.bright_white()
+        );
+    }
+
+    println!();
+    println!(
+        "{} {}",
+        "📁 Directorio:".bold(),
+        backups.directorio().display().to_string().bright_magenta()
+    );
+    println!(
+        "{} {}",
+        "📊 Total:".bold(),
+        lista.len().to_string().bright_green()
+    );
+
+    Ok(())
+}
+
+async fn verificar_backup(backups: Backups, args: VerificarArgs) -> Result<()> {
+    println!("{}", "🔍 Verificando copia...".cyan().bold());
+
+    let path = backups.find(&args.archivo)?;
+
+    backup::verify(&path).await?;
+    println!("{}", "✅ La copia es válida.".green().bold());
+
+    Ok(())
+}
+
+async fn exportar_datos(database_url: &str, args: ExportarArgs) -> Result<()> {
+    let db = Database::new(database_url).await?;
+    let repo = DatasetRepository::new(db.pool().clone());
+
+    let dataset = repo.export().await?;
+    let json = serde_json::to_string_pretty(&dataset)
+        .map_err(|e| AppError::Internal(format!("Error serializando datos: {}", e)))?;
+
+    // Sin archivo se escribe sólo el JSON para poder redirigir la salida
+    let archivo = match args.archivo {
+        Some(archivo) => archivo,
+        None => {
+            println!("{}", json);
+            return Ok(());
+        }
+    };
+
+    println!("{}", "📤 Exportando datos...".cyan().bold());
+
+    std::fs::write(&archivo, json)
+        .map_err(|e| AppError::Internal(format!("Error escribiendo {}: {}", archivo, e)))?;
+
+    println!("{}", "✅ Datos exportados exitosamente!".green().bold());
+    println!();
+    println!("   Archivo: {}", archivo.bright_magenta());
+    println!(
+        "   Categorías: {}",
+        dataset.categorias.len().to_string().bright_green()
+    );
+    println!(
+        "   Retiros: {}",
+        dataset.retiros.len().to_string().bright_green()
+    );
+    println!(
+        "   Transacciones: {}",
+        dataset.transacciones.len().to_string().bright_green()
+    );
+    println!(
+        "   Plantillas: {}",
+        dataset.plantillas.len().to_string().bright_green()
+    );
+    println!(
+        "   Etiquetas asignadas: {}",
+        dataset.etiquetas.len().to_string().bright_green()
+    );
+    println!(
+        "   Cuentas: {}",
+        dataset.cuentas.len().to_string().bright_green()
+    );
+    println!(
+        "   Transferencias: {}",
+        dataset.transferencias.len().to_string().bright_green()
+    );
+    println!(
+        "   Arqueos: {}",
+        dataset.arqueos.len().to_string().bright_green()
+    );
+    println!(
+        "   Líneas de extracto: {}",
+        dataset.lineas_extracto.len().to_string().bright_green()
+    );
+    println!(
+        "   Reglas: {}",
+        dataset.reglas.len().to_string().bright_green()
+    );
+    println!(
+        "   Comprobantes: {}",
+        dataset.adjuntos.len().to_string().bright_green()
+    );
+    println!(
+        "   En la papelera: {}",
+        dataset.papelera.len().to_string().bright_green()
+    );
+
+    Ok(())
+}
+
+async fn importar_datos(database_url: &str, args: ImportarArgs) -> Result<()> {
+    let contenido = std::fs::read_to_string(&args.archivo)
+        .map_err(|e| AppError::Validation(format!("Error leyendo {}: {}", args.archivo, e)))?;
+    let dataset: Dataset = serde_json::from_str(&contenido)
+        .map_err(|e| AppError::Validation(format!("JSON inválido: {}", e)))?;
+
+    let modo: ModoImportacion = args.modo.into();
+
+    if let ModoImportacion::Reemplazar = modo {
+        if !args.force {
+            println!(
+                "{}",
+                "⚠️  ¿Estás seguro de que quieres reemplazar todos los datos?"
+                    .yellow()
+                    .bold()
+            );
+            println!("   Archivo: {}", args.archivo.bright_magenta());
+            println!();
+            println!(
+                "{}",
+                "⚠️  ADVERTENCIA: Se eliminarán todas las categorías, retiros, transacciones, plantillas, etiquetas, cuentas, arqueos, extractos, reglas y comprobantes actuales."
+                    .red()
+            );
+            println!(
+                "{}",
+                "Usa --force para confirmar el reemplazo.".bright_black()
+            );
+            return Ok(());
+        }
+    }
+
+    println!("{}", "📥 Importando datos...".cyan().bold());
+
+    let db = Database::new(database_url).await?;
+
+    if let ModoImportacion::Reemplazar = modo {
+        let copia = Backups::new(database_url)?
+            .create(db.pool(), "antes-importar")
+            .await?;
+        println!(
+            "   Copia de seguridad: {}",
+            copia.display().to_string().bright_black()
+        );
+    }
+
+    let repo = DatasetRepository::new(db.pool().clone());
+
+    // Sin base de datos en archivo no hay almacén de adjuntos
+    let almacen = AlmacenAdjuntos::new(database_url).ok();
+    let resumen = repo
+        .import(dataset, modo, args.conflictos.into(), almacen.as_ref())
+        .await?;
+    println!("{}", "✅ Datos importados exitosamente!".green().bold());
+    println!();
+    println!("📊 {}", "Resumen:".bold());
+    imprimir_resumen("Categorías", &resumen.categorias);
+    imprimir_resumen("Retiros", &resumen.retiros);
+    imprimir_resumen("Transacciones", &resumen.transacciones);
+    imprimir_resumen("Plantillas", &resumen.plantillas);
+    imprimir_resumen("Etiquetas", &resumen.etiquetas);
+    imprimir_resumen("Cuentas", &resumen.cuentas);
+    imprimir_resumen("Transferencias", &resumen.transferencias);
+    imprimir_resumen("Arqueos", &resumen.arqueos);
+    imprimir_resumen("Líneas de extracto", &resumen.lineas_extracto);
+    imprimir_resumen("Reglas", &resumen.reglas);
+    imprimir_resumen("Comprobantes", &resumen.adjuntos);
+
+    if resumen.adjuntos_sin_archivo > 0 {
+        println!();
+        println!(
+            "{}",
+            format!(
+                "⚠️  {} comprobantes sin importar: sus archivos no están en el almacén de adjuntos",
+                resumen.adjuntos_sin_archivo
+            )
+            .yellow()
+        );
+    }
+
+    Ok(())
+}
+
+fn imprimir_resumen(entidad: &str, resumen: &ResumenEntidad) {
+    println!(
+        "   {}: {} nuevos, {} sin cambios, {} sobrescritos, {} omitidos",
+        entidad,
+        resumen.insertados.to_string().bright_green(),
+        resumen.sin_cambios,
+        resumen.sobrescritos.to_string().bright_yellow(),
+        resumen.omitidos.to_string().bright_black()
+    );
+}
+
+async fn purgar_adjuntos(backups: Backups, database_url: &str) -> Result<()> {
+    println!("{}", "🧹 Purgando archivos de comprobantes...".cyan().bold());
+
+    let db = Database::new(database_url).await?;
+    let borrados = AlmacenAdjuntos::new(database_url)?
+        .purgar_huerfanos(db.pool(), &backups)
+        .await?;
+
+    println!("{}", "✅ Purga completada!".green().bold());
+    println!("   Archivos borrados: {}", borrados.to_string().bright_green());
+
+    Ok(())
+}
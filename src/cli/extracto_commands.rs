@@ -0,0 +1,753 @@
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+use clap::{Args, Subcommand};
+use clap_complete::ArgValueCandidates;
+use colored::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::completions;
+use super::output::{self, OutputFormat};
+use super::prompts;
+use super::resolver;
+use crate::database::Database;
+use crate::extractos;
+use crate::models::{
+    AccionExtracto, CandidatoExtracto, DecisionExtracto, EstadoCandidato, FormatoExtracto,
+    InformeConciliacion, ResumenExtracto, TipoTransaccion,
+};
+use crate::repositories::{
+    CategoriaRepository, CuentaRepository, ExtractoRepository, OpcionesExtracto, RetiroRepository,
+    TransaccionRepository,
+};
+use crate::{AppError, Result};
+
+#[derive(Subcommand)]
+pub enum ExtractoCommands {
+    /// Importar un extracto bancario (Norma 43, CAMT.053 u OFX) en un retiro
+    Importar(ImportarExtractoArgs),
+    /// Listar las líneas de extracto ya importadas
+    Lineas(LineasArgs),
+    /// Informe de conciliación: movimientos del banco y transacciones sin enlazar
+    Conciliacion(ConciliacionArgs),
+    /// Cerrar la conciliación: las transacciones punteadas pasan a conciliadas
+    Conciliar(ConciliacionArgs),
+    /// Enlazar a mano una línea de extracto con una transacción
+    Vincular(VincularArgs),
+    /// Deshacer el enlace de una línea de extracto con su transacción
+    Desvincular(DesvincularArgs),
+}
+
+#[derive(Args)]
+pub struct ImportarExtractoArgs {
+    /// Archivo del extracto
+    pub archivo: PathBuf,
+
+    /// Retiro al que pertenecen los movimientos (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_retiros))]
+    pub retiro_id: String,
+
+    /// Cuenta bancaria del extracto (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_cuentas))]
+    pub cuenta: Option<String>,
+
+    /// Formato del extracto (por defecto se detecta por el contenido)
+    #[arg(long, value_enum)]
+    pub formato: Option<CliFormatoExtracto>,
+
+    /// Categoría de los abonos que se importen como ingresos (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_categorias))]
+    pub categoria_ingreso: Option<String>,
+
+    /// Categoría de los cargos que se importen como gastos (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_categorias))]
+    pub categoria_gasto: Option<String>,
+
+    /// Días de diferencia admitidos al buscar transacciones ya registradas
+    #[arg(long, default_value = "3")]
+    pub dias: u32,
+
+    /// Revisar cada movimiento antes de importarlo
+    #[arg(long, conflicts_with = "aplicar")]
+    pub revisar: bool,
+
+    /// Importar sin preguntar: crea los movimientos nuevos y enlaza los que coinciden
+    #[arg(long)]
+    pub aplicar: bool,
+}
+
+#[derive(Args)]
+pub struct LineasArgs {
+    /// Filtrar por retiro (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_retiros))]
+    pub retiro_id: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ConciliacionArgs {
+    /// Retiro (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_retiros))]
+    pub retiro_id: String,
+
+    /// Limitar a una cuenta (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_cuentas))]
+    pub cuenta: Option<String>,
+}
+
+#[derive(Args)]
+pub struct VincularArgs {
+    /// Línea de extracto (ID o prefijo del ID)
+    pub linea: String,
+
+    /// Transacción (ID o prefijo del ID)
+    pub transaccion: String,
+}
+
+#[derive(Args)]
+pub struct DesvincularArgs {
+    /// Línea de extracto (ID o prefijo del ID)
+    pub linea: String,
+}
+
+#[derive(clap::ValueEnum, Clone)]
+pub enum CliFormatoExtracto {
+    /// Cuaderno 43 de la AEB
+    Norma43,
+    /// ISO 20022 camt.053 (XML)
+    Camt053,
+    /// Open Financial Exchange
+    Ofx,
+}
+
+impl From<CliFormatoExtracto> for FormatoExtracto {
+    fn from(cli_formato: CliFormatoExtracto) -> Self {
+        match cli_formato {
+            CliFormatoExtracto::Norma43 => FormatoExtracto::Norma43,
+            CliFormatoExtracto::Camt053 => FormatoExtracto::Camt053,
+            CliFormatoExtracto::Ofx => FormatoExtracto::Ofx,
+        }
+    }
+}
+
+pub async fn handle_extracto_command(
+    command: ExtractoCommands,
+    database_url: &str,
+    output: OutputFormat,
+) -> Result<()> {
+    // Conectar a la base de datos
+    let db = Database::new(database_url).await?;
+    let repo = ExtractoRepository::new(db.pool().clone());
+
+    match command {
+        ExtractoCommands::Importar(args) => importar_extracto(&db, repo, args, output).await,
+        ExtractoCommands::Lineas(args) => listar_lineas(&db, repo, args, output).await,
+        ExtractoCommands::Conciliacion(args) => {
+            informe_conciliacion(&db, repo, args, output).await
+        }
+        ExtractoCommands::Conciliar(args) => conciliar(&db, repo, args, output).await,
+        ExtractoCommands::Vincular(args) => vincular_linea(&db, repo, args, output).await,
+        ExtractoCommands::Desvincular(args) => desvincular_linea(repo, args, output).await,
+    }
+}
+
+async fn importar_extracto(
+    db: &Database,
+    repo: ExtractoRepository,
+    args: ImportarExtractoArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let revisar = prompts::modo_interactivo(args.revisar)?;
+
+    let retiro_id =
+        resolver::resolver_retiro(&RetiroRepository::new(db.pool().clone()), &args.retiro_id)
+            .await?;
+    let cuenta_id = match &args.cuenta {
+        Some(referencia) => Some(
+            resolver::resolver_cuenta(&CuentaRepository::new(db.pool().clone()), referencia)
+                .await?,
+        ),
+        None => None,
+    };
+    let categoria_repo = CategoriaRepository::new(db.pool().clone());
+    let categoria_ingreso_id = match &args.categoria_ingreso {
+        Some(referencia) => Some(resolver::resolver_categoria(&categoria_repo, referencia).await?),
+        None => None,
+    };
+    let categoria_gasto_id = match &args.categoria_gasto {
+        Some(referencia) => Some(resolver::resolver_categoria(&categoria_repo, referencia).await?),
+        None => None,
+    };
+
+    let contenido = std::fs::read(&args.archivo).map_err(|e| {
+        AppError::Validation(format!("No se pudo leer {}: {}", args.archivo.display(), e))
+    })?;
+    let (formato, movimientos) = extractos::leer(&contenido, args.formato.map(Into::into))?;
+
+    let opciones = OpcionesExtracto {
+        retiro_id,
+        cuenta_id,
+        categoria_ingreso_id,
+        categoria_gasto_id,
+        dias: args.dias,
+    };
+    let candidatos = repo.preparar(formato, &movimientos, &opciones).await?;
+
+    if output.is_structured() && !args.aplicar {
+        return print_candidatos(output, &candidatos);
+    }
+
+    let decisiones = if revisar {
+        match revisar_candidatos(candidatos)? {
+            Some(decisiones) => decisiones,
+            None => {
+                println!("{}", "🚫 Importación cancelada.".yellow());
+                return Ok(());
+            }
+        }
+    } else if args.aplicar {
+        candidatos
+            .into_iter()
+            .map(|candidato| DecisionExtracto {
+                accion: accion_por_defecto(&candidato),
+                candidato,
+            })
+            .collect()
+    } else {
+        mostrar_vista_previa(formato, &candidatos);
+        return Ok(());
+    };
+
+    if !output.is_structured() {
+        println!("{}", "🏦 Importando extracto...".cyan().bold());
+    }
+
+    let resumen = repo.importar(decisiones).await?;
+    if output.is_structured() {
+        return output::print_one(output, &resumen);
+    }
+
+    println!("{}", "✅ Extracto importado!".green().bold());
+    imprimir_resumen(&resumen);
+
+    Ok(())
+}
+
+/// Sin revisión: crear lo nuevo, enlazar lo que coincide y no repetir lo ya importado
+fn accion_por_defecto(candidato: &CandidatoExtracto) -> AccionExtracto {
+    match candidato.estado {
+        EstadoCandidato::Nueva => AccionExtracto::Importar,
+        EstadoCandidato::Coincide => AccionExtracto::Vincular,
+        EstadoCandidato::YaImportada => AccionExtracto::Omitir,
+    }
+}
+
+/// Preguntar qué hacer con cada movimiento; `None` si se cancela la importación
+fn revisar_candidatos(candidatos: Vec<CandidatoExtracto>) -> Result<Option<Vec<DecisionExtracto>>> {
+    let pendientes = candidatos
+        .iter()
+        .filter(|c| c.estado != EstadoCandidato::YaImportada)
+        .count();
+    println!(
+        "{} {} {}",
+        "🔎 Revisando".cyan().bold(),
+        pendientes.to_string().bright_white().bold(),
+        "movimientos...".cyan().bold()
+    );
+
+    let mut decisiones = Vec::new();
+    let mut aceptar_resto = false;
+    let mut revisado = 0;
+    for candidato in candidatos {
+        if candidato.estado == EstadoCandidato::YaImportada || aceptar_resto {
+            decisiones.push(DecisionExtracto {
+                accion: accion_por_defecto(&candidato),
+                candidato,
+            });
+            continue;
+        }
+
+        revisado += 1;
+        println!();
+        println!(
+            "[{}/{}] {}  {}  {}",
+            revisado,
+            pendientes,
+            candidato.fecha.format("%d/%m/%Y").to_string().bright_cyan(),
+            formatear_monto(candidato.monto),
+            candidato.descripcion.bright_white()
+        );
+        if let Some(transaccion_id) = candidato.transaccion_id {
+            println!(
+                "   Coincide con la transacción {} (parecido {:.0}%)",
+                transaccion_id.to_string()[..8].bright_blue(),
+                candidato.puntuacion.unwrap_or_default() * 100.0
+            );
+        }
+        if let Some(regla) = &candidato.regla {
+            println!("   Categorizado por la regla '{}'", regla.bright_white());
+        }
+
+        let mut opciones = Vec::new();
+        if candidato.estado == EstadoCandidato::Coincide {
+            opciones.push((AccionExtracto::Vincular, "Es la transacción ya registrada"));
+        }
+        if candidato.propuesta.is_some() {
+            opciones.push((AccionExtracto::Importar, "Importar como transacción nueva"));
+        }
+        opciones.push((AccionExtracto::Omitir, "Omitir"));
+        let textos: Vec<&str> = opciones
+            .iter()
+            .map(|(_, texto)| *texto)
+            .chain(["Aceptar el resto sin preguntar", "Cancelar la importación"])
+            .collect();
+
+        let eleccion = prompts::seleccionar("¿Qué hacer?", &textos, 0)?;
+        let accion = match eleccion.checked_sub(opciones.len()) {
+            None => opciones[eleccion].0,
+            Some(0) => {
+                aceptar_resto = true;
+                accion_por_defecto(&candidato)
+            }
+            Some(_) => return Ok(None),
+        };
+        // Sin categoría para su tipo, un movimiento nuevo no se puede importar
+        let accion = if accion == AccionExtracto::Importar && candidato.propuesta.is_none() {
+            AccionExtracto::Omitir
+        } else {
+            accion
+        };
+        decisiones.push(DecisionExtracto { candidato, accion });
+    }
+
+    Ok(Some(decisiones))
+}
+
+fn mostrar_vista_previa(formato: FormatoExtracto, candidatos: &[CandidatoExtracto]) {
+    println!(
+        "{} {}",
+        "🏦 Movimientos del extracto".cyan().bold(),
+        format!("({})", formato).bright_black()
+    );
+    println!();
+
+    println!(
+        "{:<12} {:>12} {:<40} {:<13} {:<10}",
+        "FECHA".bold(),
+        "MONTO".bold(),
+        "DESCRIPCIÓN".bold(),
+        "ESTADO".bold(),
+        "TRANSACCIÓN".bold()
+    );
+    println!("{}", "─".repeat(92).bright_black());
+
+    for candidato in candidatos {
+        let descripcion: String = candidato.descripcion.chars().take(40).collect();
+        let estado = match candidato.estado {
+            EstadoCandidato::Nueva => candidato.estado.to_string().bright_green(),
+            EstadoCandidato::Coincide => candidato.estado.to_string().bright_yellow(),
+            EstadoCandidato::YaImportada => candidato.estado.to_string().bright_black(),
+        };
+        println!(
+            "{:<12} {:>12} {:<40} {:<13} {:<10}",
+            candidato.fecha.format("%d/%m/%Y").to_string().bright_cyan(),
+            formatear_monto(candidato.monto),
+            descripcion.bright_white(),
+            estado,
+            candidato
+                .transaccion_id
+                .map(|id| id.to_string()[..8].to_string())
+                .unwrap_or_default()
+                .bright_blue()
+        );
+    }
+
+    let contar = |estado| candidatos.iter().filter(|c| c.estado == estado).count();
+    println!();
+    println!("   Nuevos: {}", contar(EstadoCandidato::Nueva));
+    println!("   Ya registrados: {}", contar(EstadoCandidato::Coincide));
+    println!("   Ya importados: {}", contar(EstadoCandidato::YaImportada));
+
+    let por_reglas = candidatos
+        .iter()
+        .filter(|c| c.estado == EstadoCandidato::Nueva && c.regla.is_some())
+        .count();
+    if por_reglas > 0 {
+        println!("   Nuevos categorizados por reglas: {}", por_reglas);
+    }
+
+    let sin_categoria = candidatos
+        .iter()
+        .filter(|c| c.estado == EstadoCandidato::Nueva && c.propuesta.is_none())
+        .count();
+    if sin_categoria > 0 {
+        println!();
+        println!(
+            "{}",
+            format!(
+                "⚠️  {} movimientos nuevos no tienen categoría: usa --categoria-ingreso y --categoria-gasto o crea reglas con 'regla crear'.",
+                sin_categoria
+            )
+            .yellow()
+        );
+    }
+    println!();
+    println!(
+        "{}",
+        "Usa --revisar para decidir movimiento a movimiento o --aplicar para importarlos."
+            .bright_black()
+    );
+}
+
+fn imprimir_resumen(resumen: &ResumenExtracto) {
+    println!("   Transacciones creadas: {}", resumen.creadas);
+    println!("   Enlazadas con transacciones existentes: {}", resumen.vinculadas);
+    println!("   Omitidas: {}", resumen.omitidas);
+    println!("   Ya importadas antes: {}", resumen.ya_importadas);
+}
+
+fn formatear_monto(monto: f64) -> ColoredString {
+    if monto >= 0.0 {
+        format!("+€{:.2}", monto).green()
+    } else {
+        format!("-€{:.2}", -monto).red()
+    }
+}
+
+/// Candidato como fila de CSV, sin la transacción propuesta
+#[derive(Serialize)]
+struct FilaCandidato<'a> {
+    fecha: NaiveDate,
+    monto: f64,
+    descripcion: &'a str,
+    referencia: &'a Option<String>,
+    estado: EstadoCandidato,
+    transaccion_id: Option<Uuid>,
+    puntuacion: Option<f64>,
+    huella: &'a str,
+    regla: &'a Option<String>,
+}
+
+fn print_candidatos(output: OutputFormat, candidatos: &[CandidatoExtracto]) -> Result<()> {
+    if output != OutputFormat::Csv {
+        return output::print_list(output, candidatos);
+    }
+
+    let filas: Vec<FilaCandidato> = candidatos
+        .iter()
+        .map(|c| FilaCandidato {
+            fecha: c.fecha,
+            monto: c.monto,
+            descripcion: &c.descripcion,
+            referencia: &c.referencia,
+            estado: c.estado,
+            transaccion_id: c.transaccion_id,
+            puntuacion: c.puntuacion,
+            huella: &c.huella,
+            regla: &c.regla,
+        })
+        .collect();
+    output::print_list(output, &filas)
+}
+
+async fn listar_lineas(
+    db: &Database,
+    repo: ExtractoRepository,
+    args: LineasArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let retiro_id = match &args.retiro_id {
+        Some(referencia) => Some(
+            resolver::resolver_retiro(&RetiroRepository::new(db.pool().clone()), referencia)
+                .await?,
+        ),
+        None => None,
+    };
+    let lineas = repo.get_lineas(retiro_id).await?;
+
+    if output.is_structured() {
+        return output::print_list(output, &lineas);
+    }
+
+    println!("{}", "🏦 Listando líneas de extracto...".cyan().bold());
+    println!();
+
+    if lineas.is_empty() {
+        println!("{}", "📭 No se ha importado ningún extracto.".yellow());
+        return Ok(());
+    }
+
+    println!(
+        "{:<10} {:<12} {:>12} {:<40} {:<9} {:<10}",
+        "ID".bold(),
+        "FECHA".bold(),
+        "MONTO".bold(),
+        "DESCRIPCIÓN".bold(),
+        "FORMATO".bold(),
+        "TRANSACCIÓN".bold()
+    );
+    println!("{}", "─".repeat(98).bright_black());
+
+    for linea in &lineas {
+        let descripcion: String = linea.descripcion.chars().take(40).collect();
+        println!(
+            "{:<10} {:<12} {:>12} {:<40} {:<9} {:<10}",
+            linea.id.to_string()[..8].bright_blue(),
+            linea.fecha.format("%d/%m/%Y").to_string().bright_cyan(),
+            formatear_monto(linea.monto),
+            descripcion.bright_white(),
+            linea.formato.to_string().bright_black(),
+            linea
+                .transaccion_id
+                .map(|id| id.to_string()[..8].to_string())
+                .unwrap_or_else(|| "-".to_string())
+                .bright_blue()
+        );
+    }
+
+    println!();
+    println!("   Líneas: {}", lineas.len());
+
+    Ok(())
+}
+
+/// Retiro y cuenta (opcional) de los comandos de conciliación
+async fn resolver_ambito(db: &Database, args: &ConciliacionArgs) -> Result<(Uuid, Option<Uuid>)> {
+    let retiro_id =
+        resolver::resolver_retiro(&RetiroRepository::new(db.pool().clone()), &args.retiro_id)
+            .await?;
+    let cuenta_id = match &args.cuenta {
+        Some(referencia) => Some(
+            resolver::resolver_cuenta(&CuentaRepository::new(db.pool().clone()), referencia)
+                .await?,
+        ),
+        None => None,
+    };
+    Ok((retiro_id, cuenta_id))
+}
+
+/// Partida sin enlazar del informe de conciliación, como fila de CSV
+#[derive(Serialize)]
+struct FilaConciliacion<'a> {
+    /// "Banco" para las líneas de extracto y "Libros" para las transacciones
+    lado: &'static str,
+    id: Uuid,
+    fecha: NaiveDate,
+    monto: f64,
+    descripcion: &'a str,
+}
+
+fn print_informe(output: OutputFormat, informe: &InformeConciliacion) -> Result<()> {
+    if output != OutputFormat::Csv {
+        return output::print_one(output, informe);
+    }
+
+    let bancarias = informe.lineas_sin_transaccion.iter().map(|l| FilaConciliacion {
+        lado: "Banco",
+        id: l.id,
+        fecha: l.fecha,
+        monto: l.monto,
+        descripcion: &l.descripcion,
+    });
+    let contables = informe.transacciones_sin_linea.iter().map(|t| FilaConciliacion {
+        lado: "Libros",
+        id: t.id,
+        fecha: t.created_at.date_naive(),
+        monto: match t.tipo {
+            TipoTransaccion::Ingreso => t.monto,
+            TipoTransaccion::Gasto => -t.monto,
+        },
+        descripcion: &t.descripcion,
+    });
+    let filas: Vec<FilaConciliacion> = bancarias.chain(contables).collect();
+    output::print_list(output, &filas)
+}
+
+async fn informe_conciliacion(
+    db: &Database,
+    repo: ExtractoRepository,
+    args: ConciliacionArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let (retiro_id, cuenta_id) = resolver_ambito(db, &args).await?;
+    let informe = repo.informe_conciliacion(retiro_id, cuenta_id).await?;
+
+    if output.is_structured() {
+        return print_informe(output, &informe);
+    }
+
+    println!("{}", "🏦 Conciliación bancaria".cyan().bold());
+    println!();
+    println!("📊 {}", "Transacciones:".bold());
+    println!("   Pendientes: {}", informe.pendientes.to_string().yellow());
+    println!("   Punteadas: {}", informe.punteadas.to_string().bright_cyan());
+    println!("   Conciliadas: {}", informe.conciliadas.to_string().green());
+
+    println!();
+    println!(
+        "🏦 {} ({})",
+        "Movimientos del banco sin transacción".bold(),
+        informe.lineas_sin_transaccion.len()
+    );
+    if informe.lineas_sin_transaccion.is_empty() {
+        println!("   {}", "Ninguno".bright_black());
+    }
+    for linea in &informe.lineas_sin_transaccion {
+        let descripcion: String = linea.descripcion.chars().take(40).collect();
+        println!(
+            "   {:<10} {:<12} {:>12} {}",
+            linea.id.to_string()[..8].bright_blue(),
+            linea.fecha.format("%d/%m/%Y").to_string().bright_cyan(),
+            formatear_monto(linea.monto),
+            descripcion.bright_white()
+        );
+    }
+
+    println!();
+    println!(
+        "📒 {} ({})",
+        "Transacciones pendientes sin movimiento en el banco".bold(),
+        informe.transacciones_sin_linea.len()
+    );
+    if informe.transacciones_sin_linea.is_empty() {
+        println!("   {}", "Ninguna".bright_black());
+    }
+    for transaccion in &informe.transacciones_sin_linea {
+        let descripcion: String = transaccion.descripcion.chars().take(40).collect();
+        let monto = match transaccion.tipo {
+            TipoTransaccion::Ingreso => transaccion.monto,
+            TipoTransaccion::Gasto => -transaccion.monto,
+        };
+        println!(
+            "   {:<10} {:<12} {:>12} {}",
+            transaccion.id.to_string()[..8].bright_blue(),
+            transaccion
+                .created_at
+                .format("%d/%m/%Y")
+                .to_string()
+                .bright_cyan(),
+            formatear_monto(monto),
+            descripcion.bright_white()
+        );
+    }
+
+    println!();
+    println!("💰 {}", "Totales:".bold());
+    println!("   Extracto: {}", formatear_monto(informe.total_extracto));
+    println!("   Libros: {}", formatear_monto(informe.total_libros));
+    let diferencia = informe.diferencia();
+    if diferencia == 0.0 {
+        println!("   Diferencia: {}", "€0.00 ✅".green().bold());
+    } else {
+        println!("   Diferencia: {}", formatear_monto(diferencia).bold());
+    }
+
+    if !informe.lineas_sin_transaccion.is_empty() {
+        println!();
+        println!(
+            "{}",
+            "Usa `extracto vincular LINEA TRANSACCION` para enlazar un movimiento con su transacción."
+                .bright_black()
+        );
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct ResultadoConciliar {
+    conciliadas: u64,
+}
+
+async fn conciliar(
+    db: &Database,
+    repo: ExtractoRepository,
+    args: ConciliacionArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let (retiro_id, cuenta_id) = resolver_ambito(db, &args).await?;
+    let conciliadas = repo.conciliar(retiro_id, cuenta_id).await?;
+
+    if output.is_structured() {
+        return output::print_one(output, &ResultadoConciliar { conciliadas });
+    }
+
+    if conciliadas == 0 {
+        println!("{}", "📭 No hay transacciones punteadas que conciliar.".yellow());
+    } else {
+        println!("{}", "✅ Conciliación cerrada!".green().bold());
+        println!("   Transacciones conciliadas: {}", conciliadas);
+    }
+
+    let informe = repo.informe_conciliacion(retiro_id, cuenta_id).await?;
+    let sin_enlazar = informe.lineas_sin_transaccion.len() + informe.transacciones_sin_linea.len();
+    if sin_enlazar > 0 {
+        println!(
+            "{}",
+            format!(
+                "⚠️  Quedan {} partidas sin enlazar: revisa `extracto conciliacion`.",
+                sin_enlazar
+            )
+            .yellow()
+        );
+    }
+
+    Ok(())
+}
+
+async fn vincular_linea(
+    db: &Database,
+    repo: ExtractoRepository,
+    args: VincularArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let linea_id = resolver::resolver_linea_extracto(&repo, &args.linea).await?;
+    let transaccion_id = resolver::resolver_transaccion(
+        &TransaccionRepository::new(db.pool().clone()),
+        &args.transaccion,
+    )
+    .await?;
+
+    let linea = repo.vincular(linea_id, transaccion_id).await?;
+    if output.is_structured() {
+        return output::print_one(output, &linea);
+    }
+
+    println!("{}", "🔗 Línea enlazada!".green().bold());
+    println!(
+        "   {}  {}  {}",
+        linea.fecha.format("%d/%m/%Y").to_string().bright_cyan(),
+        formatear_monto(linea.monto),
+        linea.descripcion.bright_white()
+    );
+    println!(
+        "   Transacción: {} (punteada)",
+        transaccion_id.to_string()[..8].bright_blue()
+    );
+
+    Ok(())
+}
+
+async fn desvincular_linea(
+    repo: ExtractoRepository,
+    args: DesvincularArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let linea_id = resolver::resolver_linea_extracto(&repo, &args.linea).await?;
+
+    let linea = repo.desvincular(linea_id).await?;
+    if output.is_structured() {
+        return output::print_one(output, &linea);
+    }
+
+    println!("{}", "✂️  Enlace deshecho.".green().bold());
+    println!(
+        "   {}  {}  {}",
+        linea.fecha.format("%d/%m/%Y").to_string().bright_cyan(),
+        formatear_monto(linea.monto),
+        linea.descripcion.bright_white()
+    );
+    println!("   La transacción vuelve a estar pendiente.");
+
+    Ok(())
+}
@@ -1,16 +1,41 @@
 pub mod categoria_commands;
 pub mod commands;
+pub mod completions;
+pub mod cuenta_commands;
+pub mod db_commands;
+pub mod extracto_commands;
+pub mod output;
+pub mod papelera_commands;
+pub mod plantilla_commands;
+pub mod prompts;
+pub mod regla_commands;
+pub mod resolver;
 pub mod retiro_commands;
 pub mod transaccion_commands;
+pub mod workspace_commands;
 
+use crate::config;
 use crate::Result;
 use clap::{Parser, Subcommand};
+use output::OutputFormat;
 
 #[derive(Parser)]
 #[command(name = "retiros")]
 #[command(about = "Sistema de Gestión Financiera para Retiros")]
 #[command(version = "0.1.0")]
 pub struct Cli {
+    /// Base de datos a usar (ruta o URL sqlite:), tiene prioridad sobre DATABASE_URL y config.toml
+    #[arg(long, global = true)]
+    pub database: Option<String>,
+
+    /// Workspace a usar en lugar del activo
+    #[arg(long, global = true)]
+    pub workspace: Option<String>,
+
+    /// Formato de salida de listar, mostrar, balance y buscar
+    #[arg(long, global = true, value_enum, default_value = "table")]
+    pub output: OutputFormat,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -26,18 +51,93 @@ pub enum Commands {
     /// Gestión de transacciones financieras
     #[command(subcommand)]
     Transaccion(transaccion_commands::TransaccionCommands),
+    /// Cuentas (caja, banco...), sus saldos y transferencias entre ellas
+    #[command(subcommand)]
+    Cuenta(cuenta_commands::CuentaCommands),
+    /// Importar extractos bancarios y compararlos con lo registrado
+    #[command(subcommand)]
+    Extracto(extracto_commands::ExtractoCommands),
+    /// Plantillas de transacciones que se repiten en cada retiro
+    #[command(subcommand)]
+    Plantilla(plantilla_commands::PlantillaCommands),
+    /// Reglas para categorizar automáticamente las transacciones
+    #[command(subcommand)]
+    Regla(regla_commands::ReglaCommands),
+    /// Papelera: restaurar o borrar definitivamente lo eliminado
+    #[command(subcommand)]
+    Papelera(papelera_commands::PapeleraCommands),
+    /// Copias de seguridad de la base de datos
+    #[command(subcommand)]
+    Db(db_commands::DbCommands),
+    /// Gestión de workspaces (una base de datos por organización)
+    #[command(subcommand)]
+    Workspace(workspace_commands::WorkspaceCommands),
+    /// Interfaz de terminal a pantalla completa
+    Tui,
+    /// Generar el script de autocompletado para bash, zsh o fish
+    Completions(completions::CompletionsArgs),
 }
 
-pub async fn run_cli() -> Result<()> {
-    let cli = Cli::parse();
+pub async fn run_cli(cli: Cli) -> Result<()> {
+    // Los comandos de workspace trabajan sobre la configuración, no sobre una base de datos concreta
+    let database_url =
+        || config::resolve_database_url(cli.database.as_deref(), cli.workspace.as_deref());
+
+    // Purga de la papelera según `[papelera] dias` antes de los comandos que usan los datos
+    if matches!(
+        cli.command,
+        Commands::Categoria(_)
+            | Commands::Retiro(_)
+            | Commands::Transaccion(_)
+            | Commands::Cuenta(_)
+            | Commands::Extracto(_)
+            | Commands::Plantilla(_)
+            | Commands::Regla(_)
+            | Commands::Papelera(_)
+            | Commands::Tui
+    ) {
+        papelera_commands::purgar_automatico(&database_url()?).await;
+    }
 
     match cli.command {
         Commands::Categoria(categoria_cmd) => {
-            categoria_commands::handle_categoria_command(categoria_cmd).await
+            categoria_commands::handle_categoria_command(categoria_cmd, &database_url()?, cli.output)
+                .await
+        }
+        Commands::Retiro(retiro_cmd) => {
+            retiro_commands::handle_retiro_command(retiro_cmd, &database_url()?, cli.output).await
         }
-        Commands::Retiro(retiro_cmd) => retiro_commands::handle_retiro_command(retiro_cmd).await,
         Commands::Transaccion(transaccion_cmd) => {
-            transaccion_commands::handle_transaccion_command(transaccion_cmd).await
+            transaccion_commands::handle_transaccion_command(
+                transaccion_cmd,
+                &database_url()?,
+                cli.output,
+            )
+            .await
+        }
+        Commands::Cuenta(cuenta_cmd) => {
+            cuenta_commands::handle_cuenta_command(cuenta_cmd, &database_url()?, cli.output).await
+        }
+        Commands::Extracto(extracto_cmd) => {
+            extracto_commands::handle_extracto_command(extracto_cmd, &database_url()?, cli.output)
+                .await
+        }
+        Commands::Plantilla(plantilla_cmd) => {
+            plantilla_commands::handle_plantilla_command(plantilla_cmd, &database_url()?, cli.output)
+                .await
+        }
+        Commands::Regla(regla_cmd) => {
+            regla_commands::handle_regla_command(regla_cmd, &database_url()?, cli.output).await
+        }
+        Commands::Papelera(papelera_cmd) => {
+            papelera_commands::handle_papelera_command(papelera_cmd, &database_url()?, cli.output)
+                .await
+        }
+        Commands::Db(db_cmd) => db_commands::handle_db_command(db_cmd, &database_url()?).await,
+        Commands::Workspace(workspace_cmd) => {
+            workspace_commands::handle_workspace_command(workspace_cmd).await
         }
+        Commands::Tui => crate::tui::run_tui(&database_url()?).await,
+        Commands::Completions(args) => completions::handle_completions_command(args),
     }
 }
@@ -0,0 +1,74 @@
+// Formatos de salida del CLI: tabla coloreada para personas, JSON y CSV para scripts
+
+use serde::Serialize;
+
+use crate::{AppError, Result};
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Texto coloreado para la terminal
+    #[default]
+    Table,
+    /// JSON con los datos de los modelos
+    Json,
+    /// CSV con cabecera
+    Csv,
+}
+
+impl OutputFormat {
+    /// Indica si la salida es para otros programas (sin texto decorativo)
+    pub fn is_structured(self) -> bool {
+        self != OutputFormat::Table
+    }
+}
+
+/// Emitir una lista de registros en formato JSON o CSV
+pub fn print_list<T: Serialize>(format: OutputFormat, rows: &[T]) -> Result<()> {
+    match format {
+        OutputFormat::Csv => print_csv(rows),
+        _ => print_json(&rows),
+    }
+}
+
+/// Emitir un único registro en formato JSON o CSV
+pub fn print_one<T: Serialize>(format: OutputFormat, value: &T) -> Result<()> {
+    match format {
+        OutputFormat::Csv => print_csv(std::slice::from_ref(value)),
+        _ => print_json(value),
+    }
+}
+
+fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<()> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| AppError::Internal(format!("Error serializando JSON: {}", e)))?;
+    println!("{}", json);
+    Ok(())
+}
+
+fn print_csv<T: Serialize>(rows: &[T]) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(std::io::stdout());
+    for row in rows {
+        writer
+            .serialize(row)
+            .map_err(|e| AppError::Internal(format!("Error serializando CSV: {}", e)))?;
+    }
+    writer
+        .flush()
+        .map_err(|e| AppError::Internal(format!("Error escribiendo CSV: {}", e)))
+}
+
+/// Mostrar un error en stderr; con `--output json` se emite como objeto JSON
+pub fn print_error(format: OutputFormat, error: &AppError) {
+    match format {
+        OutputFormat::Json => {
+            let json = serde_json::json!({
+                "error": {
+                    "code": error.code(),
+                    "message": error.to_string(),
+                }
+            });
+            eprintln!("{}", json);
+        }
+        _ => eprintln!("Error: {}", error),
+    }
+}
@@ -0,0 +1,215 @@
+use chrono::{Duration, Utc};
+use clap::{Args, Subcommand};
+use clap_complete::ArgValueCandidates;
+use colored::*;
+
+use super::completions;
+use super::output::{self, OutputFormat};
+use super::resolver;
+use crate::database::Database;
+use crate::models::{ResumenVaciado, TipoElementoPapelera};
+use crate::repositories::PapeleraRepository;
+use crate::{AppError, Result};
+
+#[derive(Subcommand)]
+pub enum PapeleraCommands {
+    /// Listar retiros, categorías y transacciones eliminados
+    Listar,
+    /// Restaurar un elemento de la papelera
+    Restaurar(RestaurarArgs),
+    /// Borrar definitivamente los elementos de la papelera
+    Vaciar(VaciarArgs),
+}
+
+#[derive(Args)]
+pub struct RestaurarArgs {
+    /// Elemento a restaurar (ID, prefijo del ID o nombre)
+    #[arg(add = ArgValueCandidates::new(completions::completar_papelera))]
+    pub id: String,
+}
+
+#[derive(Args)]
+pub struct VaciarArgs {
+    /// Borrar solo lo que lleva más de N días en la papelera
+    #[arg(long)]
+    pub dias: Option<u32>,
+
+    /// Confirmar el borrado sin preguntar
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+pub async fn handle_papelera_command(
+    command: PapeleraCommands,
+    database_url: &str,
+    output: OutputFormat,
+) -> Result<()> {
+    // Conectar a la base de datos
+    let db = Database::new(database_url).await?;
+    let repo = PapeleraRepository::new(db.pool().clone());
+
+    match command {
+        PapeleraCommands::Listar => listar_papelera(repo, output).await,
+        PapeleraCommands::Restaurar(args) => restaurar_elemento(repo, args).await,
+        PapeleraCommands::Vaciar(args) => vaciar_papelera(repo, args).await,
+    }
+}
+
+async fn listar_papelera(repo: PapeleraRepository, output: OutputFormat) -> Result<()> {
+    let elementos = repo.listar().await?;
+
+    if output.is_structured() {
+        return output::print_list(output, &elementos);
+    }
+
+    println!("{}", "🗑️  Listando papelera...".cyan().bold());
+    println!();
+
+    if elementos.is_empty() {
+        println!("{}", "📭 La papelera está vacía.".yellow());
+        return Ok(());
+    }
+
+    println!(
+        "{:<10} {:<12} {:<35} {:<18}",
+        "ID".bold(),
+        "TIPO".bold(),
+        "DESCRIPCIÓN".bold(),
+        "ELIMINADO".bold()
+    );
+    println!("{}", "─".repeat(80).bright_black());
+
+    for elemento in &elementos {
+        let descripcion = if elemento.transacciones > 0 {
+            format!(
+                "{} (+{} transacciones)",
+                elemento.descripcion, elemento.transacciones
+            )
+        } else {
+            elemento.descripcion.clone()
+        };
+        let tipo_color = match elemento.tipo {
+            TipoElementoPapelera::Retiro => elemento.tipo.to_string().bright_cyan(),
+            TipoElementoPapelera::Categoria => elemento.tipo.to_string().bright_magenta(),
+            TipoElementoPapelera::Transaccion => elemento.tipo.to_string().bright_yellow(),
+        };
+
+        println!(
+            "{:<10} {:<12} {:<35} {:<18}",
+            elemento.id.to_string()[..8].bright_blue(),
+            tipo_color,
+            descripcion.bright_white(),
+            elemento
+                .deleted_at
+                .format("%d/%m/%Y %H:%M")
+                .to_string()
+                .bright_black()
+        );
+    }
+
+    println!();
+    println!("   Elementos: {}", elementos.len());
+
+    Ok(())
+}
+
+async fn restaurar_elemento(repo: PapeleraRepository, args: RestaurarArgs) -> Result<()> {
+    let id = resolver::resolver_papelera(&repo, &args.id).await?;
+
+    println!("{}", "♻️  Restaurando elemento...".cyan().bold());
+
+    let elemento = repo
+        .restaurar(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Elemento de la papelera".to_string()))?;
+    println!("{}", "✅ Elemento restaurado exitosamente!".green().bold());
+    println!("   Tipo: {}", elemento.tipo.to_string().bright_yellow());
+    println!("   Descripción: {}", elemento.descripcion.bright_white());
+    if elemento.transacciones > 0 {
+        println!(
+            "   Transacciones restauradas: {}",
+            elemento.transacciones.to_string().bright_green()
+        );
+    }
+
+    Ok(())
+}
+
+async fn vaciar_papelera(repo: PapeleraRepository, args: VaciarArgs) -> Result<()> {
+    let antes_de = args
+        .dias
+        .map(|dias| Utc::now() - Duration::days(i64::from(dias)));
+
+    if !args.force {
+        let pendientes = repo
+            .listar()
+            .await?
+            .iter()
+            .filter(|e| antes_de.is_none_or(|fecha| e.deleted_at <= fecha))
+            .count();
+        println!(
+            "{}",
+            "⚠️  ¿Estás seguro de que quieres vaciar la papelera?"
+                .yellow()
+                .bold()
+        );
+        println!("   Elementos a borrar: {}", pendientes.to_string().bright_white());
+        println!();
+        println!(
+            "{}",
+            "⚠️  ADVERTENCIA: Los elementos no se podrán restaurar.".red()
+        );
+        println!(
+            "{}",
+            "Usa --force para confirmar el borrado.".bright_black()
+        );
+        return Ok(());
+    }
+
+    println!("{}", "🗑️  Vaciando papelera...".cyan().bold());
+
+    let resumen = repo.vaciar(antes_de).await?;
+    println!("{}", "✅ Papelera vaciada!".green().bold());
+    imprimir_resumen(&resumen);
+
+    Ok(())
+}
+
+/// Purga automática de lo que supera la retención de `[papelera] dias`.
+/// Un fallo se registra pero no impide ejecutar el comando.
+pub async fn purgar_automatico(database_url: &str) {
+    let dias = match crate::config::Config::load() {
+        Ok(config) => config.papelera.dias,
+        Err(e) => {
+            tracing::error!("{}", e);
+            return;
+        }
+    };
+    if dias == 0 {
+        return;
+    }
+
+    let resultado = async {
+        let db = Database::new(database_url).await?;
+        PapeleraRepository::new(db.pool().clone())
+            .purgar_caducados(dias)
+            .await
+    }
+    .await;
+
+    match resultado {
+        Ok(resumen) if resumen.total() > 0 => tracing::info!(
+            "Papelera: {} elementos con más de {} días borrados definitivamente",
+            resumen.total(),
+            dias
+        ),
+        Ok(_) => {}
+        Err(e) => tracing::error!("Error purgando la papelera: {}", e),
+    }
+}
+
+fn imprimir_resumen(resumen: &ResumenVaciado) {
+    println!("   Retiros: {}", resumen.retiros);
+    println!("   Categorías: {}", resumen.categorias);
+    println!("   Transacciones: {}", resumen.transacciones);
+}
@@ -0,0 +1,348 @@
+use clap::{Args, Subcommand};
+use clap_complete::ArgValueCandidates;
+use colored::*;
+use validator::Validate;
+
+use super::completions;
+use super::output::{self, OutputFormat};
+use super::prompts;
+use super::resolver;
+use super::transaccion_commands::{tipo_transaccion, CliTipoTransaccion};
+use crate::database::Database;
+use crate::models::{CreatePlantillaTransaccion, CreateTransaccion, TipoTransaccion};
+use crate::repositories::{CategoriaRepository, PlantillaRepository, RetiroRepository};
+use crate::{AppError, Result};
+
+#[derive(Subcommand)]
+pub enum PlantillaCommands {
+    /// Crear una nueva plantilla de transacción
+    Crear(CrearPlantillaArgs),
+    /// Listar plantillas
+    Listar,
+    /// Eliminar una plantilla
+    Eliminar(EliminarPlantillaArgs),
+    /// Crear en un retiro las transacciones de varias plantillas a la vez
+    Aplicar(AplicarPlantillasArgs),
+}
+
+#[derive(Args)]
+pub struct CrearPlantillaArgs {
+    /// Nombre de la plantilla (se pregunta si no se indica)
+    #[arg(short, long)]
+    pub nombre: Option<String>,
+
+    /// Categoría: ID, prefijo del ID o nombre (se elige de una lista si no se indica)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_categorias))]
+    pub categoria_id: Option<String>,
+
+    /// Tipo de transacción (ingreso/gasto, por defecto el de la categoría)
+    #[arg(short, long, value_enum)]
+    pub tipo: Option<CliTipoTransaccion>,
+
+    /// Monto por defecto
+    #[arg(short, long)]
+    pub monto: Option<f64>,
+
+    /// Descripción de las transacciones generadas
+    #[arg(short, long)]
+    pub descripcion: Option<String>,
+}
+
+#[derive(Args)]
+pub struct EliminarPlantillaArgs {
+    /// Plantilla a eliminar (ID, prefijo del ID o nombre)
+    #[arg(add = ArgValueCandidates::new(completions::completar_plantillas))]
+    pub id: String,
+
+    /// Confirmar eliminación sin preguntar
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Args)]
+pub struct AplicarPlantillasArgs {
+    /// Retiro en el que crear las transacciones (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_retiros))]
+    pub retiro_id: String,
+
+    /// Plantillas a aplicar (ID, prefijo del ID o nombre); todas si no se indica ninguna
+    #[arg(add = ArgValueCandidates::new(completions::completar_plantillas))]
+    pub plantillas: Vec<String>,
+}
+
+pub async fn handle_plantilla_command(
+    command: PlantillaCommands,
+    database_url: &str,
+    output: OutputFormat,
+) -> Result<()> {
+    // Conectar a la base de datos
+    let db = Database::new(database_url).await?;
+    let repo = PlantillaRepository::new(db.pool().clone());
+
+    match command {
+        PlantillaCommands::Crear(args) => crear_plantilla(&db, repo, args).await,
+        PlantillaCommands::Listar => listar_plantillas(&db, repo, output).await,
+        PlantillaCommands::Eliminar(args) => eliminar_plantilla(repo, args).await,
+        PlantillaCommands::Aplicar(args) => aplicar_plantillas(&db, repo, args, output).await,
+    }
+}
+
+async fn crear_plantilla(
+    db: &Database,
+    repo: PlantillaRepository,
+    args: CrearPlantillaArgs,
+) -> Result<()> {
+    let categoria_repo = CategoriaRepository::new(db.pool().clone());
+
+    let nombre_borrador = CreatePlantillaTransaccion {
+        nombre: String::new(),
+        categoria_id: uuid::Uuid::nil(),
+        tipo: TipoTransaccion::Gasto,
+        monto: 1.0,
+        descripcion: "-".to_string(),
+    };
+    let nombre = prompts::requerido(args.nombre, "--nombre", || {
+        prompts::pedir_campo("Nombre", None, &nombre_borrador, "nombre", |p, v| {
+            p.nombre = v
+        })
+    })?;
+
+    let categoria_id = match &args.categoria_id {
+        Some(referencia) => resolver::resolver_categoria(&categoria_repo, referencia).await?,
+        None if prompts::is_interactive() => {
+            prompts::seleccionar_categoria(&categoria_repo, None).await?
+        }
+        None => return Err(prompts::falta_argumento("--categoria-id")),
+    };
+    let categoria = categoria_repo
+        .get_by_id(categoria_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Categoría".to_string()))?;
+
+    // Sin --tipo se usa el de la categoría
+    let tipo: TipoTransaccion = match args.tipo {
+        Some(tipo) => tipo.into(),
+        None => tipo_transaccion(&categoria.tipo),
+    };
+
+    // Monto y descripción se validan con las reglas de la transacción que generará
+    let borrador = CreateTransaccion {
+        retiro_id: uuid::Uuid::nil(),
+        categoria_id,
+        tipo: tipo.clone(),
+        monto: 1.0,
+        descripcion: "-".to_string(),
+        cuenta_id: None,
+        metodo_pago: None,
+    };
+    let monto = prompts::requerido(args.monto, "--monto", || {
+        prompts::pedir_campo("Monto por defecto (€)", None, &borrador, "monto", |t, v| {
+            t.monto = v
+        })
+    })?;
+    let descripcion = prompts::requerido(args.descripcion, "--descripcion", || {
+        prompts::pedir_campo("Descripción", None, &borrador, "descripcion", |t, v| {
+            t.descripcion = v
+        })
+    })?;
+
+    println!("{}", "🆕 Creando nueva plantilla...".cyan().bold());
+
+    let create_data = CreatePlantillaTransaccion {
+        nombre,
+        categoria_id,
+        tipo,
+        monto,
+        descripcion,
+    };
+
+    // Validar datos antes de crear
+    create_data
+        .validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
+
+    let plantilla = repo.create(create_data).await?;
+    println!("{}", "✅ Plantilla creada exitosamente!".green().bold());
+    println!();
+    println!("📋 {}", "Detalles:".bold());
+    println!("   ID: {}", plantilla.id.to_string().bright_blue());
+    println!("   Nombre: {}", plantilla.nombre.bright_white());
+    println!("   Categoría: {}", categoria.nombre.bright_magenta());
+    println!("   Tipo: {}", plantilla.tipo.to_string().bright_yellow());
+    println!(
+        "   Monto: {}",
+        format!("€{:.2}", plantilla.monto).bright_green()
+    );
+    println!("   Descripción: {}", plantilla.descripcion.bright_white());
+
+    Ok(())
+}
+
+async fn listar_plantillas(
+    db: &Database,
+    repo: PlantillaRepository,
+    output: OutputFormat,
+) -> Result<()> {
+    let plantillas = repo.get_all().await?;
+
+    if output.is_structured() {
+        return output::print_list(output, &plantillas);
+    }
+
+    println!("{}", "📋 Listando plantillas...".cyan().bold());
+    println!();
+
+    if plantillas.is_empty() {
+        println!("{}", "📭 No se encontraron plantillas.".yellow());
+        return Ok(());
+    }
+
+    let categorias = CategoriaRepository::new(db.pool().clone())
+        .get_all_incluyendo_archivadas()
+        .await?;
+
+    println!(
+        "{:<10} {:<25} {:<20} {:<10} {:<12} {:<30}",
+        "ID".bold(),
+        "NOMBRE".bold(),
+        "CATEGORÍA".bold(),
+        "TIPO".bold(),
+        "MONTO".bold(),
+        "DESCRIPCIÓN".bold()
+    );
+    println!("{}", "─".repeat(110).bright_black());
+
+    let mut total_ingresos = 0.0;
+    let mut total_gastos = 0.0;
+
+    for plantilla in &plantillas {
+        let tipo_color = match plantilla.tipo {
+            TipoTransaccion::Ingreso => {
+                total_ingresos += plantilla.monto;
+                plantilla.tipo.to_string().green()
+            }
+            TipoTransaccion::Gasto => {
+                total_gastos += plantilla.monto;
+                plantilla.tipo.to_string().red()
+            }
+        };
+
+        let categoria = categorias
+            .iter()
+            .find(|c| c.id == plantilla.categoria_id)
+            .map(|c| c.nombre.clone())
+            .unwrap_or_default();
+
+        println!(
+            "{:<10} {:<25} {:<20} {:<10} {:<12} {:<30}",
+            plantilla.id.to_string()[..8].bright_blue(),
+            plantilla.nombre.bright_white(),
+            categoria.bright_magenta(),
+            tipo_color,
+            format!("€{:.2}", plantilla.monto).bright_green(),
+            plantilla.descripcion.bright_white(),
+        );
+    }
+
+    println!();
+    println!("{}", "📊 Resumen:".bold());
+    println!(
+        "   Total ingresos: {}",
+        format!("€{:.2}", total_ingresos).green()
+    );
+    println!("   Total gastos: {}", format!("€{:.2}", total_gastos).red());
+    println!("   Plantillas: {}", plantillas.len());
+
+    Ok(())
+}
+
+async fn eliminar_plantilla(repo: PlantillaRepository, args: EliminarPlantillaArgs) -> Result<()> {
+    let id = resolver::resolver_plantilla(&repo, &args.id).await?;
+
+    // Verificar que la plantilla existe
+    let plantilla = match repo.get_by_id(id).await? {
+        Some(p) => p,
+        None => {
+            return Err(AppError::NotFound("Plantilla".to_string()));
+        }
+    };
+
+    if !args.force {
+        println!(
+            "{}",
+            "⚠️  ¿Estás seguro de que quieres eliminar esta plantilla?"
+                .yellow()
+                .bold()
+        );
+        println!("   Nombre: {}", plantilla.nombre.bright_white());
+        println!(
+            "   Monto: {}",
+            format!("€{:.2}", plantilla.monto).bright_green()
+        );
+        println!();
+        println!(
+            "{}",
+            "Usa --force para confirmar la eliminación.".bright_black()
+        );
+        return Ok(());
+    }
+
+    println!("{}", "🗑️  Eliminando plantilla...".cyan().bold());
+
+    match repo.delete(id).await? {
+        true => {
+            println!("{}", "✅ Plantilla eliminada exitosamente!".green().bold());
+        }
+        false => {
+            return Err(AppError::Internal("Error eliminando plantilla".to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+async fn aplicar_plantillas(
+    db: &Database,
+    repo: PlantillaRepository,
+    args: AplicarPlantillasArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let retiro_repo = RetiroRepository::new(db.pool().clone());
+    let retiro_id = resolver::resolver_retiro(&retiro_repo, &args.retiro_id).await?;
+
+    let mut plantilla_ids = Vec::new();
+    for referencia in &args.plantillas {
+        plantilla_ids.push(resolver::resolver_plantilla(&repo, referencia).await?);
+    }
+
+    if output.is_structured() {
+        let transacciones = repo.aplicar(retiro_id, &plantilla_ids).await?;
+        return output::print_list(output, &transacciones);
+    }
+
+    println!("{}", "🧩 Aplicando plantillas al retiro...".cyan().bold());
+
+    let transacciones = repo.aplicar(retiro_id, &plantilla_ids).await?;
+    println!(
+        "{}",
+        format!("✅ {} transacciones creadas!", transacciones.len())
+            .green()
+            .bold()
+    );
+    println!();
+    for transaccion in &transacciones {
+        let tipo_color = match transaccion.tipo {
+            TipoTransaccion::Ingreso => transaccion.tipo.to_string().green(),
+            TipoTransaccion::Gasto => transaccion.tipo.to_string().red(),
+        };
+        println!(
+            "   {} {:<8} {:<12} {}",
+            transaccion.id.to_string()[..8].bright_blue(),
+            tipo_color,
+            format!("€{:.2}", transaccion.monto).bright_green(),
+            transaccion.descripcion.bright_white()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,278 @@
+// Preguntas interactivas para los comandos crear/actualizar del CLI
+// Los valores se validan al introducirlos con las mismas reglas `validator` de los modelos
+
+use std::io::IsTerminal;
+use std::str::FromStr;
+
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{FuzzySelect, Input, Select};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::models::{EstadoRetiro, TipoCategoria};
+use crate::repositories::{CategoriaRepository, RetiroRepository};
+use crate::{AppError, Result};
+
+/// Indica si se puede preguntar al usuario (entrada y salida conectadas a una terminal)
+pub fn is_interactive() -> bool {
+    std::io::stdin().is_terminal() && std::io::stderr().is_terminal()
+}
+
+/// Comprobar que `--interactive` solo se usa desde una terminal
+pub fn modo_interactivo(solicitado: bool) -> Result<bool> {
+    if solicitado && !is_interactive() {
+        return Err(AppError::Validation(
+            "El modo interactivo requiere una terminal".to_string(),
+        ));
+    }
+    Ok(solicitado)
+}
+
+/// Usar el valor recibido por argumento o preguntarlo si estamos en una terminal
+pub fn requerido<V>(
+    valor: Option<V>,
+    argumento: &str,
+    preguntar: impl FnOnce() -> Result<V>,
+) -> Result<V> {
+    match valor {
+        Some(valor) => Ok(valor),
+        None if is_interactive() => preguntar(),
+        None => Err(falta_argumento(argumento)),
+    }
+}
+
+/// Error para un argumento obligatorio que no se puede preguntar
+pub fn falta_argumento(argumento: &str) -> AppError {
+    AppError::Validation(format!(
+        "Falta el argumento {} (ejecuta el comando en una terminal para introducirlo de forma interactiva)",
+        argumento
+    ))
+}
+
+/// Preguntar un valor con validación en línea
+pub fn pedir<V>(
+    prompt: &str,
+    actual: Option<V>,
+    mut validar: impl FnMut(&V) -> std::result::Result<(), String>,
+) -> Result<V>
+where
+    V: Clone + ToString + FromStr,
+    <V as FromStr>::Err: ToString,
+{
+    let theme = ColorfulTheme::default();
+    let mut input = Input::<V>::with_theme(&theme).with_prompt(prompt);
+    if let Some(actual) = actual {
+        input = input.default(actual);
+    }
+    input
+        .validate_with(move |valor: &V| validar(valor))
+        .interact_text()
+        .map_err(error_entrada)
+}
+
+/// Preguntar un texto opcional; una respuesta vacía se interpreta como "sin valor"
+pub fn pedir_opcional(
+    prompt: &str,
+    actual: Option<String>,
+    mut validar: impl FnMut(&String) -> std::result::Result<(), String>,
+) -> Result<Option<String>> {
+    let theme = ColorfulTheme::default();
+    let valor = Input::<String>::with_theme(&theme)
+        .with_prompt(format!("{} (opcional)", prompt))
+        .with_initial_text(actual.unwrap_or_default())
+        .allow_empty(true)
+        .validate_with(move |valor: &String| {
+            if valor.trim().is_empty() {
+                Ok(())
+            } else {
+                validar(valor)
+            }
+        })
+        .interact_text()
+        .map_err(error_entrada)?;
+
+    let valor = valor.trim().to_string();
+    Ok(if valor.is_empty() { None } else { Some(valor) })
+}
+
+/// Preguntar un campo de un struct validándolo con sus reglas `validator`
+///
+/// `borrador` es una instancia cualquiera del struct: solo se tienen en cuenta
+/// los errores del campo indicado.
+pub fn pedir_campo<T, V>(
+    prompt: &str,
+    actual: Option<V>,
+    borrador: &T,
+    campo: &str,
+    asignar: impl Fn(&mut T, V),
+) -> Result<V>
+where
+    T: Validate + Clone,
+    V: Clone + ToString + FromStr,
+    <V as FromStr>::Err: ToString,
+{
+    pedir(prompt, actual, |valor: &V| {
+        let mut datos = borrador.clone();
+        asignar(&mut datos, valor.clone());
+        validar_campo(&datos, campo)
+    })
+}
+
+/// Variante de `pedir_campo` para campos `Option<String>`
+pub fn pedir_campo_opcional<T>(
+    prompt: &str,
+    actual: Option<String>,
+    borrador: &T,
+    campo: &str,
+    asignar: impl Fn(&mut T, Option<String>),
+) -> Result<Option<String>>
+where
+    T: Validate + Clone,
+{
+    pedir_opcional(prompt, actual, |valor: &String| {
+        let mut datos = borrador.clone();
+        asignar(&mut datos, Some(valor.clone()));
+        validar_campo(&datos, campo)
+    })
+}
+
+/// Validar un único campo de un struct, ignorando los errores del resto
+pub fn validar_campo<T: Validate>(datos: &T, campo: &str) -> std::result::Result<(), String> {
+    let errores = match datos.validate() {
+        Ok(()) => return Ok(()),
+        Err(errores) => errores,
+    };
+
+    match errores.field_errors().get(campo) {
+        Some(errores) => Err(errores
+            .iter()
+            .map(describir_error)
+            .collect::<Vec<_>>()
+            .join(", ")),
+        None => Ok(()),
+    }
+}
+
+fn describir_error(error: &ValidationError) -> String {
+    if let Some(mensaje) = &error.message {
+        return mensaje.to_string();
+    }
+
+    let min = error.params.get("min");
+    let max = error.params.get("max");
+    match (error.code.as_ref(), min, max) {
+        ("length", Some(min), Some(max)) if min == max => {
+            format!("Debe tener exactamente {} caracteres", min)
+        }
+        ("length", Some(min), Some(max)) => {
+            format!("Debe tener entre {} y {} caracteres", min, max)
+        }
+        ("length", Some(min), None) => format!("Debe tener al menos {} caracteres", min),
+        ("length", None, Some(max)) => format!("Debe tener como máximo {} caracteres", max),
+        ("range", Some(min), Some(max)) => format!("Debe estar entre {} y {}", min, max),
+        ("range", Some(min), None) => format!("Debe ser como mínimo {}", min),
+        ("range", None, Some(max)) => format!("Debe ser como máximo {}", max),
+        (codigo, _, _) => format!("Valor inválido ({})", codigo),
+    }
+}
+
+/// Elegir una opción de una lista corta
+pub fn seleccionar<S: ToString>(prompt: &str, opciones: &[S], actual: usize) -> Result<usize> {
+    Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .items(opciones)
+        .default(actual)
+        .interact_opt()
+        .map_err(error_entrada)?
+        .ok_or_else(cancelado)
+}
+
+/// Elegir una opción de una lista filtrando por texto
+pub fn seleccionar_fuzzy<S: ToString>(
+    prompt: &str,
+    opciones: &[S],
+    actual: usize,
+) -> Result<usize> {
+    FuzzySelect::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .items(opciones)
+        .default(actual)
+        .max_length(12)
+        .interact_opt()
+        .map_err(error_entrada)?
+        .ok_or_else(cancelado)
+}
+
+/// Elegir el tipo de una categoría o transacción
+pub fn seleccionar_tipo(actual: Option<&TipoCategoria>) -> Result<TipoCategoria> {
+    let actual = match actual {
+        Some(TipoCategoria::Ingreso) => 0,
+        _ => 1,
+    };
+    match seleccionar("Tipo", &["Ingreso", "Gasto"], actual)? {
+        0 => Ok(TipoCategoria::Ingreso),
+        _ => Ok(TipoCategoria::Gasto),
+    }
+}
+
+/// Elegir un retiro de la base de datos (por defecto el primero activo)
+pub async fn seleccionar_retiro(repo: &RetiroRepository) -> Result<Uuid> {
+    let retiros = repo.get_all().await?;
+    if retiros.is_empty() {
+        return Err(AppError::NotFound(
+            "No hay retiros. Crea uno con 'retiro crear'".to_string(),
+        ));
+    }
+
+    let opciones: Vec<String> = retiros
+        .iter()
+        .map(|r| {
+            format!(
+                "{} · {} · {} [{}]",
+                r.nombre,
+                r.estado,
+                r.fecha_inicio.format("%d/%m/%Y"),
+                &r.id.to_string()[..8]
+            )
+        })
+        .collect();
+    let actual = retiros
+        .iter()
+        .position(|r| matches!(r.estado, EstadoRetiro::Activo))
+        .unwrap_or(0);
+
+    let indice = seleccionar_fuzzy("Retiro", &opciones, actual)?;
+    Ok(retiros[indice].id)
+}
+
+/// Elegir una categoría de la base de datos, opcionalmente filtrada por tipo
+pub async fn seleccionar_categoria(
+    repo: &CategoriaRepository,
+    tipo: Option<TipoCategoria>,
+) -> Result<Uuid> {
+    let categorias = match tipo {
+        Some(tipo) => repo.get_by_tipo(tipo).await?,
+        None => repo.get_all().await?,
+    };
+    if categorias.is_empty() {
+        return Err(AppError::NotFound(
+            "No hay categorías disponibles. Crea una con 'categoria crear'".to_string(),
+        ));
+    }
+
+    let opciones: Vec<String> = categorias
+        .iter()
+        .map(|c| format!("{} · {} [{}]", c.nombre, c.tipo, &c.id.to_string()[..8]))
+        .collect();
+
+    let indice = seleccionar_fuzzy("Categoría", &opciones, 0)?;
+    Ok(categorias[indice].id)
+}
+
+fn cancelado() -> AppError {
+    AppError::Validation("Operación cancelada".to_string())
+}
+
+fn error_entrada(e: dialoguer::Error) -> AppError {
+    AppError::Internal(format!("Error leyendo la entrada: {}", e))
+}
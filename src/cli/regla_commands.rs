@@ -0,0 +1,499 @@
+use clap::{Args, Subcommand};
+use clap_complete::ArgValueCandidates;
+use colored::*;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::completions;
+use super::output::{self, OutputFormat};
+use super::prompts;
+use super::resolver;
+use super::transaccion_commands::{tipo_transaccion, CliTipoTransaccion};
+use crate::database::Database;
+use crate::models::{
+    compilar_patron, CreateReglaCategoria, ReglaCategoria, TipoTransaccion,
+};
+use crate::repositories::{CategoriaRepository, ReglaRepository, RetiroRepository};
+use crate::{AppError, Result};
+
+#[derive(Subcommand)]
+pub enum ReglaCommands {
+    /// Crear una regla que asigna categoría y etiquetas según la descripción y el monto
+    Crear(CrearReglaArgs),
+    /// Listar reglas en el orden en que se evalúan
+    Listar,
+    /// Eliminar una regla
+    Eliminar(EliminarReglaArgs),
+    /// Mostrar qué transacciones ya registradas cumplirían una regla
+    Probar(ProbarReglaArgs),
+}
+
+#[derive(Args)]
+pub struct CrearReglaArgs {
+    /// Nombre de la regla (se pregunta si no se indica)
+    #[arg(short, long)]
+    pub nombre: Option<String>,
+
+    /// Expresión regular que se busca en la descripción, sin distinguir mayúsculas
+    /// (p. ej. "mercadona|supermercado")
+    #[arg(short, long)]
+    pub patron: Option<String>,
+
+    /// Categoría que se asigna: ID, prefijo del ID o nombre (se elige de una lista si no se indica)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_categorias))]
+    pub categoria_id: Option<String>,
+
+    /// Tipo de transacción al que se aplica (por defecto el de la categoría)
+    #[arg(short, long, value_enum)]
+    pub tipo: Option<CliTipoTransaccion>,
+
+    /// Monto mínimo, incluido (en valor absoluto)
+    #[arg(long)]
+    pub monto_min: Option<f64>,
+
+    /// Monto máximo, incluido (en valor absoluto)
+    #[arg(long)]
+    pub monto_max: Option<f64>,
+
+    /// Etiqueta que se añade a la transacción (se puede repetir o separar por comas)
+    #[arg(long = "etiqueta", value_delimiter = ',', add = ArgValueCandidates::new(completions::completar_etiquetas))]
+    pub etiquetas: Vec<String>,
+
+    /// Prioridad: si varias reglas coinciden se aplica la de mayor prioridad
+    #[arg(long, default_value = "0", allow_negative_numbers = true)]
+    pub prioridad: i64,
+}
+
+#[derive(Args)]
+pub struct EliminarReglaArgs {
+    /// Regla a eliminar (ID, prefijo del ID o nombre)
+    #[arg(add = ArgValueCandidates::new(completions::completar_reglas))]
+    pub id: String,
+
+    /// Confirmar eliminación sin preguntar
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Args)]
+pub struct ProbarReglaArgs {
+    /// Regla guardada a probar (ID, prefijo del ID o nombre)
+    #[arg(
+        conflicts_with_all = ["patron", "categoria_id", "tipo", "monto_min", "monto_max"],
+        add = ArgValueCandidates::new(completions::completar_reglas)
+    )]
+    pub regla: Option<String>,
+
+    /// Probar una regla sin guardarla: expresión regular de la descripción
+    #[arg(short, long, required_unless_present = "regla")]
+    pub patron: Option<String>,
+
+    /// Categoría de la regla sin guardar (ID, prefijo del ID o nombre)
+    #[arg(long, required_unless_present = "regla", add = ArgValueCandidates::new(completions::completar_categorias))]
+    pub categoria_id: Option<String>,
+
+    /// Tipo de la regla sin guardar (por defecto el de la categoría)
+    #[arg(short, long, value_enum)]
+    pub tipo: Option<CliTipoTransaccion>,
+
+    /// Monto mínimo de la regla sin guardar
+    #[arg(long)]
+    pub monto_min: Option<f64>,
+
+    /// Monto máximo de la regla sin guardar
+    #[arg(long)]
+    pub monto_max: Option<f64>,
+
+    /// Buscar solo en un retiro (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_retiros))]
+    pub retiro_id: Option<String>,
+}
+
+pub async fn handle_regla_command(
+    command: ReglaCommands,
+    database_url: &str,
+    output: OutputFormat,
+) -> Result<()> {
+    // Conectar a la base de datos
+    let db = Database::new(database_url).await?;
+    let repo = ReglaRepository::new(db.pool().clone());
+
+    match command {
+        ReglaCommands::Crear(args) => crear_regla(&db, repo, args).await,
+        ReglaCommands::Listar => listar_reglas(&db, repo, output).await,
+        ReglaCommands::Eliminar(args) => eliminar_regla(repo, args).await,
+        ReglaCommands::Probar(args) => probar_regla(&db, repo, args, output).await,
+    }
+}
+
+async fn crear_regla(db: &Database, repo: ReglaRepository, args: CrearReglaArgs) -> Result<()> {
+    let categoria_repo = CategoriaRepository::new(db.pool().clone());
+
+    let borrador = CreateReglaCategoria {
+        nombre: String::new(),
+        patron: ".".to_string(),
+        monto_min: None,
+        monto_max: None,
+        tipo: TipoTransaccion::Gasto,
+        categoria_id: Uuid::nil(),
+        etiquetas: Vec::new(),
+        prioridad: 0,
+    };
+    let nombre = prompts::requerido(args.nombre, "--nombre", || {
+        prompts::pedir_campo("Nombre", None, &borrador, "nombre", |r, v| r.nombre = v)
+    })?;
+    let patron = prompts::requerido(args.patron, "--patron", || {
+        prompts::pedir("Patrón (expresión regular)", None, |patron: &String| {
+            compilar_patron(patron).map(|_| ()).map_err(|e| e.to_string())
+        })
+    })?;
+
+    let categoria_id = match &args.categoria_id {
+        Some(referencia) => resolver::resolver_categoria(&categoria_repo, referencia).await?,
+        None if prompts::is_interactive() => {
+            prompts::seleccionar_categoria(&categoria_repo, None).await?
+        }
+        None => return Err(prompts::falta_argumento("--categoria-id")),
+    };
+    let categoria = categoria_repo
+        .get_by_id(categoria_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Categoría".to_string()))?;
+
+    // Sin --tipo se usa el de la categoría
+    let tipo: TipoTransaccion = match args.tipo {
+        Some(tipo) => tipo.into(),
+        None => tipo_transaccion(&categoria.tipo),
+    };
+
+    println!("{}", "🆕 Creando nueva regla...".cyan().bold());
+
+    let create_data = CreateReglaCategoria {
+        nombre,
+        patron,
+        monto_min: args.monto_min,
+        monto_max: args.monto_max,
+        tipo,
+        categoria_id,
+        etiquetas: args.etiquetas,
+        prioridad: args.prioridad,
+    };
+
+    let regla = repo.create(create_data).await?;
+    println!("{}", "✅ Regla creada exitosamente!".green().bold());
+    println!();
+    println!("📋 {}", "Detalles:".bold());
+    println!("   ID: {}", regla.id.to_string().bright_blue());
+    println!("   Nombre: {}", regla.nombre.bright_white());
+    println!("   Patrón: {}", regla.patron.bright_cyan());
+    println!("   Tipo: {}", regla.tipo.to_string().bright_yellow());
+    println!("   Monto: {}", formatear_rango(&regla).bright_green());
+    println!("   Categoría: {}", categoria.nombre.bright_magenta());
+    if !regla.etiquetas.is_empty() {
+        println!("   Etiquetas: {}", regla.etiquetas.join(", ").bright_yellow());
+    }
+    println!("   Prioridad: {}", regla.prioridad);
+    println!();
+    println!(
+        "{}",
+        "💡 Usa 'regla probar' para ver qué transacciones registradas cumple.".bright_black()
+    );
+
+    Ok(())
+}
+
+/// Regla como fila de CSV, con las etiquetas separadas por comas
+#[derive(Serialize)]
+struct FilaRegla<'a> {
+    id: Uuid,
+    nombre: &'a str,
+    patron: &'a str,
+    monto_min: Option<f64>,
+    monto_max: Option<f64>,
+    tipo: &'a TipoTransaccion,
+    categoria_id: Uuid,
+    etiquetas: String,
+    prioridad: i64,
+}
+
+async fn listar_reglas(db: &Database, repo: ReglaRepository, output: OutputFormat) -> Result<()> {
+    let reglas = repo.get_all().await?;
+
+    if output == OutputFormat::Csv {
+        let filas: Vec<FilaRegla> = reglas
+            .iter()
+            .map(|r| FilaRegla {
+                id: r.id,
+                nombre: &r.nombre,
+                patron: &r.patron,
+                monto_min: r.monto_min,
+                monto_max: r.monto_max,
+                tipo: &r.tipo,
+                categoria_id: r.categoria_id,
+                etiquetas: r.etiquetas.join(","),
+                prioridad: r.prioridad,
+            })
+            .collect();
+        return output::print_list(output, &filas);
+    }
+    if output.is_structured() {
+        return output::print_list(output, &reglas);
+    }
+
+    println!("{}", "📋 Listando reglas de categorización...".cyan().bold());
+    println!();
+
+    if reglas.is_empty() {
+        println!("{}", "📭 No se encontraron reglas.".yellow());
+        return Ok(());
+    }
+
+    let categorias = CategoriaRepository::new(db.pool().clone())
+        .get_all_incluyendo_archivadas()
+        .await?;
+
+    println!(
+        "{:<10} {:<20} {:<25} {:<8} {:<18} {:<20} {:<5} {:<20}",
+        "ID".bold(),
+        "NOMBRE".bold(),
+        "PATRÓN".bold(),
+        "TIPO".bold(),
+        "MONTO".bold(),
+        "CATEGORÍA".bold(),
+        "PRIO".bold(),
+        "ETIQUETAS".bold()
+    );
+    println!("{}", "─".repeat(130).bright_black());
+
+    for regla in &reglas {
+        let tipo_color = match regla.tipo {
+            TipoTransaccion::Ingreso => regla.tipo.to_string().green(),
+            TipoTransaccion::Gasto => regla.tipo.to_string().red(),
+        };
+        let categoria = categorias.iter().find(|c| c.id == regla.categoria_id);
+        let nombre_categoria = match categoria {
+            Some(c) if c.archivada => format!("{} (archivada)", c.nombre).bright_black(),
+            Some(c) => c.nombre.bright_magenta(),
+            None => "(eliminada)".bright_black(),
+        };
+
+        println!(
+            "{:<10} {:<20} {:<25} {:<8} {:<18} {:<20} {:<5} {:<20}",
+            regla.id.to_string()[..8].bright_blue(),
+            regla.nombre.bright_white(),
+            regla.patron.bright_cyan(),
+            tipo_color,
+            formatear_rango(regla).bright_green(),
+            nombre_categoria,
+            regla.prioridad,
+            regla.etiquetas.join(", ").bright_yellow(),
+        );
+    }
+
+    println!();
+    println!("   Reglas: {}", reglas.len());
+    println!(
+        "{}",
+        "   Se aplica la primera que coincide al crear o importar transacciones sin categoría."
+            .bright_black()
+    );
+
+    Ok(())
+}
+
+async fn eliminar_regla(repo: ReglaRepository, args: EliminarReglaArgs) -> Result<()> {
+    let id = resolver::resolver_regla(&repo, &args.id).await?;
+
+    // Verificar que la regla existe
+    let regla = match repo.get_by_id(id).await? {
+        Some(r) => r,
+        None => {
+            return Err(AppError::NotFound("Regla".to_string()));
+        }
+    };
+
+    if !args.force {
+        println!(
+            "{}",
+            "⚠️  ¿Estás seguro de que quieres eliminar esta regla?"
+                .yellow()
+                .bold()
+        );
+        println!("   Nombre: {}", regla.nombre.bright_white());
+        println!("   Patrón: {}", regla.patron.bright_cyan());
+        println!();
+        println!(
+            "{}",
+            "Usa --force para confirmar la eliminación.".bright_black()
+        );
+        return Ok(());
+    }
+
+    println!("{}", "🗑️  Eliminando regla...".cyan().bold());
+
+    match repo.delete(id).await? {
+        true => {
+            println!("{}", "✅ Regla eliminada exitosamente!".green().bold());
+        }
+        false => {
+            return Err(AppError::Internal("Error eliminando regla".to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+async fn probar_regla(
+    db: &Database,
+    repo: ReglaRepository,
+    args: ProbarReglaArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let categoria_repo = CategoriaRepository::new(db.pool().clone());
+
+    let regla = match &args.regla {
+        Some(referencia) => {
+            let id = resolver::resolver_regla(&repo, referencia).await?;
+            repo.get_by_id(id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Regla".to_string()))?
+        }
+        None => {
+            // Regla sin guardar: se valida igual que al crearla
+            let referencia = args
+                .categoria_id
+                .as_deref()
+                .ok_or_else(|| prompts::falta_argumento("--categoria-id"))?;
+            let categoria_id = resolver::resolver_categoria(&categoria_repo, referencia).await?;
+            let categoria = categoria_repo
+                .get_by_id(categoria_id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Categoría".to_string()))?;
+            let data = CreateReglaCategoria {
+                nombre: "(sin guardar)".to_string(),
+                patron: args
+                    .patron
+                    .clone()
+                    .ok_or_else(|| prompts::falta_argumento("--patron"))?,
+                monto_min: args.monto_min,
+                monto_max: args.monto_max,
+                tipo: match args.tipo {
+                    Some(tipo) => tipo.into(),
+                    None => tipo_transaccion(&categoria.tipo),
+                },
+                categoria_id,
+                etiquetas: Vec::new(),
+                prioridad: 0,
+            };
+            data.validar()?;
+            ReglaCategoria::new(data)
+        }
+    };
+
+    let retiro_id = match &args.retiro_id {
+        Some(referencia) => Some(
+            resolver::resolver_retiro(&RetiroRepository::new(db.pool().clone()), referencia)
+                .await?,
+        ),
+        None => None,
+    };
+
+    let coincidencias = repo.probar(&regla, retiro_id).await?;
+
+    if output.is_structured() {
+        return output::print_list(output, &coincidencias);
+    }
+
+    let categorias = categoria_repo.get_all_incluyendo_archivadas().await?;
+    let nombre_categoria = |id: Uuid| {
+        categorias
+            .iter()
+            .find(|c| c.id == id)
+            .map(|c| c.nombre.clone())
+            .unwrap_or_default()
+    };
+
+    println!(
+        "{}",
+        format!("🔎 Probando la regla '{}' con las transacciones registradas...", regla.nombre)
+            .cyan()
+            .bold()
+    );
+    println!(
+        "   Patrón: {} · Tipo: {} · Monto: {} → {}",
+        regla.patron.bright_cyan(),
+        regla.tipo.to_string().bright_yellow(),
+        formatear_rango(&regla).bright_green(),
+        nombre_categoria(regla.categoria_id).bright_magenta()
+    );
+    println!();
+
+    if coincidencias.is_empty() {
+        println!("{}", "📭 Ninguna transacción cumple la regla.".yellow());
+        return Ok(());
+    }
+
+    println!(
+        "{:<10} {:<17} {:<12} {:<22} {:<40}",
+        "ID".bold(),
+        "FECHA".bold(),
+        "MONTO".bold(),
+        "CATEGORÍA ACTUAL".bold(),
+        "DESCRIPCIÓN".bold()
+    );
+    println!("{}", "─".repeat(105).bright_black());
+
+    for coincidencia in &coincidencias {
+        let categoria = nombre_categoria(coincidencia.categoria_id);
+        let categoria = if coincidencia.misma_categoria {
+            format!("✓ {}", categoria).green()
+        } else {
+            format!("✗ {}", categoria).yellow()
+        };
+        println!(
+            "{:<10} {:<17} {:<12} {:<22} {:<40}",
+            coincidencia.transaccion_id.to_string()[..8].bright_blue(),
+            coincidencia
+                .fecha
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+                .bright_cyan(),
+            format!("€{:.2}", coincidencia.monto).bright_green(),
+            categoria,
+            coincidencia.descripcion.bright_white(),
+        );
+    }
+
+    let distintas = coincidencias.iter().filter(|c| !c.misma_categoria).count();
+    println!();
+    println!("{}", "📊 Resumen:".bold());
+    println!("   Coincidencias: {}", coincidencias.len());
+    println!(
+        "   Ya en {}: {}",
+        nombre_categoria(regla.categoria_id),
+        (coincidencias.len() - distintas).to_string().green()
+    );
+    println!(
+        "   En otra categoría: {}",
+        distintas.to_string().yellow()
+    );
+    if distintas > 0 {
+        println!(
+            "{}",
+            "   La regla no cambia transacciones ya registradas; solo se aplica a las nuevas."
+                .bright_black()
+        );
+    }
+
+    Ok(())
+}
+
+/// Rango de montos de una regla en texto ("cualquiera" si no tiene)
+fn formatear_rango(regla: &ReglaCategoria) -> String {
+    match (regla.monto_min, regla.monto_max) {
+        (None, None) => "cualquiera".to_string(),
+        (Some(min), None) => format!("≥ €{:.2}", min),
+        (None, Some(max)) => format!("≤ €{:.2}", max),
+        (Some(min), Some(max)) => format!("€{:.2} – €{:.2}", min, max),
+    }
+}
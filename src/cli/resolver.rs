@@ -0,0 +1,261 @@
+// Resolución de referencias a entidades en el CLI
+// Se acepta el ID completo, un prefijo único del ID (como en git) o el nombre exacto
+
+use uuid::Uuid;
+
+use crate::models::TipoElementoPapelera;
+use crate::repositories::{
+    ArqueoRepository, CategoriaRepository, CuentaRepository, ExtractoRepository, PapeleraRepository,
+    PlantillaRepository, ReglaRepository, RetiroRepository, TransaccionRepository,
+};
+use crate::{AppError, Result};
+
+/// Longitud mínima de un prefijo de ID para evitar coincidencias accidentales
+const PREFIJO_MINIMO: usize = 4;
+
+struct Candidato {
+    id: Uuid,
+    /// Nombre con el que se puede referenciar (None si la entidad no tiene nombre único)
+    nombre: Option<String>,
+    /// Texto mostrado al listar candidatos ambiguos
+    detalle: String,
+}
+
+/// Resolver un retiro por ID, prefijo de ID o nombre
+pub async fn resolver_retiro(repo: &RetiroRepository, referencia: &str) -> Result<Uuid> {
+    if let Ok(id) = Uuid::parse_str(referencia) {
+        return Ok(id);
+    }
+
+    let candidatos: Vec<Candidato> = repo
+        .get_all()
+        .await?
+        .into_iter()
+        .map(|r| Candidato {
+            id: r.id,
+            detalle: format!("{} · {}", r.nombre, r.estado),
+            nombre: Some(r.nombre),
+        })
+        .collect();
+    resolver("retiro", referencia, &candidatos)
+}
+
+/// Resolver una categoría por ID, prefijo de ID o nombre (también las archivadas)
+pub async fn resolver_categoria(repo: &CategoriaRepository, referencia: &str) -> Result<Uuid> {
+    if let Ok(id) = Uuid::parse_str(referencia) {
+        return Ok(id);
+    }
+
+    let candidatos: Vec<Candidato> = repo
+        .get_all_incluyendo_archivadas()
+        .await?
+        .into_iter()
+        .map(|c| Candidato {
+            id: c.id,
+            detalle: format!(
+                "{} · {}{}",
+                c.nombre,
+                c.tipo,
+                if c.archivada { " · archivada" } else { "" }
+            ),
+            nombre: Some(c.nombre),
+        })
+        .collect();
+    resolver("categoría", referencia, &candidatos)
+}
+
+/// Resolver una plantilla por ID, prefijo de ID o nombre
+pub async fn resolver_plantilla(repo: &PlantillaRepository, referencia: &str) -> Result<Uuid> {
+    if let Ok(id) = Uuid::parse_str(referencia) {
+        return Ok(id);
+    }
+
+    let candidatos: Vec<Candidato> = repo
+        .get_all()
+        .await?
+        .into_iter()
+        .map(|p| Candidato {
+            id: p.id,
+            detalle: format!("{} · €{:.2}", p.nombre, p.monto),
+            nombre: Some(p.nombre),
+        })
+        .collect();
+    resolver("plantilla", referencia, &candidatos)
+}
+
+/// Resolver una regla de categorización por ID, prefijo de ID o nombre
+pub async fn resolver_regla(repo: &ReglaRepository, referencia: &str) -> Result<Uuid> {
+    if let Ok(id) = Uuid::parse_str(referencia) {
+        return Ok(id);
+    }
+
+    let candidatos: Vec<Candidato> = repo
+        .get_all()
+        .await?
+        .into_iter()
+        .map(|r| Candidato {
+            id: r.id,
+            detalle: format!("{} · /{}/", r.nombre, r.patron),
+            nombre: Some(r.nombre),
+        })
+        .collect();
+    resolver("regla", referencia, &candidatos)
+}
+
+/// Resolver una cuenta por ID, prefijo de ID o nombre (los nombres son únicos)
+pub async fn resolver_cuenta(repo: &CuentaRepository, referencia: &str) -> Result<Uuid> {
+    if let Ok(id) = Uuid::parse_str(referencia) {
+        return Ok(id);
+    }
+
+    let candidatos: Vec<Candidato> = repo
+        .get_all()
+        .await?
+        .into_iter()
+        .map(|c| Candidato {
+            id: c.id,
+            detalle: format!("{} · {}", c.nombre, c.tipo),
+            nombre: Some(c.nombre),
+        })
+        .collect();
+    resolver("cuenta", referencia, &candidatos)
+}
+
+/// Resolver una transferencia por ID o prefijo de ID
+pub async fn resolver_transferencia(repo: &CuentaRepository, referencia: &str) -> Result<Uuid> {
+    if let Ok(id) = Uuid::parse_str(referencia) {
+        return Ok(id);
+    }
+
+    let candidatos: Vec<Candidato> = repo
+        .get_transferencias(None)
+        .await?
+        .into_iter()
+        .map(|t| Candidato {
+            id: t.id,
+            nombre: None,
+            detalle: format!("{} · €{:.2}", t.descripcion, t.monto),
+        })
+        .collect();
+    resolver("transferencia", referencia, &candidatos)
+}
+
+/// Resolver un arqueo por ID o prefijo de ID
+pub async fn resolver_arqueo(repo: &ArqueoRepository, referencia: &str) -> Result<Uuid> {
+    if let Ok(id) = Uuid::parse_str(referencia) {
+        return Ok(id);
+    }
+
+    let candidatos: Vec<Candidato> = repo
+        .get_all(None, None)
+        .await?
+        .into_iter()
+        .map(|a| Candidato {
+            id: a.id,
+            nombre: None,
+            detalle: format!(
+                "{} · €{:.2} contados",
+                a.created_at.format("%Y-%m-%d %H:%M"),
+                a.contado
+            ),
+        })
+        .collect();
+    resolver("arqueo", referencia, &candidatos)
+}
+
+/// Resolver una transacción por ID o prefijo de ID (las descripciones no son únicas)
+pub async fn resolver_transaccion(repo: &TransaccionRepository, referencia: &str) -> Result<Uuid> {
+    if let Ok(id) = Uuid::parse_str(referencia) {
+        return Ok(id);
+    }
+
+    let candidatos: Vec<Candidato> = repo
+        .get_all()
+        .await?
+        .into_iter()
+        .map(|t| Candidato {
+            id: t.id,
+            nombre: None,
+            detalle: format!("{} · €{:.2}", t.descripcion, t.monto),
+        })
+        .collect();
+    resolver("transacción", referencia, &candidatos)
+}
+
+/// Resolver una línea de extracto por ID o prefijo de ID
+pub async fn resolver_linea_extracto(repo: &ExtractoRepository, referencia: &str) -> Result<Uuid> {
+    if let Ok(id) = Uuid::parse_str(referencia) {
+        return Ok(id);
+    }
+
+    let candidatos: Vec<Candidato> = repo
+        .get_lineas(None)
+        .await?
+        .into_iter()
+        .map(|l| Candidato {
+            id: l.id,
+            nombre: None,
+            detalle: format!("{} · {} · €{:.2}", l.fecha, l.descripcion, l.monto),
+        })
+        .collect();
+    resolver("línea de extracto", referencia, &candidatos)
+}
+
+/// Resolver un elemento de la papelera por ID, prefijo de ID o nombre (retiros y categorías)
+pub async fn resolver_papelera(repo: &PapeleraRepository, referencia: &str) -> Result<Uuid> {
+    if let Ok(id) = Uuid::parse_str(referencia) {
+        return Ok(id);
+    }
+
+    let candidatos: Vec<Candidato> = repo
+        .listar()
+        .await?
+        .into_iter()
+        .map(|e| Candidato {
+            id: e.id,
+            detalle: format!("{} · {}", e.descripcion, e.tipo),
+            nombre: match e.tipo {
+                TipoElementoPapelera::Transaccion => None,
+                _ => Some(e.descripcion),
+            },
+        })
+        .collect();
+    resolver("elemento de la papelera", referencia, &candidatos)
+}
+
+/// Buscar coincidencias por prefijo de ID y por nombre exacto (sin distinguir mayúsculas)
+fn resolver(entidad: &str, referencia: &str, candidatos: &[Candidato]) -> Result<Uuid> {
+    let referencia = referencia.trim();
+    let prefijo = referencia.to_lowercase();
+    let es_prefijo = prefijo.len() >= PREFIJO_MINIMO
+        && prefijo.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+
+    let coincidencias: Vec<&Candidato> = candidatos
+        .iter()
+        .filter(|c| {
+            (es_prefijo && c.id.to_string().starts_with(&prefijo))
+                || c.nombre
+                    .as_deref()
+                    .is_some_and(|nombre| nombre.to_lowercase() == prefijo)
+        })
+        .collect();
+
+    match coincidencias.as_slice() {
+        [candidato] => Ok(candidato.id),
+        [] => Err(AppError::NotFound(format!(
+            "{} '{}' (usa el ID, un prefijo de al menos {} caracteres o el nombre)",
+            entidad, referencia, PREFIJO_MINIMO
+        ))),
+        varias => {
+            let lista = varias
+                .iter()
+                .map(|c| format!("  {}  {}", &c.id.to_string()[..8], c.detalle))
+                .collect::<Vec<_>>()
+                .join("\n");
+            Err(AppError::Ambiguous(format!(
+                "'{}' coincide con varias entidades de tipo {}; indica un prefijo de ID más largo:\n{}",
+                referencia, entidad, lista
+            )))
+        }
+    }
+}
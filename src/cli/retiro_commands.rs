@@ -1,9 +1,13 @@
 use chrono::{DateTime, NaiveDateTime, Utc};
 use clap::{Args, Subcommand};
+use clap_complete::ArgValueCandidates;
 use colored::*;
-use uuid::Uuid;
 use validator::Validate;
 
+use super::completions;
+use super::output::{self, OutputFormat};
+use super::prompts;
+use super::resolver;
 use crate::database::Database;
 use crate::models::{CreateRetiro, EstadoRetiro};
 use crate::repositories::RetiroRepository;
@@ -25,13 +29,15 @@ pub enum RetiroCommands {
     Eliminar(EliminarRetiroArgs),
     /// Buscar retiros por nombre
     Buscar(BuscarRetiroArgs),
+    /// Clonar un retiro como plantilla de la siguiente edición
+    Clonar(ClonarRetiroArgs),
 }
 
 #[derive(Args)]
 pub struct CrearRetiroArgs {
-    /// Nombre del retiro
+    /// Nombre del retiro (se pregunta si no se indica)
     #[arg(short, long)]
-    pub nombre: String,
+    pub nombre: Option<String>,
 
     /// Descripción del retiro
     #[arg(short, long)]
@@ -39,11 +45,11 @@ pub struct CrearRetiroArgs {
 
     /// Fecha de inicio (YYYY-MM-DD HH:MM:SS)
     #[arg(long)]
-    pub fecha_inicio: String,
+    pub fecha_inicio: Option<String>,
 
     /// Fecha de fin (YYYY-MM-DD HH:MM:SS)
     #[arg(long)]
-    pub fecha_fin: String,
+    pub fecha_fin: Option<String>,
 
     /// Ubicación del retiro
     #[arg(short, long)]
@@ -51,7 +57,11 @@ pub struct CrearRetiroArgs {
 
     /// Número de participantes
     #[arg(short, long)]
-    pub participantes: i32,
+    pub participantes: Option<i32>,
+
+    /// Preguntar también por los campos opcionales
+    #[arg(short, long)]
+    pub interactive: bool,
 }
 
 #[derive(Args)]
@@ -63,14 +73,16 @@ pub struct ListarRetiroArgs {
 
 #[derive(Args)]
 pub struct MostrarRetiroArgs {
-    /// ID del retiro
+    /// Retiro (ID, prefijo del ID o nombre)
+    #[arg(add = ArgValueCandidates::new(completions::completar_retiros))]
     pub id: String,
 }
 
 #[derive(Args)]
 pub struct ActualizarRetiroArgs {
-    /// ID del retiro a actualizar
-    pub id: String,
+    /// Retiro a actualizar: ID, prefijo del ID o nombre (se elige de una lista si no se indica)
+    #[arg(add = ArgValueCandidates::new(completions::completar_retiros))]
+    pub id: Option<String>,
 
     /// Nuevo nombre del retiro
     #[arg(short, long)]
@@ -95,11 +107,16 @@ pub struct ActualizarRetiroArgs {
     /// Nuevo número de participantes
     #[arg(short, long)]
     pub participantes: Option<i32>,
+
+    /// Preguntar todos los campos mostrando los valores actuales
+    #[arg(short, long)]
+    pub interactive: bool,
 }
 
 #[derive(Args)]
 pub struct EstadoRetiroArgs {
-    /// ID del retiro
+    /// Retiro (ID, prefijo del ID o nombre)
+    #[arg(add = ArgValueCandidates::new(completions::completar_retiros))]
     pub id: String,
 
     /// Nuevo estado del retiro
@@ -109,7 +126,8 @@ pub struct EstadoRetiroArgs {
 
 #[derive(Args)]
 pub struct EliminarRetiroArgs {
-    /// ID del retiro a eliminar
+    /// Retiro a eliminar (ID, prefijo del ID o nombre)
+    #[arg(add = ArgValueCandidates::new(completions::completar_retiros))]
     pub id: String,
 
     /// Confirmar eliminación sin preguntar
@@ -123,6 +141,25 @@ pub struct BuscarRetiroArgs {
     pub query: String,
 }
 
+#[derive(Args)]
+pub struct ClonarRetiroArgs {
+    /// Retiro a clonar (ID, prefijo del ID o nombre)
+    #[arg(add = ArgValueCandidates::new(completions::completar_retiros))]
+    pub id: String,
+
+    /// Nombre del nuevo retiro (por defecto el original con el mes de inicio)
+    #[arg(short, long)]
+    pub nombre: Option<String>,
+
+    /// Fecha de inicio de la nueva edición (por defecto seis meses después del original)
+    #[arg(long)]
+    pub fecha_inicio: Option<String>,
+
+    /// Añadir también los gastos fijos de las plantillas de transacción
+    #[arg(long)]
+    pub plantillas: bool,
+}
+
 #[derive(clap::ValueEnum, Clone)]
 pub enum CliEstadoRetiro {
     Planificacion,
@@ -140,22 +177,24 @@ impl From<CliEstadoRetiro> for EstadoRetiro {
     }
 }
 
-pub async fn handle_retiro_command(command: RetiroCommands) -> Result<()> {
+pub async fn handle_retiro_command(
+    command: RetiroCommands,
+    database_url: &str,
+    output: OutputFormat,
+) -> Result<()> {
     // Conectar a la base de datos
-    let database_url =
-        std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite:./retiros.db".to_string());
-
-    let db = Database::new(&database_url).await?;
+    let db = Database::new(database_url).await?;
     let repo = RetiroRepository::new(db.pool().clone());
 
     match command {
         RetiroCommands::Crear(args) => crear_retiro(repo, args).await,
-        RetiroCommands::Listar(args) => listar_retiros(repo, args).await,
-        RetiroCommands::Mostrar(args) => mostrar_retiro(repo, args).await,
+        RetiroCommands::Listar(args) => listar_retiros(repo, args, output).await,
+        RetiroCommands::Mostrar(args) => mostrar_retiro(repo, args, output).await,
         RetiroCommands::Actualizar(args) => actualizar_retiro(repo, args).await,
         RetiroCommands::Estado(args) => cambiar_estado_retiro(repo, args).await,
         RetiroCommands::Eliminar(args) => eliminar_retiro(repo, args).await,
-        RetiroCommands::Buscar(args) => buscar_retiros(repo, args).await,
+        RetiroCommands::Buscar(args) => buscar_retiros(repo, args, output).await,
+        RetiroCommands::Clonar(args) => clonar_retiro(repo, args).await,
     }
 }
 
@@ -177,83 +216,165 @@ fn parse_datetime(date_str: &str) -> Result<DateTime<Utc>> {
     )))
 }
 
+/// Preguntar una fecha validando el formato y, si se indica, que no sea anterior a `minima`
+fn pedir_fecha(
+    prompt: &str,
+    actual: Option<DateTime<Utc>>,
+    minima: Option<DateTime<Utc>>,
+) -> Result<DateTime<Utc>> {
+    let actual = actual.map(|fecha| fecha.format("%Y-%m-%d %H:%M:%S").to_string());
+    let texto = prompts::pedir(prompt, actual, |valor: &String| {
+        let fecha = parse_datetime(valor)
+            .map_err(|_| "Use YYYY-MM-DD o YYYY-MM-DD HH:MM:SS".to_string())?;
+        match minima {
+            Some(minima) if fecha < minima => {
+                Err("No puede ser anterior a la fecha de inicio".to_string())
+            }
+            _ => Ok(()),
+        }
+    })?;
+    parse_datetime(&texto)
+}
+
+/// Datos de ejemplo para validar campos sueltos de `CreateRetiro`
+fn borrador_retiro() -> CreateRetiro {
+    CreateRetiro {
+        nombre: String::new(),
+        descripcion: None,
+        fecha_inicio: Utc::now(),
+        fecha_fin: Utc::now(),
+        ubicacion: None,
+        numero_participantes: 1,
+    }
+}
+
 async fn crear_retiro(repo: RetiroRepository, args: CrearRetiroArgs) -> Result<()> {
-    println!("{}", "🆕 Creando nuevo retiro...".cyan().bold());
+    let interactivo = prompts::modo_interactivo(args.interactive)?;
+    let borrador = borrador_retiro();
+
+    // Los campos obligatorios que falten se preguntan; los opcionales solo con --interactive
+    let nombre = prompts::requerido(args.nombre, "--nombre", || {
+        prompts::pedir_campo("Nombre", None, &borrador, "nombre", |r, v| r.nombre = v)
+    })?;
+    let descripcion = match args.descripcion {
+        None if interactivo => prompts::pedir_campo_opcional(
+            "Descripción",
+            None,
+            &borrador,
+            "descripcion",
+            |r, v| r.descripcion = v,
+        )?,
+        descripcion => descripcion,
+    };
+    let fecha_inicio = match &args.fecha_inicio {
+        Some(fecha) => parse_datetime(fecha)?,
+        None => prompts::requerido(None, "--fecha-inicio", || {
+            pedir_fecha("Fecha de inicio (YYYY-MM-DD [HH:MM:SS])", None, None)
+        })?,
+    };
+    let fecha_fin = match &args.fecha_fin {
+        Some(fecha) => parse_datetime(fecha)?,
+        None => prompts::requerido(None, "--fecha-fin", || {
+            pedir_fecha(
+                "Fecha de fin (YYYY-MM-DD [HH:MM:SS])",
+                None,
+                Some(fecha_inicio),
+            )
+        })?,
+    };
+    let ubicacion = match args.ubicacion {
+        None if interactivo => prompts::pedir_campo_opcional(
+            "Ubicación",
+            None,
+            &borrador,
+            "ubicacion",
+            |r, v| r.ubicacion = v,
+        )?,
+        ubicacion => ubicacion,
+    };
+    let numero_participantes = prompts::requerido(args.participantes, "--participantes", || {
+        prompts::pedir_campo(
+            "Número de participantes",
+            None,
+            &borrador,
+            "numero_participantes",
+            |r, v| r.numero_participantes = v,
+        )
+    })?;
 
-    let fecha_inicio = parse_datetime(&args.fecha_inicio)?;
-    let fecha_fin = parse_datetime(&args.fecha_fin)?;
+    println!("{}", "🆕 Creando nuevo retiro...".cyan().bold());
 
     let create_data = CreateRetiro {
-        nombre: args.nombre.clone(),
-        descripcion: args.descripcion.clone(),
+        nombre,
+        descripcion,
         fecha_inicio,
         fecha_fin,
-        ubicacion: args.ubicacion.clone(),
-        numero_participantes: args.participantes,
+        ubicacion,
+        numero_participantes,
     };
 
     // Validar datos antes de crear
-    if let Err(e) = create_data.validate() {
-        println!("{} {}", "❌ Error de validación:".red().bold(), e);
-        return Err(AppError::Validation(e.to_string()));
-    }
+    create_data
+        .validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
 
-    match repo.create(create_data).await {
-        Ok(retiro) => {
-            println!("{}", "✅ Retiro creado exitosamente!".green().bold());
-            println!();
-            println!("📋 {}", "Detalles:".bold());
-            println!("   ID: {}", retiro.id.to_string().bright_blue());
-            println!("   Nombre: {}", retiro.nombre.bright_white());
-            println!(
-                "   Estado: {}",
-                format!("{}", retiro.estado).bright_yellow()
-            );
-            println!(
-                "   Participantes: {}",
-                retiro.numero_participantes.to_string().bright_green()
-            );
-            println!(
-                "   Fecha inicio: {}",
-                retiro
-                    .fecha_inicio
-                    .format("%Y-%m-%d %H:%M")
-                    .to_string()
-                    .bright_cyan()
-            );
-            println!(
-                "   Fecha fin: {}",
-                retiro
-                    .fecha_fin
-                    .format("%Y-%m-%d %H:%M")
-                    .to_string()
-                    .bright_cyan()
-            );
-            if let Some(ubicacion) = &retiro.ubicacion {
-                println!("   Ubicación: {}", ubicacion.bright_magenta());
-            }
-            if let Some(descripcion) = &retiro.descripcion {
-                println!("   Descripción: {}", descripcion.bright_black());
-            }
-        }
-        Err(e) => {
-            println!("{} {}", "❌ Error creando retiro:".red().bold(), e);
-            return Err(e);
-        }
+    let retiro = repo.create(create_data).await?;
+    println!("{}", "✅ Retiro creado exitosamente!".green().bold());
+    println!();
+    println!("📋 {}", "Detalles:".bold());
+    println!("   ID: {}", retiro.id.to_string().bright_blue());
+    println!("   Nombre: {}", retiro.nombre.bright_white());
+    println!(
+        "   Estado: {}",
+        format!("{}", retiro.estado).bright_yellow()
+    );
+    println!(
+        "   Participantes: {}",
+        retiro.numero_participantes.to_string().bright_green()
+    );
+    println!(
+        "   Fecha inicio: {}",
+        retiro
+            .fecha_inicio
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+            .bright_cyan()
+    );
+    println!(
+        "   Fecha fin: {}",
+        retiro
+            .fecha_fin
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+            .bright_cyan()
+    );
+    if let Some(ubicacion) = &retiro.ubicacion {
+        println!("   Ubicación: {}", ubicacion.bright_magenta());
+    }
+    if let Some(descripcion) = &retiro.descripcion {
+        println!("   Descripción: {}", descripcion.bright_black());
     }
 
     Ok(())
 }
 
-async fn listar_retiros(repo: RetiroRepository, args: ListarRetiroArgs) -> Result<()> {
-    println!("{}", "📋 Listando retiros...".cyan().bold());
-    println!();
-
+async fn listar_retiros(
+    repo: RetiroRepository,
+    args: ListarRetiroArgs,
+    output: OutputFormat,
+) -> Result<()> {
     let retiros = match args.estado {
         Some(estado) => repo.get_by_estado(estado.into()).await?,
         None => repo.get_all().await?,
     };
 
+    if output.is_structured() {
+        return output::print_list(output, &retiros);
+    }
+
+    println!("{}", "📋 Listando retiros...".cyan().bold());
+    println!();
+
     if retiros.is_empty() {
         println!("{}", "📭 No se encontraron retiros.".yellow());
         return Ok(());
@@ -306,11 +427,22 @@ async fn listar_retiros(repo: RetiroRepository, args: ListarRetiroArgs) -> Resul
     Ok(())
 }
 
-async fn mostrar_retiro(repo: RetiroRepository, args: MostrarRetiroArgs) -> Result<()> {
-    println!("{}", "🔍 Buscando retiro...".cyan().bold());
+async fn mostrar_retiro(
+    repo: RetiroRepository,
+    args: MostrarRetiroArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let id = resolver::resolver_retiro(&repo, &args.id).await?;
+
+    if output.is_structured() {
+        let retiro = repo
+            .get_by_id(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Retiro".to_string()))?;
+        return output::print_one(output, &retiro);
+    }
 
-    let id =
-        Uuid::parse_str(&args.id).map_err(|_| AppError::Validation("ID inválido".to_string()))?;
+    println!("{}", "🔍 Buscando retiro...".cyan().bold());
 
     match repo.get_by_id(id).await? {
         Some(retiro) => {
@@ -374,7 +506,6 @@ async fn mostrar_retiro(repo: RetiroRepository, args: MostrarRetiroArgs) -> Resu
             );
         }
         None => {
-            println!("{}", "❌ Retiro no encontrado.".red().bold());
             return Err(AppError::NotFound("Retiro".to_string()));
         }
     }
@@ -383,16 +514,18 @@ async fn mostrar_retiro(repo: RetiroRepository, args: MostrarRetiroArgs) -> Resu
 }
 
 async fn actualizar_retiro(repo: RetiroRepository, args: ActualizarRetiroArgs) -> Result<()> {
-    println!("{}", "✏️  Actualizando retiro...".cyan().bold());
+    let interactivo = prompts::modo_interactivo(args.interactive)?;
 
-    let id =
-        Uuid::parse_str(&args.id).map_err(|_| AppError::Validation("ID inválido".to_string()))?;
+    let id = match &args.id {
+        Some(referencia) => resolver::resolver_retiro(&repo, referencia).await?,
+        None if prompts::is_interactive() => prompts::seleccionar_retiro(&repo).await?,
+        None => return Err(prompts::falta_argumento("<ID>")),
+    };
 
     // Obtener retiro actual
     let retiro_actual = match repo.get_by_id(id).await? {
         Some(ret) => ret,
         None => {
-            println!("{}", "❌ Retiro no encontrado.".red().bold());
             return Err(AppError::NotFound("Retiro".to_string()));
         }
     };
@@ -410,8 +543,15 @@ async fn actualizar_retiro(repo: RetiroRepository, args: ActualizarRetiroArgs) -
         retiro_actual.fecha_fin
     };
 
+    let sin_cambios = args.nombre.is_none()
+        && args.descripcion.is_none()
+        && args.fecha_inicio.is_none()
+        && args.fecha_fin.is_none()
+        && args.ubicacion.is_none()
+        && args.participantes.is_none();
+
     // Crear datos de actualización usando valores actuales como default
-    let update_data = CreateRetiro {
+    let mut update_data = CreateRetiro {
         nombre: args.nombre.unwrap_or(retiro_actual.nombre),
         descripcion: args.descripcion.or(retiro_actual.descripcion),
         fecha_inicio,
@@ -422,12 +562,56 @@ async fn actualizar_retiro(repo: RetiroRepository, args: ActualizarRetiroArgs) -
             .unwrap_or(retiro_actual.numero_participantes),
     };
 
-    // Validar datos
-    if let Err(e) = update_data.validate() {
-        println!("{} {}", "❌ Error de validación:".red().bold(), e);
-        return Err(AppError::Validation(e.to_string()));
+    // Sin cambios indicados en una terminal se preguntan todos los campos
+    if interactivo || (sin_cambios && prompts::is_interactive()) {
+        let borrador = update_data.clone();
+        update_data.nombre = prompts::pedir_campo(
+            "Nombre",
+            Some(borrador.nombre.clone()),
+            &borrador,
+            "nombre",
+            |r, v| r.nombre = v,
+        )?;
+        update_data.descripcion = prompts::pedir_campo_opcional(
+            "Descripción",
+            borrador.descripcion.clone(),
+            &borrador,
+            "descripcion",
+            |r, v| r.descripcion = v,
+        )?;
+        update_data.fecha_inicio = pedir_fecha(
+            "Fecha de inicio (YYYY-MM-DD [HH:MM:SS])",
+            Some(borrador.fecha_inicio),
+            None,
+        )?;
+        update_data.fecha_fin = pedir_fecha(
+            "Fecha de fin (YYYY-MM-DD [HH:MM:SS])",
+            Some(borrador.fecha_fin),
+            Some(update_data.fecha_inicio),
+        )?;
+        update_data.ubicacion = prompts::pedir_campo_opcional(
+            "Ubicación",
+            borrador.ubicacion.clone(),
+            &borrador,
+            "ubicacion",
+            |r, v| r.ubicacion = v,
+        )?;
+        update_data.numero_participantes = prompts::pedir_campo(
+            "Número de participantes",
+            Some(borrador.numero_participantes),
+            &borrador,
+            "numero_participantes",
+            |r, v| r.numero_participantes = v,
+        )?;
     }
 
+    println!("{}", "✏️  Actualizando retiro...".cyan().bold());
+
+    // Validar datos
+    update_data
+        .validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
+
     match repo.update(id, update_data).await? {
         Some(retiro) => {
             println!("{}", "✅ Retiro actualizado exitosamente!".green().bold());
@@ -464,12 +648,6 @@ async fn actualizar_retiro(repo: RetiroRepository, args: ActualizarRetiroArgs) -
             }
         }
         None => {
-            println!(
-                "{}",
-                "❌ Error: Retiro no encontrado durante la actualización."
-                    .red()
-                    .bold()
-            );
             return Err(AppError::NotFound("Retiro".to_string()));
         }
     }
@@ -480,8 +658,7 @@ async fn actualizar_retiro(repo: RetiroRepository, args: ActualizarRetiroArgs) -
 async fn cambiar_estado_retiro(repo: RetiroRepository, args: EstadoRetiroArgs) -> Result<()> {
     println!("{}", "🔄 Cambiando estado del retiro...".cyan().bold());
 
-    let id =
-        Uuid::parse_str(&args.id).map_err(|_| AppError::Validation("ID inválido".to_string()))?;
+    let id = resolver::resolver_retiro(&repo, &args.id).await?;
 
     let nuevo_estado: EstadoRetiro = args.estado.into();
 
@@ -497,7 +674,62 @@ async fn cambiar_estado_retiro(repo: RetiroRepository, args: EstadoRetiroArgs) -
             );
         }
         None => {
-            println!("{}", "❌ Retiro no encontrado.".red().bold());
+            return Err(AppError::NotFound("Retiro".to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+async fn clonar_retiro(repo: RetiroRepository, args: ClonarRetiroArgs) -> Result<()> {
+    let id = resolver::resolver_retiro(&repo, &args.id).await?;
+    let fecha_inicio = args.fecha_inicio.as_deref().map(parse_datetime).transpose()?;
+
+    println!("{}", "📑 Clonando retiro...".cyan().bold());
+
+    match repo
+        .clonar(id, args.nombre, fecha_inicio, args.plantillas)
+        .await?
+    {
+        Some(clon) => {
+            let retiro = &clon.retiro;
+            println!("{}", "✅ Retiro clonado exitosamente!".green().bold());
+            println!();
+            println!("📋 {}", "Nueva edición:".bold());
+            println!("   ID: {}", retiro.id.to_string().bright_blue());
+            println!("   Nombre: {}", retiro.nombre.bright_white());
+            println!(
+                "   Estado: {}",
+                format!("{}", retiro.estado).bright_yellow()
+            );
+            println!(
+                "   Fecha inicio: {}",
+                retiro
+                    .fecha_inicio
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+                    .bright_cyan()
+            );
+            println!(
+                "   Fecha fin: {}",
+                retiro
+                    .fecha_fin
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+                    .bright_cyan()
+            );
+            println!(
+                "   Participantes: {}",
+                retiro.numero_participantes.to_string().bright_green()
+            );
+            if args.plantillas {
+                println!(
+                    "   Desde plantillas: {}",
+                    clon.desde_plantillas.len().to_string().bright_green()
+                );
+            }
+        }
+        None => {
             return Err(AppError::NotFound("Retiro".to_string()));
         }
     }
@@ -506,14 +738,12 @@ async fn cambiar_estado_retiro(repo: RetiroRepository, args: EstadoRetiroArgs) -
 }
 
 async fn eliminar_retiro(repo: RetiroRepository, args: EliminarRetiroArgs) -> Result<()> {
-    let id =
-        Uuid::parse_str(&args.id).map_err(|_| AppError::Validation("ID inválido".to_string()))?;
+    let id = resolver::resolver_retiro(&repo, &args.id).await?;
 
     // Verificar que el retiro existe
     let retiro = match repo.get_by_id(id).await? {
         Some(ret) => ret,
         None => {
-            println!("{}", "❌ Retiro no encontrado.".red().bold());
             return Err(AppError::NotFound("Retiro".to_string()));
         }
     };
@@ -537,7 +767,7 @@ async fn eliminar_retiro(repo: RetiroRepository, args: EliminarRetiroArgs) -> Re
         println!();
         println!(
             "{}",
-            "⚠️  ADVERTENCIA: Esto también eliminará todas las transacciones asociadas.".red()
+            "⚠️  Sus transacciones también irán a la papelera.".yellow()
         );
         println!(
             "{}",
@@ -550,13 +780,13 @@ async fn eliminar_retiro(repo: RetiroRepository, args: EliminarRetiroArgs) -> Re
 
     match repo.delete(id).await? {
         true => {
-            println!("{}", "✅ Retiro eliminado exitosamente!".green().bold());
-        }
-        false => {
+            println!("{}", "✅ Retiro enviado a la papelera!".green().bold());
             println!(
-                "{}",
-                "❌ Error: No se pudo eliminar el retiro.".red().bold()
+                "   {}",
+                format!("Restaurable con: papelera restaurar {}", id).bright_black()
             );
+        }
+        false => {
             return Err(AppError::Internal("Error eliminando retiro".to_string()));
         }
     }
@@ -564,7 +794,17 @@ async fn eliminar_retiro(repo: RetiroRepository, args: EliminarRetiroArgs) -> Re
     Ok(())
 }
 
-async fn buscar_retiros(repo: RetiroRepository, args: BuscarRetiroArgs) -> Result<()> {
+async fn buscar_retiros(
+    repo: RetiroRepository,
+    args: BuscarRetiroArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let retiros = repo.search_by_name(&args.query).await?;
+
+    if output.is_structured() {
+        return output::print_list(output, &retiros);
+    }
+
     println!(
         "{} '{}'",
         "🔍 Buscando retiros con:".cyan().bold(),
@@ -572,8 +812,6 @@ async fn buscar_retiros(repo: RetiroRepository, args: BuscarRetiroArgs) -> Resul
     );
     println!();
 
-    let retiros = repo.search_by_name(&args.query).await?;
-
     if retiros.is_empty() {
         println!(
             "{}",
@@ -1,11 +1,23 @@
+use chrono::NaiveDate;
+use std::path::PathBuf;
 use clap::{Args, Subcommand};
+use clap_complete::ArgValueCandidates;
 use colored::*;
-use uuid::Uuid;
 use validator::Validate;
 
-use crate::database::Database;
-use crate::models::{CreateTransaccion, TipoTransaccion};
-use crate::repositories::TransaccionRepository;
+use super::completions;
+use super::output::{self, OutputFormat};
+use super::prompts;
+use super::resolver;
+use crate::database::{AlmacenAdjuntos, Database};
+use crate::models::{
+    normalizar_etiquetas, CreateTransaccion, EstadoConciliacion, EtiquetaTransaccion, MetodoPago,
+    TipoCategoria, TipoTransaccion,
+};
+use crate::repositories::{
+    AdjuntoRepository, CategoriaRepository, CuentaRepository, EtiquetaRepository, PeriodoFlujo,
+    ReglaRepository, RetiroRepository, TransaccionRepository,
+};
 use crate::{AppError, Result};
 
 #[derive(Subcommand)]
@@ -20,45 +32,83 @@ pub enum TransaccionCommands {
     Eliminar(EliminarTransaccionArgs),
     /// Calcular balance de un retiro
     Balance(BalanceArgs),
+    /// Mostrar el flujo de caja acumulado por periodo
+    Flujo(FlujoArgs),
+    /// Adjuntar comprobantes (PDF o imagen) a una transacción
+    Adjuntar(AdjuntarArgs),
+    /// Listar los comprobantes de una transacción
+    Adjuntos(AdjuntosArgs),
+    /// Informe de gastos sin comprobante por retiro
+    SinComprobante(SinComprobanteArgs),
+    /// Añadir o quitar etiquetas de una transacción
+    Etiquetar(EtiquetarArgs),
+    /// Totales de ingresos y gastos por etiqueta
+    Etiquetas(EtiquetasArgs),
+    /// Cambiar a mano el estado de conciliación bancaria de una transacción
+    Conciliacion(ConciliacionArgs),
 }
 
 #[derive(Args)]
 pub struct CrearTransaccionArgs {
-    /// ID del retiro
-    #[arg(long)]
-    pub retiro_id: String,
+    /// Retiro: ID, prefijo del ID o nombre (se elige de una lista si no se indica)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_retiros))]
+    pub retiro_id: Option<String>,
 
-    /// ID de la categoría
-    #[arg(long)]
-    pub categoria_id: String,
+    /// Categoría: ID, prefijo del ID o nombre (se elige de una lista si no se indica)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_categorias))]
+    pub categoria_id: Option<String>,
 
-    /// Tipo de transacción (ingreso/gasto)
+    /// Tipo de transacción (ingreso/gasto, por defecto el de la categoría)
     #[arg(short, long, value_enum)]
-    pub tipo: CliTipoTransaccion,
+    pub tipo: Option<CliTipoTransaccion>,
 
     /// Monto de la transacción
     #[arg(short, long)]
-    pub monto: f64,
+    pub monto: Option<f64>,
 
     /// Descripción de la transacción
     #[arg(short, long)]
-    pub descripcion: String,
+    pub descripcion: Option<String>,
 
     /// Fecha de la transacción (YYYY-MM-DD HH:MM:SS, opcional - usa ahora por defecto)
     #[arg(short, long)]
     pub fecha: Option<String>,
+
+    /// Cuenta donde entra o sale el dinero (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_cuentas))]
+    pub cuenta: Option<String>,
+
+    /// Método de pago
+    #[arg(long, value_enum)]
+    pub metodo_pago: Option<CliMetodoPago>,
+
+    /// Etiqueta de la transacción (se puede repetir o separar por comas)
+    #[arg(long = "etiqueta", value_delimiter = ',', add = ArgValueCandidates::new(completions::completar_etiquetas))]
+    pub etiquetas: Vec<String>,
+
+    /// No aplicar las reglas de categorización cuando no se indica la categoría
+    #[arg(long)]
+    pub sin_reglas: bool,
 }
 
 #[derive(Args)]
 pub struct ListarTransaccionArgs {
-    /// Filtrar por retiro
-    #[arg(long)]
+    /// Filtrar por retiro (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_retiros))]
     pub retiro_id: Option<String>,
 
     /// Filtrar por tipo de transacción
     #[arg(short, long, value_enum)]
     pub tipo: Option<CliTipoTransaccion>,
 
+    /// Mostrar solo las transacciones con esta etiqueta (se puede repetir: deben tenerlas todas)
+    #[arg(long = "etiqueta", value_delimiter = ',', add = ArgValueCandidates::new(completions::completar_etiquetas))]
+    pub etiquetas: Vec<String>,
+
+    /// Filtrar por estado de conciliación bancaria
+    #[arg(long, value_enum)]
+    pub conciliacion: Option<CliEstadoConciliacion>,
+
     /// Limitar número de resultados
     #[arg(short, long, default_value = "20")]
     pub limit: usize,
@@ -66,13 +116,13 @@ pub struct ListarTransaccionArgs {
 
 #[derive(Args)]
 pub struct MostrarTransaccionArgs {
-    /// ID de la transacción
+    /// Transacción (ID o prefijo del ID)
     pub id: String,
 }
 
 #[derive(Args)]
 pub struct EliminarTransaccionArgs {
-    /// ID de la transacción a eliminar
+    /// Transacción a eliminar (ID o prefijo del ID)
     pub id: String,
 
     /// Confirmar eliminación sin preguntar
@@ -82,10 +132,103 @@ pub struct EliminarTransaccionArgs {
 
 #[derive(Args)]
 pub struct BalanceArgs {
-    /// ID del retiro para calcular balance
+    /// Retiro para calcular balance (ID, prefijo del ID o nombre)
+    #[arg(add = ArgValueCandidates::new(completions::completar_retiros))]
     pub retiro_id: String,
 }
 
+#[derive(Args)]
+pub struct FlujoArgs {
+    /// Filtrar por retiro (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_retiros))]
+    pub retiro_id: Option<String>,
+
+    /// Agrupar por periodo
+    #[arg(short, long, value_enum, default_value = "dia")]
+    pub periodo: CliPeriodoFlujo,
+
+    /// Fecha inicial del rango (YYYY-MM-DD)
+    #[arg(long)]
+    pub desde: Option<String>,
+
+    /// Fecha final del rango (YYYY-MM-DD)
+    #[arg(long)]
+    pub hasta: Option<String>,
+}
+
+#[derive(Args)]
+pub struct AdjuntarArgs {
+    /// Transacción (ID o prefijo del ID)
+    pub id: String,
+
+    /// Archivos a adjuntar (PDF, JPEG, PNG, WebP o HEIC)
+    #[arg(required = true)]
+    pub archivos: Vec<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct AdjuntosArgs {
+    /// Transacción (ID o prefijo del ID)
+    pub id: String,
+}
+
+#[derive(Args)]
+pub struct SinComprobanteArgs {
+    /// Filtrar por retiro (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_retiros))]
+    pub retiro_id: Option<String>,
+}
+
+#[derive(Args)]
+pub struct EtiquetarArgs {
+    /// Transacción (ID o prefijo del ID)
+    pub id: String,
+
+    /// Etiquetas a añadir (o a quitar con --quitar)
+    #[arg(required = true, value_delimiter = ',', add = ArgValueCandidates::new(completions::completar_etiquetas))]
+    pub etiquetas: Vec<String>,
+
+    /// Quitar las etiquetas en lugar de añadirlas
+    #[arg(long)]
+    pub quitar: bool,
+}
+
+#[derive(Args)]
+pub struct EtiquetasArgs {
+    /// Filtrar por retiro (ID, prefijo del ID o nombre)
+    #[arg(long, add = ArgValueCandidates::new(completions::completar_retiros))]
+    pub retiro_id: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ConciliacionArgs {
+    /// Transacción (ID o prefijo del ID)
+    pub id: String,
+
+    /// Nuevo estado
+    #[arg(value_enum)]
+    pub estado: CliEstadoConciliacion,
+}
+
+#[derive(clap::ValueEnum, Clone)]
+pub enum CliMetodoPago {
+    Efectivo,
+    Transferencia,
+    Tarjeta,
+    Bizum,
+}
+
+impl From<CliMetodoPago> for MetodoPago {
+    fn from(cli_metodo: CliMetodoPago) -> Self {
+        match cli_metodo {
+            CliMetodoPago::Efectivo => MetodoPago::Efectivo,
+            CliMetodoPago::Transferencia => MetodoPago::Transferencia,
+            CliMetodoPago::Tarjeta => MetodoPago::Tarjeta,
+            CliMetodoPago::Bizum => MetodoPago::Bizum,
+        }
+    }
+}
+
 #[derive(clap::ValueEnum, Clone)]
 pub enum CliTipoTransaccion {
     Ingreso,
@@ -101,101 +244,329 @@ impl From<CliTipoTransaccion> for TipoTransaccion {
     }
 }
 
-pub async fn handle_transaccion_command(command: TransaccionCommands) -> Result<()> {
-    // Conectar a la base de datos
-    let database_url =
-        std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite:./retiros.db".to_string());
+#[derive(clap::ValueEnum, Clone)]
+pub enum CliEstadoConciliacion {
+    /// Aún no se ha visto en el banco
+    Pendiente,
+    /// Comprobada contra el extracto
+    Punteada,
+    /// Cerrada en una conciliación
+    Conciliada,
+}
+
+impl From<CliEstadoConciliacion> for EstadoConciliacion {
+    fn from(cli_estado: CliEstadoConciliacion) -> Self {
+        match cli_estado {
+            CliEstadoConciliacion::Pendiente => EstadoConciliacion::Pendiente,
+            CliEstadoConciliacion::Punteada => EstadoConciliacion::Punteada,
+            CliEstadoConciliacion::Conciliada => EstadoConciliacion::Conciliada,
+        }
+    }
+}
+
+#[derive(clap::ValueEnum, Clone)]
+pub enum CliPeriodoFlujo {
+    Dia,
+    Semana,
+    Mes,
+}
+
+impl From<CliPeriodoFlujo> for PeriodoFlujo {
+    fn from(cli_periodo: CliPeriodoFlujo) -> Self {
+        match cli_periodo {
+            CliPeriodoFlujo::Dia => PeriodoFlujo::Dia,
+            CliPeriodoFlujo::Semana => PeriodoFlujo::Semana,
+            CliPeriodoFlujo::Mes => PeriodoFlujo::Mes,
+        }
+    }
+}
 
-    let db = Database::new(&database_url).await?;
+pub async fn handle_transaccion_command(
+    command: TransaccionCommands,
+    database_url: &str,
+    output: OutputFormat,
+) -> Result<()> {
+    // Conectar a la base de datos
+    let db = Database::new(database_url).await?;
     let repo = TransaccionRepository::new(db.pool().clone());
+    // Para resolver referencias a retiros por nombre o prefijo de ID
+    let retiro_repo = RetiroRepository::new(db.pool().clone());
 
     match command {
-        TransaccionCommands::Crear(args) => crear_transaccion(repo, args).await,
-        TransaccionCommands::Listar(args) => listar_transacciones(repo, args).await,
-        TransaccionCommands::Mostrar(args) => mostrar_transaccion(repo, args).await,
+        TransaccionCommands::Crear(args) => crear_transaccion(&db, repo, args).await,
+        TransaccionCommands::Listar(args) => {
+            listar_transacciones(&db, repo, &retiro_repo, args, output).await
+        }
+        TransaccionCommands::Mostrar(args) => mostrar_transaccion(&db, repo, args, output).await,
         TransaccionCommands::Eliminar(args) => eliminar_transaccion(repo, args).await,
-        TransaccionCommands::Balance(args) => calcular_balance(repo, args).await,
+        TransaccionCommands::Balance(args) => {
+            calcular_balance(repo, &retiro_repo, args, output).await
+        }
+        TransaccionCommands::Flujo(args) => mostrar_flujo_caja(repo, &retiro_repo, args).await,
+        TransaccionCommands::Adjuntar(args) => {
+            adjuntar_comprobantes(&db, repo, database_url, args, output).await
+        }
+        TransaccionCommands::Adjuntos(args) => {
+            listar_adjuntos(&db, repo, database_url, args, output).await
+        }
+        TransaccionCommands::SinComprobante(args) => {
+            gastos_sin_comprobante(&db, &retiro_repo, args, output).await
+        }
+        TransaccionCommands::Etiquetar(args) => {
+            etiquetar_transaccion(&db, repo, args, output).await
+        }
+        TransaccionCommands::Etiquetas(args) => {
+            totales_por_etiqueta(&db, &retiro_repo, args, output).await
+        }
+        TransaccionCommands::Conciliacion(args) => {
+            cambiar_conciliacion(repo, args, output).await
+        }
     }
 }
 
+/// Tipo de transacción que corresponde a un tipo de categoría
+pub(super) fn tipo_transaccion(tipo: &TipoCategoria) -> TipoTransaccion {
+    match tipo {
+        TipoCategoria::Ingreso => TipoTransaccion::Ingreso,
+        TipoCategoria::Gasto => TipoTransaccion::Gasto,
+    }
+}
 
-async fn crear_transaccion(repo: TransaccionRepository, args: CrearTransaccionArgs) -> Result<()> {
-    println!("{}", "💰 Creando nueva transacción...".cyan().bold());
+async fn crear_transaccion(
+    db: &Database,
+    repo: TransaccionRepository,
+    args: CrearTransaccionArgs,
+) -> Result<()> {
+    let retiro_repo = RetiroRepository::new(db.pool().clone());
+    let categoria_repo = CategoriaRepository::new(db.pool().clone());
+
+    // Los campos que falten se preguntan; retiro y categoría se eligen de una lista
+    let retiro_id = match &args.retiro_id {
+        Some(referencia) => resolver::resolver_retiro(&retiro_repo, referencia).await?,
+        None if prompts::is_interactive() => prompts::seleccionar_retiro(&retiro_repo).await?,
+        None => return Err(prompts::falta_argumento("--retiro-id")),
+    };
 
-    let retiro_id = Uuid::parse_str(&args.retiro_id)
-        .map_err(|_| AppError::Validation("ID de retiro inválido".to_string()))?;
+    let mut categoria = match &args.categoria_id {
+        Some(referencia) => {
+            let id = resolver::resolver_categoria(&categoria_repo, referencia).await?;
+            Some(
+                categoria_repo
+                    .get_by_id(id)
+                    .await?
+                    .ok_or_else(|| AppError::NotFound("Categoría".to_string()))?,
+            )
+        }
+        None => None,
+    };
+
+    // Sin categoría se prueban las reglas de categorización: para ello se piden
+    // antes la descripción y el monto
+    let mut monto = args.monto;
+    let mut descripcion = args.descripcion;
+    let mut regla = None;
+    if categoria.is_none() && !args.sin_reglas {
+        let motor = ReglaRepository::new(db.pool().clone()).motor().await?;
+        if !motor.is_empty() {
+            let borrador = CreateTransaccion {
+                retiro_id,
+                categoria_id: uuid::Uuid::nil(),
+                tipo: TipoTransaccion::Gasto,
+                monto: 1.0,
+                descripcion: "-".to_string(),
+                cuenta_id: None,
+                metodo_pago: None,
+            };
+            let texto = prompts::requerido(descripcion.take(), "--descripcion", || {
+                prompts::pedir_campo("Descripción", None, &borrador, "descripcion", |t, v| {
+                    t.descripcion = v
+                })
+            })?;
+            let importe = prompts::requerido(monto.take(), "--monto", || {
+                prompts::pedir_campo("Monto (€)", None, &borrador, "monto", |t, v| t.monto = v)
+            })?;
+
+            let tipo = args.tipo.clone().map(TipoTransaccion::from);
+            regla = motor.evaluar(&texto, Some(importe), tipo.as_ref()).cloned();
+            if let Some(regla) = &regla {
+                categoria = categoria_repo.get_by_id(regla.categoria_id).await?;
+            }
+            descripcion = Some(texto);
+            monto = Some(importe);
+        }
+    }
+    if let (Some(regla), Some(categoria)) = (&regla, &categoria) {
+        println!(
+            "🪄 Regla '{}' aplicada: categoría {}",
+            regla.nombre.bright_white(),
+            categoria.nombre.bright_magenta()
+        );
+    }
 
-    let categoria_id = Uuid::parse_str(&args.categoria_id)
-        .map_err(|_| AppError::Validation("ID de categoría inválido".to_string()))?;
+    // Sin --tipo se usa el de la categoría indicada, o se pregunta
+    let tipo: TipoTransaccion = match (args.tipo, &categoria) {
+        (Some(tipo), _) => tipo.into(),
+        (None, Some(categoria)) => tipo_transaccion(&categoria.tipo),
+        (None, None) => tipo_transaccion(&prompts::requerido(None, "--tipo", || {
+            prompts::seleccionar_tipo(None)
+        })?),
+    };
+
+    let categoria_id = match categoria {
+        Some(categoria) => categoria.id,
+        None if prompts::is_interactive() => {
+            let tipo_categoria = match tipo {
+                TipoTransaccion::Ingreso => TipoCategoria::Ingreso,
+                TipoTransaccion::Gasto => TipoCategoria::Gasto,
+            };
+            prompts::seleccionar_categoria(&categoria_repo, Some(tipo_categoria)).await?
+        }
+        None => return Err(prompts::falta_argumento("--categoria-id")),
+    };
+
+    // Comprobar las etiquetas antes de crear nada; se suman las de la regla aplicada
+    let mut etiquetas = args.etiquetas;
+    if let Some(regla) = regla {
+        etiquetas.extend(regla.etiquetas);
+    }
+    let etiquetas = normalizar_etiquetas(&etiquetas)?;
+
+    let cuenta_id = match &args.cuenta {
+        Some(referencia) => Some(
+            resolver::resolver_cuenta(&CuentaRepository::new(db.pool().clone()), referencia)
+                .await?,
+        ),
+        None => None,
+    };
+
+    let borrador = CreateTransaccion {
+        retiro_id,
+        categoria_id,
+        tipo: tipo.clone(),
+        monto: 1.0,
+        descripcion: "-".to_string(),
+        cuenta_id: None,
+        metodo_pago: None,
+    };
+    let monto = prompts::requerido(monto, "--monto", || {
+        prompts::pedir_campo("Monto (€)", None, &borrador, "monto", |t, v| t.monto = v)
+    })?;
+    let descripcion = prompts::requerido(descripcion, "--descripcion", || {
+        prompts::pedir_campo("Descripción", None, &borrador, "descripcion", |t, v| {
+            t.descripcion = v
+        })
+    })?;
+
+    println!("{}", "💰 Creando nueva transacción...".cyan().bold());
 
     // La fecha ya no se usa en CreateTransaccion, se asigna automáticamente en el modelo
 
     let create_data = CreateTransaccion {
         retiro_id,
         categoria_id,
-        tipo: args.tipo.into(),
-        monto: args.monto,
-        descripcion: args.descripcion.clone(),
+        tipo,
+        monto,
+        descripcion,
+        cuenta_id,
+        metodo_pago: args.metodo_pago.map(Into::into),
     };
 
     // Validar datos antes de crear
-    if let Err(e) = create_data.validate() {
-        println!("{} {}", "❌ Error de validación:".red().bold(), e);
-        return Err(AppError::Validation(e.to_string()));
-    }
+    create_data
+        .validate()
+        .map_err(|e| AppError::Validation(e.to_string()))?;
 
-    match repo.create(create_data).await {
-        Ok(transaccion) => {
-            println!("{}", "✅ Transacción creada exitosamente!".green().bold());
-            println!();
-            println!("📋 {}", "Detalles:".bold());
-            println!("   ID: {}", transaccion.id.to_string().bright_blue());
-            println!(
-                "   Tipo: {}",
-                format!("{}", transaccion.tipo).bright_yellow()
-            );
-            println!(
-                "   Monto: {}",
-                format!("€{:.2}", transaccion.monto).bright_green()
-            );
-            println!("   Descripción: {}", transaccion.descripcion.bright_white());
-            println!(
-                "   Fecha: {}",
-                transaccion
-                    .created_at
-                    .format("%Y-%m-%d %H:%M")
-                    .to_string()
-                    .bright_cyan()
-            );
-            println!(
-                "   Retiro ID: {}",
-                transaccion.retiro_id.to_string().bright_magenta()
-            );
-            println!(
-                "   Categoría ID: {}",
-                transaccion.categoria_id.to_string().bright_magenta()
-            );
-        }
-        Err(e) => {
-            println!("{} {}", "❌ Error creando transacción:".red().bold(), e);
-            return Err(e);
-        }
+    let transaccion = repo.create(create_data).await?;
+    let etiquetas = if etiquetas.is_empty() {
+        Vec::new()
+    } else {
+        EtiquetaRepository::new(db.pool().clone())
+            .agregar(transaccion.id, &etiquetas)
+            .await?
+    };
+
+    println!("{}", "✅ Transacción creada exitosamente!".green().bold());
+    println!();
+    println!("📋 {}", "Detalles:".bold());
+    println!("   ID: {}", transaccion.id.to_string().bright_blue());
+    println!(
+        "   Tipo: {}",
+        format!("{}", transaccion.tipo).bright_yellow()
+    );
+    println!(
+        "   Monto: {}",
+        format!("€{:.2}", transaccion.monto).bright_green()
+    );
+    println!("   Descripción: {}", transaccion.descripcion.bright_white());
+    println!(
+        "   Fecha: {}",
+        transaccion
+            .created_at
+            .format("%Y-%m-%d %H:%M")
+            .to_string()
+            .bright_cyan()
+    );
+    println!(
+        "   Retiro ID: {}",
+        transaccion.retiro_id.to_string().bright_magenta()
+    );
+    println!(
+        "   Categoría ID: {}",
+        transaccion.categoria_id.to_string().bright_magenta()
+    );
+    if let Some(cuenta_id) = transaccion.cuenta_id {
+        println!("   Cuenta ID: {}", cuenta_id.to_string().bright_magenta());
+    }
+    if let Some(metodo_pago) = transaccion.metodo_pago {
+        println!("   Método de pago: {}", metodo_pago.to_string().bright_yellow());
+    }
+    if !etiquetas.is_empty() {
+        println!("   Etiquetas: {}", etiquetas.join(", ").bright_yellow());
     }
 
     Ok(())
 }
 
 async fn listar_transacciones(
+    db: &Database,
     repo: TransaccionRepository,
+    retiro_repo: &RetiroRepository,
     args: ListarTransaccionArgs,
+    output: OutputFormat,
 ) -> Result<()> {
+    let conciliacion: Option<EstadoConciliacion> = args.conciliacion.map(Into::into);
+
+    if output.is_structured() {
+        let retiro_id_str = args.retiro_id.as_deref().ok_or_else(|| {
+            AppError::Validation("Por favor especifica un retiro con --retiro-id".to_string())
+        })?;
+        let retiro_id = resolver::resolver_retiro(retiro_repo, retiro_id_str).await?;
+
+        let transacciones: Vec<_> = repo
+            .get_by_retiro_con_etiquetas(retiro_id, &args.etiquetas)
+            .await?
+            .into_iter()
+            .filter(|t| conciliacion.is_none_or(|estado| t.conciliacion == estado))
+            .take(args.limit)
+            .collect();
+        return output::print_list(output, &transacciones);
+    }
+
     println!("{}", "📋 Listando transacciones...".cyan().bold());
     println!();
 
-    let transacciones = if let Some(retiro_id_str) = &args.retiro_id {
-        let retiro_id = Uuid::parse_str(retiro_id_str)
-            .map_err(|_| AppError::Validation("ID de retiro inválido".to_string()))?;
-        repo.get_by_retiro(retiro_id).await?
+    let (transacciones, etiquetas) = if let Some(retiro_id_str) = &args.retiro_id {
+        let retiro_id = resolver::resolver_retiro(retiro_repo, retiro_id_str).await?;
+        (
+            repo.get_by_retiro_con_etiquetas(retiro_id, &args.etiquetas)
+                .await?
+                .into_iter()
+                .filter(|t| conciliacion.is_none_or(|estado| t.conciliacion == estado))
+                .collect::<Vec<_>>(),
+            EtiquetaRepository::new(db.pool().clone())
+                .get_by_retiro(retiro_id)
+                .await?,
+        )
     } else {
         // Para este ejemplo simplificado, no implementamos get_all
         println!(
@@ -211,14 +582,15 @@ async fn listar_transacciones(
     }
 
     println!(
-        "{:<38} {:<10} {:<12} {:<30} {:<12}",
+        "{:<38} {:<10} {:<12} {:<30} {:<12} {}",
         "ID".bold(),
         "TIPO".bold(),
         "MONTO".bold(),
         "DESCRIPCIÓN".bold(),
-        "FECHA".bold()
+        "FECHA".bold(),
+        "ETIQUETAS".bold()
     );
-    println!("{}", "─".repeat(110).bright_black());
+    println!("{}", "─".repeat(125).bright_black());
 
     let mut total_ingresos = 0.0;
     let mut total_gastos = 0.0;
@@ -247,7 +619,7 @@ async fn listar_transacciones(
         };
 
         println!(
-            "{:<38} {:<10} {:<12} {:<30} {:<12}",
+            "{:<38} {:<10} {:<12} {:<30} {:<12} {}",
             transaccion.id.to_string().bright_blue(),
             tipo_color,
             format!("€{:.2}", transaccion.monto).bright_green(),
@@ -257,6 +629,11 @@ async fn listar_transacciones(
                 .format("%Y-%m-%d")
                 .to_string()
                 .bright_cyan(),
+            etiquetas
+                .get(&transaccion.id)
+                .map(|e| e.join(", "))
+                .unwrap_or_default()
+                .bright_yellow(),
         );
 
         count += 1;
@@ -283,13 +660,22 @@ async fn listar_transacciones(
 }
 
 async fn mostrar_transaccion(
+    db: &Database,
     repo: TransaccionRepository,
     args: MostrarTransaccionArgs,
+    output: OutputFormat,
 ) -> Result<()> {
-    println!("{}", "🔍 Buscando transacción...".cyan().bold());
+    let id = resolver::resolver_transaccion(&repo, &args.id).await?;
 
-    let id =
-        Uuid::parse_str(&args.id).map_err(|_| AppError::Validation("ID inválido".to_string()))?;
+    if output.is_structured() {
+        let transaccion = repo
+            .get_by_id(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Transacción".to_string()))?;
+        return output::print_one(output, &transaccion);
+    }
+
+    println!("{}", "🔍 Buscando transacción...".cyan().bold());
 
     match repo.get_by_id(id).await? {
         Some(transaccion) => {
@@ -322,6 +708,29 @@ async fn mostrar_transaccion(
                 "   Categoría ID: {}",
                 transaccion.categoria_id.to_string().bright_magenta()
             );
+            if let Some(cuenta_id) = transaccion.cuenta_id {
+                println!("   Cuenta ID: {}", cuenta_id.to_string().bright_magenta());
+            }
+            if let Some(metodo_pago) = transaccion.metodo_pago {
+                println!("   Método de pago: {}", metodo_pago.to_string().bright_yellow());
+            }
+            match transaccion.linea_extracto_id {
+                Some(linea_id) => println!(
+                    "   Conciliación: {} (línea de extracto {})",
+                    transaccion.conciliacion.to_string().bright_yellow(),
+                    linea_id.to_string()[..8].bright_blue()
+                ),
+                None => println!(
+                    "   Conciliación: {}",
+                    transaccion.conciliacion.to_string().bright_yellow()
+                ),
+            }
+            let etiquetas = EtiquetaRepository::new(db.pool().clone())
+                .get_by_transaccion(transaccion.id)
+                .await?;
+            if !etiquetas.is_empty() {
+                println!("   Etiquetas: {}", etiquetas.join(", ").bright_yellow());
+            }
             println!(
                 "   Creado: {}",
                 transaccion
@@ -340,7 +749,6 @@ async fn mostrar_transaccion(
             );
         }
         None => {
-            println!("{}", "❌ Transacción no encontrada.".red().bold());
             return Err(AppError::NotFound("Transacción".to_string()));
         }
     }
@@ -352,14 +760,12 @@ async fn eliminar_transaccion(
     repo: TransaccionRepository,
     args: EliminarTransaccionArgs,
 ) -> Result<()> {
-    let id =
-        Uuid::parse_str(&args.id).map_err(|_| AppError::Validation("ID inválido".to_string()))?;
+    let id = resolver::resolver_transaccion(&repo, &args.id).await?;
 
     // Verificar que la transacción existe
     let transaccion = match repo.get_by_id(id).await? {
         Some(t) => t,
         None => {
-            println!("{}", "❌ Transacción no encontrada.".red().bold());
             return Err(AppError::NotFound("Transacción".to_string()));
         }
     };
@@ -394,14 +800,14 @@ async fn eliminar_transaccion(
         true => {
             println!(
                 "{}",
-                "✅ Transacción eliminada exitosamente!".green().bold()
+                "✅ Transacción enviada a la papelera!".green().bold()
             );
-        }
-        false => {
             println!(
-                "{}",
-                "❌ Error: No se pudo eliminar la transacción.".red().bold()
+                "   {}",
+                format!("Restaurable con: papelera restaurar {}", id).bright_black()
             );
+        }
+        false => {
             return Err(AppError::Internal(
                 "Error eliminando transacción".to_string(),
             ));
@@ -411,68 +817,448 @@ async fn eliminar_transaccion(
     Ok(())
 }
 
-async fn calcular_balance(repo: TransaccionRepository, args: BalanceArgs) -> Result<()> {
+async fn calcular_balance(
+    repo: TransaccionRepository,
+    retiro_repo: &RetiroRepository,
+    args: BalanceArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let retiro_id = resolver::resolver_retiro(retiro_repo, &args.retiro_id).await?;
+
+    if output.is_structured() {
+        let resumen = repo.get_financial_summary(retiro_id).await?;
+        return output::print_one(output, &resumen);
+    }
+
     println!("{}", "💰 Calculando balance del retiro...".cyan().bold());
 
-    let retiro_id = Uuid::parse_str(&args.retiro_id)
-        .map_err(|_| AppError::Validation("ID de retiro inválido".to_string()))?;
+    let resumen = repo.get_financial_summary(retiro_id).await?;
+    println!("{}", "✅ Balance calculado!".green().bold());
+    println!();
 
-    match repo.calculate_balance(retiro_id, None).await {
-        Ok(balance) => {
-            println!("{}", "✅ Balance calculado!".green().bold());
-            println!();
+    let balance = resumen.balance;
+
+    println!("📊 {}", "Resumen financiero:".bold());
+    println!("   Retiro ID: {}", resumen.retiro_id.to_string().bright_blue());
+    println!(
+        "   Total ingresos: {} ({} transacciones)",
+        format!("€{:.2}", resumen.total_ingresos).green(),
+        resumen.count_ingresos
+    );
+    println!(
+        "   Total gastos: {} ({} transacciones)",
+        format!("€{:.2}", resumen.total_gastos).red(),
+        resumen.count_gastos
+    );
+    println!(
+        "   {}: {}",
+        "Balance final".bold(),
+        format!("€{:.2}", balance).bright_yellow()
+    );
 
-            // También obtener las transacciones para mostrar más detalles
-            let transacciones = repo.get_by_retiro(retiro_id).await?;
-            let mut total_ingresos = 0.0;
-            let mut total_gastos = 0.0;
-            let mut count_ingresos = 0;
-            let mut count_gastos = 0;
-
-            for transaccion in &transacciones {
-                match transaccion.tipo {
-                    TipoTransaccion::Ingreso => {
-                        total_ingresos += transaccion.monto;
-                        count_ingresos += 1;
-                    }
-                    TipoTransaccion::Gasto => {
-                        total_gastos += transaccion.monto;
-                        count_gastos += 1;
-                    }
-                }
+    if balance > 0.0 {
+        println!("   Estado: {}", "Superávit ✅".green());
+    } else if balance < 0.0 {
+        println!("   Estado: {}", "Déficit ⚠️".red());
+    } else {
+        println!("   Estado: {}", "Equilibrado 🟰".yellow());
+    }
+
+    Ok(())
+}
+
+fn parse_date(date_str: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(date_str, "%Y-%m-%d").map_err(|_| {
+        AppError::Validation(format!(
+            "Formato de fecha inválido: {}. Use YYYY-MM-DD",
+            date_str
+        ))
+    })
+}
+
+/// Generar un sparkline ASCII a partir de una serie de valores
+fn sparkline(valores: &[f64]) -> String {
+    const BARRAS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let min = valores.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = valores.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let rango = max - min;
+
+    valores
+        .iter()
+        .map(|valor| {
+            if rango <= f64::EPSILON {
+                BARRAS[BARRAS.len() / 2]
+            } else {
+                let indice = ((valor - min) / rango * (BARRAS.len() - 1) as f64).round() as usize;
+                BARRAS[indice.min(BARRAS.len() - 1)]
             }
+        })
+        .collect()
+}
 
-            println!("📊 {}", "Resumen financiero:".bold());
-            println!("   Retiro ID: {}", retiro_id.to_string().bright_blue());
-            println!(
-                "   Total ingresos: {} ({} transacciones)",
-                format!("€{:.2}", total_ingresos).green(),
-                count_ingresos
-            );
+async fn mostrar_flujo_caja(
+    repo: TransaccionRepository,
+    retiro_repo: &RetiroRepository,
+    args: FlujoArgs,
+) -> Result<()> {
+    println!("{}", "📈 Calculando flujo de caja...".cyan().bold());
+    println!();
+
+    if args.retiro_id.is_none() && args.desde.is_none() && args.hasta.is_none() {
+        println!(
+            "{}",
+            "⚠️  Por favor especifica un retiro con --retiro-id o un rango con --desde/--hasta"
+                .yellow()
+        );
+        return Ok(());
+    }
+
+    let retiro_id = match &args.retiro_id {
+        Some(retiro_id_str) => Some(resolver::resolver_retiro(retiro_repo, retiro_id_str).await?),
+        None => None,
+    };
+    let desde = args.desde.as_deref().map(parse_date).transpose()?;
+    let hasta = args.hasta.as_deref().map(parse_date).transpose()?;
+
+    let serie = repo
+        .get_flujo_caja(retiro_id, args.periodo.into(), desde, hasta)
+        .await?;
+
+    if serie.is_empty() {
+        println!("{}", "📭 No se encontraron transacciones.".yellow());
+        return Ok(());
+    }
+
+    println!(
+        "{:<12} {:<12} {:<12} {:<14} {:<14} {:<12}",
+        "PERIODO".bold(),
+        "INGRESOS".bold(),
+        "GASTOS".bold(),
+        "ING. ACUM.".bold(),
+        "GAS. ACUM.".bold(),
+        "BALANCE".bold()
+    );
+    println!("{}", "─".repeat(82).bright_black());
+
+    for punto in &serie {
+        let balance = format!("€{:.2}", punto.balance);
+        let balance_color = if punto.balance < 0.0 {
+            balance.red()
+        } else {
+            balance.bright_yellow()
+        };
+
+        println!(
+            "{:<12} {:<12} {:<12} {:<14} {:<14} {:<12}",
+            punto.periodo.format("%Y-%m-%d").to_string().bright_cyan(),
+            format!("€{:.2}", punto.ingresos).green(),
+            format!("€{:.2}", punto.gastos).red(),
+            format!("€{:.2}", punto.ingresos_acumulados).green(),
+            format!("€{:.2}", punto.gastos_acumulados).red(),
+            balance_color
+        );
+    }
+
+    let balances: Vec<f64> = serie.iter().map(|punto| punto.balance).collect();
+
+    println!();
+    println!("{}", "📊 Evolución del balance:".bold());
+    println!("   {}", sparkline(&balances).bright_yellow());
+    println!(
+        "   Periodos: {}",
+        serie.len().to_string().bright_green()
+    );
+
+    Ok(())
+}
+
+async fn adjuntar_comprobantes(
+    db: &Database,
+    repo: TransaccionRepository,
+    database_url: &str,
+    args: AdjuntarArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let id = resolver::resolver_transaccion(&repo, &args.id).await?;
+    let almacen = AlmacenAdjuntos::new(database_url)?;
+    let adjunto_repo = AdjuntoRepository::new(db.pool().clone());
+
+    if !output.is_structured() {
+        println!("{}", "📎 Adjuntando comprobantes...".cyan().bold());
+    }
+
+    let mut adjuntos = Vec::new();
+    for archivo in &args.archivos {
+        let contenido = std::fs::read(archivo).map_err(|e| {
+            AppError::Validation(format!("No se pudo leer {}: {}", archivo.display(), e))
+        })?;
+        let nombre = archivo.to_string_lossy();
+
+        let adjunto = adjunto_repo
+            .adjuntar(&almacen, id, &nombre, &contenido)
+            .await?;
+        if !output.is_structured() {
             println!(
-                "   Total gastos: {} ({} transacciones)",
-                format!("€{:.2}", total_gastos).red(),
-                count_gastos
+                "   {} {} ({}, {})",
+                "✅".green(),
+                adjunto.nombre.bright_white(),
+                adjunto.tipo_mime.bright_black(),
+                formatear_tamano(adjunto.tamano).bright_black()
             );
+        }
+        adjuntos.push(adjunto);
+    }
+
+    if output.is_structured() {
+        return output::print_list(output, &adjuntos);
+    }
+
+    println!(
+        "{}",
+        format!("✅ {} comprobantes adjuntados!", adjuntos.len())
+            .green()
+            .bold()
+    );
+
+    Ok(())
+}
+
+async fn listar_adjuntos(
+    db: &Database,
+    repo: TransaccionRepository,
+    database_url: &str,
+    args: AdjuntosArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let id = resolver::resolver_transaccion(&repo, &args.id).await?;
+    let transaccion = repo
+        .get_by_id(id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Transacción".to_string()))?;
+    let adjuntos = AdjuntoRepository::new(db.pool().clone())
+        .get_by_transaccion(id)
+        .await?;
+
+    if output.is_structured() {
+        return output::print_list(output, &adjuntos);
+    }
+
+    println!(
+        "{} {}",
+        "📎 Comprobantes de".cyan().bold(),
+        transaccion.descripcion.bright_white()
+    );
+    println!();
+
+    if adjuntos.is_empty() {
+        println!("{}", "📭 La transacción no tiene comprobantes.".yellow());
+        return Ok(());
+    }
+
+    let almacen = AlmacenAdjuntos::new(database_url)?;
+    println!(
+        "{:<10} {:<30} {:<18} {:<10} {:<12}",
+        "ID".bold(),
+        "NOMBRE".bold(),
+        "TIPO".bold(),
+        "TAMAÑO".bold(),
+        "FECHA".bold()
+    );
+    println!("{}", "─".repeat(85).bright_black());
+
+    for adjunto in &adjuntos {
+        println!(
+            "{:<10} {:<30} {:<18} {:<10} {:<12}",
+            adjunto.id.to_string()[..8].bright_blue(),
+            adjunto.nombre.bright_white(),
+            adjunto.tipo_mime.bright_yellow(),
+            formatear_tamano(adjunto.tamano),
+            adjunto.created_at.format("%d/%m/%Y").to_string().bright_cyan()
+        );
+        println!("   {}", almacen.ruta(&adjunto.hash).display().to_string().bright_black());
+    }
+
+    Ok(())
+}
+
+async fn gastos_sin_comprobante(
+    db: &Database,
+    retiro_repo: &RetiroRepository,
+    args: SinComprobanteArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let retiro_id = match &args.retiro_id {
+        Some(referencia) => Some(resolver::resolver_retiro(retiro_repo, referencia).await?),
+        None => None,
+    };
+    let gastos = AdjuntoRepository::new(db.pool().clone())
+        .gastos_sin_comprobante(retiro_id)
+        .await?;
+
+    if output.is_structured() {
+        return output::print_list(output, &gastos);
+    }
+
+    println!("{}", "🧾 Gastos sin comprobante...".cyan().bold());
+    println!();
+
+    if gastos.is_empty() {
+        println!("{}", "✅ Todos los gastos tienen comprobante.".green());
+        return Ok(());
+    }
+
+    let mut total = 0.0;
+    for grupo in gastos.chunk_by(|a, b| a.retiro_id == b.retiro_id) {
+        let subtotal: f64 = grupo.iter().map(|g| g.monto).sum();
+        total += subtotal;
+
+        println!(
+            "🏕️  {} {}",
+            grupo[0].retiro.bold(),
+            format!("({} gastos, €{:.2})", grupo.len(), subtotal).bright_black()
+        );
+        for gasto in grupo {
             println!(
-                "   {}: {}",
-                "Balance final".bold(),
-                format!("€{:.2}", balance).bright_yellow()
+                "   {:<10} {:<12} {:<20} {:<12} {}",
+                gasto.transaccion_id.to_string()[..8].bright_blue(),
+                gasto.fecha.format("%d/%m/%Y").to_string().bright_cyan(),
+                gasto.categoria.bright_magenta(),
+                format!("€{:.2}", gasto.monto).red(),
+                gasto.descripcion.bright_white()
             );
-
-            if balance > 0.0 {
-                println!("   Estado: {}", "Superávit ✅".green());
-            } else if balance < 0.0 {
-                println!("   Estado: {}", "Déficit ⚠️".red());
-            } else {
-                println!("   Estado: {}", "Equilibrado 🟰".yellow());
-            }
-        }
-        Err(e) => {
-            println!("{} {}", "❌ Error calculando balance:".red().bold(), e);
-            return Err(e);
         }
+        println!();
+    }
+
+    println!("{}", "📊 Resumen:".bold());
+    println!("   Gastos sin comprobante: {}", gastos.len());
+    println!("   Importe sin justificar: {}", format!("€{:.2}", total).red());
+
+    Ok(())
+}
+
+async fn etiquetar_transaccion(
+    db: &Database,
+    repo: TransaccionRepository,
+    args: EtiquetarArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let id = resolver::resolver_transaccion(&repo, &args.id).await?;
+    let etiqueta_repo = EtiquetaRepository::new(db.pool().clone());
+
+    let resultado = if args.quitar {
+        etiqueta_repo.quitar(id, &args.etiquetas).await
+    } else {
+        etiqueta_repo.agregar(id, &args.etiquetas).await
+    };
+
+    let etiquetas = resultado?;
+
+    if output.is_structured() {
+        let asignaciones: Vec<_> = etiquetas
+            .into_iter()
+            .map(|etiqueta| EtiquetaTransaccion {
+                transaccion_id: id,
+                etiqueta,
+            })
+            .collect();
+        return output::print_list(output, &asignaciones);
+    }
+
+    println!("{}", "🏷️  Etiquetas actualizadas!".green().bold());
+    if etiquetas.is_empty() {
+        println!("   La transacción no tiene etiquetas.");
+    } else {
+        println!("   Etiquetas: {}", etiquetas.join(", ").bright_yellow());
+    }
+
+    Ok(())
+}
+
+async fn cambiar_conciliacion(
+    repo: TransaccionRepository,
+    args: ConciliacionArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let id = resolver::resolver_transaccion(&repo, &args.id).await?;
+
+    let transaccion = repo.set_conciliacion(id, args.estado.into()).await?;
+    if output.is_structured() {
+        return output::print_one(output, &transaccion);
+    }
+
+    println!("{}", "✅ Estado de conciliación actualizado!".green().bold());
+    println!("   Transacción: {}", transaccion.descripcion.bright_white());
+    println!(
+        "   Conciliación: {}",
+        transaccion.conciliacion.to_string().bright_yellow()
+    );
+
+    Ok(())
+}
+
+async fn totales_por_etiqueta(
+    db: &Database,
+    retiro_repo: &RetiroRepository,
+    args: EtiquetasArgs,
+    output: OutputFormat,
+) -> Result<()> {
+    let retiro_id = match &args.retiro_id {
+        Some(referencia) => Some(resolver::resolver_retiro(retiro_repo, referencia).await?),
+        None => None,
+    };
+    let totales = EtiquetaRepository::new(db.pool().clone())
+        .get_totales(retiro_id)
+        .await?;
+
+    if output.is_structured() {
+        return output::print_list(output, &totales);
+    }
+
+    println!("{}", "🏷️  Totales por etiqueta...".cyan().bold());
+    println!();
+
+    if totales.is_empty() {
+        println!("{}", "📭 No hay transacciones etiquetadas.".yellow());
+        return Ok(());
+    }
+
+    println!(
+        "{:<30} {:>12} {:>12} {:>12} {:>6}",
+        "ETIQUETA".bold(),
+        "INGRESOS".bold(),
+        "GASTOS".bold(),
+        "BALANCE".bold(),
+        "NÚM.".bold()
+    );
+    println!("{}", "─".repeat(76).bright_black());
+
+    for total in &totales {
+        println!(
+            "{:<30} {:>12} {:>12} {:>12} {:>6}",
+            total.etiqueta.bright_yellow(),
+            format!("€{:.2}", total.ingresos).green(),
+            format!("€{:.2}", total.gastos).red(),
+            format!("€{:.2}", total.ingresos - total.gastos).bright_white(),
+            total.transacciones
+        );
     }
 
+    println!();
+    println!(
+        "{}",
+        "   Una transacción con varias etiquetas suma en cada una de ellas.".bright_black()
+    );
+
     Ok(())
 }
+
+/// Tamaño de archivo legible (B, KB o MB)
+fn formatear_tamano(bytes: i64) -> String {
+    match bytes {
+        b if b < 1024 => format!("{} B", b),
+        b if b < 1024 * 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{:.1} MB", b as f64 / (1024.0 * 1024.0)),
+    }
+}
@@ -0,0 +1,133 @@
+use clap::{Args, Subcommand};
+use colored::*;
+
+use crate::config::{self, Config};
+use crate::database::Database;
+use crate::Result;
+
+#[derive(Subcommand)]
+pub enum WorkspaceCommands {
+    /// Crear un nuevo workspace con su propia base de datos
+    Crear(CrearWorkspaceArgs),
+    /// Listar workspaces
+    Listar,
+    /// Seleccionar el workspace activo
+    Usar(UsarWorkspaceArgs),
+}
+
+#[derive(Args)]
+pub struct CrearWorkspaceArgs {
+    /// Nombre del workspace (letras, números, '-' y '_')
+    pub nombre: String,
+
+    /// Ruta de la base de datos (por defecto en el directorio de datos)
+    #[arg(long)]
+    pub database: Option<String>,
+
+    /// Seleccionar el workspace como activo tras crearlo
+    #[arg(short, long)]
+    pub usar: bool,
+}
+
+#[derive(Args)]
+pub struct UsarWorkspaceArgs {
+    /// Nombre del workspace
+    pub nombre: String,
+}
+
+pub async fn handle_workspace_command(command: WorkspaceCommands) -> Result<()> {
+    match command {
+        WorkspaceCommands::Crear(args) => crear_workspace(args).await,
+        WorkspaceCommands::Listar => listar_workspaces().await,
+        WorkspaceCommands::Usar(args) => usar_workspace(args).await,
+    }
+}
+
+async fn crear_workspace(args: CrearWorkspaceArgs) -> Result<()> {
+    println!("{}", "🆕 Creando nuevo workspace...".cyan().bold());
+
+    let mut config = Config::load()?;
+    let database = config.add_workspace(&args.nombre, args.database)?.database.clone();
+
+    // Crear la base de datos y aplicar las migraciones antes de guardar la configuración
+    Database::new(&config::normalize_database_url(&database)).await?;
+
+    if args.usar {
+        config.set_workspace_activo(&args.nombre)?;
+    }
+    config.save()?;
+
+    println!("{}", "✅ Workspace creado exitosamente!".green().bold());
+    println!();
+    println!("📋 {}", "Detalles:".bold());
+    println!("   Nombre: {}", args.nombre.bright_white());
+    println!("   Base de datos: {}", database.bright_magenta());
+    if args.usar {
+        println!("   Estado: {}", "Activo".green());
+    }
+
+    Ok(())
+}
+
+async fn listar_workspaces() -> Result<()> {
+    println!("{}", "📋 Listando workspaces...".cyan().bold());
+    println!();
+
+    let config = Config::load()?;
+
+    if config.workspaces.is_empty() {
+        println!("{}", "📭 No hay workspaces configurados.".yellow());
+        return Ok(());
+    }
+
+    println!(
+        "{:<3} {:<25} {:<60}",
+        "".bold(),
+        "NOMBRE".bold(),
+        "BASE DE DATOS".bold()
+    );
+    println!("{}", "─".repeat(90).bright_black());
+
+    for (nombre, workspace) in &config.workspaces {
+        let activo = config.workspace_activo.as_deref() == Some(nombre.as_str());
+        let marca = if activo { "●".green() } else { " ".normal() };
+
+        println!(
+            "{:<3} {:<25} {:<60}",
+            marca,
+            nombre.bright_white(),
+            workspace.database.bright_magenta()
+        );
+    }
+
+    println!();
+    println!(
+        "{} {}",
+        "📊 Total:".bold(),
+        config.workspaces.len().to_string().bright_green()
+    );
+
+    Ok(())
+}
+
+async fn usar_workspace(args: UsarWorkspaceArgs) -> Result<()> {
+    let mut config = Config::load()?;
+
+    config.set_workspace_activo(&args.nombre)?;
+    config.save()?;
+
+    println!(
+        "{} {}",
+        "✅ Workspace activo:".green().bold(),
+        args.nombre.bright_white()
+    );
+
+    if std::env::var("DATABASE_URL").is_ok() {
+        println!(
+            "{}",
+            "⚠️  DATABASE_URL está definida y tiene prioridad sobre el workspace activo.".yellow()
+        );
+    }
+
+    Ok(())
+}
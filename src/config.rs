@@ -0,0 +1,253 @@
+use crate::{AppError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Nombre del directorio de la aplicación dentro de los directorios del sistema
+const APP_DIR: &str = "retiros";
+const CONFIG_FILE: &str = "config.toml";
+const DATABASE_FILE: &str = "retiros.db";
+const WORKSPACES_DIR: &str = "workspaces";
+
+/// Configuración persistente de la aplicación (`config.toml`)
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Ruta o URL de la base de datos (`sqlite:...`)
+    pub database: Option<String>,
+
+    /// Workspace seleccionado con `workspace usar`
+    pub workspace_activo: Option<String>,
+
+    /// Workspaces registrados, cada uno con su propia base de datos
+    pub workspaces: BTreeMap<String, Workspace>,
+
+    /// Política de copias de seguridad
+    pub backup: BackupConfig,
+
+    /// Retención de la papelera
+    pub papelera: PapeleraConfig,
+}
+
+/// Configuración de copias de seguridad (`[backup]` en config.toml)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupConfig {
+    /// Directorio de las copias; por defecto `backups/` junto a la base de datos
+    pub directorio: Option<String>,
+
+    /// Número de copias a conservar por base de datos
+    pub mantener: usize,
+
+    /// Copia automática al iniciar la app desktop y antes de operaciones destructivas
+    pub automatico: bool,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            directorio: None,
+            mantener: 10,
+            automatico: true,
+        }
+    }
+}
+
+/// Configuración de la papelera (`[papelera]` en config.toml)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PapeleraConfig {
+    /// Días que se conservan los elementos eliminados antes de purgarlos; 0 desactiva la purga
+    pub dias: u32,
+}
+
+impl Default for PapeleraConfig {
+    fn default() -> Self {
+        Self { dias: 30 }
+    }
+}
+
+/// Espacio de trabajo (organización) con su propia base de datos SQLite
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    /// Ruta o URL de la base de datos del workspace
+    pub database: String,
+}
+
+impl Config {
+    /// Ruta del archivo de configuración.
+    /// Se puede sobrescribir con `RETIROS_CONFIG`; por defecto usa el directorio de
+    /// configuración del sistema (`~/.config/retiros/config.toml` en Linux).
+    pub fn path() -> Result<PathBuf> {
+        if let Ok(path) = std::env::var("RETIROS_CONFIG") {
+            return Ok(PathBuf::from(path));
+        }
+
+        dirs::config_dir()
+            .map(|dir| dir.join(APP_DIR).join(CONFIG_FILE))
+            .ok_or_else(|| {
+                AppError::Config("No se pudo determinar el directorio de configuración".to_string())
+            })
+    }
+
+    /// Cargar la configuración; si el archivo no existe se usan los valores por defecto
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contenido = std::fs::read_to_string(&path).map_err(|e| {
+            AppError::Config(format!("Error leyendo {}: {}", path.display(), e))
+        })?;
+
+        toml::from_str(&contenido)
+            .map_err(|e| AppError::Config(format!("Error en {}: {}", path.display(), e)))
+    }
+
+    /// Guardar la configuración, creando el directorio si hace falta
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| AppError::Config(format!("Error creando {}: {}", dir.display(), e)))?;
+        }
+
+        let contenido = toml::to_string_pretty(self)
+            .map_err(|e| AppError::Config(format!("Error serializando configuración: {}", e)))?;
+
+        std::fs::write(&path, contenido)
+            .map_err(|e| AppError::Config(format!("Error escribiendo {}: {}", path.display(), e)))
+    }
+
+    /// Obtener un workspace por nombre
+    pub fn workspace(&self, nombre: &str) -> Result<&Workspace> {
+        self.workspaces
+            .get(nombre)
+            .ok_or_else(|| AppError::NotFound(format!("Workspace '{}'", nombre)))
+    }
+
+    /// Registrar un nuevo workspace. Si no se indica base de datos se usa
+    /// `workspaces/<nombre>.db` dentro del directorio de datos.
+    pub fn add_workspace(&mut self, nombre: &str, database: Option<String>) -> Result<&Workspace> {
+        validate_workspace_name(nombre)?;
+
+        if self.workspaces.contains_key(nombre) {
+            return Err(AppError::Validation(format!(
+                "Ya existe un workspace llamado '{}'",
+                nombre
+            )));
+        }
+
+        let database = match database {
+            Some(database) => database,
+            None => {
+                let dir = data_dir()?.join(WORKSPACES_DIR);
+                std::fs::create_dir_all(&dir).map_err(|e| {
+                    AppError::Config(format!("Error creando {}: {}", dir.display(), e))
+                })?;
+                dir.join(format!("{}.db", nombre)).display().to_string()
+            }
+        };
+
+        Ok(self
+            .workspaces
+            .entry(nombre.to_string())
+            .or_insert(Workspace { database }))
+    }
+
+    /// Marcar un workspace existente como activo
+    pub fn set_workspace_activo(&mut self, nombre: &str) -> Result<()> {
+        self.workspace(nombre)?;
+        self.workspace_activo = Some(nombre.to_string());
+        Ok(())
+    }
+}
+
+/// Los nombres de workspace se usan como nombre de archivo
+fn validate_workspace_name(nombre: &str) -> Result<()> {
+    let valido = !nombre.is_empty()
+        && nombre.len() <= 50
+        && nombre
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if valido {
+        Ok(())
+    } else {
+        Err(AppError::Validation(
+            "El nombre del workspace solo puede contener letras, números, '-' y '_' (máx. 50)"
+                .to_string(),
+        ))
+    }
+}
+
+/// Directorio de datos por defecto según el sistema operativo.
+/// Se puede sobrescribir con `RETIROS_DATA_DIR`; por defecto es `~/.local/share/retiros`
+/// en Linux, `~/Library/Application Support/retiros` en macOS y `%APPDATA%\retiros` en Windows.
+pub fn data_dir() -> Result<PathBuf> {
+    let dir = match std::env::var("RETIROS_DATA_DIR") {
+        Ok(dir) => PathBuf::from(dir),
+        Err(_) => dirs::data_dir()
+            .map(|dir| dir.join(APP_DIR))
+            .ok_or_else(|| {
+                AppError::Config("No se pudo determinar el directorio de datos".to_string())
+            })?,
+    };
+
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| AppError::Config(format!("Error creando {}: {}", dir.display(), e)))?;
+
+    Ok(dir)
+}
+
+/// Resolver la URL de la base de datos para todos los puntos de entrada.
+///
+/// Orden de prioridad:
+/// 1. Opción `--database` del CLI
+/// 2. Opción `--workspace` del CLI
+/// 3. Variable de entorno `DATABASE_URL`
+/// 4. Workspace activo del archivo de configuración
+/// 5. Clave `database` del archivo de configuración
+/// 6. `retiros.db` en el directorio de datos del sistema
+///
+/// El entorno tiene prioridad sobre el archivo de configuración para poder apuntar
+/// una ejecución concreta a otra base de datos sin tocar `config.toml`.
+pub fn resolve_database_url(
+    cli_database: Option<&str>,
+    cli_workspace: Option<&str>,
+) -> Result<String> {
+    if let Some(database) = cli_database {
+        return Ok(normalize_database_url(database));
+    }
+
+    let config = Config::load()?;
+
+    if let Some(nombre) = cli_workspace {
+        return Ok(normalize_database_url(&config.workspace(nombre)?.database));
+    }
+
+    if let Ok(database) = std::env::var("DATABASE_URL") {
+        return Ok(normalize_database_url(&database));
+    }
+
+    if let Some(nombre) = &config.workspace_activo {
+        return Ok(normalize_database_url(&config.workspace(nombre)?.database));
+    }
+
+    if let Some(database) = config.database {
+        return Ok(normalize_database_url(&database));
+    }
+
+    let path = data_dir()?.join(DATABASE_FILE);
+    Ok(format!("sqlite:{}", path.display()))
+}
+
+/// Aceptar tanto URLs `sqlite:` como rutas simples a un archivo
+pub fn normalize_database_url(database: &str) -> String {
+    if database.starts_with("sqlite:") {
+        database.to_string()
+    } else {
+        format!("sqlite:{}", database)
+    }
+}
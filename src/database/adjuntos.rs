@@ -0,0 +1,152 @@
+use crate::{AppError, Result};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use super::backup::database_path;
+use super::Backups;
+
+/// Antigüedad mínima de un archivo sin adjunto para purgarlo: al adjuntar, el archivo se
+/// guarda antes de crear el adjunto que lo referencia
+const MARGEN_PURGA: Duration = Duration::from_secs(60 * 60);
+
+/// Archivos de los comprobantes, guardados por contenido en
+/// `adjuntos/<base>/<2 primeros caracteres del hash>/<hash>` junto a la base de datos.
+/// Un mismo archivo adjunto a varias transacciones se guarda una sola vez.
+pub struct AlmacenAdjuntos {
+    directorio: PathBuf,
+}
+
+impl AlmacenAdjuntos {
+    /// Preparar el almacén de la base de datos indicada
+    pub fn new(database_url: &str) -> Result<Self> {
+        let database_path = database_path(database_url).map_err(|_| {
+            AppError::Validation("Los adjuntos requieren una base de datos en archivo".to_string())
+        })?;
+
+        let base = database_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("retiros")
+            .to_string();
+        let directorio = database_path
+            .parent()
+            .map(|dir| dir.join("adjuntos"))
+            .unwrap_or_else(|| PathBuf::from("adjuntos"))
+            .join(base);
+
+        Ok(Self { directorio })
+    }
+
+    pub fn directorio(&self) -> &Path {
+        &self.directorio
+    }
+
+    /// SHA-256 del contenido en hexadecimal
+    pub fn hash(contenido: &[u8]) -> String {
+        Sha256::digest(contenido)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Ruta del archivo con el hash indicado
+    pub fn ruta(&self, hash: &str) -> PathBuf {
+        self.directorio.join(&hash[..2]).join(hash)
+    }
+
+    /// Guardar el contenido si aún no existe y devolver su hash
+    pub fn guardar(&self, contenido: &[u8]) -> Result<String> {
+        let hash = Self::hash(contenido);
+        let path = self.ruta(&hash);
+        if path.exists() {
+            return Ok(hash);
+        }
+
+        let dir = path.parent().unwrap_or(&self.directorio);
+        std::fs::create_dir_all(dir)
+            .map_err(|e| AppError::Internal(format!("Error creando {}: {}", dir.display(), e)))?;
+
+        // Escribir en un temporal y renombrar para no dejar archivos a medias
+        let temporal = path.with_extension("tmp");
+        std::fs::write(&temporal, contenido).map_err(|e| {
+            AppError::Internal(format!("Error escribiendo {}: {}", temporal.display(), e))
+        })?;
+        std::fs::rename(&temporal, &path).map_err(|e| {
+            AppError::Internal(format!("Error guardando {}: {}", path.display(), e))
+        })?;
+
+        Ok(hash)
+    }
+
+    /// Leer un archivo comprobando que su contenido coincide con el hash
+    pub fn leer(&self, hash: &str) -> Result<Vec<u8>> {
+        let path = self.ruta(hash);
+        let contenido = std::fs::read(&path).map_err(|e| {
+            AppError::NotFound(format!("Archivo del adjunto {} ({})", path.display(), e))
+        })?;
+
+        if Self::hash(&contenido) != hash {
+            return Err(AppError::Validation(format!(
+                "El archivo {} está dañado: su contenido no coincide con el hash",
+                path.display()
+            )));
+        }
+
+        Ok(contenido)
+    }
+
+    /// Borrar los archivos que no referencia ningún adjunto de la base de datos ni de sus copias
+    /// de seguridad, para que restaurar una copia no deje comprobantes sin archivo.
+    /// Eliminar adjuntos o vaciar la papelera nunca borra archivos: solo lo hace este paso de
+    /// mantenimiento (`db purgar-adjuntos`). Devuelve cuántos se borraron.
+    pub async fn purgar_huerfanos(&self, pool: &SqlitePool, backups: &Backups) -> Result<usize> {
+        if !self.directorio.exists() {
+            return Ok(0);
+        }
+
+        let mut en_uso: HashSet<String> = sqlx::query!("SELECT DISTINCT hash FROM adjuntos")
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .map(|row| row.hash)
+            .collect();
+        en_uso.extend(backups.hashes_adjuntos().await?);
+
+        let limite = SystemTime::now() - MARGEN_PURGA;
+
+        let subdirectorios = std::fs::read_dir(&self.directorio).map_err(|e| {
+            AppError::Internal(format!("Error leyendo {}: {}", self.directorio.display(), e))
+        })?;
+
+        let mut borrados = 0;
+        for subdirectorio in subdirectorios.flatten() {
+            let archivos = match std::fs::read_dir(subdirectorio.path()) {
+                Ok(archivos) => archivos,
+                Err(_) => continue,
+            };
+            for archivo in archivos.flatten() {
+                let nombre = archivo.file_name().to_string_lossy().into_owned();
+                if en_uso.contains(&nombre) {
+                    continue;
+                }
+                let modificado = archivo.metadata().and_then(|m| m.modified());
+                if modificado.map_or(true, |modificado| modificado > limite) {
+                    continue;
+                }
+                std::fs::remove_file(archivo.path()).map_err(|e| {
+                    AppError::Internal(format!(
+                        "Error borrando {}: {}",
+                        archivo.path().display(),
+                        e
+                    ))
+                })?;
+                borrados += 1;
+            }
+        }
+
+        Ok(borrados)
+    }
+}
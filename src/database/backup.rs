@@ -0,0 +1,575 @@
+use crate::config::Config;
+use crate::{AppError, Result};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    Row, SqlitePool,
+};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Formato de la marca de tiempo incluida en el nombre de cada copia
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+/// Tablas que debe contener una copia para poder restaurarse
+const TABLAS_REQUERIDAS: [&str; 3] = ["categorias", "retiros", "transacciones"];
+
+/// Copia de seguridad existente en disco
+#[derive(Debug, Clone)]
+pub struct BackupInfo {
+    pub path: PathBuf,
+    pub fecha: DateTime<Local>,
+    pub bytes: u64,
+}
+
+/// Copias de seguridad de una base de datos SQLite.
+/// Los archivos se llaman `<base>-<YYYYMMDD-HHMMSS>-<etiqueta>.db`; si ya existe una copia
+/// con ese nombre (dos en el mismo segundo) se añade `-2`, `-3`... a la etiqueta.
+pub struct Backups {
+    database_path: PathBuf,
+    directorio: PathBuf,
+    mantener: usize,
+}
+
+impl Backups {
+    /// Preparar las copias de la base de datos indicada según `[backup]` en config.toml
+    pub fn new(database_url: &str) -> Result<Self> {
+        let database_path = database_path(database_url)?;
+        let config = Config::load()?.backup;
+
+        let directorio = match config.directorio {
+            Some(directorio) => PathBuf::from(directorio),
+            None => database_path
+                .parent()
+                .map(|dir| dir.join("backups"))
+                .unwrap_or_else(|| PathBuf::from("backups")),
+        };
+
+        Ok(Self {
+            database_path,
+            directorio,
+            mantener: config.mantener,
+        })
+    }
+
+    pub fn directorio(&self) -> &Path {
+        &self.directorio
+    }
+
+    /// Crear una copia consistente con `VACUUM INTO`, válida aunque haya otras conexiones abiertas,
+    /// y aplicar la política de retención
+    pub async fn create(&self, pool: &SqlitePool, etiqueta: &str) -> Result<PathBuf> {
+        let path = self.copiar(pool, etiqueta).await?;
+        self.apply_retention(None)?;
+        Ok(path)
+    }
+
+    /// Crear la copia sin aplicar la retención
+    async fn copiar(&self, pool: &SqlitePool, etiqueta: &str) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.directorio).map_err(|e| {
+            AppError::Internal(format!("Error creando {}: {}", self.directorio.display(), e))
+        })?;
+
+        let marca = Local::now().format(TIMESTAMP_FORMAT).to_string();
+        let path = nombre_libre(&self.directorio, &self.prefijo(), &marca, etiqueta);
+
+        sqlx::query("VACUUM INTO ?1")
+            .bind(path.display().to_string())
+            .execute(pool)
+            .await?;
+
+        Ok(path)
+    }
+
+    /// Listar las copias de esta base de datos, de la más reciente a la más antigua
+    pub fn list(&self) -> Result<Vec<BackupInfo>> {
+        if !self.directorio.exists() {
+            return Ok(Vec::new());
+        }
+
+        let entradas = std::fs::read_dir(&self.directorio).map_err(|e| {
+            AppError::Internal(format!("Error leyendo {}: {}", self.directorio.display(), e))
+        })?;
+
+        let prefijo = format!("{}-", self.prefijo());
+        let mut backups = Vec::new();
+        for entrada in entradas.flatten() {
+            let path = entrada.path();
+            let nombre = match path.file_name().and_then(|n| n.to_str()) {
+                Some(nombre) if nombre.starts_with(&prefijo) && nombre.ends_with(".db") => nombre,
+                _ => continue,
+            };
+
+            // Descartar archivos de otras bases de datos cuyo nombre empiece igual
+            let fecha = match nombre
+                .get(prefijo.len()..prefijo.len() + 15)
+                .and_then(|ts| NaiveDateTime::parse_from_str(ts, TIMESTAMP_FORMAT).ok())
+                .and_then(|ts| Local.from_local_datetime(&ts).earliest())
+            {
+                Some(fecha) => fecha,
+                None => continue,
+            };
+
+            let metadata = entrada.metadata().ok();
+            let bytes = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
+            let modificado = metadata.and_then(|m| m.modified().ok());
+            backups.push((modificado, BackupInfo { path, fecha, bytes }));
+        }
+
+        // Dos copias del mismo segundo se ordenan por la hora de modificación del archivo
+        backups.sort_by(|(ma, a), (mb, b)| {
+            b.fecha
+                .cmp(&a.fecha)
+                .then_with(|| mb.cmp(ma))
+                .then_with(|| b.path.cmp(&a.path))
+        });
+        Ok(backups.into_iter().map(|(_, backup)| backup).collect())
+    }
+
+    /// Hashes de los archivos de comprobantes que referencian las copias conservadas.
+    /// Las copias anteriores a los comprobantes no tienen la tabla y no aportan ninguno.
+    pub async fn hashes_adjuntos(&self) -> Result<HashSet<String>> {
+        let mut hashes = HashSet::new();
+        for backup in self.list()? {
+            let pool = open_pool(&backup.path, true).await?;
+            let resultado = hashes_adjuntos(&pool).await;
+            pool.close().await;
+            hashes.extend(resultado.map_err(|e| {
+                AppError::Internal(format!("Error leyendo {}: {}", backup.path.display(), e))
+            })?);
+        }
+        Ok(hashes)
+    }
+
+    /// Localizar una copia por ruta o por nombre de archivo dentro del directorio de copias
+    pub fn find(&self, archivo: &str) -> Result<PathBuf> {
+        let path = PathBuf::from(archivo);
+        if path.exists() {
+            return Ok(path);
+        }
+
+        let path = self.directorio.join(archivo);
+        if path.exists() {
+            return Ok(path);
+        }
+
+        Err(AppError::NotFound(format!("Copia de seguridad {}", archivo)))
+    }
+
+    /// Restaurar una copia verificada sobre la base de datos.
+    /// Antes se guarda una copia del estado actual, cuya ruta se devuelve.
+    /// No debe haber otras conexiones abiertas a la base de datos.
+    pub async fn restore(&self, backup: &Path) -> Result<Option<PathBuf>> {
+        verify(backup).await?;
+
+        // Copiar junto al destino (y renombrar luego para que el reemplazo sea atómico) antes de
+        // guardar el estado actual: la retención de esa copia podría borrar la que se restaura
+        let temporal = self.database_path.with_extension("db.restaurando");
+        std::fs::copy(backup, &temporal)
+            .map_err(|e| AppError::Internal(format!("Error copiando la copia: {}", e)))?;
+
+        let copia_previa = if self.database_path.exists() {
+            let copia = match open_pool(&self.database_path, false).await {
+                Ok(pool) => {
+                    let copia = self.copiar(&pool, "antes-restaurar").await;
+                    pool.close().await;
+                    copia
+                }
+                Err(e) => Err(e),
+            };
+            match copia {
+                Ok(copia) => Some(copia),
+                Err(e) => {
+                    let _ = std::fs::remove_file(&temporal);
+                    return Err(e);
+                }
+            }
+        } else {
+            None
+        };
+
+        std::fs::rename(&temporal, &self.database_path)
+            .map_err(|e| AppError::Internal(format!("Error reemplazando la base de datos: {}", e)))?;
+
+        // Los archivos WAL de la base de datos anterior ya no son válidos
+        for sufijo in ["-wal", "-shm"] {
+            let mut path = self.database_path.clone().into_os_string();
+            path.push(sufijo);
+            let _ = std::fs::remove_file(PathBuf::from(path));
+        }
+
+        // La retención se aplica cuando la copia elegida ya está restaurada, y nunca la borra
+        self.apply_retention(Some(backup))?;
+
+        Ok(copia_previa)
+    }
+
+    /// Borrar las copias más antiguas que excedan el número a conservar, salvo `excepto`
+    fn apply_retention(&self, excepto: Option<&Path>) -> Result<usize> {
+        let excepto = excepto.and_then(|path| path.canonicalize().ok());
+        let sobrantes: Vec<BackupInfo> = self
+            .list()?
+            .into_iter()
+            .skip(self.mantener)
+            .filter(|backup| excepto.is_none() || backup.path.canonicalize().ok() != excepto)
+            .collect();
+
+        for backup in &sobrantes {
+            std::fs::remove_file(&backup.path).map_err(|e| {
+                AppError::Internal(format!("Error borrando {}: {}", backup.path.display(), e))
+            })?;
+        }
+
+        Ok(sobrantes.len())
+    }
+
+    fn prefijo(&self) -> String {
+        self.database_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("retiros")
+            .to_string()
+    }
+}
+
+/// Primera ruta libre para una copia: `<prefijo>-<marca>-<etiqueta>.db`, o con `-2`, `-3`...
+/// tras la etiqueta si ya hay una copia con ese nombre
+fn nombre_libre(directorio: &Path, prefijo: &str, marca: &str, etiqueta: &str) -> PathBuf {
+    let mut path = directorio.join(format!("{}-{}-{}.db", prefijo, marca, etiqueta));
+    let mut n = 2;
+    while path.exists() {
+        path = directorio.join(format!("{}-{}-{}-{}.db", prefijo, marca, etiqueta, n));
+        n += 1;
+    }
+    path
+}
+
+/// Verificar la integridad de una copia antes de restaurarla
+pub async fn verify(path: &Path) -> Result<()> {
+    if !path.is_file() {
+        return Err(AppError::NotFound(format!("Copia de seguridad {}", path.display())));
+    }
+
+    let pool = open_pool(path, true).await?;
+    let resultado = verify_pool(&pool).await;
+    pool.close().await;
+
+    resultado
+}
+
+async fn verify_pool(pool: &SqlitePool) -> Result<()> {
+    let integridad: Vec<String> = sqlx::query("PRAGMA integrity_check")
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| row.try_get::<String, _>(0))
+        .collect::<std::result::Result<_, _>>()?;
+
+    if integridad != ["ok"] {
+        return Err(AppError::Validation(format!(
+            "La copia está dañada: {}",
+            integridad.join("; ")
+        )));
+    }
+
+    let claves_rotas = sqlx::query("PRAGMA foreign_key_check")
+        .fetch_all(pool)
+        .await?
+        .len();
+    if claves_rotas > 0 {
+        return Err(AppError::Validation(format!(
+            "La copia tiene {} referencias inválidas",
+            claves_rotas
+        )));
+    }
+
+    for tabla in TABLAS_REQUERIDAS {
+        let existe = sqlx::query("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1")
+            .bind(tabla)
+            .fetch_optional(pool)
+            .await?
+            .is_some();
+
+        if !existe {
+            return Err(AppError::Validation(format!(
+                "La copia no contiene la tabla {}",
+                tabla
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+async fn hashes_adjuntos(pool: &SqlitePool) -> Result<Vec<String>> {
+    let tiene_adjuntos =
+        sqlx::query("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'adjuntos'")
+            .fetch_optional(pool)
+            .await?
+            .is_some();
+    if !tiene_adjuntos {
+        return Ok(Vec::new());
+    }
+
+    Ok(sqlx::query("SELECT DISTINCT hash FROM adjuntos")
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| row.try_get::<String, _>(0))
+        .collect::<std::result::Result<_, _>>()?)
+}
+
+async fn open_pool(path: &Path, read_only: bool) -> Result<SqlitePool> {
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .read_only(read_only);
+
+    Ok(SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await?)
+}
+
+/// Extraer la ruta del archivo de una URL `sqlite:`
+pub(super) fn database_path(database_url: &str) -> Result<PathBuf> {
+    let path = database_url
+        .trim_start_matches("sqlite:")
+        .trim_start_matches("//");
+    let path = path.split('?').next().unwrap_or(path);
+
+    if path.is_empty() || path == ":memory:" {
+        return Err(AppError::Validation(
+            "Las copias de seguridad requieren una base de datos en archivo".to_string(),
+        ));
+    }
+
+    Ok(PathBuf::from(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+
+    /// Directorio temporal propio de cada test
+    fn directorio_temporal() -> PathBuf {
+        let directorio = std::env::temp_dir().join(format!("backups-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&directorio).unwrap();
+        directorio
+    }
+
+    fn backups(directorio: &Path, mantener: usize) -> Backups {
+        Backups {
+            database_path: directorio.join("retiros.db"),
+            directorio: directorio.join("backups"),
+            mantener,
+        }
+    }
+
+    fn nombres(backups: &Backups) -> Vec<String> {
+        backups
+            .list()
+            .unwrap()
+            .iter()
+            .map(|b| b.path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn numera_las_copias_del_mismo_segundo() {
+        let directorio = directorio_temporal();
+        let marca = "20261018-120000";
+
+        let primera = nombre_libre(&directorio, "retiros", marca, "manual");
+        assert!(primera.ends_with("retiros-20261018-120000-manual.db"));
+        std::fs::write(&primera, b"").unwrap();
+
+        let segunda = nombre_libre(&directorio, "retiros", marca, "manual");
+        assert!(segunda.ends_with("retiros-20261018-120000-manual-2.db"));
+        std::fs::write(&segunda, b"").unwrap();
+
+        let tercera = nombre_libre(&directorio, "retiros", marca, "manual");
+        assert!(tercera.ends_with("retiros-20261018-120000-manual-3.db"));
+
+        std::fs::remove_dir_all(&directorio).unwrap();
+    }
+
+    #[test]
+    fn la_retencion_conserva_las_mas_recientes_de_esta_base() {
+        let directorio = directorio_temporal();
+        let backups = backups(&directorio, 2);
+        std::fs::create_dir_all(backups.directorio()).unwrap();
+        for nombre in [
+            "retiros-20261016-090000-manual.db",
+            "retiros-20261018-090000-manual.db",
+            "retiros-20261017-090000-auto.db",
+            // De otras bases de datos o que no son copias: no cuentan ni se borran
+            "retiros-pruebas-20261015-090000-manual.db",
+            "otra-20261015-090000-manual.db",
+            "retiros-20261015-090000-manual.txt",
+        ] {
+            std::fs::write(backups.directorio().join(nombre), b"").unwrap();
+        }
+
+        assert_eq!(backups.apply_retention(None).unwrap(), 1);
+        assert_eq!(
+            nombres(&backups),
+            vec![
+                "retiros-20261018-090000-manual.db",
+                "retiros-20261017-090000-auto.db"
+            ]
+        );
+        assert!(backups
+            .directorio()
+            .join("retiros-pruebas-20261015-090000-manual.db")
+            .exists());
+        assert!(backups
+            .directorio()
+            .join("retiros-20261015-090000-manual.txt")
+            .exists());
+
+        std::fs::remove_dir_all(&directorio).unwrap();
+    }
+
+    #[tokio::test]
+    async fn la_retencion_no_borra_la_copia_restaurada() {
+        let directorio = directorio_temporal();
+        let backups = backups(&directorio, 1);
+        let url = format!("sqlite:{}", backups.database_path.display());
+
+        let db = Database::new(&url).await.unwrap();
+        let antigua = backups.create(db.pool(), "manual").await.unwrap();
+        sqlx::query("DELETE FROM categorias")
+            .execute(db.pool())
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO categorias (id, nombre, tipo, color) VALUES ('c1', 'Comida', 'Gasto', '#FF0000')",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        db.pool().close().await;
+
+        // Con una sola copia conservada, la del estado actual desplazaría a la elegida
+        let previa = backups.restore(&antigua).await.unwrap().unwrap();
+
+        assert!(antigua.exists());
+        assert_eq!(backups.list().unwrap().len(), 2);
+        assert!(previa.exists());
+        assert!(previa
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .contains("antes-restaurar"));
+
+        let db = Database::new(&url).await.unwrap();
+        let categorias: i64 = sqlx::query("SELECT COUNT(*) FROM categorias WHERE id = 'c1'")
+            .fetch_one(db.pool())
+            .await
+            .unwrap()
+            .get(0);
+        assert_eq!(categorias, 0);
+        db.pool().close().await;
+
+        std::fs::remove_dir_all(&directorio).unwrap();
+    }
+
+    #[tokio::test]
+    async fn la_purga_de_adjuntos_respeta_las_copias_conservadas() {
+        use crate::database::AlmacenAdjuntos;
+        use crate::models::{
+            CreateCategoria, CreateRetiro, CreateTransaccion, TipoCategoria, TipoTransaccion,
+        };
+        use crate::repositories::{
+            AdjuntoRepository, CategoriaRepository, RetiroRepository, TransaccionRepository,
+        };
+        use std::time::{Duration, SystemTime};
+
+        let directorio = directorio_temporal();
+        let backups = backups(&directorio, 5);
+        let url = format!("sqlite:{}", backups.database_path.display());
+        let almacen = AlmacenAdjuntos::new(&url).unwrap();
+        let db = Database::new(&url).await.unwrap();
+        let pool = db.pool().clone();
+
+        let categoria = CategoriaRepository::new(pool.clone())
+            .create(CreateCategoria {
+                nombre: "Comida".to_string(),
+                tipo: TipoCategoria::Gasto,
+                color: "#FF0000".to_string(),
+                padre_id: None,
+            })
+            .await
+            .unwrap();
+        let retiro = RetiroRepository::new(pool.clone())
+            .create(CreateRetiro {
+                nombre: "Retiro de otoño".to_string(),
+                descripcion: None,
+                fecha_inicio: chrono::Utc::now(),
+                fecha_fin: chrono::Utc::now(),
+                ubicacion: None,
+                numero_participantes: 10,
+            })
+            .await
+            .unwrap();
+        let transaccion = TransaccionRepository::new(pool.clone())
+            .create(CreateTransaccion {
+                retiro_id: retiro.id,
+                categoria_id: categoria.id,
+                tipo: TipoTransaccion::Gasto,
+                monto: 45.5,
+                descripcion: "Compra del desayuno".to_string(),
+                cuenta_id: None,
+                metodo_pago: None,
+            })
+            .await
+            .unwrap();
+
+        let adjuntos = AdjuntoRepository::new(pool.clone());
+        let en_uso = adjuntos
+            .adjuntar(&almacen, transaccion.id, "ticket.pdf", b"%PDF-1.4 en uso")
+            .await
+            .unwrap();
+        let en_copia = adjuntos
+            .adjuntar(
+                &almacen,
+                transaccion.id,
+                "factura.pdf",
+                b"%PDF-1.4 en copia",
+            )
+            .await
+            .unwrap();
+        let copia = backups.create(&pool, "manual").await.unwrap();
+
+        // Eliminar el adjunto conserva el archivo
+        assert!(adjuntos.delete(en_copia.id).await.unwrap());
+        assert!(almacen.ruta(&en_copia.hash).exists());
+
+        let huerfano = almacen.guardar(b"huerfano antiguo").unwrap();
+        let reciente = almacen.guardar(b"huerfano reciente").unwrap();
+        let hace_un_dia = SystemTime::now() - Duration::from_secs(24 * 60 * 60);
+        for hash in [&en_uso.hash, &en_copia.hash, &huerfano] {
+            std::fs::File::options()
+                .write(true)
+                .open(almacen.ruta(hash))
+                .unwrap()
+                .set_modified(hace_un_dia)
+                .unwrap();
+        }
+
+        assert_eq!(almacen.purgar_huerfanos(&pool, &backups).await.unwrap(), 1);
+        assert!(!almacen.ruta(&huerfano).exists());
+        assert!(almacen.ruta(&en_uso.hash).exists());
+        assert!(almacen.ruta(&en_copia.hash).exists());
+        assert!(almacen.ruta(&reciente).exists());
+
+        // Sin la copia que lo referencia, el archivo ya se puede purgar
+        std::fs::remove_file(copia).unwrap();
+        assert_eq!(almacen.purgar_huerfanos(&pool, &backups).await.unwrap(), 1);
+        assert!(!almacen.ruta(&en_copia.hash).exists());
+
+        pool.close().await;
+        std::fs::remove_dir_all(&directorio).unwrap();
+    }
+}
@@ -1,5 +1,9 @@
 use crate::Result;
-use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    SqlitePool,
+};
+use std::str::FromStr;
 
 pub struct Database {
     pool: SqlitePool,
@@ -7,15 +11,41 @@ pub struct Database {
 
 impl Database {
     pub async fn new(database_url: &str) -> Result<Self> {
+        // Crear el archivo si no existe (p. ej. en el directorio de datos por defecto)
+        let options = SqliteConnectOptions::from_str(database_url)?.create_if_missing(true);
+
         let pool = SqlitePoolOptions::new()
             .max_connections(10)
-            .connect(database_url)
+            .connect_with(options)
             .await?;
 
-        Ok(Self { pool })
+        let database = Self { pool };
+        database.run_migrations().await?;
+
+        Ok(database)
     }
 
     pub fn pool(&self) -> &SqlitePool {
         &self.pool
     }
+
+    /// Aplicar las migraciones pendientes en bases de datos nuevas o gestionadas con sqlx.
+    /// Las bases de datos creadas a mano (con tablas pero sin `_sqlx_migrations`) no se tocan.
+    async fn run_migrations(&self) -> Result<()> {
+        let row = sqlx::query!(
+            r#"
+            SELECT 
+                (SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations') as "gestionada!: i64",
+                (SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'categorias') as "inicializada!: i64"
+            "#
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        if row.gestionada > 0 || row.inicializada == 0 {
+            sqlx::migrate!("./migrations").run(&self.pool).await?;
+        }
+
+        Ok(())
+    }
 }
@@ -1,4 +1,8 @@
 // Módulo de base de datos - se implementará en la siguiente fase
+pub mod adjuntos;
+pub mod backup;
 pub mod connection;
 
+pub use adjuntos::AlmacenAdjuntos;
+pub use backup::Backups;
 pub use connection::*;
@@ -1,179 +1,1218 @@
 #[cfg(feature = "desktop")]
-use crate::database::connection::Database;
+use super::historial::{self, EstadoHistorial, Operacion};
+#[cfg(feature = "desktop")]
+use crate::database::{connection::Database, AlmacenAdjuntos, Backups};
 #[cfg(feature = "desktop")]
 use crate::models::*;
 #[cfg(feature = "desktop")]
-use crate::repositories::*;
+use crate::repositories::*;
+#[cfg(feature = "desktop")]
+use chrono::{DateTime, NaiveDate, Utc};
+#[cfg(feature = "desktop")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "desktop")]
+use uuid::Uuid;
+
+#[cfg(feature = "desktop")]
+async fn get_database_pool() -> Result<sqlx::SqlitePool, String> {
+    // Misma resolución que el CLI: DATABASE_URL, workspace activo, config.toml o directorio de datos
+    let database_url = crate::config::resolve_database_url(None, None).map_err(|e| e.to_string())?;
+    
+    let db = Database::new(&database_url).await
+        .map_err(|e| format!("Error conectando a la base de datos: {}", e))?;
+    
+    Ok(db.pool().clone())
+}
+
+/// Almacén de comprobantes de la base de datos activa
+#[cfg(feature = "desktop")]
+fn get_almacen_adjuntos() -> Result<AlmacenAdjuntos, String> {
+    let database_url = crate::config::resolve_database_url(None, None).map_err(|e| e.to_string())?;
+    
+    AlmacenAdjuntos::new(&database_url).map_err(|e| e.to_string())
+}
+
+/// Copia de seguridad automática (si está activada en config.toml) antes de operaciones destructivas
+#[cfg(feature = "desktop")]
+pub(crate) async fn backup_automatico(etiqueta: &str) -> Result<Option<std::path::PathBuf>, String> {
+    let config = crate::config::Config::load().map_err(|e| e.to_string())?;
+    if !config.backup.automatico {
+        return Ok(None);
+    }
+    
+    let database_url = crate::config::resolve_database_url(None, None).map_err(|e| e.to_string())?;
+    let backups = Backups::new(&database_url).map_err(|e| e.to_string())?;
+    let pool = get_database_pool().await?;
+    
+    let path = backups
+        .create(&pool, etiqueta)
+        .await
+        .map_err(|e| format!("Error creando copia de seguridad: {}", e))?;
+    
+    Ok(Some(path))
+}
+
+// ============================================================================
+// COMANDOS PARA CATEGORÍAS
+// ============================================================================
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn get_categorias(incluir_archivadas: Option<bool>) -> Result<Vec<Categoria>, String> {
+    let pool = get_database_pool().await?;
+    let repo = CategoriaRepository::new(pool);
+    
+    if incluir_archivadas.unwrap_or(false) {
+        repo.get_all_incluyendo_archivadas().await.map_err(|e| e.to_string())
+    } else {
+        repo.get_all().await.map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn create_categoria(data: CreateCategoria) -> Result<Categoria, String> {
+    let pool = get_database_pool().await?;
+    let repo = CategoriaRepository::new(pool);
+    
+    let categoria = repo.create(data).await.map_err(|e| e.to_string())?;
+    historial::registrar(
+        format!("Crear categoría '{}'", categoria.nombre),
+        Operacion::Restaurar {
+            tipo: TipoElementoPapelera::Categoria,
+            ids: vec![categoria.id],
+        },
+    )
+    .await;
+    
+    Ok(categoria)
+}
+
+/// Archivar una categoría y sus subcategorías
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn archivar_categoria(id: String) -> Result<Option<Categoria>, String> {
+    let pool = get_database_pool().await?;
+    let repo = CategoriaRepository::new(pool);
+    
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    repo.set_archivada(uuid, true).await.map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn desarchivar_categoria(id: String) -> Result<Option<Categoria>, String> {
+    let pool = get_database_pool().await?;
+    let repo = CategoriaRepository::new(pool);
+    
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    repo.set_archivada(uuid, false).await.map_err(|e| e.to_string())
+}
+
+/// Color de la paleta que no usan otras categorías, para rellenar el formulario
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn sugerir_color_categoria() -> Result<String, String> {
+    let pool = get_database_pool().await?;
+    let repo = CategoriaRepository::new(pool);
+    
+    repo.sugerir_color().await.map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn update_categoria(id: String, data: CreateCategoria) -> Result<Option<Categoria>, String> {
+    let pool = get_database_pool().await?;
+    let repo = CategoriaRepository::new(pool);
+    
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    let anterior = repo.get_by_id(uuid).await.map_err(|e| e.to_string())?;
+    
+    let categoria = repo.update(uuid, data.clone()).await.map_err(|e| e.to_string())?;
+    if let (Some(anterior), Some(categoria)) = (anterior, &categoria) {
+        historial::registrar(
+            format!("Editar categoría '{}'", categoria.nombre),
+            Operacion::ActualizarCategoria {
+                id: uuid,
+                datos: data,
+                anteriores: historial::datos_categoria(&anterior),
+            },
+        )
+        .await;
+    }
+    
+    Ok(categoria)
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn delete_categoria(id: String) -> Result<bool, String> {
+    let pool = get_database_pool().await?;
+    let repo = CategoriaRepository::new(pool);
+    
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    let categoria = repo.get_by_id(uuid).await.map_err(|e| e.to_string())?;
+    
+    let eliminada = repo.delete(uuid).await.map_err(|e| e.to_string())?;
+    if let (true, Some(categoria)) = (eliminada, categoria) {
+        historial::registrar(
+            format!("Eliminar categoría '{}'", categoria.nombre),
+            Operacion::Eliminar {
+                tipo: TipoElementoPapelera::Categoria,
+                ids: vec![uuid],
+            },
+        )
+        .await;
+    }
+    
+    Ok(eliminada)
+}
+
+// ============================================================================
+// COMANDOS PARA RETIROS
+// ============================================================================
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn get_retiros() -> Result<Vec<Retiro>, String> {
+    let pool = get_database_pool().await?;
+    let repo = RetiroRepository::new(pool);
+    
+    repo.get_all().await.map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn create_retiro(data: CreateRetiro) -> Result<Retiro, String> {
+    let pool = get_database_pool().await?;
+    let repo = RetiroRepository::new(pool);
+    
+    let retiro = repo.create(data).await.map_err(|e| e.to_string())?;
+    historial::registrar(
+        format!("Crear retiro '{}'", retiro.nombre),
+        Operacion::Restaurar {
+            tipo: TipoElementoPapelera::Retiro,
+            ids: vec![retiro.id],
+        },
+    )
+    .await;
+    
+    Ok(retiro)
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn update_retiro(id: String, data: CreateRetiro) -> Result<Option<Retiro>, String> {
+    let pool = get_database_pool().await?;
+    let repo = RetiroRepository::new(pool);
+    
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    let anterior = repo.get_by_id(uuid).await.map_err(|e| e.to_string())?;
+    
+    let retiro = repo.update(uuid, data.clone()).await.map_err(|e| e.to_string())?;
+    if let (Some(anterior), Some(retiro)) = (anterior, &retiro) {
+        historial::registrar(
+            format!("Editar retiro '{}'", retiro.nombre),
+            Operacion::ActualizarRetiro {
+                id: uuid,
+                datos: data,
+                anteriores: historial::datos_retiro(&anterior),
+            },
+        )
+        .await;
+    }
+    
+    Ok(retiro)
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn update_retiro_estado(id: String, estado: String) -> Result<Option<Retiro>, String> {
+    let pool = get_database_pool().await?;
+    let repo = RetiroRepository::new(pool);
+    
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    
+    // Convertir string a EstadoRetiro
+    let nuevo_estado = match estado.as_str() {
+        "Planificacion" => EstadoRetiro::Planificacion,
+        "Activo" => EstadoRetiro::Activo,
+        "Finalizado" => EstadoRetiro::Finalizado,
+        _ => return Err("Estado no válido".to_string()),
+    };
+    
+    let anterior = repo.get_by_id(uuid).await.map_err(|e| e.to_string())?;
+    
+    let retiro = repo
+        .update_estado(uuid, nuevo_estado.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+    if let (Some(anterior), Some(retiro)) = (anterior, &retiro) {
+        historial::registrar(
+            format!("Cambiar estado de '{}' a {}", retiro.nombre, nuevo_estado),
+            Operacion::CambiarEstadoRetiro {
+                id: uuid,
+                estado: nuevo_estado,
+                anterior: anterior.estado,
+            },
+        )
+        .await;
+    }
+    
+    Ok(retiro)
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn clonar_retiro(
+    id: String,
+    nombre: Option<String>,
+    fecha_inicio: Option<DateTime<Utc>>,
+    con_plantillas: Option<bool>,
+) -> Result<Option<RetiroClonado>, String> {
+    let pool = get_database_pool().await?;
+    let repo = RetiroRepository::new(pool);
+
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    let clon = repo
+        .clonar(uuid, nombre, fecha_inicio, con_plantillas.unwrap_or(false))
+        .await
+        .map_err(|e| e.to_string())?;
+    // Al deshacer, el retiro va a la papelera junto con las transacciones de las plantillas
+    if let Some(clon) = &clon {
+        historial::registrar(
+            format!("Clonar retiro como '{}'", clon.retiro.nombre),
+            Operacion::Restaurar {
+                tipo: TipoElementoPapelera::Retiro,
+                ids: vec![clon.retiro.id],
+            },
+        )
+        .await;
+    }
+    
+    Ok(clon)
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn delete_retiro(id: String) -> Result<bool, String> {
+    let pool = get_database_pool().await?;
+    let repo = RetiroRepository::new(pool);
+    
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    
+    let retiro = repo.get_by_id(uuid).await.map_err(|e| e.to_string())?;
+    
+    // El retiro y sus transacciones van a la papelera; la copia se hace al vaciarla
+    let eliminado = repo.delete(uuid).await.map_err(|e| e.to_string())?;
+    if let (true, Some(retiro)) = (eliminado, retiro) {
+        historial::registrar(
+            format!("Eliminar retiro '{}'", retiro.nombre),
+            Operacion::Eliminar {
+                tipo: TipoElementoPapelera::Retiro,
+                ids: vec![uuid],
+            },
+        )
+        .await;
+    }
+    
+    Ok(eliminado)
+}
+
+// ============================================================================
+// COMANDOS PARA TRANSACCIONES
+// ============================================================================
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn get_transacciones(
+    retiro_id: Option<String>,
+    etiquetas: Option<Vec<String>>,
+) -> Result<Vec<Transaccion>, String> {
+    let pool = get_database_pool().await?;
+    let repo = TransaccionRepository::new(pool);
+    
+    match retiro_id {
+        Some(id_str) => {
+            let uuid = Uuid::parse_str(&id_str).map_err(|e| e.to_string())?;
+            // Con etiquetas, solo las transacciones que las tienen todas
+            repo.get_by_retiro_con_etiquetas(uuid, &etiquetas.unwrap_or_default())
+                .await
+                .map_err(|e| e.to_string())
+        }
+        None => {
+            // No hay get_all para transacciones, devolver lista vacía
+            Ok(vec![])
+        }
+    }
+}
+
+/// Crear una transacción; con `categoria_id` nulo la categoría y las etiquetas las
+/// decide la primera regla que coincida
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn create_transaccion(mut data: CreateTransaccion) -> Result<Transaccion, String> {
+    let pool = get_database_pool().await?;
+    let repo = TransaccionRepository::new(pool.clone());
+    
+    // Sin categoría (UUID nulo) se aplican las reglas de categorización
+    let mut regla = None;
+    if data.categoria_id.is_nil() {
+        let motor = ReglaRepository::new(pool.clone())
+            .motor()
+            .await
+            .map_err(|e| e.to_string())?;
+        let encontrada = motor
+            .evaluar(&data.descripcion, Some(data.monto), Some(&data.tipo))
+            .cloned()
+            .ok_or_else(|| {
+                "Ninguna regla de categorización coincide; indica una categoría".to_string()
+            })?;
+        data.categoria_id = encontrada.categoria_id;
+        regla = Some(encontrada);
+    }
+    
+    let transaccion = repo.create(data).await.map_err(|e| e.to_string())?;
+    if let Some(regla) = regla.filter(|r| !r.etiquetas.is_empty()) {
+        EtiquetaRepository::new(pool)
+            .agregar(transaccion.id, &regla.etiquetas)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    historial::registrar(
+        format!("Crear transacción '{}'", transaccion.descripcion),
+        Operacion::Restaurar {
+            tipo: TipoElementoPapelera::Transaccion,
+            ids: vec![transaccion.id],
+        },
+    )
+    .await;
+    
+    Ok(transaccion)
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn delete_transaccion(id: String) -> Result<bool, String> {
+    let pool = get_database_pool().await?;
+    let repo = TransaccionRepository::new(pool);
+    
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    let transaccion = repo.get_by_id(uuid).await.map_err(|e| e.to_string())?;
+    
+    let eliminada = repo.delete(uuid).await.map_err(|e| e.to_string())?;
+    if let (true, Some(transaccion)) = (eliminada, transaccion) {
+        historial::registrar(
+            format!("Eliminar transacción '{}'", transaccion.descripcion),
+            Operacion::Eliminar {
+                tipo: TipoElementoPapelera::Transaccion,
+                ids: vec![uuid],
+            },
+        )
+        .await;
+    }
+    
+    Ok(eliminada)
+}
+
+// ============================================================================
+// COMANDOS PARA CUENTAS Y TRANSFERENCIAS
+// ============================================================================
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn get_cuentas() -> Result<Vec<Cuenta>, String> {
+    let pool = get_database_pool().await?;
+    let repo = CuentaRepository::new(pool);
+    
+    repo.get_all().await.map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn create_cuenta(data: CreateCuenta) -> Result<Cuenta, String> {
+    let pool = get_database_pool().await?;
+    let repo = CuentaRepository::new(pool);
+    
+    let cuenta = repo.create(data).await.map_err(|e| e.to_string())?;
+    historial::registrar(
+        format!("Crear cuenta '{}'", cuenta.nombre),
+        Operacion::CrearCuenta {
+            cuenta: cuenta.clone(),
+        },
+    )
+    .await;
+    
+    Ok(cuenta)
+}
+
+/// Eliminar una cuenta; falla si tiene transacciones, transferencias o arqueos
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn delete_cuenta(id: String) -> Result<bool, String> {
+    let pool = get_database_pool().await?;
+    let repo = CuentaRepository::new(pool);
+    
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    let cuenta = repo.get_by_id(uuid).await.map_err(|e| e.to_string())?;
+    
+    let eliminada = repo.delete(uuid).await.map_err(|e| e.to_string())?;
+    if let (true, Some(cuenta)) = (eliminada, cuenta) {
+        historial::registrar(
+            format!("Eliminar cuenta '{}'", cuenta.nombre),
+            Operacion::EliminarCuenta { cuenta },
+        )
+        .await;
+    }
+    
+    Ok(eliminada)
+}
+
+/// Saldo de cada cuenta: de un retiro, o total si no se indica
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn get_saldos_cuentas(retiro_id: Option<String>) -> Result<Vec<SaldoCuenta>, String> {
+    let pool = get_database_pool().await?;
+    let repo = CuentaRepository::new(pool);
+    
+    let retiro_uuid = retiro_id
+        .map(|id| Uuid::parse_str(&id).map_err(|e| e.to_string()))
+        .transpose()?;
+    repo.get_saldos(retiro_uuid)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn get_transferencias(retiro_id: Option<String>) -> Result<Vec<Transferencia>, String> {
+    let pool = get_database_pool().await?;
+    let repo = CuentaRepository::new(pool);
+    
+    let retiro_uuid = retiro_id
+        .map(|id| Uuid::parse_str(&id).map_err(|e| e.to_string()))
+        .transpose()?;
+    repo.get_transferencias(retiro_uuid)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn create_transferencia(data: CreateTransferencia) -> Result<Transferencia, String> {
+    let pool = get_database_pool().await?;
+    let repo = CuentaRepository::new(pool);
+    
+    let transferencia = repo
+        .create_transferencia(data)
+        .await
+        .map_err(|e| e.to_string())?;
+    historial::registrar(
+        format!("Registrar transferencia '{}'", transferencia.descripcion),
+        Operacion::CrearTransferencia {
+            transferencia: transferencia.clone(),
+        },
+    )
+    .await;
+    
+    Ok(transferencia)
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn delete_transferencia(id: String) -> Result<bool, String> {
+    let pool = get_database_pool().await?;
+    let repo = CuentaRepository::new(pool);
+    
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    let transferencia = repo
+        .get_transferencias(None)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|t| t.id == uuid);
+    
+    let eliminada = repo
+        .delete_transferencia(uuid)
+        .await
+        .map_err(|e| e.to_string())?;
+    if let (true, Some(transferencia)) = (eliminada, transferencia) {
+        historial::registrar(
+            format!("Eliminar transferencia '{}'", transferencia.descripcion),
+            Operacion::EliminarTransferencia { transferencia },
+        )
+        .await;
+    }
+    
+    Ok(eliminada)
+}
+
+/// Registrar un arqueo de una cuenta; con `ajustar` se corrige el descuadre con una transacción
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn create_arqueo(data: CreateArqueo) -> Result<Arqueo, String> {
+    let pool = get_database_pool().await?;
+    let repo = ArqueoRepository::new(pool);
+    
+    let arqueo = repo.create(data).await.map_err(|e| e.to_string())?;
+    historial::registrar(
+        format!("Registrar arqueo de €{:.2}", arqueo.contado),
+        Operacion::CrearArqueo {
+            arqueo: arqueo.clone(),
+        },
+    )
+    .await;
+    
+    Ok(arqueo)
+}
+
+/// Historial de arqueos, del más reciente al más antiguo
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn get_arqueos(
+    retiro_id: Option<String>,
+    cuenta_id: Option<String>,
+) -> Result<Vec<Arqueo>, String> {
+    let pool = get_database_pool().await?;
+    let repo = ArqueoRepository::new(pool);
+    
+    let retiro_uuid = retiro_id
+        .map(|id| Uuid::parse_str(&id).map_err(|e| e.to_string()))
+        .transpose()?;
+    let cuenta_uuid = cuenta_id
+        .map(|id| Uuid::parse_str(&id).map_err(|e| e.to_string()))
+        .transpose()?;
+    repo.get_all(retiro_uuid, cuenta_uuid)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Eliminar un arqueo; su transacción de ajuste, si la hay, va a la papelera
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn delete_arqueo(id: String) -> Result<bool, String> {
+    let pool = get_database_pool().await?;
+    let repo = ArqueoRepository::new(pool);
+    
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    let arqueo = repo
+        .get_all(None, None)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|a| a.id == uuid);
+    
+    let eliminado = repo.delete(uuid).await.map_err(|e| e.to_string())?;
+    if let (true, Some(arqueo)) = (eliminado, arqueo) {
+        historial::registrar(
+            format!("Eliminar arqueo de €{:.2}", arqueo.contado),
+            Operacion::EliminarArqueo { arqueo },
+        )
+        .await;
+    }
+    
+    Ok(eliminado)
+}
+
+// ============================================================================
+// COMANDOS PARA EXTRACTOS BANCARIOS
+// ============================================================================
+
+/// Leer un extracto (Norma 43, CAMT.053 u OFX) y proponer qué hacer con cada movimiento
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn preparar_extracto(
+    retiro_id: String,
+    cuenta_id: Option<String>,
+    contenido: Vec<u8>,
+    formato: Option<FormatoExtracto>,
+    categoria_ingreso_id: Option<String>,
+    categoria_gasto_id: Option<String>,
+    dias: Option<u32>,
+) -> Result<Vec<CandidatoExtracto>, String> {
+    let pool = get_database_pool().await?;
+    let repo = ExtractoRepository::new(pool);
+    
+    let parse = |id: Option<String>| {
+        id.map(|id| Uuid::parse_str(&id).map_err(|e| e.to_string()))
+            .transpose()
+    };
+    let opciones = OpcionesExtracto {
+        retiro_id: Uuid::parse_str(&retiro_id).map_err(|e| e.to_string())?,
+        cuenta_id: parse(cuenta_id)?,
+        categoria_ingreso_id: parse(categoria_ingreso_id)?,
+        categoria_gasto_id: parse(categoria_gasto_id)?,
+        dias: dias.unwrap_or(3),
+    };
+    let (formato, movimientos) =
+        crate::extractos::leer(&contenido, formato).map_err(|e| e.to_string())?;
+    repo.preparar(formato, &movimientos, &opciones)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Aplicar las decisiones revisadas: importar, enlazar u omitir cada movimiento
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn importar_extracto(decisiones: Vec<DecisionExtracto>) -> Result<ResumenExtracto, String> {
+    let pool = get_database_pool().await?;
+    let repo = ExtractoRepository::new(pool);
+    
+    repo.importar(decisiones).await.map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn get_lineas_extracto(retiro_id: Option<String>) -> Result<Vec<LineaExtracto>, String> {
+    let pool = get_database_pool().await?;
+    let repo = ExtractoRepository::new(pool);
+    
+    let retiro_uuid = retiro_id
+        .map(|id| Uuid::parse_str(&id).map_err(|e| e.to_string()))
+        .transpose()?;
+    repo.get_lineas(retiro_uuid).await.map_err(|e| e.to_string())
+}
+
+/// Informe de conciliación de un retiro (opcionalmente de una cuenta)
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn get_informe_conciliacion(
+    retiro_id: String,
+    cuenta_id: Option<String>,
+) -> Result<InformeConciliacion, String> {
+    let pool = get_database_pool().await?;
+    let repo = ExtractoRepository::new(pool);
+    
+    let retiro_uuid = Uuid::parse_str(&retiro_id).map_err(|e| e.to_string())?;
+    let cuenta_uuid = cuenta_id
+        .map(|id| Uuid::parse_str(&id).map_err(|e| e.to_string()))
+        .transpose()?;
+    repo.informe_conciliacion(retiro_uuid, cuenta_uuid)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Pasar a conciliadas las transacciones punteadas; devuelve cuántas han cambiado
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn conciliar_retiro(retiro_id: String, cuenta_id: Option<String>) -> Result<u64, String> {
+    let pool = get_database_pool().await?;
+    let repo = ExtractoRepository::new(pool);
+    
+    let retiro_uuid = Uuid::parse_str(&retiro_id).map_err(|e| e.to_string())?;
+    let cuenta_uuid = cuenta_id
+        .map(|id| Uuid::parse_str(&id).map_err(|e| e.to_string()))
+        .transpose()?;
+    repo.conciliar(retiro_uuid, cuenta_uuid)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn vincular_linea_extracto(
+    linea_id: String,
+    transaccion_id: String,
+) -> Result<LineaExtracto, String> {
+    let pool = get_database_pool().await?;
+    let repo = ExtractoRepository::new(pool);
+    
+    let linea_uuid = Uuid::parse_str(&linea_id).map_err(|e| e.to_string())?;
+    let transaccion_uuid = Uuid::parse_str(&transaccion_id).map_err(|e| e.to_string())?;
+    repo.vincular(linea_uuid, transaccion_uuid)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[cfg(feature = "desktop")]
-use serde::{Deserialize, Serialize};
+#[tauri::command]
+pub async fn desvincular_linea_extracto(linea_id: String) -> Result<LineaExtracto, String> {
+    let pool = get_database_pool().await?;
+    let repo = ExtractoRepository::new(pool);
+    
+    let linea_uuid = Uuid::parse_str(&linea_id).map_err(|e| e.to_string())?;
+    repo.desvincular(linea_uuid).await.map_err(|e| e.to_string())
+}
+
+/// Cambiar a mano el estado de conciliación de una transacción
 #[cfg(feature = "desktop")]
-use uuid::Uuid;
+#[tauri::command]
+pub async fn set_conciliacion_transaccion(
+    id: String,
+    estado: EstadoConciliacion,
+) -> Result<Transaccion, String> {
+    let pool = get_database_pool().await?;
+    let repo = TransaccionRepository::new(pool);
+    
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    repo.set_conciliacion(uuid, estado)
+        .await
+        .map_err(|e| e.to_string())
+}
 
+// ============================================================================
+// COMANDOS PARA ETIQUETAS
+// ============================================================================
+
+/// Todas las etiquetas con el número de transacciones que las usan
 #[cfg(feature = "desktop")]
-async fn get_database_pool() -> Result<sqlx::SqlitePool, String> {
-    // Intentar usar la variable de entorno primero, sino buscar en el directorio de la app
-    let database_url = if let Ok(url) = std::env::var("DATABASE_URL") {
-        url
-    } else {
-        // Buscar en el directorio de recursos del bundle macOS
-        let db_path = std::env::current_exe()
-            .ok()
-            .and_then(|exe| exe.parent().map(|p| p.join("../Resources/retiros.db")))
-            .and_then(|p| p.canonicalize().ok())
-            .and_then(|p| p.to_str().map(String::from))
-            .unwrap_or_else(|| "./retiros.db".to_string());
-        
-        format!("sqlite:{}", db_path)
-    };
+#[tauri::command]
+pub async fn get_etiquetas() -> Result<Vec<Etiqueta>, String> {
+    let pool = get_database_pool().await?;
+    let repo = EtiquetaRepository::new(pool);
     
-    let db = Database::new(&database_url).await
-        .map_err(|e| format!("Error conectando a la base de datos: {}", e))?;
+    repo.get_all().await.map_err(|e| e.to_string())
+}
+
+/// Etiquetas de cada transacción de un retiro, por ID de transacción
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn get_etiquetas_retiro(
+    retiro_id: String,
+) -> Result<std::collections::HashMap<Uuid, Vec<String>>, String> {
+    let pool = get_database_pool().await?;
+    let repo = EtiquetaRepository::new(pool);
     
-    Ok(db.pool().clone())
+    let uuid = Uuid::parse_str(&retiro_id).map_err(|e| e.to_string())?;
+    repo.get_by_retiro(uuid).await.map_err(|e| e.to_string())
+}
+
+/// Sustituir las etiquetas de una transacción; devuelve las que quedan
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn set_etiquetas_transaccion(
+    transaccion_id: String,
+    etiquetas: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let pool = get_database_pool().await?;
+    let repo = EtiquetaRepository::new(pool);
+    
+    let uuid = Uuid::parse_str(&transaccion_id).map_err(|e| e.to_string())?;
+    repo.reemplazar(uuid, &etiquetas)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Totales de ingresos y gastos por etiqueta de un retiro, o de todos
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn get_totales_por_etiqueta(
+    retiro_id: Option<String>,
+) -> Result<Vec<TotalEtiqueta>, String> {
+    let pool = get_database_pool().await?;
+    let repo = EtiquetaRepository::new(pool);
+    
+    let retiro_uuid = retiro_id
+        .map(|id| Uuid::parse_str(&id).map_err(|e| e.to_string()))
+        .transpose()?;
+    repo.get_totales(retiro_uuid)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 // ============================================================================
-// COMANDOS PARA CATEGORÍAS
+// COMANDOS PARA COMPROBANTES
 // ============================================================================
 
+/// Adjuntar un comprobante (PDF o imagen) a una transacción
 #[cfg(feature = "desktop")]
 #[tauri::command]
-pub async fn get_categorias() -> Result<Vec<Categoria>, String> {
+pub async fn upload_adjunto(
+    transaccion_id: String,
+    nombre: String,
+    contenido: Vec<u8>,
+) -> Result<Adjunto, String> {
     let pool = get_database_pool().await?;
-    let repo = CategoriaRepository::new(pool);
+    let repo = AdjuntoRepository::new(pool);
+    let almacen = get_almacen_adjuntos()?;
     
-    repo.get_all().await.map_err(|e| e.to_string())
+    let uuid = Uuid::parse_str(&transaccion_id).map_err(|e| e.to_string())?;
+    let adjunto = repo
+        .adjuntar(&almacen, uuid, &nombre, &contenido)
+        .await
+        .map_err(|e| e.to_string())?;
+    historial::registrar(
+        format!("Adjuntar '{}'", adjunto.nombre),
+        Operacion::CrearAdjunto {
+            adjunto: adjunto.clone(),
+            contenido,
+        },
+    )
+    .await;
+    
+    Ok(adjunto)
 }
 
 #[cfg(feature = "desktop")]
 #[tauri::command]
-pub async fn create_categoria(data: CreateCategoria) -> Result<Categoria, String> {
+pub async fn get_adjuntos(transaccion_id: String) -> Result<Vec<Adjunto>, String> {
     let pool = get_database_pool().await?;
-    let repo = CategoriaRepository::new(pool);
+    let repo = AdjuntoRepository::new(pool);
     
-    repo.create(data).await.map_err(|e| e.to_string())
+    let uuid = Uuid::parse_str(&transaccion_id).map_err(|e| e.to_string())?;
+    repo.get_by_transaccion(uuid).await.map_err(|e| e.to_string())
 }
 
+/// Contenido de un comprobante para mostrarlo o descargarlo
 #[cfg(feature = "desktop")]
 #[tauri::command]
-pub async fn update_categoria(id: String, data: CreateCategoria) -> Result<Option<Categoria>, String> {
+pub async fn get_contenido_adjunto(id: String) -> Result<Vec<u8>, String> {
     let pool = get_database_pool().await?;
-    let repo = CategoriaRepository::new(pool);
+    let repo = AdjuntoRepository::new(pool);
     
     let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
-    repo.update(uuid, data).await.map_err(|e| e.to_string())
+    let adjunto = repo
+        .get_by_id(uuid)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Adjunto no encontrado".to_string())?;
+    
+    get_almacen_adjuntos()?
+        .leer(&adjunto.hash)
+        .map_err(|e| e.to_string())
 }
 
 #[cfg(feature = "desktop")]
 #[tauri::command]
-pub async fn delete_categoria(id: String) -> Result<bool, String> {
+pub async fn delete_adjunto(id: String) -> Result<bool, String> {
     let pool = get_database_pool().await?;
-    let repo = CategoriaRepository::new(pool);
+    let repo = AdjuntoRepository::new(pool);
+    let almacen = get_almacen_adjuntos()?;
     
     let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
-    repo.delete(uuid).await.map_err(|e| e.to_string())
+    let adjunto = repo.get_by_id(uuid).await.map_err(|e| e.to_string())?;
+    // Guardar el contenido para poder deshacer aunque luego se purgue el archivo
+    let contenido = match &adjunto {
+        Some(adjunto) => Some(almacen.leer(&adjunto.hash).map_err(|e| e.to_string())?),
+        None => None,
+    };
+    
+    let eliminado = repo.delete(uuid).await.map_err(|e| e.to_string())?;
+    if let (true, Some(adjunto), Some(contenido)) = (eliminado, adjunto, contenido) {
+        historial::registrar(
+            format!("Eliminar comprobante '{}'", adjunto.nombre),
+            Operacion::EliminarAdjunto { adjunto, contenido },
+        )
+        .await;
+    }
+    
+    Ok(eliminado)
+}
+
+/// Informe de gastos sin comprobante, ordenados por retiro
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn get_gastos_sin_comprobante(
+    retiro_id: Option<String>,
+) -> Result<Vec<GastoSinComprobante>, String> {
+    let pool = get_database_pool().await?;
+    let repo = AdjuntoRepository::new(pool);
+    
+    let retiro_uuid = retiro_id
+        .map(|id| Uuid::parse_str(&id).map_err(|e| e.to_string()))
+        .transpose()?;
+    repo.gastos_sin_comprobante(retiro_uuid)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 // ============================================================================
-// COMANDOS PARA RETIROS
+// COMANDOS PARA PLANTILLAS DE TRANSACCIÓN
 // ============================================================================
 
 #[cfg(feature = "desktop")]
 #[tauri::command]
-pub async fn get_retiros() -> Result<Vec<Retiro>, String> {
+pub async fn get_plantillas() -> Result<Vec<PlantillaTransaccion>, String> {
     let pool = get_database_pool().await?;
-    let repo = RetiroRepository::new(pool);
+    let repo = PlantillaRepository::new(pool);
     
     repo.get_all().await.map_err(|e| e.to_string())
 }
 
 #[cfg(feature = "desktop")]
 #[tauri::command]
-pub async fn create_retiro(data: CreateRetiro) -> Result<Retiro, String> {
+pub async fn create_plantilla(data: CreatePlantillaTransaccion) -> Result<PlantillaTransaccion, String> {
     let pool = get_database_pool().await?;
-    let repo = RetiroRepository::new(pool);
+    let repo = PlantillaRepository::new(pool);
     
-    repo.create(data).await.map_err(|e| e.to_string())
+    let plantilla = repo.create(data.clone()).await.map_err(|e| e.to_string())?;
+    historial::registrar(
+        format!("Crear plantilla '{}'", plantilla.nombre),
+        Operacion::CrearPlantilla {
+            id: plantilla.id,
+            datos: data,
+        },
+    )
+    .await;
+    
+    Ok(plantilla)
 }
 
 #[cfg(feature = "desktop")]
 #[tauri::command]
-pub async fn update_retiro(id: String, data: CreateRetiro) -> Result<Option<Retiro>, String> {
+pub async fn delete_plantilla(id: String) -> Result<bool, String> {
     let pool = get_database_pool().await?;
-    let repo = RetiroRepository::new(pool);
+    let repo = PlantillaRepository::new(pool);
     
     let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
-    repo.update(uuid, data).await.map_err(|e| e.to_string())
+    let plantilla = repo.get_by_id(uuid).await.map_err(|e| e.to_string())?;
+    
+    let eliminada = repo.delete(uuid).await.map_err(|e| e.to_string())?;
+    if let (true, Some(plantilla)) = (eliminada, plantilla) {
+        historial::registrar(
+            format!("Eliminar plantilla '{}'", plantilla.nombre),
+            Operacion::EliminarPlantilla {
+                id: uuid,
+                datos: CreatePlantillaTransaccion {
+                    nombre: plantilla.nombre,
+                    categoria_id: plantilla.categoria_id,
+                    tipo: plantilla.tipo,
+                    monto: plantilla.monto,
+                    descripcion: plantilla.descripcion,
+                },
+            },
+        )
+        .await;
+    }
+    
+    Ok(eliminada)
 }
 
+/// Crear en un retiro las transacciones de las plantillas indicadas (todas si la lista está vacía)
 #[cfg(feature = "desktop")]
 #[tauri::command]
-pub async fn update_retiro_estado(id: String, estado: String) -> Result<Option<Retiro>, String> {
+pub async fn aplicar_plantillas(
+    retiro_id: String,
+    plantilla_ids: Vec<String>,
+) -> Result<Vec<Transaccion>, String> {
     let pool = get_database_pool().await?;
-    let repo = RetiroRepository::new(pool);
+    let repo = PlantillaRepository::new(pool);
     
-    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    let retiro_uuid = Uuid::parse_str(&retiro_id).map_err(|e| e.to_string())?;
+    let plantilla_uuids = plantilla_ids
+        .iter()
+        .map(|id| Uuid::parse_str(id).map_err(|e| e.to_string()))
+        .collect::<Result<Vec<_>, String>>()?;
     
-    // Convertir string a EstadoRetiro
-    let nuevo_estado = match estado.as_str() {
-        "Planificacion" => EstadoRetiro::Planificacion,
-        "Activo" => EstadoRetiro::Activo,
-        "Finalizado" => EstadoRetiro::Finalizado,
-        _ => return Err("Estado no válido".to_string()),
-    };
+    let transacciones = repo
+        .aplicar(retiro_uuid, &plantilla_uuids)
+        .await
+        .map_err(|e| e.to_string())?;
+    historial::registrar(
+        format!("Aplicar {} plantillas", transacciones.len()),
+        Operacion::Restaurar {
+            tipo: TipoElementoPapelera::Transaccion,
+            ids: transacciones.iter().map(|t| t.id).collect(),
+        },
+    )
+    .await;
     
-    repo.update_estado(uuid, nuevo_estado).await.map_err(|e| e.to_string())
+    Ok(transacciones)
 }
 
+// ============================================================================
+// COMANDOS PARA REGLAS DE CATEGORIZACIÓN
+// ============================================================================
+
 #[cfg(feature = "desktop")]
 #[tauri::command]
-pub async fn delete_retiro(id: String) -> Result<bool, String> {
+pub async fn get_reglas() -> Result<Vec<ReglaCategoria>, String> {
     let pool = get_database_pool().await?;
-    let repo = RetiroRepository::new(pool);
+    let repo = ReglaRepository::new(pool);
+    
+    repo.get_all().await.map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn create_regla(data: CreateReglaCategoria) -> Result<ReglaCategoria, String> {
+    let pool = get_database_pool().await?;
+    let repo = ReglaRepository::new(pool);
+    
+    let regla = repo.create(data).await.map_err(|e| e.to_string())?;
+    historial::registrar(
+        format!("Crear regla '{}'", regla.nombre),
+        Operacion::CrearRegla {
+            regla: regla.clone(),
+        },
+    )
+    .await;
+    
+    Ok(regla)
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn delete_regla(id: String) -> Result<bool, String> {
+    let pool = get_database_pool().await?;
+    let repo = ReglaRepository::new(pool);
     
     let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
-    repo.delete(uuid).await.map_err(|e| e.to_string())
+    let regla = repo.get_by_id(uuid).await.map_err(|e| e.to_string())?;
+    
+    let eliminada = repo.delete(uuid).await.map_err(|e| e.to_string())?;
+    if let (true, Some(regla)) = (eliminada, regla) {
+        historial::registrar(
+            format!("Eliminar regla '{}'", regla.nombre),
+            Operacion::EliminarRegla { regla },
+        )
+        .await;
+    }
+    
+    Ok(eliminada)
+}
+
+/// Transacciones ya registradas que cumplirían una regla (guardada o no)
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn probar_regla(
+    data: CreateReglaCategoria,
+    retiro_id: Option<String>,
+) -> Result<Vec<CoincidenciaRegla>, String> {
+    let pool = get_database_pool().await?;
+    let repo = ReglaRepository::new(pool);
+    
+    data.validar().map_err(|e| e.to_string())?;
+    let retiro_uuid = retiro_id
+        .map(|id| Uuid::parse_str(&id).map_err(|e| e.to_string()))
+        .transpose()?;
+    repo.probar(&ReglaCategoria::new(data), retiro_uuid)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Regla que se aplicaría a una transacción nueva, para proponer su categoría y etiquetas
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn sugerir_categoria(
+    descripcion: String,
+    monto: Option<f64>,
+    tipo: Option<TipoTransaccion>,
+) -> Result<Option<ReglaCategoria>, String> {
+    let pool = get_database_pool().await?;
+    let motor = ReglaRepository::new(pool)
+        .motor()
+        .await
+        .map_err(|e| e.to_string())?;
+    
+    Ok(motor.evaluar(&descripcion, monto, tipo.as_ref()).cloned())
 }
 
 // ============================================================================
-// COMANDOS PARA TRANSACCIONES
+// COMANDOS PARA LA PAPELERA
 // ============================================================================
 
 #[cfg(feature = "desktop")]
 #[tauri::command]
-pub async fn get_transacciones(retiro_id: Option<String>) -> Result<Vec<Transaccion>, String> {
+pub async fn get_papelera() -> Result<Vec<ElementoPapelera>, String> {
     let pool = get_database_pool().await?;
-    let repo = TransaccionRepository::new(pool);
+    let repo = PapeleraRepository::new(pool);
     
-    match retiro_id {
-        Some(id_str) => {
-            let uuid = Uuid::parse_str(&id_str).map_err(|e| e.to_string())?;
-            repo.get_by_retiro(uuid).await.map_err(|e| e.to_string())
-        }
-        None => {
-            // No hay get_all para transacciones, devolver lista vacía
-            Ok(vec![])
-        }
+    repo.listar().await.map_err(|e| e.to_string())
+}
+
+/// Restaurar un elemento de la papelera (un retiro vuelve con sus transacciones)
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn restaurar_papelera(id: String) -> Result<Option<ElementoPapelera>, String> {
+    let pool = get_database_pool().await?;
+    let repo = PapeleraRepository::new(pool);
+    
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    let elemento = repo.restaurar(uuid).await.map_err(|e| e.to_string())?;
+    if let Some(elemento) = &elemento {
+        historial::registrar(
+            format!("Restaurar '{}'", elemento.descripcion),
+            Operacion::Restaurar {
+                tipo: elemento.tipo,
+                ids: vec![elemento.id],
+            },
+        )
+        .await;
     }
+    
+    Ok(elemento)
 }
 
+/// Borrar definitivamente la papelera, o solo lo que lleva más de `dias` en ella
 #[cfg(feature = "desktop")]
 #[tauri::command]
-pub async fn create_transaccion(data: CreateTransaccion) -> Result<Transaccion, String> {
+pub async fn vaciar_papelera(dias: Option<u32>) -> Result<ResumenVaciado, String> {
     let pool = get_database_pool().await?;
-    let repo = TransaccionRepository::new(pool);
+    let repo = PapeleraRepository::new(pool);
     
-    repo.create(data).await.map_err(|e| e.to_string())
+    let antes_de = dias.map(|dias| Utc::now() - chrono::Duration::days(i64::from(dias)));
+    
+    // El borrado no se puede deshacer: guardar antes una copia
+    backup_automatico("antes-vaciar-papelera").await?;
+    
+    let resumen = repo.vaciar(antes_de).await.map_err(|e| e.to_string())?;
+    
+    // Lo borrado ya no se puede restaurar, así que tampoco deshacer
+    historial::limpiar().await;
+    
+    Ok(resumen)
 }
 
+/// Borrar los archivos de comprobantes que no usa la base de datos ni sus copias de seguridad
 #[cfg(feature = "desktop")]
 #[tauri::command]
-pub async fn delete_transaccion(id: String) -> Result<bool, String> {
+pub async fn purgar_adjuntos() -> Result<usize, String> {
+    let database_url = crate::config::resolve_database_url(None, None).map_err(|e| e.to_string())?;
+    let backups = Backups::new(&database_url).map_err(|e| e.to_string())?;
     let pool = get_database_pool().await?;
-    let repo = TransaccionRepository::new(pool);
     
-    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
-    repo.delete(uuid).await.map_err(|e| e.to_string())
+    get_almacen_adjuntos()?
+        .purgar_huerfanos(&pool, &backups)
+        .await
+        .map_err(|e| format!("Error purgando comprobantes: {}", e))
+}
+
+/// Purga automática al iniciar según `[papelera] dias` en config.toml
+#[cfg(feature = "desktop")]
+pub(crate) async fn purgar_papelera_automatico() -> Result<ResumenVaciado, String> {
+    let config = crate::config::Config::load().map_err(|e| e.to_string())?;
+    let pool = get_database_pool().await?;
+    
+    PapeleraRepository::new(pool)
+        .purgar_caducados(config.papelera.dias)
+        .await
+        .map_err(|e| format!("Error purgando la papelera: {}", e))
+}
+
+// ============================================================================
+// COMANDOS PARA DESHACER Y REHACER
+// ============================================================================
+
+/// Deshacer la última operación de la sesión
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn deshacer() -> Result<EstadoHistorial, String> {
+    let pool = get_database_pool().await?;
+    let almacen = get_almacen_adjuntos()?;
+    
+    historial::deshacer(&pool, &almacen)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Rehacer la última operación deshecha
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn rehacer() -> Result<EstadoHistorial, String> {
+    let pool = get_database_pool().await?;
+    let almacen = get_almacen_adjuntos()?;
+    
+    historial::rehacer(&pool, &almacen)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Estado del historial, para restaurar los botones tras recargar la interfaz
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn get_historial() -> Result<EstadoHistorial, String> {
+    Ok(historial::estado().await)
 }
 
 // ============================================================================
@@ -198,16 +1237,14 @@ pub async fn get_balance_retiro(retiro_id: String) -> Result<BalanceRetiro, Stri
     
     let uuid = Uuid::parse_str(&retiro_id).map_err(|e| e.to_string())?;
     
-    let total_ingresos = transaccion_repo.calculate_balance(uuid, Some(TipoTransaccion::Ingreso)).await.map_err(|e| e.to_string())?;
-    let total_gastos = transaccion_repo.calculate_balance(uuid, Some(TipoTransaccion::Gasto)).await.map_err(|e| e.to_string())?;
-    let transacciones_count = transaccion_repo.count_by_retiro(uuid).await.map_err(|e| e.to_string())?;
+    let resumen = transaccion_repo.get_financial_summary(uuid).await.map_err(|e| e.to_string())?;
     
     Ok(BalanceRetiro {
         retiro_id,
-        balance: total_ingresos - total_gastos,
-        total_ingresos,
-        total_gastos,
-        transacciones_count: transacciones_count as i32,
+        balance: resumen.balance,
+        total_ingresos: resumen.total_ingresos,
+        total_gastos: resumen.total_gastos,
+        transacciones_count: resumen.total_transacciones,
     })
 }
 
@@ -338,28 +1375,125 @@ pub async fn get_retiros_finalizados_recientes() -> Result<Vec<RetiroFinalizadoC
         .await
         .map_err(|e| e.to_string())?;
     
-    let mut resultados = Vec::new();
+    let ids: Vec<Uuid> = retiros_finalizados.iter().map(|r| r.id).collect();
+    let resumenes = transaccion_repo
+        .get_financial_summaries(&ids)
+        .await
+        .map_err(|e| e.to_string())?;
     
-    for retiro in retiros_finalizados {
-        let total_ingresos = transaccion_repo
-            .calculate_balance(retiro.id, Some(TipoTransaccion::Ingreso))
-            .await
-            .map_err(|e| e.to_string())?;
-        
-        let total_gastos = transaccion_repo
-            .calculate_balance(retiro.id, Some(TipoTransaccion::Gasto))
-            .await
-            .map_err(|e| e.to_string())?;
-        
-        resultados.push(RetiroFinalizadoConBalance {
+    let resultados = retiros_finalizados
+        .into_iter()
+        .zip(resumenes)
+        .map(|(retiro, resumen)| RetiroFinalizadoConBalance {
             id: retiro.id.to_string(),
             nombre: retiro.nombre,
             fecha_fin: retiro.fecha_fin.format("%Y-%m-%d").to_string(),
             numero_participantes: retiro.numero_participantes,
-            balance: total_ingresos - total_gastos,
-            total_gastos,
-        });
-    }
+            balance: resumen.balance,
+            total_gastos: resumen.total_gastos,
+        })
+        .collect();
     
     Ok(resultados)
-}
\ No newline at end of file
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn get_flujo_caja(
+    retiro_id: Option<String>,
+    periodo: String,
+    desde: Option<String>,
+    hasta: Option<String>,
+) -> Result<Vec<PuntoFlujoCaja>, String> {
+    let pool = get_database_pool().await?;
+    let transaccion_repo = TransaccionRepository::new(pool);
+
+    let retiro_uuid = match retiro_id {
+        Some(id_str) => Some(Uuid::parse_str(&id_str).map_err(|e| e.to_string())?),
+        None => None,
+    };
+
+    // Convertir string a PeriodoFlujo
+    let periodo = match periodo.as_str() {
+        "Dia" => PeriodoFlujo::Dia,
+        "Semana" => PeriodoFlujo::Semana,
+        "Mes" => PeriodoFlujo::Mes,
+        _ => return Err("Periodo no válido".to_string()),
+    };
+
+    let parse_fecha = |fecha: String| {
+        NaiveDate::parse_from_str(&fecha, "%Y-%m-%d").map_err(|e| e.to_string())
+    };
+    let desde = desde.map(parse_fecha).transpose()?;
+    let hasta = hasta.map(parse_fecha).transpose()?;
+
+    transaccion_repo
+        .get_flujo_caja(retiro_uuid, periodo, desde, hasta)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Totales por categoría en orden de árbol, acumulando las subcategorías
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn get_totales_por_categoria(
+    retiro_id: Option<String>,
+) -> Result<Vec<TotalCategoria>, String> {
+    let pool = get_database_pool().await?;
+    let transaccion_repo = TransaccionRepository::new(pool);
+
+    let retiro_uuid = match retiro_id {
+        Some(id_str) => Some(Uuid::parse_str(&id_str).map_err(|e| e.to_string())?),
+        None => None,
+    };
+
+    transaccion_repo
+        .get_totales_por_categoria(retiro_uuid)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// ============================================================================
+// COMANDOS PARA WORKSPACES
+// ============================================================================
+
+#[cfg(feature = "desktop")]
+#[derive(Serialize, Deserialize)]
+pub struct WorkspaceInfo {
+    pub nombre: String,
+    pub database: String,
+    pub activo: bool,
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn get_workspaces() -> Result<Vec<WorkspaceInfo>, String> {
+    let config = crate::config::Config::load().map_err(|e| e.to_string())?;
+    
+    Ok(config
+        .workspaces
+        .iter()
+        .map(|(nombre, workspace)| WorkspaceInfo {
+            nombre: nombre.clone(),
+            database: workspace.database.clone(),
+            activo: config.workspace_activo.as_deref() == Some(nombre.as_str()),
+        })
+        .collect())
+}
+
+#[cfg(feature = "desktop")]
+#[tauri::command]
+pub async fn set_workspace_activo(nombre: String) -> Result<(), String> {
+    let mut config = crate::config::Config::load().map_err(|e| e.to_string())?;
+    
+    config.set_workspace_activo(&nombre).map_err(|e| e.to_string())?;
+    config.save().map_err(|e| e.to_string())?;
+    
+    // Abrir la base de datos para aplicar las migraciones pendientes del workspace
+    get_database_pool().await?;
+    
+    // El historial se refiere a la base de datos anterior
+    historial::limpiar().await;
+    
+    Ok(())
+}
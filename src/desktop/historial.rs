@@ -0,0 +1,434 @@
+// Historial de deshacer/rehacer de la app desktop
+// Vive en el proceso de Rust, así que sobrevive a las recargas de la interfaz durante la sesión
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::database::AlmacenAdjuntos;
+use crate::models::{
+    Adjunto, Arqueo, Categoria, CreateCategoria, CreatePlantillaTransaccion, CreateRetiro, Cuenta,
+    EstadoRetiro, ReglaCategoria, Retiro, TipoElementoPapelera, Transferencia,
+};
+use crate::repositories::{
+    AdjuntoRepository, ArqueoRepository, CategoriaRepository, CuentaRepository, PapeleraRepository,
+    PlantillaRepository, ReglaRepository, RetiroRepository, TransaccionRepository,
+};
+use crate::{AppError, Result};
+
+/// Máximo de operaciones que se pueden deshacer
+const MAX_OPERACIONES: usize = 50;
+
+static HISTORIAL: Mutex<Historial> = Mutex::const_new(Historial::new());
+
+/// Operación realizada, con los datos necesarios para repetirla o aplicar su inversa.
+///
+/// Deshacer una creación envía el elemento a la papelera; rehacerla lo restaura de ahí.
+#[derive(Debug, Clone)]
+pub enum Operacion {
+    /// Elementos que vuelven a estar activos (creados o restaurados de la papelera)
+    Restaurar {
+        tipo: TipoElementoPapelera,
+        ids: Vec<Uuid>,
+    },
+    /// Elementos enviados a la papelera
+    Eliminar {
+        tipo: TipoElementoPapelera,
+        ids: Vec<Uuid>,
+    },
+    ActualizarCategoria {
+        id: Uuid,
+        datos: CreateCategoria,
+        anteriores: CreateCategoria,
+    },
+    ActualizarRetiro {
+        id: Uuid,
+        datos: CreateRetiro,
+        anteriores: CreateRetiro,
+    },
+    CambiarEstadoRetiro {
+        id: Uuid,
+        estado: EstadoRetiro,
+        anterior: EstadoRetiro,
+    },
+    /// Las plantillas se borran definitivamente: al recrearlas reciben un ID nuevo
+    CrearPlantilla {
+        id: Uuid,
+        datos: CreatePlantillaTransaccion,
+    },
+    EliminarPlantilla {
+        id: Uuid,
+        datos: CreatePlantillaTransaccion,
+    },
+    /// Cuentas, transferencias, reglas y arqueos también se borran definitivamente, pero
+    /// al recrearlos conservan su ID para que sigan valiendo las operaciones posteriores
+    CrearCuenta {
+        cuenta: Cuenta,
+    },
+    EliminarCuenta {
+        cuenta: Cuenta,
+    },
+    CrearTransferencia {
+        transferencia: Transferencia,
+    },
+    EliminarTransferencia {
+        transferencia: Transferencia,
+    },
+    CrearRegla {
+        regla: ReglaCategoria,
+    },
+    EliminarRegla {
+        regla: ReglaCategoria,
+    },
+    /// La transacción de ajuste del arqueo va a la papelera con él y vuelve al recrearlo
+    CrearArqueo {
+        arqueo: Arqueo,
+    },
+    EliminarArqueo {
+        arqueo: Arqueo,
+    },
+    /// Se guarda el contenido del comprobante: el archivo se borra del disco al quedar huérfano
+    CrearAdjunto {
+        adjunto: Adjunto,
+        contenido: Vec<u8>,
+    },
+    EliminarAdjunto {
+        adjunto: Adjunto,
+        contenido: Vec<u8>,
+    },
+}
+
+/// Estado del historial para los botones "Deshacer"/"Rehacer" de la interfaz
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EstadoHistorial {
+    /// Operación que se acaba de deshacer o rehacer
+    pub aplicada: Option<String>,
+    /// Descripción de lo que deshará el siguiente "Deshacer"
+    pub deshacer: Option<String>,
+    /// Descripción de lo que rehará el siguiente "Rehacer"
+    pub rehacer: Option<String>,
+    pub pasos_deshacer: usize,
+    pub pasos_rehacer: usize,
+}
+
+struct Entrada {
+    descripcion: String,
+    operacion: Operacion,
+}
+
+struct Historial {
+    deshacer: Vec<Entrada>,
+    rehacer: Vec<Entrada>,
+}
+
+impl Historial {
+    const fn new() -> Self {
+        Self {
+            deshacer: Vec::new(),
+            rehacer: Vec::new(),
+        }
+    }
+
+    fn estado(&self, aplicada: Option<String>) -> EstadoHistorial {
+        EstadoHistorial {
+            aplicada,
+            deshacer: self.deshacer.last().map(|e| e.descripcion.clone()),
+            rehacer: self.rehacer.last().map(|e| e.descripcion.clone()),
+            pasos_deshacer: self.deshacer.len(),
+            pasos_rehacer: self.rehacer.len(),
+        }
+    }
+}
+
+impl Operacion {
+    /// Operación que anula a esta
+    fn inversa(self) -> Operacion {
+        match self {
+            Operacion::Restaurar { tipo, ids } => Operacion::Eliminar { tipo, ids },
+            Operacion::Eliminar { tipo, ids } => Operacion::Restaurar { tipo, ids },
+            Operacion::ActualizarCategoria {
+                id,
+                datos,
+                anteriores,
+            } => Operacion::ActualizarCategoria {
+                id,
+                datos: anteriores,
+                anteriores: datos,
+            },
+            Operacion::ActualizarRetiro {
+                id,
+                datos,
+                anteriores,
+            } => Operacion::ActualizarRetiro {
+                id,
+                datos: anteriores,
+                anteriores: datos,
+            },
+            Operacion::CambiarEstadoRetiro {
+                id,
+                estado,
+                anterior,
+            } => Operacion::CambiarEstadoRetiro {
+                id,
+                estado: anterior,
+                anterior: estado,
+            },
+            Operacion::CrearPlantilla { id, datos } => Operacion::EliminarPlantilla { id, datos },
+            Operacion::EliminarPlantilla { id, datos } => Operacion::CrearPlantilla { id, datos },
+            Operacion::CrearCuenta { cuenta } => Operacion::EliminarCuenta { cuenta },
+            Operacion::EliminarCuenta { cuenta } => Operacion::CrearCuenta { cuenta },
+            Operacion::CrearTransferencia { transferencia } => {
+                Operacion::EliminarTransferencia { transferencia }
+            }
+            Operacion::EliminarTransferencia { transferencia } => {
+                Operacion::CrearTransferencia { transferencia }
+            }
+            Operacion::CrearRegla { regla } => Operacion::EliminarRegla { regla },
+            Operacion::EliminarRegla { regla } => Operacion::CrearRegla { regla },
+            Operacion::CrearArqueo { arqueo } => Operacion::EliminarArqueo { arqueo },
+            Operacion::EliminarArqueo { arqueo } => Operacion::CrearArqueo { arqueo },
+            Operacion::CrearAdjunto { adjunto, contenido } => {
+                Operacion::EliminarAdjunto { adjunto, contenido }
+            }
+            Operacion::EliminarAdjunto { adjunto, contenido } => {
+                Operacion::CrearAdjunto { adjunto, contenido }
+            }
+        }
+    }
+
+    /// Aplicar la operación. Devuelve la operación tal como quedó (una plantilla recreada cambia de ID).
+    async fn aplicar(self, pool: &SqlitePool, almacen: &AlmacenAdjuntos) -> Result<Operacion> {
+        match self {
+            Operacion::Restaurar { tipo, ids } => {
+                let papelera = PapeleraRepository::new(pool.clone());
+                for id in &ids {
+                    if papelera.restaurar(*id).await?.is_none() {
+                        return Err(AppError::NotFound(format!("{} en la papelera", tipo)));
+                    }
+                }
+                Ok(Operacion::Restaurar { tipo, ids })
+            }
+            Operacion::Eliminar { tipo, ids } => {
+                for id in &ids {
+                    let eliminado = match tipo {
+                        TipoElementoPapelera::Retiro => {
+                            RetiroRepository::new(pool.clone()).delete(*id).await?
+                        }
+                        TipoElementoPapelera::Categoria => {
+                            CategoriaRepository::new(pool.clone()).delete(*id).await?
+                        }
+                        TipoElementoPapelera::Transaccion => {
+                            TransaccionRepository::new(pool.clone()).delete(*id).await?
+                        }
+                    };
+                    if !eliminado {
+                        return Err(AppError::NotFound(tipo.to_string()));
+                    }
+                }
+                Ok(Operacion::Eliminar { tipo, ids })
+            }
+            Operacion::ActualizarCategoria {
+                id,
+                datos,
+                anteriores,
+            } => {
+                CategoriaRepository::new(pool.clone())
+                    .update(id, datos.clone())
+                    .await?
+                    .ok_or_else(|| AppError::NotFound("Categoría".to_string()))?;
+                Ok(Operacion::ActualizarCategoria {
+                    id,
+                    datos,
+                    anteriores,
+                })
+            }
+            Operacion::ActualizarRetiro {
+                id,
+                datos,
+                anteriores,
+            } => {
+                RetiroRepository::new(pool.clone())
+                    .update(id, datos.clone())
+                    .await?
+                    .ok_or_else(|| AppError::NotFound("Retiro".to_string()))?;
+                Ok(Operacion::ActualizarRetiro {
+                    id,
+                    datos,
+                    anteriores,
+                })
+            }
+            Operacion::CambiarEstadoRetiro {
+                id,
+                estado,
+                anterior,
+            } => {
+                RetiroRepository::new(pool.clone())
+                    .update_estado(id, estado.clone())
+                    .await?
+                    .ok_or_else(|| AppError::NotFound("Retiro".to_string()))?;
+                Ok(Operacion::CambiarEstadoRetiro {
+                    id,
+                    estado,
+                    anterior,
+                })
+            }
+            Operacion::CrearPlantilla { datos, .. } => {
+                let plantilla = PlantillaRepository::new(pool.clone())
+                    .create(datos.clone())
+                    .await?;
+                Ok(Operacion::CrearPlantilla {
+                    id: plantilla.id,
+                    datos,
+                })
+            }
+            Operacion::EliminarPlantilla { id, datos } => {
+                if !PlantillaRepository::new(pool.clone()).delete(id).await? {
+                    return Err(AppError::NotFound("Plantilla".to_string()));
+                }
+                Ok(Operacion::EliminarPlantilla { id, datos })
+            }
+            Operacion::CrearCuenta { cuenta } => {
+                CuentaRepository::new(pool.clone()).restaurar(&cuenta).await?;
+                Ok(Operacion::CrearCuenta { cuenta })
+            }
+            Operacion::EliminarCuenta { cuenta } => {
+                if !CuentaRepository::new(pool.clone()).delete(cuenta.id).await? {
+                    return Err(AppError::NotFound("Cuenta".to_string()));
+                }
+                Ok(Operacion::EliminarCuenta { cuenta })
+            }
+            Operacion::CrearTransferencia { transferencia } => {
+                CuentaRepository::new(pool.clone())
+                    .restaurar_transferencia(&transferencia)
+                    .await?;
+                Ok(Operacion::CrearTransferencia { transferencia })
+            }
+            Operacion::EliminarTransferencia { transferencia } => {
+                if !CuentaRepository::new(pool.clone())
+                    .delete_transferencia(transferencia.id)
+                    .await?
+                {
+                    return Err(AppError::NotFound("Transferencia".to_string()));
+                }
+                Ok(Operacion::EliminarTransferencia { transferencia })
+            }
+            Operacion::CrearRegla { regla } => {
+                ReglaRepository::new(pool.clone()).restaurar(&regla).await?;
+                Ok(Operacion::CrearRegla { regla })
+            }
+            Operacion::EliminarRegla { regla } => {
+                if !ReglaRepository::new(pool.clone()).delete(regla.id).await? {
+                    return Err(AppError::NotFound("Regla".to_string()));
+                }
+                Ok(Operacion::EliminarRegla { regla })
+            }
+            Operacion::CrearArqueo { arqueo } => {
+                let arqueo = ArqueoRepository::new(pool.clone()).restaurar(&arqueo).await?;
+                Ok(Operacion::CrearArqueo { arqueo })
+            }
+            Operacion::EliminarArqueo { arqueo } => {
+                if !ArqueoRepository::new(pool.clone()).delete(arqueo.id).await? {
+                    return Err(AppError::NotFound("Arqueo".to_string()));
+                }
+                Ok(Operacion::EliminarArqueo { arqueo })
+            }
+            Operacion::CrearAdjunto { adjunto, contenido } => {
+                AdjuntoRepository::new(pool.clone())
+                    .restaurar(almacen, &adjunto, &contenido)
+                    .await?;
+                Ok(Operacion::CrearAdjunto { adjunto, contenido })
+            }
+            Operacion::EliminarAdjunto { adjunto, contenido } => {
+                if !AdjuntoRepository::new(pool.clone())
+                    .delete(adjunto.id)
+                    .await?
+                {
+                    return Err(AppError::NotFound("Adjunto".to_string()));
+                }
+                Ok(Operacion::EliminarAdjunto { adjunto, contenido })
+            }
+        }
+    }
+}
+
+/// Datos de una categoría tal como se envían al actualizarla
+pub fn datos_categoria(categoria: &Categoria) -> CreateCategoria {
+    CreateCategoria {
+        nombre: categoria.nombre.clone(),
+        tipo: categoria.tipo.clone(),
+        color: categoria.color.clone(),
+        padre_id: categoria.padre_id,
+    }
+}
+
+/// Datos de un retiro tal como se envían al actualizarlo
+pub fn datos_retiro(retiro: &Retiro) -> CreateRetiro {
+    CreateRetiro {
+        nombre: retiro.nombre.clone(),
+        descripcion: retiro.descripcion.clone(),
+        fecha_inicio: retiro.fecha_inicio,
+        fecha_fin: retiro.fecha_fin,
+        ubicacion: retiro.ubicacion.clone(),
+        numero_participantes: retiro.numero_participantes,
+    }
+}
+
+/// Registrar una operación ya realizada; descarta lo que hubiera para rehacer
+pub async fn registrar(descripcion: impl Into<String>, operacion: Operacion) {
+    let mut historial = HISTORIAL.lock().await;
+    historial.rehacer.clear();
+    historial.deshacer.push(Entrada {
+        descripcion: descripcion.into(),
+        operacion,
+    });
+    if historial.deshacer.len() > MAX_OPERACIONES {
+        historial.deshacer.remove(0);
+    }
+}
+
+/// Deshacer la última operación. Si falla (p. ej. porque los datos cambiaron desde fuera
+/// de la app) la operación se descarta del historial.
+pub async fn deshacer(pool: &SqlitePool, almacen: &AlmacenAdjuntos) -> Result<EstadoHistorial> {
+    let mut historial = HISTORIAL.lock().await;
+    let entrada = historial
+        .deshacer
+        .pop()
+        .ok_or_else(|| AppError::Validation("No hay nada que deshacer".to_string()))?;
+
+    let aplicada = entrada.operacion.inversa().aplicar(pool, almacen).await?;
+    historial.rehacer.push(Entrada {
+        descripcion: entrada.descripcion.clone(),
+        operacion: aplicada.inversa(),
+    });
+
+    Ok(historial.estado(Some(entrada.descripcion)))
+}
+
+/// Rehacer la última operación deshecha
+pub async fn rehacer(pool: &SqlitePool, almacen: &AlmacenAdjuntos) -> Result<EstadoHistorial> {
+    let mut historial = HISTORIAL.lock().await;
+    let entrada = historial
+        .rehacer
+        .pop()
+        .ok_or_else(|| AppError::Validation("No hay nada que rehacer".to_string()))?;
+
+    let aplicada = entrada.operacion.aplicar(pool, almacen).await?;
+    historial.deshacer.push(Entrada {
+        descripcion: entrada.descripcion.clone(),
+        operacion: aplicada,
+    });
+
+    Ok(historial.estado(Some(entrada.descripcion)))
+}
+
+pub async fn estado() -> EstadoHistorial {
+    HISTORIAL.lock().await.estado(None)
+}
+
+/// Vaciar el historial (al cambiar de workspace o tras borrar definitivamente la papelera)
+pub async fn limpiar() {
+    let mut historial = HISTORIAL.lock().await;
+    historial.deshacer.clear();
+    historial.rehacer.clear();
+}
@@ -1,5 +1,7 @@
 #[cfg(feature = "desktop")]
 pub mod commands;
+#[cfg(feature = "desktop")]
+pub mod historial;
 
 #[cfg(feature = "desktop")]
 use crate::Result;
@@ -8,24 +10,83 @@ use tauri::Builder;
 
 #[cfg(feature = "desktop")]
 pub async fn run_desktop() -> Result<()> {
+    // Copia de seguridad automática al iniciar; un fallo no impide abrir la app
+    if let Err(e) = commands::backup_automatico("inicio").await {
+        tracing::error!("{}", e);
+    }
+    if let Err(e) = commands::purgar_papelera_automatico().await {
+        tracing::error!("{}", e);
+    }
+
     Builder::default()
         .invoke_handler(tauri::generate_handler![
             commands::get_categorias,
             commands::create_categoria,
+            commands::sugerir_color_categoria,
             commands::update_categoria,
             commands::delete_categoria,
+            commands::archivar_categoria,
+            commands::desarchivar_categoria,
             commands::get_retiros,
             commands::create_retiro,
             commands::update_retiro,
             commands::update_retiro_estado,
+            commands::clonar_retiro,
             commands::delete_retiro,
             commands::get_transacciones,
             commands::create_transaccion,
             commands::delete_transaccion,
+            commands::upload_adjunto,
+            commands::get_adjuntos,
+            commands::get_contenido_adjunto,
+            commands::delete_adjunto,
+            commands::get_gastos_sin_comprobante,
+            commands::get_cuentas,
+            commands::create_cuenta,
+            commands::delete_cuenta,
+            commands::get_saldos_cuentas,
+            commands::get_transferencias,
+            commands::create_transferencia,
+            commands::delete_transferencia,
+            commands::create_arqueo,
+            commands::get_arqueos,
+            commands::delete_arqueo,
+            commands::preparar_extracto,
+            commands::importar_extracto,
+            commands::get_lineas_extracto,
+            commands::get_informe_conciliacion,
+            commands::conciliar_retiro,
+            commands::vincular_linea_extracto,
+            commands::desvincular_linea_extracto,
+            commands::set_conciliacion_transaccion,
+            commands::get_etiquetas,
+            commands::get_etiquetas_retiro,
+            commands::set_etiquetas_transaccion,
+            commands::get_totales_por_etiqueta,
+            commands::get_plantillas,
+            commands::create_plantilla,
+            commands::delete_plantilla,
+            commands::aplicar_plantillas,
+            commands::get_reglas,
+            commands::create_regla,
+            commands::delete_regla,
+            commands::probar_regla,
+            commands::sugerir_categoria,
+            commands::get_papelera,
+            commands::restaurar_papelera,
+            commands::vaciar_papelera,
+            commands::purgar_adjuntos,
+            commands::deshacer,
+            commands::rehacer,
+            commands::get_historial,
             commands::get_balance_retiro,
             commands::get_balance_global,
             commands::get_estadisticas_admin,
-            commands::get_retiros_finalizados_recientes
+            commands::get_retiros_finalizados_recientes,
+            commands::get_flujo_caja,
+            commands::get_totales_por_categoria,
+            commands::get_workspaces,
+            commands::set_workspace_activo
         ])
         .run(tauri::generate_context!())
         .map_err(|e| crate::AppError::Desktop(e.to_string()))?;
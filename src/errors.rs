@@ -5,12 +5,24 @@ pub enum AppError {
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
 
+    #[error("Migration error: {0}")]
+    Migration(#[from] sqlx::migrate::MigrateError),
+
+    #[error("Configuration error: {0}")]
+    Config(String),
+
     #[error("Validation error: {0}")]
     Validation(String),
 
     #[error("Not found: {0}")]
     NotFound(String),
 
+    #[error("Ambiguous reference: {0}")]
+    Ambiguous(String),
+
+    #[error("Already exists: {0}")]
+    Duplicate(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 
@@ -19,3 +31,20 @@ pub enum AppError {
 }
 
 pub type Result<T> = std::result::Result<T, AppError>;
+
+impl AppError {
+    /// Identificador estable del tipo de error (para salidas legibles por máquina)
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Database(_) => "database",
+            AppError::Migration(_) => "migration",
+            AppError::Config(_) => "config",
+            AppError::Validation(_) => "validation",
+            AppError::NotFound(_) => "not_found",
+            AppError::Ambiguous(_) => "ambiguous",
+            AppError::Duplicate(_) => "duplicate",
+            AppError::Internal(_) => "internal",
+            AppError::Desktop(_) => "desktop",
+        }
+    }
+}
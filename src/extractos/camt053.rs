@@ -0,0 +1,124 @@
+// ISO 20022 camt.053 (BankToCustomerStatement): un <Ntry> por movimiento
+
+use chrono::NaiveDate;
+
+use super::marcado::{bloque, bloques, valor, valores};
+use super::{componer_descripcion, parse_importe};
+use crate::models::MovimientoExtracto;
+use crate::{AppError, Result};
+
+pub(super) fn leer(texto: &str) -> Result<Vec<MovimientoExtracto>> {
+    if bloque(texto, "Stmt").is_none() {
+        return Err(AppError::Validation(
+            "CAMT.053: no se encuentra ningún extracto (<Stmt>)".to_string(),
+        ));
+    }
+
+    let mut movimientos = Vec::new();
+    for (numero, entrada) in bloques(texto, "Ntry").into_iter().enumerate() {
+        let error = |detalle: &str| {
+            AppError::Validation(format!("CAMT.053, movimiento {}: {}", numero + 1, detalle))
+        };
+
+        // Solo los movimientos contabilizados: los pendientes aún pueden cambiar
+        let estado = bloque(entrada, "Sts")
+            .and_then(|sts| valor(sts, "Cd"))
+            .or_else(|| valor(entrada, "Sts"))
+            .unwrap_or_default();
+        if estado.eq_ignore_ascii_case("PDNG") || estado.eq_ignore_ascii_case("INFO") {
+            continue;
+        }
+
+        let importe = valor(entrada, "Amt")
+            .and_then(|amt| parse_importe(&amt))
+            .ok_or_else(|| error("importe no válido"))?;
+        let cargo = match valor(entrada, "CdtDbtInd").as_deref() {
+            Some("DBIT") => true,
+            Some("CRDT") => false,
+            _ => return Err(error("falta el indicador de cargo o abono (CdtDbtInd)")),
+        };
+
+        let fecha = ["BookgDt", "ValDt"]
+            .iter()
+            .filter_map(|nombre| bloque(entrada, nombre))
+            .find_map(|fecha| valor(fecha, "Dt").or_else(|| valor(fecha, "DtTm")))
+            .and_then(|fecha| NaiveDate::parse_from_str(fecha.get(..10)?, "%Y-%m-%d").ok())
+            .ok_or_else(|| error("falta la fecha de contabilización"))?;
+
+        // La contraparte es quien paga en un abono y quien cobra en un cargo
+        let contraparte = bloque(entrada, "RltdPties")
+            .and_then(|partes| bloque(partes, if cargo { "Cdtr" } else { "Dbtr" }))
+            .and_then(|parte| valor(parte, "Nm"));
+        let mut partes: Vec<String> = contraparte.into_iter().collect();
+        let concepto = valores(entrada, "Ustrd");
+        if concepto.is_empty() {
+            partes.extend(valor(entrada, "AddtlNtryInf"));
+        } else {
+            partes.push(concepto.join(" "));
+        }
+
+        let referencia = valor(entrada, "AcctSvcrRef").or_else(|| {
+            valor(entrada, "EndToEndId").filter(|r| !r.eq_ignore_ascii_case("NOTPROVIDED"))
+        });
+
+        movimientos.push(MovimientoExtracto {
+            fecha,
+            monto: if cargo { -importe.abs() } else { importe.abs() },
+            descripcion: componer_descripcion(&partes),
+            referencia,
+        });
+    }
+
+    Ok(movimientos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXTRACTO: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.02"><BkToCstmrStmt><Stmt><Id>1</Id>
+<Ntry><Amt Ccy="EUR">45.50</Amt><CdtDbtInd>DBIT</CdtDbtInd><Sts>BOOK</Sts><BookgDt><Dt>2026-10-19</Dt></BookgDt>
+<NtryDtls><TxDtls><Refs><EndToEndId>NOTPROVIDED</EndToEndId></Refs><RltdPties><Cdtr><Nm>Panaderia L&amp;M</Nm></Cdtr></RltdPties><RmtInf><Ustrd>Compra pan</Ustrd></RmtInf></TxDtls></NtryDtls></Ntry>
+<Ntry><Amt Ccy="EUR">10.00</Amt><CdtDbtInd>CRDT</CdtDbtInd><Sts>PDNG</Sts><BookgDt><Dt>2026-10-20</Dt></BookgDt></Ntry>
+<Ntry><Amt Ccy="EUR">100,00</Amt><CdtDbtInd>CRDT</CdtDbtInd><Sts><Cd>BOOK</Cd></Sts><ValDt><DtTm>2026-10-17T10:00:00</DtTm></ValDt><AcctSvcrRef>REF-9</AcctSvcrRef>
+<NtryDtls><TxDtls><RltdPties><Dbtr><Nm>Ana</Nm></Dbtr><Cdtr><Nm>Casa de retiros</Nm></Cdtr></RltdPties></TxDtls></NtryDtls><AddtlNtryInf>Cuota</AddtlNtryInf></Ntry>
+</Stmt></BkToCstmrStmt></Document>"#;
+
+    #[test]
+    fn lee_los_movimientos_contabilizados() {
+        let movimientos = leer(EXTRACTO).unwrap();
+
+        // El movimiento pendiente (PDNG) se descarta
+        assert_eq!(movimientos.len(), 2);
+
+        assert_eq!(
+            movimientos[0].fecha,
+            NaiveDate::from_ymd_opt(2026, 10, 19).unwrap()
+        );
+        assert_eq!(movimientos[0].monto, -45.5);
+        assert_eq!(movimientos[0].descripcion, "Panaderia L&M - Compra pan");
+        assert_eq!(movimientos[0].referencia, None);
+
+        // Sin fecha de contabilización vale la de valor; en un abono la contraparte es el deudor
+        assert_eq!(
+            movimientos[1].fecha,
+            NaiveDate::from_ymd_opt(2026, 10, 17).unwrap()
+        );
+        assert_eq!(movimientos[1].monto, 100.0);
+        assert_eq!(movimientos[1].descripcion, "Ana - Cuota");
+        assert_eq!(movimientos[1].referencia.as_deref(), Some("REF-9"));
+    }
+
+    #[test]
+    fn rechaza_extractos_incompletos() {
+        assert!(leer("<Document></Document>").is_err());
+        assert!(leer(
+            "<Stmt><Ntry><Amt>1.00</Amt><BookgDt><Dt>2026-10-19</Dt></BookgDt></Ntry></Stmt>"
+        )
+        .is_err());
+        assert!(
+            leer("<Stmt><Ntry><Amt>1.00</Amt><CdtDbtInd>DBIT</CdtDbtInd></Ntry></Stmt>").is_err()
+        );
+    }
+}
@@ -0,0 +1,165 @@
+// Lectura mínima de etiquetas XML/SGML para CAMT.053 y OFX
+// Basta con localizar bloques y valores de hoja; se ignoran los prefijos de espacio de nombres
+// y en OFX SGML las hojas no tienen etiqueta de cierre
+
+/// Posición de la siguiente etiqueta de apertura `<nombre>` (con o sin prefijo y atributos)
+/// a partir de `desde`. Devuelve el inicio de la etiqueta y el final de su `>`.
+fn buscar_apertura(texto: &str, nombre: &str, desde: usize) -> Option<(usize, usize)> {
+    let mut posicion = desde;
+    while let Some(relativa) = texto[posicion..].find('<') {
+        let inicio = posicion + relativa;
+        let fin = inicio + texto[inicio..].find('>')?;
+        let etiqueta = &texto[inicio + 1..fin];
+        let nombre_etiqueta = etiqueta
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or("");
+        let local = nombre_etiqueta.rsplit(':').next().unwrap_or("");
+        if local.eq_ignore_ascii_case(nombre) && !etiqueta.starts_with('/') {
+            return Some((inicio, fin + 1));
+        }
+        posicion = fin + 1;
+    }
+    None
+}
+
+/// Posición de la siguiente etiqueta de cierre `</nombre>` a partir de `desde`
+fn buscar_cierre(texto: &str, nombre: &str, desde: usize) -> Option<(usize, usize)> {
+    let mut posicion = desde;
+    while let Some(relativa) = texto[posicion..].find("</") {
+        let inicio = posicion + relativa;
+        let fin = inicio + texto[inicio..].find('>')?;
+        let local = texto[inicio + 2..fin].trim().rsplit(':').next().unwrap_or("");
+        if local.eq_ignore_ascii_case(nombre) {
+            return Some((inicio, fin + 1));
+        }
+        posicion = fin + 1;
+    }
+    None
+}
+
+/// Contenido de cada bloque `<nombre>...</nombre>`
+pub(super) fn bloques<'a>(texto: &'a str, nombre: &str) -> Vec<&'a str> {
+    let mut bloques = Vec::new();
+    let mut posicion = 0;
+    while let Some((_, inicio)) = buscar_apertura(texto, nombre, posicion) {
+        // Una etiqueta vacía `<nombre/>` no tiene contenido
+        if texto[..inicio].ends_with("/>") {
+            posicion = inicio;
+            continue;
+        }
+        match buscar_cierre(texto, nombre, inicio) {
+            Some((fin, siguiente)) => {
+                bloques.push(&texto[inicio..fin]);
+                posicion = siguiente;
+            }
+            None => break,
+        }
+    }
+    bloques
+}
+
+/// Primer bloque `<nombre>...</nombre>`
+pub(super) fn bloque<'a>(texto: &'a str, nombre: &str) -> Option<&'a str> {
+    bloques(texto, nombre).into_iter().next()
+}
+
+/// Texto de cada hoja `<nombre>valor` (hasta la siguiente etiqueta), sin las vacías
+pub(super) fn valores(texto: &str, nombre: &str) -> Vec<String> {
+    let mut valores = Vec::new();
+    let mut posicion = 0;
+    while let Some((_, inicio)) = buscar_apertura(texto, nombre, posicion) {
+        let fin = texto[inicio..]
+            .find('<')
+            .map(|f| inicio + f)
+            .unwrap_or(texto.len());
+        let valor = decodificar(texto[inicio..fin].trim());
+        if !valor.is_empty() {
+            valores.push(valor);
+        }
+        posicion = fin;
+    }
+    valores
+}
+
+/// Primera hoja `<nombre>valor` no vacía
+pub(super) fn valor(texto: &str, nombre: &str) -> Option<String> {
+    valores(texto, nombre).into_iter().next()
+}
+
+/// Sustituir las entidades XML
+fn decodificar(texto: &str) -> String {
+    if !texto.contains('&') {
+        return texto.to_string();
+    }
+
+    let mut resultado = String::with_capacity(texto.len());
+    let mut resto = texto;
+    while let Some(inicio) = resto.find('&') {
+        resultado.push_str(&resto[..inicio]);
+        let entidad = &resto[inicio..];
+        let Some(fin) = entidad.find(';').filter(|&fin| fin <= 10) else {
+            resultado.push('&');
+            resto = &entidad[1..];
+            continue;
+        };
+        let caracter = match &entidad[1..fin] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            numero => numero
+                .strip_prefix("#x")
+                .or_else(|| numero.strip_prefix("#X"))
+                .map(|hex| u32::from_str_radix(hex, 16).ok())
+                .unwrap_or_else(|| numero.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match caracter {
+            Some(caracter) => {
+                resultado.push(caracter);
+                resto = &entidad[fin + 1..];
+            }
+            None => {
+                resultado.push('&');
+                resto = &entidad[1..];
+            }
+        }
+    }
+    resultado.push_str(resto);
+    resultado
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encuentra_bloques_con_prefijo_y_atributos() {
+        let texto = r#"<ns:Ntry a="1"><Amt Ccy="EUR">1.00</Amt></ns:Ntry><Ntry/><Ntry><Amt>2.00</Amt></Ntry>"#;
+
+        assert_eq!(
+            bloques(texto, "Ntry"),
+            vec![r#"<Amt Ccy="EUR">1.00</Amt>"#, "<Amt>2.00</Amt>"]
+        );
+        assert_eq!(bloque(texto, "ntry"), Some(r#"<Amt Ccy="EUR">1.00</Amt>"#));
+        assert_eq!(bloque(texto, "Stmt"), None);
+    }
+
+    #[test]
+    fn lee_hojas_sin_cierre_y_omite_las_vacias() {
+        let texto = "<NAME>\n<NAME>Pan\n<MEMO>Uno</MEMO><MEMO>Dos";
+
+        assert_eq!(valor(texto, "NAME").as_deref(), Some("Pan"));
+        assert_eq!(valores(texto, "MEMO"), vec!["Uno", "Dos"]);
+        assert_eq!(valor(texto, "FITID"), None);
+    }
+
+    #[test]
+    fn decodifica_entidades() {
+        assert_eq!(decodificar("L&amp;M &lt;&gt; &quot;&apos;"), "L&M <> \"'");
+        assert_eq!(decodificar("Espa&#241;a &#xE9;"), "España é");
+        assert_eq!(decodificar("A & B &desconocida;"), "A & B &desconocida;");
+    }
+}
@@ -0,0 +1,140 @@
+// Lectura de extractos bancarios: Norma 43 (AEB), CAMT.053 y OFX
+// Cada formato devuelve los movimientos con la fecha, el importe con signo y una descripción
+
+mod camt053;
+mod marcado;
+mod norma43;
+mod ofx;
+
+use crate::models::{FormatoExtracto, MovimientoExtracto};
+use crate::{AppError, Result};
+
+/// Máximo de caracteres de la descripción de un movimiento (el de las transacciones)
+const MAX_DESCRIPCION: usize = 300;
+
+/// Reconocer el formato de un extracto por su contenido
+pub fn detectar_formato(contenido: &[u8]) -> Option<FormatoExtracto> {
+    let inicio = String::from_utf8_lossy(&contenido[..contenido.len().min(4096)]).to_string();
+    let inicio_mayusculas = inicio.to_uppercase();
+
+    if inicio.contains("camt.053") || inicio.contains("BkToCstmrStmt") {
+        Some(FormatoExtracto::Camt053)
+    } else if inicio_mayusculas.contains("OFXHEADER") || inicio_mayusculas.contains("<OFX>") {
+        Some(FormatoExtracto::Ofx)
+    } else if inicio.trim_start_matches('\u{feff}').starts_with("11") {
+        Some(FormatoExtracto::Norma43)
+    } else {
+        None
+    }
+}
+
+/// Leer los movimientos de un extracto; sin formato se detecta por el contenido
+pub fn leer(
+    contenido: &[u8],
+    formato: Option<FormatoExtracto>,
+) -> Result<(FormatoExtracto, Vec<MovimientoExtracto>)> {
+    let formato = match formato.or_else(|| detectar_formato(contenido)) {
+        Some(formato) => formato,
+        None => {
+            return Err(AppError::Validation(
+                "No se reconoce el formato del extracto (se admiten Norma 43, CAMT.053 y OFX)"
+                    .to_string(),
+            ))
+        }
+    };
+
+    let movimientos = match formato {
+        FormatoExtracto::Norma43 => norma43::leer(contenido)?,
+        FormatoExtracto::Camt053 => camt053::leer(&texto(contenido))?,
+        FormatoExtracto::Ofx => ofx::leer(&texto(contenido))?,
+    };
+    if movimientos.is_empty() {
+        return Err(AppError::Validation(format!(
+            "El extracto ({}) no contiene movimientos",
+            formato
+        )));
+    }
+
+    Ok((formato, movimientos))
+}
+
+/// Texto del archivo: UTF-8 o, si no lo es, Latin-1 (habitual en los bancos españoles)
+fn texto(contenido: &[u8]) -> String {
+    match std::str::from_utf8(contenido) {
+        Ok(texto) => texto.trim_start_matches('\u{feff}').to_string(),
+        Err(_) => contenido.iter().map(|&byte| byte as char).collect(),
+    }
+}
+
+/// Juntar las partes no vacías de una descripción, sin espacios sobrantes y con el
+/// largo máximo de una transacción
+fn componer_descripcion(partes: &[String]) -> String {
+    let mut descripcion = partes
+        .iter()
+        .map(|p| p.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>()
+        .join(" - ");
+    if descripcion.is_empty() {
+        descripcion = "Movimiento bancario".to_string();
+    }
+    descripcion.chars().take(MAX_DESCRIPCION).collect()
+}
+
+/// Leer un importe escrito con punto o coma decimal
+fn parse_importe(texto: &str) -> Option<f64> {
+    let texto = texto.trim().replace(' ', "");
+    let texto = if texto.contains(',') && !texto.contains('.') {
+        texto.replace(',', ".")
+    } else {
+        texto.replace(',', "")
+    };
+    texto.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detecta_el_formato_por_el_contenido() {
+        assert_eq!(
+            detectar_formato(b"<Document><BkToCstmrStmt>"),
+            Some(FormatoExtracto::Camt053)
+        );
+        assert_eq!(
+            detectar_formato(b"ofxheader:100"),
+            Some(FormatoExtracto::Ofx)
+        );
+        assert_eq!(
+            detectar_formato("\u{feff}1100491234".as_bytes()),
+            Some(FormatoExtracto::Norma43)
+        );
+        assert_eq!(detectar_formato(b"fecha;importe"), None);
+        assert!(leer(b"fecha;importe", None).is_err());
+    }
+
+    #[test]
+    fn lee_importes_con_punto_o_coma_decimal() {
+        assert_eq!(parse_importe("12.50"), Some(12.5));
+        assert_eq!(parse_importe(" 12,50 "), Some(12.5));
+        assert_eq!(parse_importe("1,234.50"), Some(1234.5));
+        assert_eq!(parse_importe("-1 234,5"), Some(-1234.5));
+        assert_eq!(parse_importe("doce"), None);
+    }
+
+    #[test]
+    fn compone_descripciones_limpias_y_acotadas() {
+        let partes = [
+            "  Pan  del\tdia ".to_string(),
+            String::new(),
+            "Compra".to_string(),
+        ];
+        assert_eq!(componer_descripcion(&partes), "Pan del dia - Compra");
+        assert_eq!(componer_descripcion(&[]), "Movimiento bancario");
+        assert_eq!(
+            componer_descripcion(&["x".repeat(400)]).chars().count(),
+            MAX_DESCRIPCION
+        );
+    }
+}
@@ -0,0 +1,177 @@
+// Cuaderno 43 de la AEB: registros de 80 caracteres en Latin-1
+//   11: cabecera de cuenta · 22: movimiento · 23: conceptos del movimiento
+//   24: equivalencia de divisa · 33: final de cuenta · 88: fin de fichero
+
+use chrono::NaiveDate;
+
+use super::componer_descripcion;
+use crate::models::MovimientoExtracto;
+use crate::{AppError, Result};
+
+/// Movimiento en construcción: los registros 23 que siguen a un 22 completan su concepto
+struct Pendiente {
+    fecha: NaiveDate,
+    monto: f64,
+    referencia: Option<String>,
+    /// La referencia 2 suele traer el concepto cuando no hay registros 23
+    referencia2: String,
+    conceptos: Vec<String>,
+}
+
+impl Pendiente {
+    fn terminar(self) -> MovimientoExtracto {
+        MovimientoExtracto {
+            fecha: self.fecha,
+            monto: self.monto,
+            descripcion: if self.conceptos.is_empty() {
+                componer_descripcion(&[self.referencia2])
+            } else {
+                componer_descripcion(&self.conceptos)
+            },
+            referencia: self.referencia,
+        }
+    }
+}
+
+pub(super) fn leer(contenido: &[u8]) -> Result<Vec<MovimientoExtracto>> {
+    // Latin-1: cada byte es un carácter, así que las posiciones del registro se conservan
+    let texto: String = contenido.iter().map(|&byte| byte as char).collect();
+
+    let mut movimientos = Vec::new();
+    let mut pendiente: Option<Pendiente> = None;
+
+    for (numero, linea) in texto.lines().enumerate() {
+        let registro: Vec<char> = linea.trim_end_matches(['\r', '\n']).chars().collect();
+        if registro.iter().all(|c| c.is_whitespace()) {
+            continue;
+        }
+        let campo = |desde: usize, hasta: usize| -> String {
+            registro
+                .get(desde - 1..hasta.min(registro.len()))
+                .map(|c| c.iter().collect::<String>().trim().to_string())
+                .unwrap_or_default()
+        };
+        let error = |detalle: &str| {
+            AppError::Validation(format!(
+                "Norma 43, línea {}: {}",
+                numero + 1,
+                detalle
+            ))
+        };
+
+        match campo(1, 2).as_str() {
+            "22" => {
+                if let Some(anterior) = pendiente.take() {
+                    movimientos.push(anterior.terminar());
+                }
+
+                let fecha = NaiveDate::parse_from_str(&campo(11, 16), "%y%m%d")
+                    .map_err(|_| error("fecha de operación no válida"))?;
+                let centimos: i64 = campo(29, 42)
+                    .parse()
+                    .map_err(|_| error("importe no válido"))?;
+                // Clave 1: cargo (debe) · 2: abono (haber)
+                let signo = match campo(28, 28).as_str() {
+                    "1" => -1.0,
+                    "2" => 1.0,
+                    _ => return Err(error("clave de debe/haber no válida")),
+                };
+                let referencia = [campo(43, 52), campo(53, 64)]
+                    .into_iter()
+                    .filter(|r| !r.is_empty() && r.chars().any(|c| c != '0'))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                pendiente = Some(Pendiente {
+                    fecha,
+                    monto: signo * centimos as f64 / 100.0,
+                    referencia: (!referencia.is_empty()).then_some(referencia),
+                    referencia2: campo(65, 80),
+                    conceptos: Vec::new(),
+                });
+            }
+            "23" => {
+                let actual = pendiente
+                    .as_mut()
+                    .ok_or_else(|| error("registro de concepto sin movimiento"))?;
+                actual.conceptos.push(format!("{} {}", campo(5, 42), campo(43, 80)));
+            }
+            // La equivalencia de divisa no cambia el importe en euros
+            "24" => {}
+            "11" | "33" | "88" => {
+                if let Some(anterior) = pendiente.take() {
+                    movimientos.push(anterior.terminar());
+                }
+            }
+            otro => {
+                return Err(error(&format!("tipo de registro desconocido '{}'", otro)));
+            }
+        }
+    }
+
+    if let Some(anterior) = pendiente.take() {
+        movimientos.push(anterior.terminar());
+    }
+
+    Ok(movimientos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXTRACTO: &str = concat!(
+        "110049123412345678902610012610312000000000000003978Titular                      \r\n",
+        "22    0001261018261018020001000000000120000000000001SEGURO      Seguro del retir\r\n",
+        "2301MAPFRE SEGUROS                        Seguro del retiro anual               \r\n",
+        "22    0001261019261019020002000000000150000000000000            Cuota           \r\n",
+        "33000000000000000000000000000000000000000000000000000000000000000000000000000000\r\n",
+        "88999999999999999999000000000000000000000000000000000000000000000000000000000000\r\n",
+    );
+
+    #[test]
+    fn lee_cargos_y_abonos_con_sus_conceptos() {
+        let movimientos = leer(EXTRACTO.as_bytes()).unwrap();
+
+        assert_eq!(movimientos.len(), 2);
+        assert_eq!(
+            movimientos[0].fecha,
+            NaiveDate::from_ymd_opt(2026, 10, 18).unwrap()
+        );
+        assert_eq!(movimientos[0].monto, -120.0);
+        assert_eq!(
+            movimientos[0].descripcion,
+            "MAPFRE SEGUROS Seguro del retiro anual"
+        );
+        assert_eq!(
+            movimientos[0].referencia.as_deref(),
+            Some("0000000001 SEGURO")
+        );
+
+        // Sin registros 23 el concepto sale de la referencia 2; una referencia a ceros no cuenta
+        assert_eq!(movimientos[1].monto, 150.0);
+        assert_eq!(movimientos[1].descripcion, "Cuota");
+        assert_eq!(movimientos[1].referencia, None);
+    }
+
+    #[test]
+    fn conserva_las_posiciones_en_latin1() {
+        let mut contenido =
+            b"22    0001261018261018020001000000000001000000000000            ".to_vec();
+        contenido.extend_from_slice(b"Pa\xF1uelos       ");
+        let movimientos = leer(&contenido).unwrap();
+
+        assert_eq!(movimientos[0].monto, -1.0);
+        assert_eq!(movimientos[0].descripcion, "Pañuelos");
+    }
+
+    #[test]
+    fn rechaza_registros_no_validos() {
+        assert!(leer(b"2301CONCEPTO SIN MOVIMIENTO").is_err());
+        assert!(leer(b"99 registro desconocido").is_err());
+        assert!(
+            leer(b"22    0001261018261018020003000000000120000000000001").is_err(),
+            "la clave de debe/haber solo puede ser 1 o 2"
+        );
+    }
+}
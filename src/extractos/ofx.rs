@@ -0,0 +1,87 @@
+// Open Financial Exchange: un <STMTTRN> por movimiento, con el importe ya con signo
+// Vale para OFX 1.x (SGML, hojas sin cierre) y 2.x (XML)
+
+use chrono::NaiveDate;
+
+use super::marcado::{bloques, valor};
+use super::{componer_descripcion, parse_importe};
+use crate::models::MovimientoExtracto;
+use crate::{AppError, Result};
+
+pub(super) fn leer(texto: &str) -> Result<Vec<MovimientoExtracto>> {
+    let mut movimientos = Vec::new();
+    for (numero, transaccion) in bloques(texto, "STMTTRN").into_iter().enumerate() {
+        let error = |detalle: &str| {
+            AppError::Validation(format!("OFX, movimiento {}: {}", numero + 1, detalle))
+        };
+
+        let monto = valor(transaccion, "TRNAMT")
+            .and_then(|importe| parse_importe(&importe))
+            .ok_or_else(|| error("importe no válido (TRNAMT)"))?;
+        // DTPOSTED: AAAAMMDD seguido opcionalmente de la hora y la zona horaria
+        let fecha = valor(transaccion, "DTPOSTED")
+            .and_then(|fecha| NaiveDate::parse_from_str(fecha.get(..8)?, "%Y%m%d").ok())
+            .ok_or_else(|| error("fecha no válida (DTPOSTED)"))?;
+
+        let nombre = valor(transaccion, "NAME").unwrap_or_default();
+        let memo = valor(transaccion, "MEMO").unwrap_or_default();
+        let partes = if memo.eq_ignore_ascii_case(&nombre) {
+            vec![nombre]
+        } else {
+            vec![nombre, memo]
+        };
+
+        movimientos.push(MovimientoExtracto {
+            fecha,
+            monto,
+            descripcion: componer_descripcion(&partes),
+            referencia: valor(transaccion, "FITID"),
+        });
+    }
+
+    Ok(movimientos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lee_ofx_sgml_sin_cierres() {
+        let extracto = "OFXHEADER:100\nDATA:OFXSGML\n<OFX><BANKMSGSRSV1><STMTTRNRS><STMTRS><BANKTRANLIST>\n\
+            <STMTTRN><TRNTYPE>DEBIT<DTPOSTED>20261018120000[-5:EST]<TRNAMT>-40.00<FITID>A1<NAME>Pan<MEMO>Pan del desayuno\n</STMTTRN>\n\
+            <STMTTRN><TRNTYPE>CREDIT<DTPOSTED>20261021<TRNAMT>25,00<FITID>A2<NAME>Devolucion<MEMO>DEVOLUCION\n</STMTTRN>\n\
+            </BANKTRANLIST></STMTRS></STMTTRNRS></BANKMSGSRSV1></OFX>";
+        let movimientos = leer(extracto).unwrap();
+
+        assert_eq!(movimientos.len(), 2);
+        assert_eq!(
+            movimientos[0].fecha,
+            NaiveDate::from_ymd_opt(2026, 10, 18).unwrap()
+        );
+        assert_eq!(movimientos[0].monto, -40.0);
+        assert_eq!(movimientos[0].descripcion, "Pan - Pan del desayuno");
+        assert_eq!(movimientos[0].referencia.as_deref(), Some("A1"));
+
+        // Coma decimal, y un memo igual al nombre no se repite
+        assert_eq!(movimientos[1].monto, 25.0);
+        assert_eq!(movimientos[1].descripcion, "Devolucion");
+    }
+
+    #[test]
+    fn lee_ofx_xml() {
+        let extracto = "<?xml version=\"1.0\"?><?OFX OFXHEADER=\"200\"?><OFX><STMTTRN><TRNTYPE>DEBIT</TRNTYPE>\
+            <DTPOSTED>20261020</DTPOSTED><TRNAMT>-12.30</TRNAMT><FITID>X</FITID><NAME>Farmacia</NAME></STMTTRN></OFX>";
+        let movimientos = leer(extracto).unwrap();
+
+        assert_eq!(movimientos.len(), 1);
+        assert_eq!(movimientos[0].monto, -12.3);
+        assert_eq!(movimientos[0].descripcion, "Farmacia");
+    }
+
+    #[test]
+    fn rechaza_importes_y_fechas_no_validos() {
+        assert!(leer("<STMTTRN><DTPOSTED>20261020<TRNAMT>doce</STMTTRN>").is_err());
+        assert!(leer("<STMTTRN><DTPOSTED>2026<TRNAMT>-1.00</STMTTRN>").is_err());
+    }
+}
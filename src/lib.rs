@@ -1,9 +1,12 @@
 pub mod cli;
+pub mod config;
 pub mod database;
 #[cfg(feature = "desktop")]
 pub mod desktop;
 pub mod errors;
+pub mod extractos;
 pub mod models;
 pub mod repositories;
+pub mod tui;
 
 pub use errors::{AppError, Result};
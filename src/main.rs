@@ -1,13 +1,14 @@
 use std::env;
 
 #[cfg(not(feature = "desktop"))]
-use backend_rust::cli::run_cli;
+use backend_rust::cli::{completions, output::print_error, run_cli, Cli};
+#[cfg(not(feature = "desktop"))]
+use clap::Parser;
 
 #[cfg(feature = "desktop")]
 use backend_rust::desktop::run_desktop;
 
 use tracing::Level;
-use tracing_subscriber;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -21,6 +22,10 @@ async fn main() -> anyhow::Result<()> {
     // Cargar variables de entorno
     dotenvy::dotenv().ok();
 
+    // Responder a las peticiones de autocompletado de la shell (termina el proceso)
+    #[cfg(not(feature = "desktop"))]
+    completions::complete();
+
     // Detectar si ejecutar como CLI o Desktop
     let args: Vec<String> = env::args().collect();
     
@@ -39,9 +44,11 @@ async fn main() -> anyhow::Result<()> {
     // Ejecutar CLI
         #[cfg(not(feature = "desktop"))]
         {
-    if let Err(e) = run_cli().await {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
+            let cli = Cli::parse();
+            let output = cli.output;
+            if let Err(e) = run_cli(cli).await {
+                print_error(output, &e);
+                std::process::exit(1);
             }
         }
         #[cfg(feature = "desktop")]
@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Tamaño máximo de un comprobante (20 MB)
+pub const MAX_TAMANO_ADJUNTO: usize = 20 * 1024 * 1024;
+
+/// Comprobante (imagen o PDF) adjunto a una transacción
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Adjunto {
+    pub id: Uuid,
+    pub transaccion_id: Uuid,
+
+    /// Nombre original del archivo
+    pub nombre: String,
+
+    pub tipo_mime: String,
+
+    /// SHA-256 del contenido, que da nombre al archivo en disco
+    pub hash: String,
+
+    /// Tamaño en bytes
+    pub tamano: i64,
+
+    pub created_at: DateTime<Utc>,
+}
+
+/// Gasto sin ningún comprobante adjunto, para el informe de auditoría
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GastoSinComprobante {
+    pub retiro_id: Uuid,
+    pub retiro: String,
+    pub transaccion_id: Uuid,
+    pub categoria: String,
+    pub monto: f64,
+    pub descripcion: String,
+    pub fecha: DateTime<Utc>,
+}
+
+/// Tipo MIME de un comprobante según sus primeros bytes.
+/// Solo se aceptan PDF e imágenes JPEG, PNG, WebP y HEIC.
+pub fn detectar_tipo_mime(contenido: &[u8]) -> Option<&'static str> {
+    match contenido {
+        [b'%', b'P', b'D', b'F', b'-', ..] => Some("application/pdf"),
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n', ..] => Some("image/png"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+        [_, _, _, _, b'f', b't', b'y', b'p', marca @ ..]
+            if [&b"heic"[..], b"heix", b"mif1"]
+                .iter()
+                .any(|m| marca.starts_with(m)) =>
+        {
+            Some("image/heic")
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,143 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{AppError, Result};
+
+/// Nombre de las categorías (una de ingreso y otra de gasto) de las transacciones de ajuste
+pub const CATEGORIA_AJUSTE_ARQUEO: &str = "Ajustes de caja";
+
+/// Billetes y monedas de euro, en céntimos
+pub const DENOMINACIONES_EURO: [i64; 15] = [
+    50000, 20000, 10000, 5000, 2000, 1000, 500, 200, 100, 50, 20, 10, 5, 2, 1,
+];
+
+/// Cantidad contada de un billete o moneda
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Denominacion {
+    pub valor: f64,
+    pub cantidad: u32,
+}
+
+/// Recuento físico del dinero de una cuenta, comparado con su saldo calculado
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct Arqueo {
+    pub id: Uuid,
+    pub retiro_id: Uuid,
+    pub cuenta_id: Uuid,
+
+    /// Saldo total de la cuenta según los movimientos registrados
+    pub saldo_calculado: f64,
+
+    #[validate(range(min = 0.0))]
+    pub contado: f64,
+
+    /// Contado menos calculado: positivo si sobra dinero, negativo si falta
+    pub diferencia: f64,
+
+    /// Vacío si solo se indicó el total contado
+    #[serde(default)]
+    pub denominaciones: Vec<Denominacion>,
+
+    #[validate(length(max = 500))]
+    #[serde(default)]
+    pub notas: Option<String>,
+
+    /// Transacción que corrige el descuadre
+    #[serde(default)]
+    pub transaccion_ajuste_id: Option<Uuid>,
+
+    pub created_at: DateTime<Utc>,
+}
+
+/// Datos de un arqueo: el total contado, el recuento por billetes y monedas, o ambos
+/// (en ese caso deben coincidir)
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateArqueo {
+    pub retiro_id: Uuid,
+    pub cuenta_id: Uuid,
+
+    #[validate(range(min = 0.0))]
+    #[serde(default)]
+    pub total: Option<f64>,
+
+    #[serde(default)]
+    pub denominaciones: Vec<Denominacion>,
+
+    #[validate(length(max = 500))]
+    #[serde(default)]
+    pub notas: Option<String>,
+
+    /// Registrar una transacción de ajuste si hay descuadre
+    #[serde(default)]
+    pub ajustar: bool,
+}
+
+impl Arqueo {
+    /// La caja cuadra si la diferencia es menor de medio céntimo
+    pub fn cuadra(&self) -> bool {
+        self.diferencia.abs() < 0.005
+    }
+}
+
+/// Redondear a céntimos
+pub fn redondear_centimos(monto: f64) -> f64 {
+    (monto * 100.0).round() / 100.0
+}
+
+/// Total contado de un arqueo, comprobando las denominaciones y que coincidan con el total indicado
+pub fn total_contado(total: Option<f64>, denominaciones: &[Denominacion]) -> Result<f64> {
+    for denominacion in denominaciones {
+        let centimos = (denominacion.valor * 100.0).round() as i64;
+        if !DENOMINACIONES_EURO.contains(&centimos)
+            || (denominacion.valor * 100.0 - centimos as f64).abs() > 1e-6
+        {
+            return Err(AppError::Validation(format!(
+                "€{} no es un billete ni una moneda de euro",
+                denominacion.valor
+            )));
+        }
+    }
+
+    let suma = redondear_centimos(
+        denominaciones
+            .iter()
+            .map(|d| d.valor * f64::from(d.cantidad))
+            .sum(),
+    );
+    match total {
+        Some(total) if !denominaciones.is_empty() && (total - suma).abs() >= 0.005 => {
+            Err(AppError::Validation(format!(
+                "El total indicado (€{:.2}) no coincide con la suma de billetes y monedas (€{:.2})",
+                total, suma
+            )))
+        }
+        Some(total) if total < 0.0 => Err(AppError::Validation(
+            "El total contado no puede ser negativo".to_string(),
+        )),
+        Some(total) => Ok(redondear_centimos(total)),
+        None if denominaciones.is_empty() => Err(AppError::Validation(
+            "Indica el total contado o el recuento de billetes y monedas".to_string(),
+        )),
+        None => Ok(suma),
+    }
+}
+
+/// Leer una denominación escrita como `VALORxCANTIDAD` (p. ej. `20x3` o `0.50x4`)
+pub fn parse_denominacion(texto: &str) -> Result<Denominacion> {
+    let error = || {
+        AppError::Validation(format!(
+            "Denominación '{}' no válida: usa VALORxCANTIDAD, p. ej. 20x3 o 0.50x4",
+            texto
+        ))
+    };
+    let (valor, cantidad) = texto
+        .trim()
+        .split_once(['x', 'X', '*'])
+        .ok_or_else(error)?;
+    Ok(Denominacion {
+        valor: valor.trim().replace(',', ".").parse().map_err(|_| error())?,
+        cantidad: cantidad.trim().parse().map_err(|_| error())?,
+    })
+}
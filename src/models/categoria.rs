@@ -1,7 +1,8 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "TEXT")]
@@ -19,30 +20,147 @@ pub struct Categoria {
 
     pub tipo: TipoCategoria,
 
-    #[validate(length(min = 7, max = 7))] // Formato #RRGGBB
+    #[validate(custom = "validar_color_hex")]
     pub color: String,
+
+    /// Categoría padre (None para las categorías de primer nivel)
+    #[serde(default)]
+    pub padre_id: Option<Uuid>,
+
+    /// Las categorías archivadas no se ofrecen al crear transacciones, pero se
+    /// conservan para el histórico
+    #[serde(default)]
+    pub archivada: bool,
+
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Validate)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct CreateCategoria {
     #[validate(length(min = 1, max = 100))]
     pub nombre: String,
 
     pub tipo: TipoCategoria,
 
-    #[validate(length(min = 7, max = 7))]
+    /// Si se deja vacío se sugiere uno de la paleta
+    #[serde(default)]
+    #[validate(custom = "validar_color_hex")]
     pub color: String,
+
+    /// Categoría padre, del mismo tipo
+    #[serde(default)]
+    pub padre_id: Option<Uuid>,
 }
 
 impl Categoria {
     pub fn new(data: CreateCategoria) -> Self {
+        let now = Utc::now();
         Self {
             id: Uuid::new_v4(),
             nombre: data.nombre,
             tipo: data.tipo,
             color: data.color,
+            padre_id: data.padre_id,
+            archivada: false,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// Paleta de colores que se sugieren a las categorías nuevas
+pub const PALETA_COLORES: [&str; 12] = [
+    "#E6194B", "#3CB44B", "#4363D8", "#F58231", "#911EB4", "#42D4F4", "#F032E6", "#BFEF45",
+    "#FABED4", "#469990", "#9A6324", "#800000",
+];
+
+/// Comprobar que un color tiene el formato #RRGGBB
+pub fn validar_color_hex(color: &str) -> Result<(), ValidationError> {
+    let valido = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if valido {
+        return Ok(());
+    }
+
+    let mut error = ValidationError::new("color_hex");
+    error.message = Some("Debe tener el formato #RRGGBB (p. ej. #FF5733)".into());
+    Err(error)
+}
+
+/// Sugerir el primer color de la paleta que no esté en uso; si ya se usan todos,
+/// el menos repetido
+pub fn sugerir_color<'a>(usados: impl IntoIterator<Item = &'a str>) -> String {
+    let usados: Vec<String> = usados.into_iter().map(|c| c.to_uppercase()).collect();
+    PALETA_COLORES
+        .iter()
+        .min_by_key(|color| usados.iter().filter(|usado| usado == color).count())
+        .map(|color| color.to_string())
+        .unwrap_or_else(|| PALETA_COLORES[0].to_string())
+}
+
+/// Total de una categoría dentro del árbol de subcategorías
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TotalCategoria {
+    pub categoria_id: Uuid,
+    pub nombre: String,
+    pub tipo: TipoCategoria,
+    pub color: String,
+    pub padre_id: Option<Uuid>,
+    pub archivada: bool,
+    /// Profundidad en el árbol (0 para las categorías de primer nivel)
+    pub nivel: usize,
+    /// Suma de las transacciones asignadas directamente a la categoría
+    pub total_propio: f64,
+    /// Suma de la categoría y de todas sus subcategorías
+    pub total_acumulado: f64,
+}
+
+/// Ordenar las categorías en profundidad (cada padre seguido de sus subcategorías),
+/// devolviendo también el nivel de cada una. Las categorías cuyo padre no está en la
+/// lista se tratan como de primer nivel.
+pub fn arbol_categorias(categorias: &[Categoria]) -> Vec<(usize, &Categoria)> {
+    fn visitar<'a>(
+        padre: &'a Categoria,
+        nivel: usize,
+        categorias: &'a [Categoria],
+        resultado: &mut Vec<(usize, &'a Categoria)>,
+    ) {
+        resultado.push((nivel, padre));
+        for hija in categorias.iter().filter(|c| c.padre_id == Some(padre.id)) {
+            // Protección frente a ciclos en datos corruptos
+            if resultado.iter().all(|(_, c)| c.id != hija.id) {
+                visitar(hija, nivel + 1, categorias, resultado);
+            }
+        }
+    }
+
+    let mut resultado = Vec::with_capacity(categorias.len());
+    for raiz in categorias.iter().filter(|c| match c.padre_id {
+        Some(padre_id) => categorias.iter().all(|otra| otra.id != padre_id),
+        None => true,
+    }) {
+        visitar(raiz, 0, categorias, &mut resultado);
+    }
+    resultado
+}
+
+/// IDs de los antecesores de una categoría, del padre a la raíz
+pub fn antecesores(id: Uuid, categorias: &[Categoria]) -> Vec<Uuid> {
+    let mut resultado = Vec::new();
+    let mut actual = categorias.iter().find(|c| c.id == id).and_then(|c| c.padre_id);
+    while let Some(padre_id) = actual {
+        if padre_id == id || resultado.contains(&padre_id) {
+            break;
         }
+        resultado.push(padre_id);
+        actual = categorias
+            .iter()
+            .find(|c| c.id == padre_id)
+            .and_then(|c| c.padre_id);
     }
+    resultado
 }
 
 // Implementar Display para facilitar la conversión a string
@@ -0,0 +1,171 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT")]
+pub enum TipoCuenta {
+    Caja,
+    Banco,
+    Tarjeta,
+    Otra,
+}
+
+/// Cómo se pagó o cobró una transacción
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT")]
+pub enum MetodoPago {
+    Efectivo,
+    Transferencia,
+    Tarjeta,
+    Bizum,
+}
+
+/// Cuenta donde está el dinero: la caja de efectivo, una cuenta bancaria, una tarjeta...
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct Cuenta {
+    pub id: Uuid,
+
+    #[validate(length(min = 1, max = 100))]
+    pub nombre: String,
+
+    pub tipo: TipoCuenta,
+
+    /// Dinero que había antes del primer movimiento registrado
+    #[serde(default)]
+    pub saldo_inicial: f64,
+
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateCuenta {
+    #[validate(length(min = 1, max = 100))]
+    pub nombre: String,
+
+    pub tipo: TipoCuenta,
+
+    #[serde(default)]
+    pub saldo_inicial: f64,
+}
+
+impl Cuenta {
+    pub fn new(data: CreateCuenta) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            nombre: data.nombre,
+            tipo: data.tipo,
+            saldo_inicial: data.saldo_inicial,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// Movimiento de dinero entre dos cuentas dentro de un retiro.
+/// No cuenta como ingreso ni como gasto.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct Transferencia {
+    pub id: Uuid,
+    pub retiro_id: Uuid,
+    pub cuenta_origen_id: Uuid,
+    pub cuenta_destino_id: Uuid,
+
+    #[validate(range(min = 0.01))]
+    pub monto: f64,
+
+    #[validate(length(min = 1, max = 300))]
+    pub descripcion: String,
+
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateTransferencia {
+    pub retiro_id: Uuid,
+    pub cuenta_origen_id: Uuid,
+    pub cuenta_destino_id: Uuid,
+
+    #[validate(range(min = 0.01))]
+    pub monto: f64,
+
+    #[validate(length(min = 1, max = 300))]
+    pub descripcion: String,
+}
+
+impl Transferencia {
+    pub fn new(data: CreateTransferencia) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            retiro_id: data.retiro_id,
+            cuenta_origen_id: data.cuenta_origen_id,
+            cuenta_destino_id: data.cuenta_destino_id,
+            monto: data.monto,
+            descripcion: data.descripcion,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Saldo de una cuenta: movimientos de un retiro o, sin retiro, el saldo total
+/// (incluido el saldo inicial). `cuenta_id` es `None` para las transacciones sin cuenta.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaldoCuenta {
+    pub cuenta_id: Option<Uuid>,
+    pub cuenta: String,
+    pub saldo_inicial: f64,
+    pub ingresos: f64,
+    pub gastos: f64,
+    pub transferencias_entrada: f64,
+    pub transferencias_salida: f64,
+    pub saldo: f64,
+}
+
+impl std::fmt::Display for TipoCuenta {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TipoCuenta::Caja => write!(f, "Caja"),
+            TipoCuenta::Banco => write!(f, "Banco"),
+            TipoCuenta::Tarjeta => write!(f, "Tarjeta"),
+            TipoCuenta::Otra => write!(f, "Otra"),
+        }
+    }
+}
+
+impl std::fmt::Display for MetodoPago {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetodoPago::Efectivo => write!(f, "Efectivo"),
+            MetodoPago::Transferencia => write!(f, "Transferencia"),
+            MetodoPago::Tarjeta => write!(f, "Tarjeta"),
+            MetodoPago::Bizum => write!(f, "Bizum"),
+        }
+    }
+}
+
+impl TipoCuenta {
+    pub fn parse(valor: &str) -> Option<Self> {
+        match valor {
+            "Caja" => Some(TipoCuenta::Caja),
+            "Banco" => Some(TipoCuenta::Banco),
+            "Tarjeta" => Some(TipoCuenta::Tarjeta),
+            "Otra" => Some(TipoCuenta::Otra),
+            _ => None,
+        }
+    }
+}
+
+impl MetodoPago {
+    pub fn parse(valor: &str) -> Option<Self> {
+        match valor {
+            "Efectivo" => Some(MetodoPago::Efectivo),
+            "Transferencia" => Some(MetodoPago::Transferencia),
+            "Tarjeta" => Some(MetodoPago::Tarjeta),
+            "Bizum" => Some(MetodoPago::Bizum),
+            _ => None,
+        }
+    }
+}
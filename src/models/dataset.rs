@@ -0,0 +1,58 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use uuid::Uuid;
+
+use super::{
+    Adjunto, Arqueo, Categoria, Cuenta, EtiquetaTransaccion, LineaExtracto, PlantillaTransaccion,
+    ReglaCategoria, Retiro, TipoElementoPapelera, Transaccion, Transferencia,
+};
+
+/// Versión actual del formato de exportación
+pub const DATASET_VERSION: u32 = 1;
+
+/// Volcado completo de los datos, portable entre instalaciones
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dataset {
+    pub version: u32,
+    pub exportado_en: DateTime<Utc>,
+    pub categorias: Vec<Categoria>,
+    pub retiros: Vec<Retiro>,
+    pub transacciones: Vec<Transaccion>,
+    /// Ausente en volcados anteriores a las plantillas
+    #[serde(default)]
+    pub plantillas: Vec<PlantillaTransaccion>,
+    /// Ausente en volcados anteriores a las etiquetas
+    #[serde(default)]
+    pub etiquetas: Vec<EtiquetaTransaccion>,
+    /// Ausentes en volcados anteriores a las cuentas
+    #[serde(default)]
+    pub cuentas: Vec<Cuenta>,
+    #[serde(default)]
+    pub transferencias: Vec<Transferencia>,
+    /// Ausente en volcados anteriores a los arqueos
+    #[serde(default)]
+    pub arqueos: Vec<Arqueo>,
+    /// Ausente en volcados anteriores a la importación de extractos
+    #[serde(default)]
+    pub lineas_extracto: Vec<LineaExtracto>,
+    /// Ausente en volcados anteriores a las reglas de categorización
+    #[serde(default)]
+    pub reglas: Vec<ReglaCategoria>,
+    /// Solo los datos de los comprobantes: los archivos se quedan en el almacén de adjuntos.
+    /// Ausente en volcados anteriores a los adjuntos
+    #[serde(default)]
+    pub adjuntos: Vec<Adjunto>,
+    /// Retiros, categorías y transacciones del volcado que están en la papelera.
+    /// Ausente en volcados anteriores a exportar la papelera
+    #[serde(default)]
+    pub papelera: Vec<EnPapelera>,
+}
+
+/// Fecha de eliminación de un elemento del volcado que está en la papelera
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnPapelera {
+    pub tipo: TipoElementoPapelera,
+    pub id: Uuid,
+    pub deleted_at: DateTime<Utc>,
+}
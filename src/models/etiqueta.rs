@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{AppError, Result};
+
+/// Longitud máxima del nombre de una etiqueta
+pub const MAX_LONGITUD_ETIQUETA: usize = 50;
+
+/// Etiqueta libre con el número de transacciones activas que la usan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Etiqueta {
+    pub id: Uuid,
+    pub nombre: String,
+    pub transacciones: i64,
+}
+
+/// Asignación de una etiqueta a una transacción, tal como se exporta
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EtiquetaTransaccion {
+    pub transaccion_id: Uuid,
+    pub etiqueta: String,
+}
+
+/// Totales de un retiro (o de todos) para una etiqueta.
+/// Una transacción con varias etiquetas cuenta en cada una de ellas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TotalEtiqueta {
+    pub etiqueta: String,
+    pub ingresos: f64,
+    pub gastos: f64,
+    pub transacciones: i64,
+}
+
+/// Normalizar el nombre de una etiqueta: sin espacios sobrantes ni comas,
+/// que separan etiquetas en la línea de comandos
+pub fn normalizar_etiqueta(nombre: &str) -> Result<String> {
+    let nombre = nombre.split_whitespace().collect::<Vec<_>>().join(" ");
+    if nombre.is_empty() || nombre.chars().count() > MAX_LONGITUD_ETIQUETA {
+        return Err(AppError::Validation(format!(
+            "Las etiquetas deben tener entre 1 y {} caracteres",
+            MAX_LONGITUD_ETIQUETA
+        )));
+    }
+    if nombre.contains(',') {
+        return Err(AppError::Validation(format!(
+            "La etiqueta '{}' no puede contener comas",
+            nombre
+        )));
+    }
+    Ok(nombre)
+}
+
+/// Normalizar una lista de etiquetas quitando las vacías y las repetidas
+/// (sin distinguir mayúsculas)
+pub fn normalizar_etiquetas(nombres: &[String]) -> Result<Vec<String>> {
+    let mut etiquetas: Vec<String> = Vec::new();
+    for nombre in nombres.iter().filter(|n| !n.trim().is_empty()) {
+        let nombre = normalizar_etiqueta(nombre)?;
+        if !etiquetas.iter().any(|e| e.eq_ignore_ascii_case(&nombre)) {
+            etiquetas.push(nombre);
+        }
+    }
+    Ok(etiquetas)
+}
@@ -0,0 +1,216 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use super::{CreateTransaccion, TipoTransaccion, Transaccion};
+
+/// Formatos de extracto bancario que se pueden importar
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT")]
+pub enum FormatoExtracto {
+    /// Cuaderno 43 de la AEB (texto de ancho fijo)
+    Norma43,
+    /// ISO 20022 camt.053 (XML)
+    Camt053,
+    /// Open Financial Exchange (SGML o XML)
+    Ofx,
+}
+
+/// Movimiento leído de un extracto
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MovimientoExtracto {
+    pub fecha: NaiveDate,
+    /// Positivo para abonos, negativo para cargos
+    pub monto: f64,
+    pub descripcion: String,
+    pub referencia: Option<String>,
+}
+
+impl MovimientoExtracto {
+    pub fn tipo(&self) -> TipoTransaccion {
+        if self.monto >= 0.0 {
+            TipoTransaccion::Ingreso
+        } else {
+            TipoTransaccion::Gasto
+        }
+    }
+}
+
+/// Situación de un movimiento del extracto frente a lo ya registrado
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum EstadoCandidato {
+    /// No hay ninguna transacción que se le parezca
+    Nueva,
+    /// Parece una transacción ya registrada a mano (mismo importe, fecha cercana)
+    Coincide,
+    /// La línea ya se importó de otro extracto
+    YaImportada,
+}
+
+/// Movimiento de un extracto listo para revisar antes de importarlo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandidatoExtracto {
+    pub retiro_id: Uuid,
+    pub cuenta_id: Option<Uuid>,
+    pub formato: FormatoExtracto,
+    pub fecha: NaiveDate,
+    /// Positivo para abonos, negativo para cargos
+    pub monto: f64,
+    pub descripcion: String,
+    pub referencia: Option<String>,
+    /// Identifica el movimiento para no importarlo dos veces
+    pub huella: String,
+    pub estado: EstadoCandidato,
+    /// Transacción con la que coincide o a la que ya está enlazado
+    pub transaccion_id: Option<Uuid>,
+    /// Parecido con esa transacción (0 a 1), si coincide
+    pub puntuacion: Option<f64>,
+    /// Transacción que se crearía; `None` si falta la categoría de su tipo
+    pub propuesta: Option<CreateTransaccion>,
+    /// Regla de categorización que ha elegido la categoría de la propuesta
+    #[serde(default)]
+    pub regla: Option<String>,
+    /// Etiquetas de esa regla, que se añaden a la transacción creada
+    #[serde(default)]
+    pub etiquetas: Vec<String>,
+}
+
+/// Qué hacer con un candidato al importar
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AccionExtracto {
+    /// Crear la transacción propuesta
+    Importar,
+    /// Enlazar la línea con la transacción con la que coincide
+    Vincular,
+    /// No crear ni enlazar nada; la línea se guarda como movimiento del banco sin transacción
+    Omitir,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionExtracto {
+    pub candidato: CandidatoExtracto,
+    pub accion: AccionExtracto,
+}
+
+/// Línea de extracto ya importada
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineaExtracto {
+    pub id: Uuid,
+    pub retiro_id: Uuid,
+    pub cuenta_id: Option<Uuid>,
+    pub formato: FormatoExtracto,
+    pub fecha: NaiveDate,
+    pub monto: f64,
+    pub descripcion: String,
+    pub referencia: Option<String>,
+    pub huella: String,
+    pub transaccion_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Resultado de importar un extracto
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResumenExtracto {
+    pub creadas: usize,
+    pub vinculadas: usize,
+    pub omitidas: usize,
+    pub ya_importadas: usize,
+}
+
+/// Conciliación bancaria de un retiro (o de una de sus cuentas)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InformeConciliacion {
+    pub retiro_id: Uuid,
+    pub cuenta_id: Option<Uuid>,
+    /// Movimientos del banco que no corresponden a ninguna transacción
+    pub lineas_sin_transaccion: Vec<LineaExtracto>,
+    /// Transacciones pendientes que no aparecen en ningún extracto
+    pub transacciones_sin_linea: Vec<Transaccion>,
+    pub pendientes: usize,
+    pub punteadas: usize,
+    pub conciliadas: usize,
+    /// Suma de las líneas de extracto (abonos menos cargos)
+    pub total_extracto: f64,
+    /// Suma de las transacciones (ingresos menos gastos)
+    pub total_libros: f64,
+}
+
+impl InformeConciliacion {
+    /// Diferencia entre el banco y los libros; cero si todo está enlazado
+    pub fn diferencia(&self) -> f64 {
+        ((self.total_extracto - self.total_libros) * 100.0).round() / 100.0
+    }
+}
+
+/// Huella de un movimiento: `ocurrencia` distingue movimientos idénticos del mismo extracto
+/// (p. ej. dos cafés del mismo importe el mismo día) y la cuenta, los mismos movimientos
+/// de cuentas distintas. Los extractos importados sin cuenta comparten sus huellas.
+pub fn huella_movimiento(
+    cuenta_id: Option<Uuid>,
+    movimiento: &MovimientoExtracto,
+    ocurrencia: usize,
+) -> String {
+    let texto = format!(
+        "{}|{}|{}|{}|{}|{}",
+        cuenta_id.map(|id| id.to_string()).unwrap_or_default(),
+        movimiento.fecha,
+        (movimiento.monto * 100.0).round() as i64,
+        movimiento.descripcion,
+        movimiento.referencia.as_deref().unwrap_or(""),
+        ocurrencia
+    );
+    Sha256::digest(texto.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Parecido entre dos descripciones (0 a 1): proporción de palabras en común,
+/// sin distinguir mayúsculas ni contar palabras de menos de tres letras
+pub fn similitud_descripcion(a: &str, b: &str) -> f64 {
+    let palabras = |texto: &str| -> Vec<String> {
+        texto
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|p| p.chars().count() >= 3)
+            .map(|p| p.to_lowercase())
+            .collect()
+    };
+    let (a, b) = (palabras(a), palabras(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let comunes = a.iter().filter(|p| b.contains(p)).count();
+    comunes as f64 / a.len().max(b.len()) as f64
+}
+
+impl std::fmt::Display for FormatoExtracto {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FormatoExtracto::Norma43 => write!(f, "Norma43"),
+            FormatoExtracto::Camt053 => write!(f, "Camt053"),
+            FormatoExtracto::Ofx => write!(f, "Ofx"),
+        }
+    }
+}
+
+impl FormatoExtracto {
+    pub fn parse(valor: &str) -> Option<Self> {
+        match valor {
+            "Norma43" => Some(FormatoExtracto::Norma43),
+            "Camt053" => Some(FormatoExtracto::Camt053),
+            "Ofx" => Some(FormatoExtracto::Ofx),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for EstadoCandidato {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EstadoCandidato::Nueva => write!(f, "Nueva"),
+            EstadoCandidato::Coincide => write!(f, "Coincide"),
+            EstadoCandidato::YaImportada => write!(f, "Ya importada"),
+        }
+    }
+}
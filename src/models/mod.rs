@@ -1,7 +1,25 @@
+pub mod adjunto;
+pub mod arqueo;
 pub mod categoria;
+pub mod cuenta;
+pub mod dataset;
+pub mod etiqueta;
+pub mod extracto;
+pub mod papelera;
+pub mod plantilla;
+pub mod regla;
 pub mod retiro;
 pub mod transaccion;
 
+pub use adjunto::*;
+pub use arqueo::*;
 pub use categoria::*;
+pub use cuenta::*;
+pub use dataset::*;
+pub use etiqueta::*;
+pub use extracto::*;
+pub use papelera::*;
+pub use plantilla::*;
+pub use regla::*;
 pub use retiro::*;
 pub use transaccion::*;
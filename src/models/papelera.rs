@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Tipo de elemento que puede estar en la papelera
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TipoElementoPapelera {
+    Retiro,
+    Categoria,
+    Transaccion,
+}
+
+/// Elemento eliminado pendiente de restaurar o purgar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElementoPapelera {
+    pub id: Uuid,
+    pub tipo: TipoElementoPapelera,
+
+    /// Nombre del retiro o categoría, o descripción de la transacción
+    pub descripcion: String,
+
+    pub deleted_at: DateTime<Utc>,
+
+    /// Transacciones eliminadas junto con el retiro (0 para otros tipos)
+    pub transacciones: i64,
+}
+
+/// Elementos borrados definitivamente al vaciar la papelera
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResumenVaciado {
+    pub retiros: u64,
+    pub categorias: u64,
+    pub transacciones: u64,
+}
+
+impl ResumenVaciado {
+    pub fn total(&self) -> u64 {
+        self.retiros + self.categorias + self.transacciones
+    }
+}
+
+impl std::fmt::Display for TipoElementoPapelera {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TipoElementoPapelera::Retiro => write!(f, "Retiro"),
+            TipoElementoPapelera::Categoria => write!(f, "Categoria"),
+            TipoElementoPapelera::Transaccion => write!(f, "Transaccion"),
+        }
+    }
+}
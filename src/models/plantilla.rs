@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{CreateTransaccion, TipoTransaccion};
+
+/// Plantilla de una transacción que se repite en cada retiro (fianza, seguro, materiales...)
+///
+/// Monto y descripción se validan con las reglas de `CreateTransaccion`,
+/// ya que son los datos con los que se creará la transacción.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate, FromRow)]
+pub struct PlantillaTransaccion {
+    pub id: Uuid,
+
+    #[validate(length(min = 1, max = 100))]
+    pub nombre: String,
+
+    pub categoria_id: Uuid,
+    pub tipo: TipoTransaccion,
+
+    #[validate(range(min = 0.01))]
+    pub monto: f64,
+
+    #[validate(length(min = 1, max = 300))]
+    pub descripcion: String,
+
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreatePlantillaTransaccion {
+    #[validate(length(min = 1, max = 100))]
+    pub nombre: String,
+
+    pub categoria_id: Uuid,
+    pub tipo: TipoTransaccion,
+
+    #[validate(range(min = 0.01))]
+    pub monto: f64,
+
+    #[validate(length(min = 1, max = 300))]
+    pub descripcion: String,
+}
+
+impl PlantillaTransaccion {
+    pub fn new(data: CreatePlantillaTransaccion) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            nombre: data.nombre,
+            categoria_id: data.categoria_id,
+            tipo: data.tipo,
+            monto: data.monto,
+            descripcion: data.descripcion,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Datos de la transacción que genera la plantilla en un retiro
+    pub fn to_create_transaccion(&self, retiro_id: Uuid) -> CreateTransaccion {
+        CreateTransaccion {
+            retiro_id,
+            categoria_id: self.categoria_id,
+            tipo: self.tipo.clone(),
+            monto: self.monto,
+            descripcion: self.descripcion.clone(),
+            cuenta_id: None,
+            metodo_pago: None,
+        }
+    }
+}
+
+impl CreatePlantillaTransaccion {
+    /// Datos de la transacción que generaría la plantilla (para validarla)
+    pub fn to_create_transaccion(&self, retiro_id: Uuid) -> CreateTransaccion {
+        CreateTransaccion {
+            retiro_id,
+            categoria_id: self.categoria_id,
+            tipo: self.tipo.clone(),
+            monto: self.monto,
+            descripcion: self.descripcion.clone(),
+            cuenta_id: None,
+            metodo_pago: None,
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use chrono::{DateTime, Utc};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::TipoTransaccion;
+use crate::{AppError, Result};
+
+/// Regla para categorizar automáticamente una transacción según su descripción y monto.
+///
+/// El patrón es una expresión regular que se busca en la descripción sin distinguir
+/// mayúsculas; el rango se compara con el monto en valor absoluto, extremos incluidos.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct ReglaCategoria {
+    pub id: Uuid,
+
+    #[validate(length(min = 1, max = 100))]
+    pub nombre: String,
+
+    #[validate(length(min = 1, max = 300))]
+    pub patron: String,
+
+    pub monto_min: Option<f64>,
+    pub monto_max: Option<f64>,
+    pub tipo: TipoTransaccion,
+    pub categoria_id: Uuid,
+    /// Etiquetas que se añaden a la transacción
+    #[serde(default)]
+    pub etiquetas: Vec<String>,
+    /// Si varias reglas coinciden se aplica la de mayor prioridad
+    #[serde(default)]
+    pub prioridad: i64,
+
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CreateReglaCategoria {
+    #[validate(length(min = 1, max = 100))]
+    pub nombre: String,
+
+    #[validate(length(min = 1, max = 300))]
+    pub patron: String,
+
+    pub monto_min: Option<f64>,
+    pub monto_max: Option<f64>,
+    pub tipo: TipoTransaccion,
+    pub categoria_id: Uuid,
+    #[serde(default)]
+    pub etiquetas: Vec<String>,
+    #[serde(default)]
+    pub prioridad: i64,
+}
+
+/// Transacción ya registrada que cumple una regla, con la categoría que tiene ahora
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoincidenciaRegla {
+    pub transaccion_id: Uuid,
+    pub retiro_id: Uuid,
+    pub fecha: DateTime<Utc>,
+    pub tipo: TipoTransaccion,
+    pub monto: f64,
+    pub descripcion: String,
+    pub categoria_id: Uuid,
+    /// La transacción ya está en la categoría de la regla
+    pub misma_categoria: bool,
+}
+
+/// Compilar el patrón de una regla (sin distinguir mayúsculas)
+pub fn compilar_patron(patron: &str) -> Result<Regex> {
+    RegexBuilder::new(patron)
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| AppError::Validation(format!("Patrón '{}' no válido: {}", patron, e)))
+}
+
+impl ReglaCategoria {
+    pub fn new(data: CreateReglaCategoria) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            nombre: data.nombre,
+            patron: data.patron,
+            monto_min: data.monto_min,
+            monto_max: data.monto_max,
+            tipo: data.tipo,
+            categoria_id: data.categoria_id,
+            etiquetas: data.etiquetas,
+            prioridad: data.prioridad,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+impl CreateReglaCategoria {
+    /// Comprobar lo que no cubre `Validate`: el patrón y el rango de montos
+    pub fn validar(&self) -> Result<()> {
+        self.validate()
+            .map_err(|e| AppError::Validation(e.to_string()))?;
+        compilar_patron(&self.patron)?;
+
+        for monto in [self.monto_min, self.monto_max].into_iter().flatten() {
+            if !monto.is_finite() || monto < 0.0 {
+                return Err(AppError::Validation(
+                    "Los montos de una regla no pueden ser negativos".to_string(),
+                ));
+            }
+        }
+        if let (Some(min), Some(max)) = (self.monto_min, self.monto_max) {
+            if min > max {
+                return Err(AppError::Validation(format!(
+                    "El monto mínimo ({:.2}) es mayor que el máximo ({:.2})",
+                    min, max
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reglas compiladas, ordenadas de mayor a menor prioridad (y por nombre si empatan)
+pub struct MotorReglas {
+    reglas: Vec<(ReglaCategoria, Regex)>,
+}
+
+impl MotorReglas {
+    pub fn new(mut reglas: Vec<ReglaCategoria>) -> Result<Self> {
+        reglas.sort_by(|a, b| {
+            b.prioridad
+                .cmp(&a.prioridad)
+                .then_with(|| a.nombre.to_lowercase().cmp(&b.nombre.to_lowercase()))
+        });
+        let reglas = reglas
+            .into_iter()
+            .map(|regla| compilar_patron(&regla.patron).map(|patron| (regla, patron)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { reglas })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reglas.is_empty()
+    }
+
+    /// Primera regla que cumple la transacción; sin `tipo` vale una regla de cualquier tipo
+    pub fn evaluar(
+        &self,
+        descripcion: &str,
+        monto: Option<f64>,
+        tipo: Option<&TipoTransaccion>,
+    ) -> Option<&ReglaCategoria> {
+        self.reglas
+            .iter()
+            .find(|(regla, patron)| {
+                tipo.is_none_or(|tipo| *tipo == regla.tipo)
+                    && cumple_rango(regla, monto)
+                    && patron.is_match(descripcion)
+            })
+            .map(|(regla, _)| regla)
+    }
+}
+
+/// Sin monto conocido solo valen las reglas sin rango
+fn cumple_rango(regla: &ReglaCategoria, monto: Option<f64>) -> bool {
+    if regla.monto_min.is_none() && regla.monto_max.is_none() {
+        return true;
+    }
+    let Some(monto) = monto.map(f64::abs) else {
+        return false;
+    };
+    regla.monto_min.is_none_or(|min| monto >= min)
+        && regla.monto_max.is_none_or(|max| monto <= max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regla(
+        nombre: &str,
+        patron: &str,
+        rango: (Option<f64>, Option<f64>),
+        tipo: TipoTransaccion,
+        prioridad: i64,
+    ) -> ReglaCategoria {
+        ReglaCategoria::new(CreateReglaCategoria {
+            nombre: nombre.to_string(),
+            patron: patron.to_string(),
+            monto_min: rango.0,
+            monto_max: rango.1,
+            tipo,
+            categoria_id: Uuid::new_v4(),
+            etiquetas: Vec::new(),
+            prioridad,
+        })
+    }
+
+    fn nombre(regla: Option<&ReglaCategoria>) -> Option<&str> {
+        regla.map(|r| r.nombre.as_str())
+    }
+
+    #[test]
+    fn aplica_la_regla_de_mayor_prioridad_y_luego_por_nombre() {
+        let motor = MotorReglas::new(vec![
+            regla(
+                "Comida",
+                "mercadona",
+                (None, None),
+                TipoTransaccion::Gasto,
+                0,
+            ),
+            regla(
+                "Bebidas",
+                "mercadona",
+                (None, None),
+                TipoTransaccion::Gasto,
+                0,
+            ),
+            regla(
+                "Compra grande",
+                "mercadona",
+                (None, None),
+                TipoTransaccion::Gasto,
+                5,
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            nombre(motor.evaluar("MERCADONA 123", None, None)),
+            Some("Compra grande")
+        );
+
+        let motor = MotorReglas::new(vec![
+            regla(
+                "comida",
+                "mercadona",
+                (None, None),
+                TipoTransaccion::Gasto,
+                0,
+            ),
+            regla(
+                "Bebidas",
+                "mercadona",
+                (None, None),
+                TipoTransaccion::Gasto,
+                0,
+            ),
+        ])
+        .unwrap();
+        assert_eq!(
+            nombre(motor.evaluar("Mercadona", None, None)),
+            Some("Bebidas")
+        );
+    }
+
+    #[test]
+    fn busca_el_patron_como_expresion_regular_sin_distinguir_mayusculas() {
+        let motor = MotorReglas::new(vec![
+            regla(
+                "Cuotas",
+                r"^(cuota|inscripci[oó]n)\b",
+                (None, None),
+                TipoTransaccion::Ingreso,
+                0,
+            ),
+            regla(
+                "Transporte",
+                "renfe|alsa",
+                (None, None),
+                TipoTransaccion::Gasto,
+                0,
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            nombre(motor.evaluar("CUOTA de Ana", None, None)),
+            Some("Cuotas")
+        );
+        assert_eq!(
+            nombre(motor.evaluar("Inscripción retiro", None, None)),
+            Some("Cuotas")
+        );
+        assert_eq!(nombre(motor.evaluar("Pago cuota", None, None)), None);
+        assert_eq!(
+            nombre(motor.evaluar("Billete ALSA", None, None)),
+            Some("Transporte")
+        );
+        assert!(MotorReglas::new(vec![regla(
+            "Rota",
+            "(",
+            (None, None),
+            TipoTransaccion::Gasto,
+            0
+        )])
+        .is_err());
+    }
+
+    #[test]
+    fn compara_el_monto_absoluto_con_extremos_incluidos() {
+        let motor = MotorReglas::new(vec![
+            regla(
+                "Grande",
+                "casa",
+                (Some(100.0), None),
+                TipoTransaccion::Gasto,
+                1,
+            ),
+            regla(
+                "Mediana",
+                "casa",
+                (Some(10.0), Some(99.99)),
+                TipoTransaccion::Gasto,
+                0,
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            nombre(motor.evaluar("Casa", Some(100.0), None)),
+            Some("Grande")
+        );
+        assert_eq!(
+            nombre(motor.evaluar("Casa", Some(-10.0), None)),
+            Some("Mediana")
+        );
+        assert_eq!(nombre(motor.evaluar("Casa", Some(5.0), None)), None);
+        // Sin monto conocido no vale ninguna regla con rango
+        assert_eq!(nombre(motor.evaluar("Casa", None, None)), None);
+    }
+
+    #[test]
+    fn filtra_por_tipo_si_se_indica() {
+        let motor = MotorReglas::new(vec![regla(
+            "Devoluciones",
+            "devoluci",
+            (None, None),
+            TipoTransaccion::Ingreso,
+            0,
+        )])
+        .unwrap();
+
+        assert_eq!(
+            nombre(motor.evaluar("Devolución", None, Some(&TipoTransaccion::Ingreso))),
+            Some("Devoluciones")
+        );
+        assert_eq!(
+            nombre(motor.evaluar("Devolución", None, Some(&TipoTransaccion::Gasto))),
+            None
+        );
+        assert_eq!(
+            nombre(motor.evaluar("Devolución", None, None)),
+            Some("Devoluciones")
+        );
+    }
+
+    #[test]
+    fn valida_el_patron_y_el_rango() {
+        let mut data = CreateReglaCategoria {
+            nombre: "Regla".to_string(),
+            patron: "casa".to_string(),
+            monto_min: Some(50.0),
+            monto_max: Some(10.0),
+            tipo: TipoTransaccion::Gasto,
+            categoria_id: Uuid::new_v4(),
+            etiquetas: Vec::new(),
+            prioridad: 0,
+        };
+        assert!(data.validar().is_err());
+
+        data.monto_min = Some(-1.0);
+        data.monto_max = None;
+        assert!(data.validar().is_err());
+
+        data.monto_min = None;
+        data.patron = "[".to_string();
+        assert!(data.validar().is_err());
+
+        data.patron = "casa".to_string();
+        assert!(data.validar().is_ok());
+    }
+}
@@ -4,6 +4,8 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
 
+use super::Transaccion;
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "TEXT")]
 pub enum EstadoRetiro {
@@ -36,7 +38,7 @@ pub struct Retiro {
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Validate)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct CreateRetiro {
     #[validate(length(min = 1, max = 200))]
     pub nombre: String,
@@ -54,6 +56,15 @@ pub struct CreateRetiro {
     pub numero_participantes: i32,
 }
 
+/// Nueva edición creada al clonar un retiro
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetiroClonado {
+    pub retiro: Retiro,
+
+    /// Transacciones creadas con las plantillas de transacción
+    pub desde_plantillas: Vec<Transaccion>,
+}
+
 impl Retiro {
     pub fn new(data: CreateRetiro) -> Self {
         let now = Utc::now();
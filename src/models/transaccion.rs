@@ -4,13 +4,28 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+use super::MetodoPago;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "TEXT")]
 pub enum TipoTransaccion {
     Ingreso,
     Gasto,
 }
 
+/// Estado de una transacción frente al extracto del banco
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT")]
+pub enum EstadoConciliacion {
+    /// Aún no se ha visto en el banco
+    #[default]
+    Pendiente,
+    /// Comprobada contra una línea del extracto (o a mano)
+    Punteada,
+    /// Cerrada en una conciliación; ya no se vuelve a revisar
+    Conciliada,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Validate, FromRow)]
 pub struct Transaccion {
     pub id: Uuid,
@@ -25,11 +40,25 @@ pub struct Transaccion {
     #[validate(length(min = 1, max = 300))]
     pub descripcion: String,
 
+    /// Cuenta donde entra o sale el dinero
+    #[serde(default)]
+    pub cuenta_id: Option<Uuid>,
+
+    #[serde(default)]
+    pub metodo_pago: Option<MetodoPago>,
+
+    #[serde(default)]
+    pub conciliacion: EstadoConciliacion,
+
+    /// Línea de extracto con la que está enlazada (se guarda en la línea)
+    #[serde(default)]
+    pub linea_extracto_id: Option<Uuid>,
+
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Validate)]
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
 pub struct CreateTransaccion {
     pub retiro_id: Uuid,
     pub categoria_id: Uuid,
@@ -41,6 +70,12 @@ pub struct CreateTransaccion {
 
     #[validate(length(min = 1, max = 300))]
     pub descripcion: String,
+
+    #[serde(default)]
+    pub cuenta_id: Option<Uuid>,
+
+    #[serde(default)]
+    pub metodo_pago: Option<MetodoPago>,
 }
 
 impl Transaccion {
@@ -53,6 +88,10 @@ impl Transaccion {
             tipo: data.tipo,
             monto: data.monto,
             descripcion: data.descripcion,
+            cuenta_id: data.cuenta_id,
+            metodo_pago: data.metodo_pago,
+            conciliacion: EstadoConciliacion::Pendiente,
+            linea_extracto_id: None,
             created_at: now,
             updated_at: now,
         }
@@ -68,3 +107,24 @@ impl std::fmt::Display for TipoTransaccion {
         }
     }
 }
+
+impl std::fmt::Display for EstadoConciliacion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EstadoConciliacion::Pendiente => write!(f, "Pendiente"),
+            EstadoConciliacion::Punteada => write!(f, "Punteada"),
+            EstadoConciliacion::Conciliada => write!(f, "Conciliada"),
+        }
+    }
+}
+
+impl EstadoConciliacion {
+    pub fn parse(valor: &str) -> Option<Self> {
+        match valor {
+            "Pendiente" => Some(EstadoConciliacion::Pendiente),
+            "Punteada" => Some(EstadoConciliacion::Punteada),
+            "Conciliada" => Some(EstadoConciliacion::Conciliada),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use crate::database::AlmacenAdjuntos;
+use crate::models::{detectar_tipo_mime, Adjunto, GastoSinComprobante, MAX_TAMANO_ADJUNTO};
+use crate::repositories::parse_flexible_datetime;
+use crate::{AppError, Result};
+use chrono::Utc;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+pub struct AdjuntoRepository {
+    pool: SqlitePool,
+}
+
+impl AdjuntoRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Adjuntar un comprobante a una transacción.
+    /// `nombre` es el nombre original del archivo; se descarta cualquier ruta.
+    pub async fn adjuntar(
+        &self,
+        almacen: &AlmacenAdjuntos,
+        transaccion_id: Uuid,
+        nombre: &str,
+        contenido: &[u8],
+    ) -> Result<Adjunto> {
+        let nombre = std::path::Path::new(nombre)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if nombre.is_empty() || nombre.chars().count() > 255 {
+            return Err(AppError::Validation(
+                "El nombre del archivo debe tener entre 1 y 255 caracteres".to_string(),
+            ));
+        }
+        if contenido.is_empty() {
+            return Err(AppError::Validation(format!("El archivo '{}' está vacío", nombre)));
+        }
+        if contenido.len() > MAX_TAMANO_ADJUNTO {
+            return Err(AppError::Validation(format!(
+                "El archivo '{}' supera el máximo de {} MB",
+                nombre,
+                MAX_TAMANO_ADJUNTO / (1024 * 1024)
+            )));
+        }
+        let tipo_mime = detectar_tipo_mime(contenido).ok_or_else(|| {
+            AppError::Validation(format!(
+                "'{}' no es un PDF ni una imagen JPEG, PNG, WebP o HEIC",
+                nombre
+            ))
+        })?;
+
+        self.validar_transaccion(transaccion_id).await?;
+
+        let transaccion_id_str = transaccion_id.to_string();
+        let hash = AlmacenAdjuntos::hash(contenido);
+        let repetido = sqlx::query!(
+            "SELECT nombre FROM adjuntos WHERE transaccion_id = ?1 AND hash = ?2",
+            transaccion_id_str,
+            hash
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        if let Some(repetido) = repetido {
+            return Err(AppError::Duplicate(format!(
+                "El archivo ya está adjunto a la transacción como '{}'",
+                repetido.nombre
+            )));
+        }
+
+        almacen.guardar(contenido)?;
+
+        let adjunto = Adjunto {
+            id: Uuid::new_v4(),
+            transaccion_id,
+            nombre,
+            tipo_mime: tipo_mime.to_string(),
+            hash,
+            tamano: contenido.len() as i64,
+            created_at: Utc::now(),
+        };
+        self.insertar(&adjunto).await?;
+
+        Ok(adjunto)
+    }
+
+    /// Volver a guardar un adjunto eliminado con su ID original (al deshacer la eliminación).
+    /// El archivo se escribe de nuevo porque al quedar huérfano se borró del disco.
+    pub async fn restaurar(
+        &self,
+        almacen: &AlmacenAdjuntos,
+        adjunto: &Adjunto,
+        contenido: &[u8],
+    ) -> Result<()> {
+        self.validar_transaccion(adjunto.transaccion_id).await?;
+        if AlmacenAdjuntos::hash(contenido) != adjunto.hash {
+            return Err(AppError::Internal(format!(
+                "El contenido no corresponde al adjunto '{}'",
+                adjunto.nombre
+            )));
+        }
+
+        almacen.guardar(contenido)?;
+        self.insertar(adjunto).await
+    }
+
+    async fn insertar(&self, adjunto: &Adjunto) -> Result<()> {
+        // Crear variables para evitar problemas de lifetime
+        let id_str = adjunto.id.to_string();
+        let transaccion_id_str = adjunto.transaccion_id.to_string();
+        let created_at_str = adjunto.created_at.to_rfc3339();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO adjuntos (id, transaccion_id, nombre, tipo_mime, hash, tamano, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+            id_str,
+            transaccion_id_str,
+            adjunto.nombre,
+            adjunto.tipo_mime,
+            adjunto.hash,
+            adjunto.tamano,
+            created_at_str
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Obtener un adjunto por ID
+    pub async fn get_by_id(&self, id: Uuid) -> Result<Option<Adjunto>> {
+        let id_str = id.to_string();
+        let row = sqlx::query!(
+            "SELECT id, transaccion_id, nombre, tipo_mime, hash, tamano, created_at FROM adjuntos WHERE id = ?1",
+            id_str
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(Some(Adjunto {
+                id: Uuid::parse_str(&row.id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                transaccion_id: Uuid::parse_str(&row.transaccion_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                nombre: row.nombre,
+                tipo_mime: row.tipo_mime,
+                hash: row.hash,
+                tamano: row.tamano,
+                created_at: parse_flexible_datetime(&row.created_at)?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    /// Obtener los adjuntos de una transacción, del más antiguo al más reciente
+    pub async fn get_by_transaccion(&self, transaccion_id: Uuid) -> Result<Vec<Adjunto>> {
+        let transaccion_id_str = transaccion_id.to_string();
+        let rows = sqlx::query!(
+            "SELECT id, transaccion_id, nombre, tipo_mime, hash, tamano, created_at FROM adjuntos WHERE transaccion_id = ?1 ORDER BY created_at, nombre",
+            transaccion_id_str
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut adjuntos = Vec::new();
+        for row in rows {
+            adjuntos.push(Adjunto {
+                id: Uuid::parse_str(&row.id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                transaccion_id: Uuid::parse_str(&row.transaccion_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                nombre: row.nombre,
+                tipo_mime: row.tipo_mime,
+                hash: row.hash,
+                tamano: row.tamano,
+                created_at: parse_flexible_datetime(&row.created_at)?,
+            });
+        }
+
+        Ok(adjuntos)
+    }
+
+    /// Obtener todos los adjuntos, también los de transacciones en la papelera (para exportar)
+    pub async fn get_all(&self) -> Result<Vec<Adjunto>> {
+        let rows = sqlx::query!(
+            "SELECT id, transaccion_id, nombre, tipo_mime, hash, tamano, created_at FROM adjuntos ORDER BY created_at, nombre"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut adjuntos = Vec::new();
+        for row in rows {
+            adjuntos.push(Adjunto {
+                id: Uuid::parse_str(&row.id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                transaccion_id: Uuid::parse_str(&row.transaccion_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                nombre: row.nombre,
+                tipo_mime: row.tipo_mime,
+                hash: row.hash,
+                tamano: row.tamano,
+                created_at: parse_flexible_datetime(&row.created_at)?,
+            });
+        }
+
+        Ok(adjuntos)
+    }
+
+    /// Eliminar un adjunto. Su archivo se conserva porque las copias de seguridad pueden
+    /// seguir usándolo; lo borra `AlmacenAdjuntos::purgar_huerfanos`.
+    pub async fn delete(&self, id: Uuid) -> Result<bool> {
+        let id_str = id.to_string();
+        let result = sqlx::query!("DELETE FROM adjuntos WHERE id = ?1", id_str)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Gastos activos sin ningún comprobante, agrupados por retiro (del más reciente al más antiguo)
+    pub async fn gastos_sin_comprobante(
+        &self,
+        retiro_id: Option<Uuid>,
+    ) -> Result<Vec<GastoSinComprobante>> {
+        let retiro_id_str = retiro_id.map(|id| id.to_string());
+        let rows = sqlx::query!(
+            r#"
+            SELECT t.retiro_id, r.nombre as retiro, t.id as transaccion_id,
+                c.nombre as categoria, t.monto, t.descripcion, t.created_at
+            FROM transacciones t
+            JOIN retiros r ON r.id = t.retiro_id
+            JOIN categorias c ON c.id = t.categoria_id
+            WHERE t.tipo = 'Gasto'
+              AND t.deleted_at IS NULL
+              AND r.deleted_at IS NULL
+              AND (?1 IS NULL OR t.retiro_id = ?1)
+              AND NOT EXISTS (SELECT 1 FROM adjuntos a WHERE a.transaccion_id = t.id)
+            ORDER BY r.fecha_inicio DESC, r.id, t.created_at
+            "#,
+            retiro_id_str
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut gastos = Vec::new();
+        for row in rows {
+            gastos.push(GastoSinComprobante {
+                retiro_id: Uuid::parse_str(&row.retiro_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                retiro: row.retiro,
+                transaccion_id: Uuid::parse_str(&row.transaccion_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                categoria: row.categoria,
+                monto: row.monto,
+                descripcion: row.descripcion,
+                fecha: parse_flexible_datetime(&row.created_at)?,
+            });
+        }
+
+        Ok(gastos)
+    }
+
+    /// Los comprobantes solo se adjuntan a transacciones activas
+    async fn validar_transaccion(&self, transaccion_id: Uuid) -> Result<()> {
+        let transaccion_id_str = transaccion_id.to_string();
+        let existe = sqlx::query!(
+            "SELECT id FROM transacciones WHERE id = ?1 AND deleted_at IS NULL",
+            transaccion_id_str
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        if existe.is_none() {
+            return Err(AppError::NotFound("Transacción".to_string()));
+        }
+
+        Ok(())
+    }
+}
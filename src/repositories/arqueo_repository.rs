@@ -0,0 +1,347 @@
+use crate::models::{
+    redondear_centimos, sugerir_color, total_contado, Arqueo, Categoria, CreateArqueo,
+    CreateCategoria, CreateTransaccion, Denominacion, MetodoPago, TipoCategoria, TipoCuenta,
+    TipoTransaccion, Transaccion, CATEGORIA_AJUSTE_ARQUEO,
+};
+use crate::repositories::{
+    error_duplicada, insertar_transaccion, parse_flexible_datetime, CuentaRepository,
+};
+use crate::{AppError, Result};
+use chrono::Utc;
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Leer las denominaciones guardadas en JSON
+pub(crate) fn parse_denominaciones(json: Option<&str>) -> Result<Vec<Denominacion>> {
+    match json {
+        Some(json) => serde_json::from_str(json)
+            .map_err(|e| AppError::Internal(format!("Invalid denominaciones: {}", e))),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Guardar las denominaciones en JSON (NULL si solo se indicó el total)
+pub(crate) fn denominaciones_json(denominaciones: &[Denominacion]) -> Result<Option<String>> {
+    if denominaciones.is_empty() {
+        return Ok(None);
+    }
+    serde_json::to_string(denominaciones)
+        .map(Some)
+        .map_err(|e| AppError::Internal(format!("Error serializando denominaciones: {}", e)))
+}
+
+pub struct ArqueoRepository {
+    pool: SqlitePool,
+}
+
+impl ArqueoRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Registrar un arqueo comparando lo contado con el saldo total de la cuenta.
+    /// Con `ajustar`, un descuadre se corrige con una transacción en la categoría
+    /// "Ajustes de caja" (ingreso si sobra dinero, gasto si falta), que se crea si no existe.
+    /// El ajuste y el arqueo se guardan en una única transacción SQL.
+    pub async fn create(&self, data: CreateArqueo) -> Result<Arqueo> {
+        data.validate()
+            .map_err(|e| AppError::Validation(e.to_string()))?;
+        let contado = total_contado(data.total, &data.denominaciones)?;
+
+        let retiro_id_str = data.retiro_id.to_string();
+        let retiro = sqlx::query!(
+            "SELECT id FROM retiros WHERE id = ?1 AND deleted_at IS NULL",
+            retiro_id_str
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        if retiro.is_none() {
+            return Err(AppError::NotFound("Retiro".to_string()));
+        }
+
+        let cuenta_repo = CuentaRepository::new(self.pool.clone());
+        let cuenta = cuenta_repo
+            .get_by_id(data.cuenta_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Cuenta".to_string()))?;
+        let saldo_calculado = cuenta_repo
+            .get_saldos(None)
+            .await?
+            .into_iter()
+            .find(|s| s.cuenta_id == Some(cuenta.id))
+            .map(|s| redondear_centimos(s.saldo))
+            .unwrap_or(cuenta.saldo_inicial);
+        let diferencia = redondear_centimos(contado - saldo_calculado);
+
+        let mut arqueo = Arqueo {
+            id: Uuid::new_v4(),
+            retiro_id: data.retiro_id,
+            cuenta_id: cuenta.id,
+            saldo_calculado,
+            contado,
+            diferencia,
+            denominaciones: data.denominaciones,
+            notas: data.notas.filter(|n| !n.trim().is_empty()),
+            transaccion_ajuste_id: None,
+            created_at: Utc::now(),
+        };
+
+        let mut tx = self.pool.begin().await?;
+
+        if data.ajustar && !arqueo.cuadra() {
+            let (tipo, tipo_categoria, motivo) = if diferencia > 0.0 {
+                (TipoTransaccion::Ingreso, TipoCategoria::Ingreso, "sobrante")
+            } else {
+                (TipoTransaccion::Gasto, TipoCategoria::Gasto, "faltante")
+            };
+            let categoria_id = categoria_ajuste(&mut tx, tipo_categoria).await?;
+            let datos_ajuste = CreateTransaccion {
+                retiro_id: arqueo.retiro_id,
+                categoria_id,
+                tipo,
+                monto: diferencia.abs(),
+                descripcion: format!("Ajuste de arqueo de {}: {}", cuenta.nombre, motivo),
+                cuenta_id: Some(cuenta.id),
+                metodo_pago: (cuenta.tipo == TipoCuenta::Caja).then_some(MetodoPago::Efectivo),
+            };
+            datos_ajuste
+                .validate()
+                .map_err(|e| AppError::Validation(e.to_string()))?;
+            let ajuste = Transaccion::new(datos_ajuste);
+            insertar_transaccion(&mut tx, &ajuste).await?;
+            arqueo.transaccion_ajuste_id = Some(ajuste.id);
+        }
+
+        insertar_arqueo(&mut tx, &arqueo).await?;
+        tx.commit().await?;
+
+        Ok(arqueo)
+    }
+
+    /// Historial de arqueos, del más reciente al más antiguo, opcionalmente de un retiro
+    /// y de una cuenta (no incluye los de retiros en la papelera)
+    pub async fn get_all(
+        &self,
+        retiro_id: Option<Uuid>,
+        cuenta_id: Option<Uuid>,
+    ) -> Result<Vec<Arqueo>> {
+        let retiro_id_str = retiro_id.map(|id| id.to_string());
+        let cuenta_id_str = cuenta_id.map(|id| id.to_string());
+        let rows = sqlx::query!(
+            r#"
+            SELECT a.id, a.retiro_id, a.cuenta_id, a.saldo_calculado, a.contado,
+                a.denominaciones, a.notas, a.transaccion_ajuste_id, a.created_at
+            FROM arqueos a
+            JOIN retiros r ON r.id = a.retiro_id
+            WHERE r.deleted_at IS NULL
+              AND (?1 IS NULL OR a.retiro_id = ?1)
+              AND (?2 IS NULL OR a.cuenta_id = ?2)
+            ORDER BY a.created_at DESC
+            "#,
+            retiro_id_str,
+            cuenta_id_str
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut arqueos = Vec::new();
+        for row in rows {
+            arqueos.push(Arqueo {
+                id: Uuid::parse_str(&row.id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                retiro_id: Uuid::parse_str(&row.retiro_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                cuenta_id: Uuid::parse_str(&row.cuenta_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                saldo_calculado: row.saldo_calculado,
+                contado: row.contado,
+                diferencia: redondear_centimos(row.contado - row.saldo_calculado),
+                denominaciones: parse_denominaciones(row.denominaciones.as_deref())?,
+                notas: row.notas,
+                transaccion_ajuste_id: row
+                    .transaccion_ajuste_id
+                    .map(|id| Uuid::parse_str(&id))
+                    .transpose()
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                created_at: parse_flexible_datetime(&row.created_at)?,
+            });
+        }
+
+        Ok(arqueos)
+    }
+
+    /// Eliminar un arqueo del historial; su transacción de ajuste, si la hay, va a la papelera
+    pub async fn delete(&self, id: Uuid) -> Result<bool> {
+        let id_str = id.to_string();
+        let mut tx = self.pool.begin().await?;
+
+        let arqueo = sqlx::query!(
+            "SELECT transaccion_ajuste_id FROM arqueos WHERE id = ?1",
+            id_str
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(arqueo) = arqueo else {
+            return Ok(false);
+        };
+
+        sqlx::query!("DELETE FROM arqueos WHERE id = ?1", id_str)
+            .execute(&mut *tx)
+            .await?;
+        if let Some(ajuste_id) = arqueo.transaccion_ajuste_id {
+            let deleted_at_str = Utc::now().to_rfc3339();
+            sqlx::query!(
+                "UPDATE transacciones SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL",
+                ajuste_id,
+                deleted_at_str
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(true)
+    }
+
+    /// Volver a guardar un arqueo eliminado tal como estaba (al deshacer la eliminación),
+    /// sacando su transacción de ajuste de la papelera. Si el ajuste ya se borró
+    /// definitivamente, el arqueo vuelve sin él.
+    pub async fn restaurar(&self, arqueo: &Arqueo) -> Result<Arqueo> {
+        let mut arqueo = arqueo.clone();
+        let mut tx = self.pool.begin().await?;
+
+        if let Some(ajuste_id) = arqueo.transaccion_ajuste_id {
+            let ajuste_id_str = ajuste_id.to_string();
+            let ajuste = sqlx::query!(
+                r#"
+                SELECT c.nombre as categoria, c.deleted_at IS NOT NULL as "categoria_eliminada!: bool"
+                FROM transacciones t
+                JOIN categorias c ON c.id = t.categoria_id
+                WHERE t.id = ?1
+                "#,
+                ajuste_id_str
+            )
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            match ajuste {
+                Some(ajuste) if ajuste.categoria_eliminada => {
+                    return Err(AppError::Validation(format!(
+                        "La categoría '{}' del ajuste está en la papelera; restáurala primero",
+                        ajuste.categoria
+                    )));
+                }
+                Some(_) => {
+                    sqlx::query!(
+                        "UPDATE transacciones SET deleted_at = NULL WHERE id = ?1",
+                        ajuste_id_str
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                None => arqueo.transaccion_ajuste_id = None,
+            }
+        }
+
+        insertar_arqueo(&mut tx, &arqueo).await?;
+        tx.commit().await?;
+
+        Ok(arqueo)
+    }
+}
+
+async fn insertar_arqueo(tx: &mut Transaction<'_, Sqlite>, arqueo: &Arqueo) -> Result<()> {
+    // Crear variables para evitar problemas de lifetime
+    let id_str = arqueo.id.to_string();
+    let retiro_id_str = arqueo.retiro_id.to_string();
+    let cuenta_id_str = arqueo.cuenta_id.to_string();
+    let denominaciones_str = denominaciones_json(&arqueo.denominaciones)?;
+    let transaccion_ajuste_id_str = arqueo.transaccion_ajuste_id.map(|id| id.to_string());
+    let created_at_str = arqueo.created_at.to_rfc3339();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO arqueos (id, retiro_id, cuenta_id, saldo_calculado, contado, denominaciones, notas, transaccion_ajuste_id, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+        "#,
+        id_str,
+        retiro_id_str,
+        cuenta_id_str,
+        arqueo.saldo_calculado,
+        arqueo.contado,
+        denominaciones_str,
+        arqueo.notas,
+        transaccion_ajuste_id_str,
+        created_at_str
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// ID de la categoría de ajustes del tipo indicado; se crea, o se restaura de la
+/// papelera, si hace falta. Si hay una activa se usa esa aunque otra del mismo nombre
+/// siga en la papelera.
+async fn categoria_ajuste(tx: &mut Transaction<'_, Sqlite>, tipo: TipoCategoria) -> Result<Uuid> {
+    let tipo_str = tipo.to_string();
+    let existente = sqlx::query!(
+        r#"
+        SELECT id, deleted_at IS NOT NULL as "eliminada!: bool" FROM categorias
+        WHERE nombre = ?1 COLLATE NOCASE AND tipo = ?2
+        ORDER BY deleted_at IS NOT NULL
+        LIMIT 1
+        "#,
+        CATEGORIA_AJUSTE_ARQUEO,
+        tipo_str
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+
+    match existente {
+        Some(row) => {
+            if row.eliminada {
+                sqlx::query!("UPDATE categorias SET deleted_at = NULL WHERE id = ?1", row.id)
+                    .execute(&mut **tx)
+                    .await?;
+            }
+            Uuid::parse_str(&row.id).map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))
+        }
+        None => {
+            let colores = sqlx::query!("SELECT color FROM categorias WHERE deleted_at IS NULL")
+                .fetch_all(&mut **tx)
+                .await?;
+            let categoria = Categoria::new(CreateCategoria {
+                nombre: CATEGORIA_AJUSTE_ARQUEO.to_string(),
+                tipo,
+                color: sugerir_color(colores.iter().map(|c| c.color.as_str())),
+                padre_id: None,
+            });
+
+            // Crear variables para evitar problemas de lifetime
+            let id_str = categoria.id.to_string();
+            let created_at_str = categoria.created_at.to_rfc3339();
+            let updated_at_str = categoria.updated_at.to_rfc3339();
+
+            sqlx::query!(
+                r#"
+                INSERT INTO categorias (id, nombre, tipo, color, created_at, updated_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                "#,
+                id_str,
+                categoria.nombre,
+                tipo_str,
+                categoria.color,
+                created_at_str,
+                updated_at_str
+            )
+            .execute(&mut **tx)
+            .await
+            .map_err(|e| error_duplicada(e, &categoria.nombre, &categoria.tipo))?;
+
+            Ok(categoria.id)
+        }
+    }
+}
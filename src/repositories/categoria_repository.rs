@@ -1,5 +1,7 @@
-use crate::models::{Categoria, CreateCategoria, TipoCategoria};
+use crate::models::{antecesores, sugerir_color, Categoria, CreateCategoria, TipoCategoria};
+use crate::repositories::parse_flexible_datetime;
 use crate::{AppError, Result};
+use chrono::Utc;
 use sqlx::SqlitePool;
 use uuid::Uuid;
 use validator::Validate;
@@ -13,30 +15,43 @@ impl CategoriaRepository {
         Self { pool }
     }
 
-    /// Crear una nueva categoría
-    pub async fn create(&self, data: CreateCategoria) -> Result<Categoria> {
+    /// Crear una nueva categoría (sin color se le asigna uno de la paleta)
+    pub async fn create(&self, mut data: CreateCategoria) -> Result<Categoria> {
+        if data.color.trim().is_empty() {
+            data.color = self.sugerir_color().await?;
+        }
+
         // Validar datos de entrada
         data.validate()
             .map_err(|e| AppError::Validation(e.to_string()))?;
+        self.validar_nombre_unico(None, &data.nombre, &data.tipo).await?;
+        self.validar_padre(None, &data.tipo, data.padre_id).await?;
 
         let categoria = Categoria::new(data);
 
         // Crear variables para evitar problemas de lifetime
         let id_str = categoria.id.to_string();
         let tipo_str = categoria.tipo.to_string();
+        let padre_id_str = categoria.padre_id.map(|id| id.to_string());
+        let created_at_str = categoria.created_at.to_rfc3339();
+        let updated_at_str = categoria.updated_at.to_rfc3339();
 
         sqlx::query!(
             r#"
-            INSERT INTO categorias (id, nombre, tipo, color)
-            VALUES (?1, ?2, ?3, ?4)
+            INSERT INTO categorias (id, nombre, tipo, color, padre_id, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
             "#,
             id_str,
             categoria.nombre,
             tipo_str,
-            categoria.color
+            categoria.color,
+            padre_id_str,
+            created_at_str,
+            updated_at_str
         )
         .execute(&self.pool)
-        .await?;
+        .await
+        .map_err(|e| error_duplicada(e, &categoria.nombre, &categoria.tipo))?;
 
         Ok(categoria)
     }
@@ -45,7 +60,7 @@ impl CategoriaRepository {
     pub async fn get_by_id(&self, id: Uuid) -> Result<Option<Categoria>> {
         let id_str = id.to_string();
         let row = sqlx::query!(
-            "SELECT id, nombre, tipo, color, created_at, updated_at FROM categorias WHERE id = ?1",
+            "SELECT id, nombre, tipo, color, padre_id, archivada, created_at, updated_at FROM categorias WHERE id = ?1 AND deleted_at IS NULL",
             id_str
         )
         .fetch_optional(&self.pool)
@@ -63,6 +78,14 @@ impl CategoriaRepository {
                         _ => return Err(AppError::Internal("Invalid tipo categoria".to_string())),
                     },
                     color: row.color,
+                    padre_id: row
+                        .padre_id
+                        .map(|id| Uuid::parse_str(&id))
+                        .transpose()
+                        .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                    archivada: row.archivada,
+                    created_at: parse_flexible_datetime(&row.created_at)?,
+                    updated_at: parse_flexible_datetime(&row.updated_at)?,
                 };
                 Ok(Some(categoria))
             }
@@ -70,10 +93,80 @@ impl CategoriaRepository {
         }
     }
 
-    /// Obtener todas las categorías
+    /// Obtener todas las categorías no archivadas
     pub async fn get_all(&self) -> Result<Vec<Categoria>> {
         let rows = sqlx::query!(
-            "SELECT id, nombre, tipo, color, created_at, updated_at FROM categorias ORDER BY nombre"
+            "SELECT id, nombre, tipo, color, padre_id, archivada, created_at, updated_at FROM categorias WHERE archivada = 0 AND deleted_at IS NULL ORDER BY nombre"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut categorias = Vec::new();
+        for row in rows {
+            let categoria = Categoria {
+                id: Uuid::parse_str(&row.id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                nombre: row.nombre,
+                tipo: match row.tipo.as_str() {
+                    "Ingreso" => TipoCategoria::Ingreso,
+                    "Gasto" => TipoCategoria::Gasto,
+                    _ => return Err(AppError::Internal("Invalid tipo categoria".to_string())),
+                },
+                color: row.color,
+                padre_id: row
+                    .padre_id
+                    .map(|id| Uuid::parse_str(&id))
+                    .transpose()
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                archivada: row.archivada,
+                created_at: parse_flexible_datetime(&row.created_at)?,
+                updated_at: parse_flexible_datetime(&row.updated_at)?,
+            };
+            categorias.push(categoria);
+        }
+
+        Ok(categorias)
+    }
+
+    /// Obtener todas las categorías, también las archivadas (para informes históricos)
+    pub async fn get_all_incluyendo_archivadas(&self) -> Result<Vec<Categoria>> {
+        let rows = sqlx::query!(
+            "SELECT id, nombre, tipo, color, padre_id, archivada, created_at, updated_at FROM categorias WHERE deleted_at IS NULL ORDER BY nombre"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut categorias = Vec::new();
+        for row in rows {
+            let categoria = Categoria {
+                id: Uuid::parse_str(&row.id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                nombre: row.nombre,
+                tipo: match row.tipo.as_str() {
+                    "Ingreso" => TipoCategoria::Ingreso,
+                    "Gasto" => TipoCategoria::Gasto,
+                    _ => return Err(AppError::Internal("Invalid tipo categoria".to_string())),
+                },
+                color: row.color,
+                padre_id: row
+                    .padre_id
+                    .map(|id| Uuid::parse_str(&id))
+                    .transpose()
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                archivada: row.archivada,
+                created_at: parse_flexible_datetime(&row.created_at)?,
+                updated_at: parse_flexible_datetime(&row.updated_at)?,
+            };
+            categorias.push(categoria);
+        }
+
+        Ok(categorias)
+    }
+
+    /// Obtener todas las categorías, también las archivadas y las de la papelera (para exportar)
+    pub async fn get_all_incluyendo_eliminadas(&self) -> Result<Vec<Categoria>> {
+        let rows = sqlx::query!(
+            "SELECT id, nombre, tipo, color, padre_id, archivada, created_at, updated_at FROM categorias ORDER BY nombre"
         )
         .fetch_all(&self.pool)
         .await?;
@@ -90,6 +183,14 @@ impl CategoriaRepository {
                     _ => return Err(AppError::Internal("Invalid tipo categoria".to_string())),
                 },
                 color: row.color,
+                padre_id: row
+                    .padre_id
+                    .map(|id| Uuid::parse_str(&id))
+                    .transpose()
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                archivada: row.archivada,
+                created_at: parse_flexible_datetime(&row.created_at)?,
+                updated_at: parse_flexible_datetime(&row.updated_at)?,
             };
             categorias.push(categoria);
         }
@@ -97,11 +198,11 @@ impl CategoriaRepository {
         Ok(categorias)
     }
 
-    /// Obtener categorías por tipo
+    /// Obtener categorías no archivadas por tipo
     pub async fn get_by_tipo(&self, tipo: TipoCategoria) -> Result<Vec<Categoria>> {
         let tipo_str = tipo.to_string();
         let rows = sqlx::query!(
-            "SELECT id, nombre, tipo, color, created_at, updated_at FROM categorias WHERE tipo = ?1 ORDER BY nombre",
+            "SELECT id, nombre, tipo, color, padre_id, archivada, created_at, updated_at FROM categorias WHERE tipo = ?1 AND archivada = 0 AND deleted_at IS NULL ORDER BY nombre",
             tipo_str
         )
         .fetch_all(&self.pool)
@@ -119,6 +220,14 @@ impl CategoriaRepository {
                     _ => return Err(AppError::Internal("Invalid tipo categoria".to_string())),
                 },
                 color: row.color,
+                padre_id: row
+                    .padre_id
+                    .map(|id| Uuid::parse_str(&id))
+                    .transpose()
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                archivada: row.archivada,
+                created_at: parse_flexible_datetime(&row.created_at)?,
+                updated_at: parse_flexible_datetime(&row.updated_at)?,
             };
             categorias.push(categoria);
         }
@@ -132,22 +241,43 @@ impl CategoriaRepository {
         data.validate()
             .map_err(|e| AppError::Validation(e.to_string()))?;
 
+        self.validar_nombre_unico(Some(id), &data.nombre, &data.tipo).await?;
+        self.validar_padre(Some(id), &data.tipo, data.padre_id).await?;
+
         let tipo_str = data.tipo.to_string();
         let id_str = id.to_string();
+        let padre_id_str = data.padre_id.map(|id| id.to_string());
+
+        // Las subcategorías deben conservar el tipo de su padre
+        let hijas_de_otro_tipo = sqlx::query!(
+            "SELECT COUNT(*) as count FROM categorias WHERE padre_id = ?1 AND tipo != ?2 AND deleted_at IS NULL",
+            id_str,
+            tipo_str
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if hijas_de_otro_tipo.count > 0 {
+            return Err(AppError::Validation(
+                "No se puede cambiar el tipo de una categoría con subcategorías de otro tipo"
+                    .to_string(),
+            ));
+        }
 
         let result = sqlx::query!(
             r#"
             UPDATE categorias 
-            SET nombre = ?1, tipo = ?2, color = ?3, updated_at = datetime('now')
-            WHERE id = ?4
+            SET nombre = ?1, tipo = ?2, color = ?3, padre_id = ?4, updated_at = datetime('now')
+            WHERE id = ?5 AND deleted_at IS NULL
             "#,
             data.nombre,
             tipo_str,
             data.color,
+            padre_id_str,
             id_str
         )
         .execute(&self.pool)
-        .await?;
+        .await
+        .map_err(|e| error_duplicada(e, &data.nombre, &data.tipo))?;
 
         if result.rows_affected() == 0 {
             return Ok(None);
@@ -157,21 +287,98 @@ impl CategoriaRepository {
         self.get_by_id(id).await
     }
 
-    /// Eliminar una categoría
+    /// Enviar una categoría a la papelera
     pub async fn delete(&self, id: Uuid) -> Result<bool> {
         let id_str = id.to_string();
-        let result = sqlx::query!("DELETE FROM categorias WHERE id = ?1", id_str)
-            .execute(&self.pool)
-            .await?;
+
+        let hijas = sqlx::query!(
+            "SELECT COUNT(*) as count FROM categorias WHERE padre_id = ?1 AND deleted_at IS NULL",
+            id_str
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if hijas.count > 0 {
+            return Err(AppError::Validation(format!(
+                "La categoría tiene {} subcategorías; elimínalas o muévelas antes",
+                hijas.count
+            )));
+        }
+
+        // Con transacciones no se puede borrar (ON DELETE RESTRICT): se propone archivarla
+        let usos = sqlx::query!(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM transacciones WHERE categoria_id = ?1 AND deleted_at IS NULL) as "transacciones!: i64",
+                (SELECT COUNT(*) FROM plantillas_transaccion WHERE categoria_id = ?1) as "plantillas!: i64",
+                (SELECT COUNT(*) FROM reglas_categoria WHERE categoria_id = ?1) as "reglas!: i64"
+            "#,
+            id_str
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if usos.transacciones > 0 || usos.plantillas > 0 {
+            return Err(AppError::Validation(format!(
+                "La categoría se usa en {} transacciones y {} plantillas; archívala en lugar de eliminarla",
+                usos.transacciones, usos.plantillas
+            )));
+        }
+        if usos.reglas > 0 {
+            return Err(AppError::Validation(format!(
+                "La categoría se usa en {} reglas de categorización; elimínalas antes",
+                usos.reglas
+            )));
+        }
+
+        let deleted_at_str = Utc::now().to_rfc3339();
+        let result = sqlx::query!(
+            "UPDATE categorias SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL",
+            id_str,
+            deleted_at_str
+        )
+        .execute(&self.pool)
+        .await?;
 
         Ok(result.rows_affected() > 0)
     }
 
+    /// Archivar o desarchivar una categoría junto con todas sus subcategorías
+    pub async fn set_archivada(&self, id: Uuid, archivada: bool) -> Result<Option<Categoria>> {
+        let categorias = self.get_all_incluyendo_archivadas().await?;
+        if categorias.iter().all(|c| c.id != id) {
+            return Ok(None);
+        }
+
+        let mut afectadas: Vec<Uuid> = categorias
+            .iter()
+            .filter(|c| c.id == id || antecesores(c.id, &categorias).contains(&id))
+            .map(|c| c.id)
+            .collect();
+        // Al desarchivar una subcategoría se desarchivan también sus antecesores
+        if !archivada {
+            afectadas.extend(antecesores(id, &categorias));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for categoria_id in afectadas {
+            let id_str = categoria_id.to_string();
+            sqlx::query!(
+                "UPDATE categorias SET archivada = ?1 WHERE id = ?2",
+                archivada,
+                id_str
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        self.get_by_id(id).await
+    }
+
     /// Contar categorías por tipo
     pub async fn count_by_tipo(&self, tipo: TipoCategoria) -> Result<i64> {
         let tipo_str = tipo.to_string();
         let row = sqlx::query!(
-            "SELECT COUNT(*) as count FROM categorias WHERE tipo = ?1",
+            "SELECT COUNT(*) as count FROM categorias WHERE tipo = ?1 AND deleted_at IS NULL",
             tipo_str
         )
         .fetch_one(&self.pool)
@@ -179,4 +386,102 @@ impl CategoriaRepository {
 
         Ok(row.count.into())
     }
+
+    /// Sugerir un color de la paleta que no usen otras categorías
+    pub async fn sugerir_color(&self) -> Result<String> {
+        let rows = sqlx::query!("SELECT color FROM categorias WHERE deleted_at IS NULL")
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(sugerir_color(rows.iter().map(|r| r.color.as_str())))
+    }
+
+    /// Comprobar que no hay otra categoría del mismo tipo con el mismo nombre
+    /// (`id` es la categoría que se actualiza, None al crear)
+    async fn validar_nombre_unico(
+        &self,
+        id: Option<Uuid>,
+        nombre: &str,
+        tipo: &TipoCategoria,
+    ) -> Result<()> {
+        let id_str = id.map(|id| id.to_string());
+        let tipo_str = tipo.to_string();
+        let existente = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as count FROM categorias
+            WHERE nombre = ?1 COLLATE NOCASE AND tipo = ?2 AND (?3 IS NULL OR id != ?3)
+              AND deleted_at IS NULL
+            "#,
+            nombre,
+            tipo_str,
+            id_str
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        if existente.count > 0 {
+            return Err(mensaje_duplicada(nombre, tipo));
+        }
+
+        Ok(())
+    }
+
+    /// Comprobar que el padre existe, es del mismo tipo y no crea un ciclo
+    /// (`id` es la categoría que se actualiza, None al crear)
+    async fn validar_padre(
+        &self,
+        id: Option<Uuid>,
+        tipo: &TipoCategoria,
+        padre_id: Option<Uuid>,
+    ) -> Result<()> {
+        let padre_id = match padre_id {
+            Some(padre_id) => padre_id,
+            None => return Ok(()),
+        };
+
+        if id == Some(padre_id) {
+            return Err(AppError::Validation(
+                "Una categoría no puede ser su propia categoría padre".to_string(),
+            ));
+        }
+
+        let categorias = self.get_all_incluyendo_archivadas().await?;
+        let padre = categorias
+            .iter()
+            .find(|c| c.id == padre_id)
+            .ok_or_else(|| AppError::NotFound("Categoría padre".to_string()))?;
+
+        if padre.tipo.to_string() != tipo.to_string() {
+            return Err(AppError::Validation(format!(
+                "La categoría padre '{}' es de tipo {}; las subcategorías deben ser del mismo tipo",
+                padre.nombre, padre.tipo
+            )));
+        }
+
+        if let Some(id) = id {
+            if antecesores(padre_id, &categorias).contains(&id) {
+                return Err(AppError::Validation(format!(
+                    "'{}' es una subcategoría de esta categoría: se crearía un ciclo",
+                    padre.nombre
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn mensaje_duplicada(nombre: &str, tipo: &TipoCategoria) -> AppError {
+    AppError::Duplicate(format!(
+        "Ya existe una categoría de tipo {} llamada '{}'",
+        tipo, nombre
+    ))
+}
+
+/// Traducir la violación del índice único (nombre, tipo) a un error legible
+pub(crate) fn error_duplicada(error: sqlx::Error, nombre: &str, tipo: &TipoCategoria) -> AppError {
+    match &error {
+        sqlx::Error::Database(e) if e.is_unique_violation() => mensaje_duplicada(nombre, tipo),
+        _ => AppError::Database(error),
+    }
 }
@@ -0,0 +1,352 @@
+use crate::models::{
+    CreateCuenta, CreateTransferencia, Cuenta, SaldoCuenta, TipoCuenta, Transferencia,
+};
+use crate::repositories::parse_flexible_datetime;
+use crate::{AppError, Result};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+use validator::Validate;
+
+pub struct CuentaRepository {
+    pool: SqlitePool,
+}
+
+impl CuentaRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Crear una nueva cuenta
+    pub async fn create(&self, data: CreateCuenta) -> Result<Cuenta> {
+        data.validate()
+            .map_err(|e| AppError::Validation(e.to_string()))?;
+
+        let cuenta = Cuenta::new(data);
+        self.insertar(&cuenta).await?;
+
+        Ok(cuenta)
+    }
+
+    /// Volver a guardar una cuenta eliminada con su ID original (al deshacer la eliminación)
+    pub async fn restaurar(&self, cuenta: &Cuenta) -> Result<()> {
+        self.insertar(cuenta).await
+    }
+
+    async fn insertar(&self, cuenta: &Cuenta) -> Result<()> {
+        let existe = sqlx::query!(
+            "SELECT COUNT(*) as count FROM cuentas WHERE nombre = ?1",
+            cuenta.nombre
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if existe.count > 0 {
+            return Err(AppError::Duplicate(format!(
+                "Ya existe una cuenta llamada '{}'",
+                cuenta.nombre
+            )));
+        }
+
+        // Crear variables para evitar problemas de lifetime
+        let id_str = cuenta.id.to_string();
+        let tipo_str = cuenta.tipo.to_string();
+        let created_at_str = cuenta.created_at.to_rfc3339();
+        let updated_at_str = cuenta.updated_at.to_rfc3339();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO cuentas (id, nombre, tipo, saldo_inicial, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+            id_str,
+            cuenta.nombre,
+            tipo_str,
+            cuenta.saldo_inicial,
+            created_at_str,
+            updated_at_str
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Obtener una cuenta por ID
+    pub async fn get_by_id(&self, id: Uuid) -> Result<Option<Cuenta>> {
+        let id_str = id.to_string();
+        let row = sqlx::query!(
+            "SELECT id, nombre, tipo, saldo_inicial, created_at, updated_at FROM cuentas WHERE id = ?1",
+            id_str
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(Some(Cuenta {
+                id: Uuid::parse_str(&row.id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                nombre: row.nombre,
+                tipo: TipoCuenta::parse(&row.tipo)
+                    .ok_or_else(|| AppError::Internal("Invalid tipo cuenta".to_string()))?,
+                saldo_inicial: row.saldo_inicial,
+                created_at: parse_flexible_datetime(&row.created_at)?,
+                updated_at: parse_flexible_datetime(&row.updated_at)?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    /// Obtener todas las cuentas
+    pub async fn get_all(&self) -> Result<Vec<Cuenta>> {
+        let rows = sqlx::query!(
+            "SELECT id, nombre, tipo, saldo_inicial, created_at, updated_at FROM cuentas ORDER BY nombre"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut cuentas = Vec::new();
+        for row in rows {
+            cuentas.push(Cuenta {
+                id: Uuid::parse_str(&row.id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                nombre: row.nombre,
+                tipo: TipoCuenta::parse(&row.tipo)
+                    .ok_or_else(|| AppError::Internal("Invalid tipo cuenta".to_string()))?,
+                saldo_inicial: row.saldo_inicial,
+                created_at: parse_flexible_datetime(&row.created_at)?,
+                updated_at: parse_flexible_datetime(&row.updated_at)?,
+            });
+        }
+
+        Ok(cuentas)
+    }
+
+    /// Eliminar una cuenta sin movimientos ni arqueos (se cuentan también los de la papelera)
+    pub async fn delete(&self, id: Uuid) -> Result<bool> {
+        let id_str = id.to_string();
+        let uso = sqlx::query!(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM transacciones WHERE cuenta_id = ?1) as "transacciones!: i64",
+                (SELECT COUNT(*) FROM transferencias
+                    WHERE cuenta_origen_id = ?1 OR cuenta_destino_id = ?1) as "transferencias!: i64",
+                (SELECT COUNT(*) FROM arqueos WHERE cuenta_id = ?1) as "arqueos!: i64"
+            "#,
+            id_str
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if uso.transacciones > 0 || uso.transferencias > 0 || uso.arqueos > 0 {
+            return Err(AppError::Validation(format!(
+                "La cuenta tiene {} transacciones, {} transferencias y {} arqueos (incluidos los de la papelera)",
+                uso.transacciones, uso.transferencias, uso.arqueos
+            )));
+        }
+
+        let result = sqlx::query!("DELETE FROM cuentas WHERE id = ?1", id_str)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Registrar una transferencia entre dos cuentas de un retiro
+    pub async fn create_transferencia(&self, data: CreateTransferencia) -> Result<Transferencia> {
+        data.validate()
+            .map_err(|e| AppError::Validation(e.to_string()))?;
+
+        let transferencia = Transferencia::new(data);
+        self.insertar_transferencia(&transferencia).await?;
+
+        Ok(transferencia)
+    }
+
+    /// Volver a guardar una transferencia eliminada con su ID original (al deshacer la eliminación)
+    pub async fn restaurar_transferencia(&self, transferencia: &Transferencia) -> Result<()> {
+        self.insertar_transferencia(transferencia).await
+    }
+
+    async fn insertar_transferencia(&self, transferencia: &Transferencia) -> Result<()> {
+        if transferencia.cuenta_origen_id == transferencia.cuenta_destino_id {
+            return Err(AppError::Validation(
+                "La cuenta de origen y la de destino deben ser distintas".to_string(),
+            ));
+        }
+
+        let retiro_id_str = transferencia.retiro_id.to_string();
+        let retiro = sqlx::query!(
+            "SELECT id FROM retiros WHERE id = ?1 AND deleted_at IS NULL",
+            retiro_id_str
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        if retiro.is_none() {
+            return Err(AppError::NotFound("Retiro".to_string()));
+        }
+        for cuenta_id in [transferencia.cuenta_origen_id, transferencia.cuenta_destino_id] {
+            if self.get_by_id(cuenta_id).await?.is_none() {
+                return Err(AppError::NotFound("Cuenta".to_string()));
+            }
+        }
+
+        // Crear variables para evitar problemas de lifetime
+        let id_str = transferencia.id.to_string();
+        let cuenta_origen_id_str = transferencia.cuenta_origen_id.to_string();
+        let cuenta_destino_id_str = transferencia.cuenta_destino_id.to_string();
+        let created_at_str = transferencia.created_at.to_rfc3339();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO transferencias (id, retiro_id, cuenta_origen_id, cuenta_destino_id, monto, descripcion, created_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+            id_str,
+            retiro_id_str,
+            cuenta_origen_id_str,
+            cuenta_destino_id_str,
+            transferencia.monto,
+            transferencia.descripcion,
+            created_at_str
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Transferencias de un retiro, o de todos los retiros activos si no se indica
+    pub async fn get_transferencias(&self, retiro_id: Option<Uuid>) -> Result<Vec<Transferencia>> {
+        let retiro_id_str = retiro_id.map(|id| id.to_string());
+        let rows = sqlx::query!(
+            r#"
+            SELECT tr.id, tr.retiro_id, tr.cuenta_origen_id, tr.cuenta_destino_id,
+                tr.monto, tr.descripcion, tr.created_at
+            FROM transferencias tr
+            JOIN retiros r ON r.id = tr.retiro_id
+            WHERE r.deleted_at IS NULL
+              AND (?1 IS NULL OR tr.retiro_id = ?1)
+            ORDER BY tr.created_at DESC
+            "#,
+            retiro_id_str
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut transferencias = Vec::new();
+        for row in rows {
+            transferencias.push(Transferencia {
+                id: Uuid::parse_str(&row.id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                retiro_id: Uuid::parse_str(&row.retiro_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                cuenta_origen_id: Uuid::parse_str(&row.cuenta_origen_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                cuenta_destino_id: Uuid::parse_str(&row.cuenta_destino_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                monto: row.monto,
+                descripcion: row.descripcion,
+                created_at: parse_flexible_datetime(&row.created_at)?,
+            });
+        }
+
+        Ok(transferencias)
+    }
+
+    /// Eliminar una transferencia
+    pub async fn delete_transferencia(&self, id: Uuid) -> Result<bool> {
+        let id_str = id.to_string();
+        let result = sqlx::query!("DELETE FROM transferencias WHERE id = ?1", id_str)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Saldo de cada cuenta. Con retiro, solo los movimientos de ese retiro;
+    /// sin retiro, el saldo total desde el saldo inicial.
+    /// Las transacciones sin cuenta se agrupan al final en "Sin cuenta".
+    pub async fn get_saldos(&self, retiro_id: Option<Uuid>) -> Result<Vec<SaldoCuenta>> {
+        let retiro_id_str = retiro_id.map(|id| id.to_string());
+        let rows = sqlx::query!(
+            r#"
+            SELECT c.id, c.nombre, c.saldo_inicial,
+                (SELECT COALESCE(SUM(t.monto), 0.0) FROM transacciones t
+                    WHERE t.cuenta_id = c.id AND t.tipo = 'Ingreso' AND t.deleted_at IS NULL
+                      AND (?1 IS NULL OR t.retiro_id = ?1)) as "ingresos!: f64",
+                (SELECT COALESCE(SUM(t.monto), 0.0) FROM transacciones t
+                    WHERE t.cuenta_id = c.id AND t.tipo = 'Gasto' AND t.deleted_at IS NULL
+                      AND (?1 IS NULL OR t.retiro_id = ?1)) as "gastos!: f64",
+                (SELECT COALESCE(SUM(tr.monto), 0.0) FROM transferencias tr
+                    JOIN retiros r ON r.id = tr.retiro_id
+                    WHERE tr.cuenta_destino_id = c.id AND r.deleted_at IS NULL
+                      AND (?1 IS NULL OR tr.retiro_id = ?1)) as "entrada!: f64",
+                (SELECT COALESCE(SUM(tr.monto), 0.0) FROM transferencias tr
+                    JOIN retiros r ON r.id = tr.retiro_id
+                    WHERE tr.cuenta_origen_id = c.id AND r.deleted_at IS NULL
+                      AND (?1 IS NULL OR tr.retiro_id = ?1)) as "salida!: f64"
+            FROM cuentas c
+            ORDER BY c.nombre
+            "#,
+            retiro_id_str
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut saldos = Vec::new();
+        for row in rows {
+            // El saldo inicial es anterior a cualquier retiro
+            let saldo_inicial = if retiro_id.is_some() { 0.0 } else { row.saldo_inicial };
+            saldos.push(SaldoCuenta {
+                cuenta_id: Some(
+                    Uuid::parse_str(&row.id)
+                        .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                ),
+                cuenta: row.nombre,
+                saldo_inicial,
+                ingresos: row.ingresos,
+                gastos: row.gastos,
+                transferencias_entrada: row.entrada,
+                transferencias_salida: row.salida,
+                saldo: saldo_inicial + row.ingresos - row.gastos + row.entrada - row.salida,
+            });
+        }
+
+        let sin_cuenta = sqlx::query!(
+            r#"
+            SELECT
+                COALESCE(SUM(CASE WHEN tipo = 'Ingreso' THEN monto END), 0.0) as "ingresos!: f64",
+                COALESCE(SUM(CASE WHEN tipo = 'Gasto' THEN monto END), 0.0) as "gastos!: f64",
+                COUNT(*) as "transacciones!: i64"
+            FROM transacciones
+            WHERE cuenta_id IS NULL AND deleted_at IS NULL
+              AND (?1 IS NULL OR retiro_id = ?1)
+            "#,
+            retiro_id_str
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if sin_cuenta.transacciones > 0 {
+            saldos.push(SaldoCuenta {
+                cuenta_id: None,
+                cuenta: "Sin cuenta".to_string(),
+                saldo_inicial: 0.0,
+                ingresos: sin_cuenta.ingresos,
+                gastos: sin_cuenta.gastos,
+                transferencias_entrada: 0.0,
+                transferencias_salida: 0.0,
+                saldo: sin_cuenta.ingresos - sin_cuenta.gastos,
+            });
+        }
+
+        Ok(saldos)
+    }
+}
+
+/// Traducir la violación del nombre único de las cuentas a un error legible
+pub(crate) fn error_cuenta_duplicada(error: sqlx::Error, nombre: &str) -> AppError {
+    match &error {
+        sqlx::Error::Database(e) if e.is_unique_violation() => {
+            AppError::Duplicate(format!("Ya existe una cuenta llamada '{}'", nombre))
+        }
+        _ => AppError::Database(error),
+    }
+}
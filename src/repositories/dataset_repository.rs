@@ -0,0 +1,1718 @@
+use crate::database::AlmacenAdjuntos;
+use crate::models::{
+    arbol_categorias, compilar_patron, normalizar_etiqueta, normalizar_etiquetas, total_contado,
+    Categoria, Dataset, ReglaCategoria, TipoElementoPapelera, DATASET_VERSION,
+};
+use crate::repositories::{
+    asignar, denominaciones_json, error_cuenta_duplicada, error_duplicada, error_regla_duplicada,
+    AdjuntoRepository, ArqueoRepository, CategoriaRepository, CuentaRepository, EtiquetaRepository,
+    ExtractoRepository, PapeleraRepository, PlantillaRepository, ReglaRepository, RetiroRepository,
+    TransaccionRepository,
+};
+use crate::{AppError, Result};
+use chrono::Utc;
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Máximo de conflictos listados en el mensaje de error
+const MAX_CONFLICTOS_LISTADOS: usize = 20;
+
+pub struct DatasetRepository {
+    pool: SqlitePool,
+}
+
+impl DatasetRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Exportar todas las categorías, retiros, transacciones, plantillas, etiquetas,
+    /// cuentas, transferencias, arqueos, líneas de extracto, reglas de categorización y
+    /// datos de los comprobantes. Los elementos de la papelera se exportan con su fecha de eliminación.
+    pub async fn export(&self) -> Result<Dataset> {
+        let categorias = CategoriaRepository::new(self.pool.clone())
+            .get_all_incluyendo_eliminadas()
+            .await?;
+        let retiros = RetiroRepository::new(self.pool.clone())
+            .get_all_incluyendo_eliminados()
+            .await?;
+        let transacciones = TransaccionRepository::new(self.pool.clone())
+            .get_all_incluyendo_eliminadas()
+            .await?;
+        let plantillas = PlantillaRepository::new(self.pool.clone())
+            .get_all()
+            .await?;
+        let etiquetas = EtiquetaRepository::new(self.pool.clone())
+            .get_asignaciones()
+            .await?;
+        let cuenta_repo = CuentaRepository::new(self.pool.clone());
+        let cuentas = cuenta_repo.get_all().await?;
+        let transferencias = cuenta_repo.get_transferencias(None).await?;
+        let arqueos = ArqueoRepository::new(self.pool.clone())
+            .get_all(None, None)
+            .await?;
+        let lineas_extracto = ExtractoRepository::new(self.pool.clone())
+            .get_lineas(None)
+            .await?;
+        let reglas = ReglaRepository::new(self.pool.clone()).get_all().await?;
+        let adjuntos = AdjuntoRepository::new(self.pool.clone()).get_all().await?;
+        let papelera = PapeleraRepository::new(self.pool.clone())
+            .get_fechas_eliminacion()
+            .await?;
+
+        Ok(Dataset {
+            version: DATASET_VERSION,
+            exportado_en: Utc::now(),
+            categorias,
+            retiros,
+            transacciones,
+            plantillas,
+            etiquetas,
+            cuentas,
+            transferencias,
+            arqueos,
+            lineas_extracto,
+            reglas,
+            adjuntos,
+            papelera,
+        })
+    }
+
+    /// Importar un volcado dentro de una única transacción SQL.
+    /// Un mismo ID con datos distintos se considera conflicto y se resuelve según `conflictos`.
+    /// Los comprobantes se vuelven a enlazar solo si su archivo está en `almacen`;
+    /// sin almacén (base de datos en memoria) se omiten.
+    pub async fn import(
+        &self,
+        dataset: Dataset,
+        modo: ModoImportacion,
+        conflictos: ResolucionConflicto,
+        almacen: Option<&AlmacenAdjuntos>,
+    ) -> Result<ResumenImportacion> {
+        if dataset.version != DATASET_VERSION {
+            return Err(AppError::Validation(format!(
+                "Versión de exportación no soportada: {} (se esperaba {})",
+                dataset.version, DATASET_VERSION
+            )));
+        }
+
+        let preparado = preparar(&dataset)?;
+
+        let mut tx = self.pool.begin().await?;
+        let mut conflictos = Conflictos::new(conflictos);
+
+        if let ModoImportacion::Reemplazar = modo {
+            // Los archivos de los comprobantes se conservan: la copia previa los sigue usando
+            // y los que vuelvan en el volcado se enlazan de nuevo
+            sqlx::query!("DELETE FROM adjuntos")
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query!("DELETE FROM arqueos")
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query!("DELETE FROM lineas_extracto")
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query!("DELETE FROM transacciones")
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query!("DELETE FROM transferencias")
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query!("DELETE FROM retiros")
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query!("DELETE FROM plantillas_transaccion")
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query!("DELETE FROM reglas_categoria")
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query!("DELETE FROM categorias")
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query!("DELETE FROM etiquetas")
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query!("DELETE FROM cuentas")
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let categorias = importar_categorias(
+            &mut tx,
+            &preparado.categorias,
+            &preparado.papelera,
+            &mut conflictos,
+        )
+        .await?;
+        let retiros =
+            importar_retiros(&mut tx, &dataset, &preparado.papelera, &mut conflictos).await?;
+        let cuentas = importar_cuentas(&mut tx, &dataset, &mut conflictos).await?;
+        let transacciones =
+            importar_transacciones(&mut tx, &dataset, &preparado.papelera, &mut conflictos).await?;
+        let transferencias = importar_transferencias(&mut tx, &dataset, &mut conflictos).await?;
+        let arqueos = importar_arqueos(&mut tx, &dataset, &mut conflictos).await?;
+        let lineas_extracto = importar_lineas_extracto(&mut tx, &dataset, &mut conflictos).await?;
+        let plantillas = importar_plantillas(&mut tx, &dataset, &mut conflictos).await?;
+        let reglas = importar_reglas(&mut tx, &preparado.reglas, &mut conflictos).await?;
+        let (adjuntos, adjuntos_sin_archivo) =
+            importar_adjuntos(&mut tx, &dataset, almacen, &mut conflictos).await?;
+
+        // Las etiquetas solo se añaden: nunca entran en conflicto
+        let mut etiquetas = ResumenEntidad::default();
+        for (transaccion_id, etiqueta) in &preparado.etiquetas {
+            let accion = if asignar(&mut tx, *transaccion_id, etiqueta).await? {
+                Accion::Insertar
+            } else {
+                Accion::SinCambios
+            };
+            etiquetas.registrar(accion);
+        }
+
+        // Al salir sin commit la transacción se descarta y no se aplica ningún cambio
+        conflictos.comprobar()?;
+        tx.commit().await?;
+
+        Ok(ResumenImportacion {
+            categorias,
+            retiros,
+            transacciones,
+            plantillas,
+            etiquetas,
+            cuentas,
+            transferencias,
+            arqueos,
+            lineas_extracto,
+            reglas,
+            adjuntos,
+            adjuntos_sin_archivo,
+        })
+    }
+}
+
+/// Fecha de eliminación (en el formato que se guarda) de cada elemento del volcado en la papelera
+type Papelera = HashMap<(TipoElementoPapelera, Uuid), String>;
+
+/// Datos del volcado ya validados, listos para importar
+struct Preparado<'a> {
+    /// Los padres antes que sus subcategorías
+    categorias: Vec<&'a Categoria>,
+    /// Cada regla con sus etiquetas normalizadas, tal como se guardan
+    reglas: Vec<(&'a ReglaCategoria, String)>,
+    papelera: Papelera,
+    etiquetas: Vec<(Uuid, String)>,
+}
+
+/// Validar el volcado completo antes de tocar la base de datos
+fn preparar(dataset: &Dataset) -> Result<Preparado<'_>> {
+    for categoria in &dataset.categorias {
+        categoria
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Categoría {}: {}", categoria.id, e)))?;
+    }
+    // Los padres se insertan antes que sus subcategorías; una categoría que no aparece
+    // en el árbol forma parte de un ciclo
+    let categorias: Vec<&Categoria> = arbol_categorias(&dataset.categorias)
+        .into_iter()
+        .map(|(_, categoria)| categoria)
+        .collect();
+    if categorias.len() != dataset.categorias.len() {
+        return Err(AppError::Validation(
+            "Las categorías importadas contienen un ciclo de subcategorías".to_string(),
+        ));
+    }
+    for retiro in &dataset.retiros {
+        retiro
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Retiro {}: {}", retiro.id, e)))?;
+    }
+    for transaccion in &dataset.transacciones {
+        transaccion
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Transacción {}: {}", transaccion.id, e)))?;
+    }
+    for plantilla in &dataset.plantillas {
+        plantilla
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Plantilla {}: {}", plantilla.id, e)))?;
+    }
+    let mut reglas = Vec::new();
+    for regla in &dataset.reglas {
+        regla
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Regla {}: {}", regla.id, e)))?;
+        compilar_patron(&regla.patron)
+            .map_err(|e| AppError::Validation(format!("Regla {}: {}", regla.id, e)))?;
+        reglas.push((regla, normalizar_etiquetas(&regla.etiquetas)?.join(",")));
+    }
+    for cuenta in &dataset.cuentas {
+        cuenta
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Cuenta {}: {}", cuenta.id, e)))?;
+    }
+    for transferencia in &dataset.transferencias {
+        transferencia.validate().map_err(|e| {
+            AppError::Validation(format!("Transferencia {}: {}", transferencia.id, e))
+        })?;
+    }
+    for arqueo in &dataset.arqueos {
+        arqueo
+            .validate()
+            .map_err(|e| AppError::Validation(format!("Arqueo {}: {}", arqueo.id, e)))?;
+        total_contado(Some(arqueo.contado), &arqueo.denominaciones)
+            .map_err(|e| AppError::Validation(format!("Arqueo {}: {}", arqueo.id, e)))?;
+    }
+    for adjunto in &dataset.adjuntos {
+        if !dataset
+            .transacciones
+            .iter()
+            .any(|t| t.id == adjunto.transaccion_id)
+        {
+            return Err(AppError::Validation(format!(
+                "Adjunto {}: la transacción {} no está en el volcado",
+                adjunto.id, adjunto.transaccion_id
+            )));
+        }
+        // El hash da nombre al archivo en el almacén: no puede contener rutas
+        if adjunto.hash.len() != 64 || !adjunto.hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(AppError::Validation(format!(
+                "Adjunto {}: hash SHA-256 inválido",
+                adjunto.id
+            )));
+        }
+    }
+    // Fecha de eliminación (en el formato que se guarda) de cada elemento en la papelera
+    let mut papelera = HashMap::new();
+    for eliminado in &dataset.papelera {
+        let en_volcado = match eliminado.tipo {
+            TipoElementoPapelera::Retiro => dataset.retiros.iter().any(|r| r.id == eliminado.id),
+            TipoElementoPapelera::Categoria => {
+                dataset.categorias.iter().any(|c| c.id == eliminado.id)
+            }
+            TipoElementoPapelera::Transaccion => {
+                dataset.transacciones.iter().any(|t| t.id == eliminado.id)
+            }
+        };
+        if !en_volcado {
+            return Err(AppError::Validation(format!(
+                "Papelera: {} {} no está en el volcado",
+                eliminado.tipo, eliminado.id
+            )));
+        }
+        papelera.insert(
+            (eliminado.tipo, eliminado.id),
+            eliminado.deleted_at.to_rfc3339(),
+        );
+    }
+    let mut etiquetas = Vec::new();
+    for asignacion in &dataset.etiquetas {
+        if !dataset
+            .transacciones
+            .iter()
+            .any(|t| t.id == asignacion.transaccion_id)
+        {
+            return Err(AppError::Validation(format!(
+                "Etiqueta '{}': la transacción {} no está en el volcado",
+                asignacion.etiqueta, asignacion.transaccion_id
+            )));
+        }
+        etiquetas.push((
+            asignacion.transaccion_id,
+            normalizar_etiqueta(&asignacion.etiqueta)?,
+        ));
+    }
+
+    Ok(Preparado {
+        categorias,
+        reglas,
+        papelera,
+        etiquetas,
+    })
+}
+
+/// Conflictos de ID encontrados durante una importación
+struct Conflictos {
+    resolucion: ResolucionConflicto,
+    pendientes: Vec<String>,
+}
+
+impl Conflictos {
+    fn new(resolucion: ResolucionConflicto) -> Self {
+        Self {
+            resolucion,
+            pendientes: Vec::new(),
+        }
+    }
+
+    /// Decidir qué hacer con un elemento, contarlo en `resumen` y anotarlo si queda en conflicto
+    fn resolver(
+        &mut self,
+        existente: Option<bool>,
+        resumen: &mut ResumenEntidad,
+        elemento: impl FnOnce() -> String,
+    ) -> Accion {
+        let accion = Accion::resolver(existente, self.resolucion);
+        resumen.registrar(accion);
+        if let Accion::Conflicto = accion {
+            self.pendientes.push(elemento());
+        }
+        accion
+    }
+
+    /// Error con los conflictos anotados, si hay alguno
+    fn comprobar(&self) -> Result<()> {
+        if self.pendientes.is_empty() {
+            return Ok(());
+        }
+
+        let mut listado = self
+            .pendientes
+            .iter()
+            .take(MAX_CONFLICTOS_LISTADOS)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        if self.pendientes.len() > MAX_CONFLICTOS_LISTADOS {
+            listado.push_str(&format!(
+                " y {} más",
+                self.pendientes.len() - MAX_CONFLICTOS_LISTADOS
+            ));
+        }
+
+        Err(AppError::Validation(format!(
+            "{} conflictos de ID con datos distintos: {}",
+            self.pendientes.len(),
+            listado
+        )))
+    }
+}
+
+/// Importar las categorías, con los padres antes que sus subcategorías
+async fn importar_categorias(
+    tx: &mut Transaction<'_, Sqlite>,
+    categorias: &[&Categoria],
+    papelera: &Papelera,
+    conflictos: &mut Conflictos,
+) -> Result<ResumenEntidad> {
+    let mut resumen = ResumenEntidad::default();
+
+    for categoria in categorias {
+        let id_str = categoria.id.to_string();
+        let tipo_str = categoria.tipo.to_string();
+        let padre_id_str = categoria.padre_id.map(|id| id.to_string());
+        let created_at_str = categoria.created_at.to_rfc3339();
+        let updated_at_str = categoria.updated_at.to_rfc3339();
+        let deleted_at_str = papelera.get(&(TipoElementoPapelera::Categoria, categoria.id));
+
+        let existente = sqlx::query!(
+            r#"SELECT (nombre = ?2 AND tipo = ?3 AND color = ?4 AND padre_id IS ?5 AND archivada = ?6 AND julianday(deleted_at) IS julianday(?7)) as "igual!: bool" FROM categorias WHERE id = ?1"#,
+            id_str,
+            categoria.nombre,
+            tipo_str,
+            categoria.color,
+            padre_id_str,
+            categoria.archivada,
+            deleted_at_str
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let accion = conflictos.resolver(existente.map(|r| r.igual), &mut resumen, || {
+            format!("categoría {}", categoria.id)
+        });
+
+        match accion {
+            Accion::Insertar => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO categorias (id, nombre, tipo, color, padre_id, archivada, created_at, updated_at, deleted_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                    "#,
+                    id_str,
+                    categoria.nombre,
+                    tipo_str,
+                    categoria.color,
+                    padre_id_str,
+                    categoria.archivada,
+                    created_at_str,
+                    updated_at_str,
+                    deleted_at_str
+                )
+                .execute(&mut **tx)
+                .await
+                .map_err(|e| error_duplicada(e, &categoria.nombre, &categoria.tipo))?;
+            }
+            Accion::Sobrescribir => {
+                sqlx::query!(
+                    "UPDATE categorias SET nombre = ?2, tipo = ?3, color = ?4, padre_id = ?5, archivada = ?6, deleted_at = ?7 WHERE id = ?1",
+                    id_str,
+                    categoria.nombre,
+                    tipo_str,
+                    categoria.color,
+                    padre_id_str,
+                    categoria.archivada,
+                    deleted_at_str
+                )
+                .execute(&mut **tx)
+                .await
+                .map_err(|e| error_duplicada(e, &categoria.nombre, &categoria.tipo))?;
+            }
+            Accion::SinCambios | Accion::Mantener | Accion::Conflicto => {}
+        }
+    }
+
+    Ok(resumen)
+}
+
+/// Importar los retiros
+async fn importar_retiros(
+    tx: &mut Transaction<'_, Sqlite>,
+    dataset: &Dataset,
+    papelera: &Papelera,
+    conflictos: &mut Conflictos,
+) -> Result<ResumenEntidad> {
+    let mut resumen = ResumenEntidad::default();
+
+    for retiro in &dataset.retiros {
+        let id_str = retiro.id.to_string();
+        let estado_str = retiro.estado.to_string();
+        let fecha_inicio_str = retiro.fecha_inicio.to_rfc3339();
+        let fecha_fin_str = retiro.fecha_fin.to_rfc3339();
+        let created_at_str = retiro.created_at.to_rfc3339();
+        let updated_at_str = retiro.updated_at.to_rfc3339();
+        let deleted_at_str = papelera.get(&(TipoElementoPapelera::Retiro, retiro.id));
+
+        let existente = sqlx::query!(
+            r#"
+            SELECT (
+                nombre = ?2 AND descripcion IS ?3
+                AND julianday(fecha_inicio) = julianday(?4) AND julianday(fecha_fin) = julianday(?5)
+                AND ubicacion IS ?6 AND numero_participantes = ?7 AND estado = ?8
+                AND julianday(deleted_at) IS julianday(?9)
+            ) as "igual!: bool"
+            FROM retiros WHERE id = ?1
+            "#,
+            id_str,
+            retiro.nombre,
+            retiro.descripcion,
+            fecha_inicio_str,
+            fecha_fin_str,
+            retiro.ubicacion,
+            retiro.numero_participantes,
+            estado_str,
+            deleted_at_str
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let accion = conflictos.resolver(existente.map(|r| r.igual), &mut resumen, || {
+            format!("retiro {}", retiro.id)
+        });
+
+        match accion {
+            Accion::Insertar => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO retiros (id, nombre, descripcion, fecha_inicio, fecha_fin, ubicacion, numero_participantes, estado, created_at, updated_at, deleted_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                    "#,
+                    id_str,
+                    retiro.nombre,
+                    retiro.descripcion,
+                    fecha_inicio_str,
+                    fecha_fin_str,
+                    retiro.ubicacion,
+                    retiro.numero_participantes,
+                    estado_str,
+                    created_at_str,
+                    updated_at_str,
+                    deleted_at_str
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+            Accion::Sobrescribir => {
+                // UPDATE y no REPLACE: un REPLACE borraría en cascada las transacciones del retiro
+                sqlx::query!(
+                    r#"
+                    UPDATE retiros
+                    SET nombre = ?2, descripcion = ?3, fecha_inicio = ?4, fecha_fin = ?5, ubicacion = ?6, numero_participantes = ?7, estado = ?8, deleted_at = ?9
+                    WHERE id = ?1
+                    "#,
+                    id_str,
+                    retiro.nombre,
+                    retiro.descripcion,
+                    fecha_inicio_str,
+                    fecha_fin_str,
+                    retiro.ubicacion,
+                    retiro.numero_participantes,
+                    estado_str,
+                    deleted_at_str
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+            Accion::SinCambios | Accion::Mantener | Accion::Conflicto => {}
+        }
+    }
+
+    Ok(resumen)
+}
+
+/// Importar las cuentas
+async fn importar_cuentas(
+    tx: &mut Transaction<'_, Sqlite>,
+    dataset: &Dataset,
+    conflictos: &mut Conflictos,
+) -> Result<ResumenEntidad> {
+    let mut resumen = ResumenEntidad::default();
+
+    for cuenta in &dataset.cuentas {
+        let id_str = cuenta.id.to_string();
+        let tipo_str = cuenta.tipo.to_string();
+        let created_at_str = cuenta.created_at.to_rfc3339();
+        let updated_at_str = cuenta.updated_at.to_rfc3339();
+
+        let existente = sqlx::query!(
+            r#"SELECT (nombre = ?2 AND tipo = ?3 AND saldo_inicial = ?4) as "igual!: bool" FROM cuentas WHERE id = ?1"#,
+            id_str,
+            cuenta.nombre,
+            tipo_str,
+            cuenta.saldo_inicial
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let accion = conflictos.resolver(existente.map(|r| r.igual), &mut resumen, || {
+            format!("cuenta {}", cuenta.id)
+        });
+
+        match accion {
+            Accion::Insertar => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO cuentas (id, nombre, tipo, saldo_inicial, created_at, updated_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                    "#,
+                    id_str,
+                    cuenta.nombre,
+                    tipo_str,
+                    cuenta.saldo_inicial,
+                    created_at_str,
+                    updated_at_str
+                )
+                .execute(&mut **tx)
+                .await
+                .map_err(|e| error_cuenta_duplicada(e, &cuenta.nombre))?;
+            }
+            Accion::Sobrescribir => {
+                sqlx::query!(
+                    "UPDATE cuentas SET nombre = ?2, tipo = ?3, saldo_inicial = ?4 WHERE id = ?1",
+                    id_str,
+                    cuenta.nombre,
+                    tipo_str,
+                    cuenta.saldo_inicial
+                )
+                .execute(&mut **tx)
+                .await
+                .map_err(|e| error_cuenta_duplicada(e, &cuenta.nombre))?;
+            }
+            Accion::SinCambios | Accion::Mantener | Accion::Conflicto => {}
+        }
+    }
+
+    Ok(resumen)
+}
+
+/// Importar las transacciones
+async fn importar_transacciones(
+    tx: &mut Transaction<'_, Sqlite>,
+    dataset: &Dataset,
+    papelera: &Papelera,
+    conflictos: &mut Conflictos,
+) -> Result<ResumenEntidad> {
+    let mut resumen = ResumenEntidad::default();
+
+    for transaccion in &dataset.transacciones {
+        let id_str = transaccion.id.to_string();
+        let retiro_id_str = transaccion.retiro_id.to_string();
+        let categoria_id_str = transaccion.categoria_id.to_string();
+        let tipo_str = transaccion.tipo.to_string();
+        let cuenta_id_str = transaccion.cuenta_id.map(|id| id.to_string());
+        let metodo_pago_str = transaccion.metodo_pago.map(|m| m.to_string());
+        let conciliacion_str = transaccion.conciliacion.to_string();
+        let created_at_str = transaccion.created_at.to_rfc3339();
+        let updated_at_str = transaccion.updated_at.to_rfc3339();
+        let deleted_at_str = papelera.get(&(TipoElementoPapelera::Transaccion, transaccion.id));
+
+        let existente = sqlx::query!(
+            r#"
+            SELECT (
+                retiro_id = ?2 AND categoria_id = ?3 AND tipo = ?4 AND monto = ?5 AND descripcion = ?6
+                AND cuenta_id IS ?7 AND metodo_pago IS ?8 AND conciliacion = ?9
+                AND julianday(deleted_at) IS julianday(?10)
+            ) as "igual!: bool"
+            FROM transacciones WHERE id = ?1
+            "#,
+            id_str,
+            retiro_id_str,
+            categoria_id_str,
+            tipo_str,
+            transaccion.monto,
+            transaccion.descripcion,
+            cuenta_id_str,
+            metodo_pago_str,
+            conciliacion_str,
+            deleted_at_str
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let accion = conflictos.resolver(existente.map(|r| r.igual), &mut resumen, || {
+            format!("transacción {}", transaccion.id)
+        });
+
+        match accion {
+            Accion::Insertar => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO transacciones (id, retiro_id, categoria_id, tipo, monto, descripcion, cuenta_id, metodo_pago, conciliacion, created_at, updated_at, deleted_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                    "#,
+                    id_str,
+                    retiro_id_str,
+                    categoria_id_str,
+                    tipo_str,
+                    transaccion.monto,
+                    transaccion.descripcion,
+                    cuenta_id_str,
+                    metodo_pago_str,
+                    conciliacion_str,
+                    created_at_str,
+                    updated_at_str,
+                    deleted_at_str
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+            Accion::Sobrescribir => {
+                sqlx::query!(
+                    r#"
+                    UPDATE transacciones
+                    SET retiro_id = ?2, categoria_id = ?3, tipo = ?4, monto = ?5, descripcion = ?6,
+                        cuenta_id = ?7, metodo_pago = ?8, conciliacion = ?9, deleted_at = ?10
+                    WHERE id = ?1
+                    "#,
+                    id_str,
+                    retiro_id_str,
+                    categoria_id_str,
+                    tipo_str,
+                    transaccion.monto,
+                    transaccion.descripcion,
+                    cuenta_id_str,
+                    metodo_pago_str,
+                    conciliacion_str,
+                    deleted_at_str
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+            Accion::SinCambios | Accion::Mantener | Accion::Conflicto => {}
+        }
+    }
+
+    Ok(resumen)
+}
+
+/// Importar las transferencias entre cuentas
+async fn importar_transferencias(
+    tx: &mut Transaction<'_, Sqlite>,
+    dataset: &Dataset,
+    conflictos: &mut Conflictos,
+) -> Result<ResumenEntidad> {
+    let mut resumen = ResumenEntidad::default();
+
+    for transferencia in &dataset.transferencias {
+        let id_str = transferencia.id.to_string();
+        let retiro_id_str = transferencia.retiro_id.to_string();
+        let cuenta_origen_id_str = transferencia.cuenta_origen_id.to_string();
+        let cuenta_destino_id_str = transferencia.cuenta_destino_id.to_string();
+        let created_at_str = transferencia.created_at.to_rfc3339();
+
+        let existente = sqlx::query!(
+            r#"
+            SELECT (
+                retiro_id = ?2 AND cuenta_origen_id = ?3 AND cuenta_destino_id = ?4
+                AND monto = ?5 AND descripcion = ?6
+            ) as "igual!: bool"
+            FROM transferencias WHERE id = ?1
+            "#,
+            id_str,
+            retiro_id_str,
+            cuenta_origen_id_str,
+            cuenta_destino_id_str,
+            transferencia.monto,
+            transferencia.descripcion
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let accion = conflictos.resolver(existente.map(|r| r.igual), &mut resumen, || {
+            format!("transferencia {}", transferencia.id)
+        });
+
+        match accion {
+            Accion::Insertar => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO transferencias (id, retiro_id, cuenta_origen_id, cuenta_destino_id, monto, descripcion, created_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                    "#,
+                    id_str,
+                    retiro_id_str,
+                    cuenta_origen_id_str,
+                    cuenta_destino_id_str,
+                    transferencia.monto,
+                    transferencia.descripcion,
+                    created_at_str
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+            Accion::Sobrescribir => {
+                sqlx::query!(
+                    r#"
+                    UPDATE transferencias
+                    SET retiro_id = ?2, cuenta_origen_id = ?3, cuenta_destino_id = ?4, monto = ?5, descripcion = ?6
+                    WHERE id = ?1
+                    "#,
+                    id_str,
+                    retiro_id_str,
+                    cuenta_origen_id_str,
+                    cuenta_destino_id_str,
+                    transferencia.monto,
+                    transferencia.descripcion
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+            Accion::SinCambios | Accion::Mantener | Accion::Conflicto => {}
+        }
+    }
+
+    Ok(resumen)
+}
+
+/// Importar los arqueos
+async fn importar_arqueos(
+    tx: &mut Transaction<'_, Sqlite>,
+    dataset: &Dataset,
+    conflictos: &mut Conflictos,
+) -> Result<ResumenEntidad> {
+    let mut resumen = ResumenEntidad::default();
+
+    for arqueo in &dataset.arqueos {
+        let id_str = arqueo.id.to_string();
+        let retiro_id_str = arqueo.retiro_id.to_string();
+        let cuenta_id_str = arqueo.cuenta_id.to_string();
+        let denominaciones_str = denominaciones_json(&arqueo.denominaciones)?;
+        // Un ajuste que no viaja en el volcado se desvincula
+        let transaccion_ajuste_id_str = arqueo
+            .transaccion_ajuste_id
+            .filter(|id| dataset.transacciones.iter().any(|t| t.id == *id))
+            .map(|id| id.to_string());
+        let created_at_str = arqueo.created_at.to_rfc3339();
+
+        let existente = sqlx::query!(
+            r#"
+            SELECT (
+                retiro_id = ?2 AND cuenta_id = ?3 AND saldo_calculado = ?4 AND contado = ?5
+                AND denominaciones IS ?6 AND notas IS ?7 AND transaccion_ajuste_id IS ?8
+            ) as "igual!: bool"
+            FROM arqueos WHERE id = ?1
+            "#,
+            id_str,
+            retiro_id_str,
+            cuenta_id_str,
+            arqueo.saldo_calculado,
+            arqueo.contado,
+            denominaciones_str,
+            arqueo.notas,
+            transaccion_ajuste_id_str
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let accion = conflictos.resolver(existente.map(|r| r.igual), &mut resumen, || {
+            format!("arqueo {}", arqueo.id)
+        });
+
+        match accion {
+            Accion::Insertar => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO arqueos (id, retiro_id, cuenta_id, saldo_calculado, contado, denominaciones, notas, transaccion_ajuste_id, created_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                    "#,
+                    id_str,
+                    retiro_id_str,
+                    cuenta_id_str,
+                    arqueo.saldo_calculado,
+                    arqueo.contado,
+                    denominaciones_str,
+                    arqueo.notas,
+                    transaccion_ajuste_id_str,
+                    created_at_str
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+            Accion::Sobrescribir => {
+                sqlx::query!(
+                    r#"
+                    UPDATE arqueos
+                    SET retiro_id = ?2, cuenta_id = ?3, saldo_calculado = ?4, contado = ?5,
+                        denominaciones = ?6, notas = ?7, transaccion_ajuste_id = ?8
+                    WHERE id = ?1
+                    "#,
+                    id_str,
+                    retiro_id_str,
+                    cuenta_id_str,
+                    arqueo.saldo_calculado,
+                    arqueo.contado,
+                    denominaciones_str,
+                    arqueo.notas,
+                    transaccion_ajuste_id_str
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+            Accion::SinCambios | Accion::Mantener | Accion::Conflicto => {}
+        }
+    }
+
+    Ok(resumen)
+}
+
+/// Importar las líneas de extracto
+async fn importar_lineas_extracto(
+    tx: &mut Transaction<'_, Sqlite>,
+    dataset: &Dataset,
+    conflictos: &mut Conflictos,
+) -> Result<ResumenEntidad> {
+    let mut resumen = ResumenEntidad::default();
+
+    for linea in &dataset.lineas_extracto {
+        let id_str = linea.id.to_string();
+        let retiro_id_str = linea.retiro_id.to_string();
+        let cuenta_id_str = linea.cuenta_id.map(|id| id.to_string());
+        let formato_str = linea.formato.to_string();
+        let fecha_str = linea.fecha.format("%Y-%m-%d").to_string();
+        // Igual que en los arqueos, una transacción que no viaja en el volcado se desvincula
+        let transaccion_id_str = linea
+            .transaccion_id
+            .filter(|id| dataset.transacciones.iter().any(|t| t.id == *id))
+            .map(|id| id.to_string());
+        // Una transacción solo se enlaza con una línea: si ya tiene otra aquí, se conserva esa
+        let transaccion_id_str = match transaccion_id_str {
+            Some(transaccion_id_str) => sqlx::query!(
+                "SELECT id FROM lineas_extracto WHERE transaccion_id = ?1 AND id <> ?2",
+                transaccion_id_str,
+                id_str
+            )
+            .fetch_optional(&mut **tx)
+            .await?
+            .is_none()
+            .then_some(transaccion_id_str),
+            None => None,
+        };
+        let created_at_str = linea.created_at.to_rfc3339();
+
+        let existente = sqlx::query!(
+            r#"
+            SELECT (
+                retiro_id = ?2 AND cuenta_id IS ?3 AND formato = ?4 AND fecha = ?5 AND monto = ?6
+                AND descripcion = ?7 AND referencia IS ?8 AND huella = ?9 AND transaccion_id IS ?10
+            ) as "igual!: bool"
+            FROM lineas_extracto WHERE id = ?1
+            "#,
+            id_str,
+            retiro_id_str,
+            cuenta_id_str,
+            formato_str,
+            fecha_str,
+            linea.monto,
+            linea.descripcion,
+            linea.referencia,
+            linea.huella,
+            transaccion_id_str
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        // El mismo movimiento importado aquí con otro ID ya está cubierto: se mantiene el local
+        if existente.is_none() {
+            let duplicada = sqlx::query!(
+                "SELECT id FROM lineas_extracto WHERE huella = ?1",
+                linea.huella
+            )
+            .fetch_optional(&mut **tx)
+            .await?;
+            if duplicada.is_some() {
+                resumen.registrar(Accion::Mantener);
+                continue;
+            }
+        }
+
+        let accion = conflictos.resolver(existente.map(|r| r.igual), &mut resumen, || {
+            format!("línea de extracto {}", linea.id)
+        });
+
+        match accion {
+            Accion::Insertar => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO lineas_extracto (id, retiro_id, cuenta_id, formato, fecha, monto, descripcion, referencia, huella, transaccion_id, created_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                    "#,
+                    id_str,
+                    retiro_id_str,
+                    cuenta_id_str,
+                    formato_str,
+                    fecha_str,
+                    linea.monto,
+                    linea.descripcion,
+                    linea.referencia,
+                    linea.huella,
+                    transaccion_id_str,
+                    created_at_str
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+            Accion::Sobrescribir => {
+                sqlx::query!(
+                    r#"
+                    UPDATE lineas_extracto
+                    SET retiro_id = ?2, cuenta_id = ?3, formato = ?4, fecha = ?5, monto = ?6,
+                        descripcion = ?7, referencia = ?8, huella = ?9, transaccion_id = ?10
+                    WHERE id = ?1
+                    "#,
+                    id_str,
+                    retiro_id_str,
+                    cuenta_id_str,
+                    formato_str,
+                    fecha_str,
+                    linea.monto,
+                    linea.descripcion,
+                    linea.referencia,
+                    linea.huella,
+                    transaccion_id_str
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+            Accion::SinCambios | Accion::Mantener | Accion::Conflicto => {}
+        }
+    }
+
+    Ok(resumen)
+}
+
+/// Importar las plantillas de transacción
+async fn importar_plantillas(
+    tx: &mut Transaction<'_, Sqlite>,
+    dataset: &Dataset,
+    conflictos: &mut Conflictos,
+) -> Result<ResumenEntidad> {
+    let mut resumen = ResumenEntidad::default();
+
+    for plantilla in &dataset.plantillas {
+        let id_str = plantilla.id.to_string();
+        let categoria_id_str = plantilla.categoria_id.to_string();
+        let tipo_str = plantilla.tipo.to_string();
+        let created_at_str = plantilla.created_at.to_rfc3339();
+        let updated_at_str = plantilla.updated_at.to_rfc3339();
+
+        let existente = sqlx::query!(
+            r#"
+            SELECT (
+                nombre = ?2 AND categoria_id = ?3 AND tipo = ?4 AND monto = ?5 AND descripcion = ?6
+            ) as "igual!: bool"
+            FROM plantillas_transaccion WHERE id = ?1
+            "#,
+            id_str,
+            plantilla.nombre,
+            categoria_id_str,
+            tipo_str,
+            plantilla.monto,
+            plantilla.descripcion
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let accion = conflictos.resolver(existente.map(|r| r.igual), &mut resumen, || {
+            format!("plantilla {}", plantilla.id)
+        });
+
+        match accion {
+            Accion::Insertar => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO plantillas_transaccion (id, nombre, categoria_id, tipo, monto, descripcion, created_at, updated_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                    "#,
+                    id_str,
+                    plantilla.nombre,
+                    categoria_id_str,
+                    tipo_str,
+                    plantilla.monto,
+                    plantilla.descripcion,
+                    created_at_str,
+                    updated_at_str
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+            Accion::Sobrescribir => {
+                sqlx::query!(
+                    r#"
+                    UPDATE plantillas_transaccion
+                    SET nombre = ?2, categoria_id = ?3, tipo = ?4, monto = ?5, descripcion = ?6
+                    WHERE id = ?1
+                    "#,
+                    id_str,
+                    plantilla.nombre,
+                    categoria_id_str,
+                    tipo_str,
+                    plantilla.monto,
+                    plantilla.descripcion
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+            Accion::SinCambios | Accion::Mantener | Accion::Conflicto => {}
+        }
+    }
+
+    Ok(resumen)
+}
+
+/// Importar las reglas de categorización con sus etiquetas ya normalizadas
+async fn importar_reglas(
+    tx: &mut Transaction<'_, Sqlite>,
+    reglas: &[(&ReglaCategoria, String)],
+    conflictos: &mut Conflictos,
+) -> Result<ResumenEntidad> {
+    let mut resumen = ResumenEntidad::default();
+
+    for (regla, etiquetas_str) in reglas {
+        let id_str = regla.id.to_string();
+        let categoria_id_str = regla.categoria_id.to_string();
+        let tipo_str = regla.tipo.to_string();
+        let created_at_str = regla.created_at.to_rfc3339();
+        let updated_at_str = regla.updated_at.to_rfc3339();
+
+        let existente = sqlx::query!(
+            r#"
+            SELECT (
+                nombre = ?2 AND patron = ?3 AND monto_min IS ?4 AND monto_max IS ?5
+                AND tipo = ?6 AND categoria_id = ?7 AND etiquetas = ?8 AND prioridad = ?9
+            ) as "igual!: bool"
+            FROM reglas_categoria WHERE id = ?1
+            "#,
+            id_str,
+            regla.nombre,
+            regla.patron,
+            regla.monto_min,
+            regla.monto_max,
+            tipo_str,
+            categoria_id_str,
+            etiquetas_str,
+            regla.prioridad
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        let accion = conflictos.resolver(existente.map(|r| r.igual), &mut resumen, || {
+            format!("regla {}", regla.id)
+        });
+
+        match accion {
+            Accion::Insertar => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO reglas_categoria (id, nombre, patron, monto_min, monto_max, tipo, categoria_id, etiquetas, prioridad, created_at, updated_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+                    "#,
+                    id_str,
+                    regla.nombre,
+                    regla.patron,
+                    regla.monto_min,
+                    regla.monto_max,
+                    tipo_str,
+                    categoria_id_str,
+                    etiquetas_str,
+                    regla.prioridad,
+                    created_at_str,
+                    updated_at_str
+                )
+                .execute(&mut **tx)
+                .await
+                .map_err(|e| error_regla_duplicada(e, &regla.nombre))?;
+            }
+            Accion::Sobrescribir => {
+                sqlx::query!(
+                    r#"
+                    UPDATE reglas_categoria
+                    SET nombre = ?2, patron = ?3, monto_min = ?4, monto_max = ?5,
+                        tipo = ?6, categoria_id = ?7, etiquetas = ?8, prioridad = ?9
+                    WHERE id = ?1
+                    "#,
+                    id_str,
+                    regla.nombre,
+                    regla.patron,
+                    regla.monto_min,
+                    regla.monto_max,
+                    tipo_str,
+                    categoria_id_str,
+                    etiquetas_str,
+                    regla.prioridad
+                )
+                .execute(&mut **tx)
+                .await
+                .map_err(|e| error_regla_duplicada(e, &regla.nombre))?;
+            }
+            Accion::SinCambios | Accion::Mantener | Accion::Conflicto => {}
+        }
+    }
+
+    Ok(resumen)
+}
+
+/// Importar los datos de los comprobantes
+async fn importar_adjuntos(
+    tx: &mut Transaction<'_, Sqlite>,
+    dataset: &Dataset,
+    almacen: Option<&AlmacenAdjuntos>,
+    conflictos: &mut Conflictos,
+) -> Result<(ResumenEntidad, usize)> {
+    let mut resumen = ResumenEntidad::default();
+    let mut sin_archivo = 0;
+
+    for adjunto in &dataset.adjuntos {
+        // Un comprobante cuyo archivo no está en este almacén quedaría roto: no se importa
+        if !almacen.is_some_and(|almacen| almacen.ruta(&adjunto.hash).exists()) {
+            sin_archivo += 1;
+            continue;
+        }
+
+        let id_str = adjunto.id.to_string();
+        let transaccion_id_str = adjunto.transaccion_id.to_string();
+        let created_at_str = adjunto.created_at.to_rfc3339();
+
+        let existente = sqlx::query!(
+            r#"
+            SELECT (
+                transaccion_id = ?2 AND nombre = ?3 AND tipo_mime = ?4 AND hash = ?5 AND tamano = ?6
+            ) as "igual!: bool"
+            FROM adjuntos WHERE id = ?1
+            "#,
+            id_str,
+            transaccion_id_str,
+            adjunto.nombre,
+            adjunto.tipo_mime,
+            adjunto.hash,
+            adjunto.tamano
+        )
+        .fetch_optional(&mut **tx)
+        .await?;
+
+        // El mismo archivo ya adjunto aquí a la transacción con otro ID: se mantiene el local
+        if existente.is_none() {
+            let duplicado = sqlx::query!(
+                "SELECT id FROM adjuntos WHERE transaccion_id = ?1 AND hash = ?2",
+                transaccion_id_str,
+                adjunto.hash
+            )
+            .fetch_optional(&mut **tx)
+            .await?;
+            if duplicado.is_some() {
+                resumen.registrar(Accion::Mantener);
+                continue;
+            }
+        }
+
+        let accion = conflictos.resolver(existente.map(|r| r.igual), &mut resumen, || {
+            format!("adjunto {}", adjunto.id)
+        });
+
+        match accion {
+            Accion::Insertar => {
+                sqlx::query!(
+                    r#"
+                    INSERT INTO adjuntos (id, transaccion_id, nombre, tipo_mime, hash, tamano, created_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                    "#,
+                    id_str,
+                    transaccion_id_str,
+                    adjunto.nombre,
+                    adjunto.tipo_mime,
+                    adjunto.hash,
+                    adjunto.tamano,
+                    created_at_str
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+            Accion::Sobrescribir => {
+                sqlx::query!(
+                    r#"
+                    UPDATE adjuntos
+                    SET transaccion_id = ?2, nombre = ?3, tipo_mime = ?4, hash = ?5, tamano = ?6
+                    WHERE id = ?1
+                    "#,
+                    id_str,
+                    transaccion_id_str,
+                    adjunto.nombre,
+                    adjunto.tipo_mime,
+                    adjunto.hash,
+                    adjunto.tamano
+                )
+                .execute(&mut **tx)
+                .await?;
+            }
+            Accion::SinCambios | Accion::Mantener | Accion::Conflicto => {}
+        }
+    }
+
+    Ok((resumen, sin_archivo))
+}
+
+/// Modo de importación
+#[derive(Debug, Clone, Copy)]
+pub enum ModoImportacion {
+    /// Añadir a los datos existentes
+    Fusionar,
+    /// Borrar todos los datos existentes antes de importar
+    Reemplazar,
+}
+
+/// Qué hacer cuando un ID ya existe con datos distintos
+#[derive(Debug, Clone, Copy)]
+pub enum ResolucionConflicto {
+    /// Cancelar la importación y listar los conflictos
+    Abortar,
+    /// Conservar los datos existentes
+    Mantener,
+    /// Sobrescribir con los datos importados
+    Sobrescribir,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Accion {
+    Insertar,
+    SinCambios,
+    Sobrescribir,
+    Mantener,
+    Conflicto,
+}
+
+impl Accion {
+    /// `existente` es `None` si el ID no existe, o si existe, si sus datos coinciden
+    fn resolver(existente: Option<bool>, conflictos: ResolucionConflicto) -> Self {
+        match (existente, conflictos) {
+            (None, _) => Accion::Insertar,
+            (Some(true), _) => Accion::SinCambios,
+            (Some(false), ResolucionConflicto::Abortar) => Accion::Conflicto,
+            (Some(false), ResolucionConflicto::Mantener) => Accion::Mantener,
+            (Some(false), ResolucionConflicto::Sobrescribir) => Accion::Sobrescribir,
+        }
+    }
+}
+
+/// Recuento de la importación de un tipo de entidad
+#[derive(Debug, Clone, Default)]
+pub struct ResumenEntidad {
+    pub insertados: usize,
+    pub sin_cambios: usize,
+    pub sobrescritos: usize,
+    pub omitidos: usize,
+}
+
+impl ResumenEntidad {
+    fn registrar(&mut self, accion: Accion) {
+        match accion {
+            Accion::Insertar => self.insertados += 1,
+            Accion::SinCambios => self.sin_cambios += 1,
+            Accion::Sobrescribir => self.sobrescritos += 1,
+            Accion::Mantener => self.omitidos += 1,
+            Accion::Conflicto => {}
+        }
+    }
+}
+
+/// Resultado de una importación
+#[derive(Debug, Clone, Default)]
+pub struct ResumenImportacion {
+    pub categorias: ResumenEntidad,
+    pub retiros: ResumenEntidad,
+    pub transacciones: ResumenEntidad,
+    pub plantillas: ResumenEntidad,
+    /// Asignaciones de etiquetas a transacciones
+    pub etiquetas: ResumenEntidad,
+    pub cuentas: ResumenEntidad,
+    pub transferencias: ResumenEntidad,
+    pub arqueos: ResumenEntidad,
+    pub lineas_extracto: ResumenEntidad,
+    pub reglas: ResumenEntidad,
+    pub adjuntos: ResumenEntidad,
+    /// Comprobantes no importados porque su archivo no está en el almacén de adjuntos
+    pub adjuntos_sin_archivo: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        CreateCategoria, CreateRetiro, CreateTransaccion, EnPapelera, TipoCategoria,
+        TipoTransaccion,
+    };
+    use crate::repositories::pool_de_prueba;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    /// Base de datos con una categoría, un retiro y una transacción
+    async fn pool_con_datos() -> SqlitePool {
+        let pool = pool_de_prueba().await;
+        let categoria = CategoriaRepository::new(pool.clone())
+            .create(CreateCategoria {
+                nombre: "Comida".to_string(),
+                tipo: TipoCategoria::Gasto,
+                color: "#FF0000".to_string(),
+                padre_id: None,
+            })
+            .await
+            .unwrap();
+        let retiro = RetiroRepository::new(pool.clone())
+            .create(CreateRetiro {
+                nombre: "Retiro de otoño".to_string(),
+                descripcion: None,
+                fecha_inicio: Utc.with_ymd_and_hms(2026, 10, 12, 0, 0, 0).unwrap(),
+                fecha_fin: Utc.with_ymd_and_hms(2026, 10, 14, 0, 0, 0).unwrap(),
+                ubicacion: None,
+                numero_participantes: 10,
+            })
+            .await
+            .unwrap();
+        TransaccionRepository::new(pool.clone())
+            .create(CreateTransaccion {
+                retiro_id: retiro.id,
+                categoria_id: categoria.id,
+                tipo: TipoTransaccion::Gasto,
+                monto: 45.5,
+                descripcion: "Compra del desayuno".to_string(),
+                cuenta_id: None,
+                metodo_pago: None,
+            })
+            .await
+            .unwrap();
+        pool
+    }
+
+    async fn color(pool: &SqlitePool, id: Uuid) -> String {
+        CategoriaRepository::new(pool.clone())
+            .get_by_id(id)
+            .await
+            .unwrap()
+            .unwrap()
+            .color
+    }
+
+    #[test]
+    fn resuelve_cada_caso_segun_el_modo_de_conflicto() {
+        use ResolucionConflicto::*;
+
+        for modo in [Abortar, Mantener, Sobrescribir] {
+            assert!(matches!(Accion::resolver(None, modo), Accion::Insertar));
+            assert!(matches!(
+                Accion::resolver(Some(true), modo),
+                Accion::SinCambios
+            ));
+        }
+        assert!(matches!(
+            Accion::resolver(Some(false), Abortar),
+            Accion::Conflicto
+        ));
+        assert!(matches!(
+            Accion::resolver(Some(false), Mantener),
+            Accion::Mantener
+        ));
+        assert!(matches!(
+            Accion::resolver(Some(false), Sobrescribir),
+            Accion::Sobrescribir
+        ));
+    }
+
+    #[tokio::test]
+    async fn fusionar_el_mismo_volcado_no_cambia_nada() {
+        let pool = pool_con_datos().await;
+        let repo = DatasetRepository::new(pool.clone());
+        let dataset = repo.export().await.unwrap();
+
+        let resumen = repo
+            .import(
+                dataset,
+                ModoImportacion::Fusionar,
+                ResolucionConflicto::Abortar,
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(resumen.categorias.sin_cambios, 1);
+        assert_eq!(resumen.retiros.sin_cambios, 1);
+        assert_eq!(resumen.transacciones.sin_cambios, 1);
+        assert_eq!(resumen.transacciones.insertados, 0);
+    }
+
+    #[tokio::test]
+    async fn los_conflictos_se_abortan_mantienen_o_sobrescriben() {
+        let pool = pool_con_datos().await;
+        let repo = DatasetRepository::new(pool.clone());
+        let mut dataset = repo.export().await.unwrap();
+        let categoria_id = dataset.categorias[0].id;
+        dataset.categorias[0].color = "#00FF00".to_string();
+
+        // Abortar: error con el conflicto listado y sin aplicar nada
+        let error = repo
+            .import(
+                dataset.clone(),
+                ModoImportacion::Fusionar,
+                ResolucionConflicto::Abortar,
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains(&format!("categoría {}", categoria_id)));
+        assert_eq!(color(&pool, categoria_id).await, "#FF0000");
+
+        let resumen = repo
+            .import(
+                dataset.clone(),
+                ModoImportacion::Fusionar,
+                ResolucionConflicto::Mantener,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(resumen.categorias.omitidos, 1);
+        assert_eq!(color(&pool, categoria_id).await, "#FF0000");
+
+        let resumen = repo
+            .import(
+                dataset,
+                ModoImportacion::Fusionar,
+                ResolucionConflicto::Sobrescribir,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(resumen.categorias.sobrescritos, 1);
+        assert_eq!(resumen.transacciones.sin_cambios, 1);
+        assert_eq!(color(&pool, categoria_id).await, "#00FF00");
+    }
+
+    #[tokio::test]
+    async fn reemplazar_borra_lo_que_no_esta_en_el_volcado() {
+        let origen = DatasetRepository::new(pool_con_datos().await);
+        let dataset = origen.export().await.unwrap();
+
+        let pool = pool_con_datos().await;
+        let repo = DatasetRepository::new(pool.clone());
+        let resumen = repo
+            .import(
+                dataset.clone(),
+                ModoImportacion::Reemplazar,
+                ResolucionConflicto::Abortar,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(resumen.categorias.insertados, 1);
+        assert_eq!(resumen.transacciones.insertados, 1);
+
+        let importado = repo.export().await.unwrap();
+        let ids = |d: &Dataset| d.transacciones.iter().map(|t| t.id).collect::<Vec<_>>();
+        assert_eq!(ids(&importado), ids(&dataset));
+        assert_eq!(importado.retiros.len(), 1);
+        assert_eq!(importado.retiros[0].id, dataset.retiros[0].id);
+    }
+
+    #[tokio::test]
+    async fn la_papelera_sobrevive_a_reemplazar() {
+        let pool = pool_con_datos().await;
+        let repo = DatasetRepository::new(pool.clone());
+        let retiro_id = RetiroRepository::new(pool.clone()).get_all().await.unwrap()[0].id;
+        RetiroRepository::new(pool.clone())
+            .delete(retiro_id)
+            .await
+            .unwrap();
+
+        let dataset = repo.export().await.unwrap();
+        assert_eq!(dataset.retiros.len(), 1);
+        assert_eq!(dataset.transacciones.len(), 1);
+        assert_eq!(dataset.papelera.len(), 2);
+
+        repo.import(
+            dataset.clone(),
+            ModoImportacion::Reemplazar,
+            ResolucionConflicto::Abortar,
+            None,
+        )
+        .await
+        .unwrap();
+        let resumen = repo
+            .import(
+                dataset,
+                ModoImportacion::Fusionar,
+                ResolucionConflicto::Abortar,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(resumen.retiros.sin_cambios, 1);
+        assert_eq!(resumen.transacciones.sin_cambios, 1);
+
+        // El retiro sigue en la papelera y se restaura junto con su transacción
+        let papelera = PapeleraRepository::new(pool.clone());
+        assert_eq!(papelera.listar().await.unwrap().len(), 1);
+        papelera.restaurar(retiro_id).await.unwrap();
+        assert_eq!(
+            TransaccionRepository::new(pool)
+                .get_by_retiro(retiro_id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn rechaza_papelera_de_elementos_que_no_estan_en_el_volcado() {
+        let repo = DatasetRepository::new(pool_con_datos().await);
+        let mut dataset = repo.export().await.unwrap();
+        dataset.papelera.push(EnPapelera {
+            tipo: TipoElementoPapelera::Retiro,
+            id: Uuid::new_v4(),
+            deleted_at: Utc::now(),
+        });
+
+        assert!(repo
+            .import(
+                dataset,
+                ModoImportacion::Fusionar,
+                ResolucionConflicto::Abortar,
+                None
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn los_comprobantes_se_enlazan_solo_si_su_archivo_esta_en_el_almacen() {
+        let directorio = std::env::temp_dir().join(format!("adjuntos-{}", Uuid::new_v4()));
+        let almacen = AlmacenAdjuntos::new(&format!(
+            "sqlite:{}",
+            directorio.join("retiros.db").display()
+        ))
+        .unwrap();
+        let origen = pool_con_datos().await;
+        let transaccion_id = TransaccionRepository::new(origen.clone())
+            .get_all()
+            .await
+            .unwrap()[0]
+            .id;
+        AdjuntoRepository::new(origen.clone())
+            .adjuntar(&almacen, transaccion_id, "ticket.pdf", b"%PDF-1.4 ticket")
+            .await
+            .unwrap();
+        let dataset = DatasetRepository::new(origen).export().await.unwrap();
+        assert_eq!(dataset.adjuntos.len(), 1);
+
+        let pool = pool_de_prueba().await;
+        let repo = DatasetRepository::new(pool.clone());
+        let resumen = repo
+            .import(
+                dataset.clone(),
+                ModoImportacion::Reemplazar,
+                ResolucionConflicto::Abortar,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(resumen.adjuntos_sin_archivo, 1);
+        assert_eq!(resumen.adjuntos.omitidos, 0);
+        assert!(AdjuntoRepository::new(pool.clone())
+            .get_all()
+            .await
+            .unwrap()
+            .is_empty());
+
+        let resumen = repo
+            .import(
+                dataset.clone(),
+                ModoImportacion::Fusionar,
+                ResolucionConflicto::Abortar,
+                Some(&almacen),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resumen.adjuntos.insertados, 1);
+        assert_eq!(
+            AdjuntoRepository::new(pool)
+                .get_by_transaccion(transaccion_id)
+                .await
+                .unwrap()[0]
+                .hash,
+            dataset.adjuntos[0].hash
+        );
+
+        // Un hash que no es SHA-256 no llega a usarse como ruta
+        let mut dataset = dataset;
+        dataset.adjuntos[0].hash = "../../etc".to_string();
+        assert!(repo
+            .import(
+                dataset,
+                ModoImportacion::Fusionar,
+                ResolucionConflicto::Abortar,
+                Some(&almacen)
+            )
+            .await
+            .is_err());
+
+        std::fs::remove_dir_all(directorio).unwrap();
+    }
+
+    #[tokio::test]
+    async fn rechaza_versiones_desconocidas() {
+        let repo = DatasetRepository::new(pool_con_datos().await);
+        let mut dataset = repo.export().await.unwrap();
+        dataset.version = DATASET_VERSION + 1;
+
+        assert!(repo
+            .import(
+                dataset,
+                ModoImportacion::Fusionar,
+                ResolucionConflicto::Abortar,
+                None
+            )
+            .await
+            .is_err());
+    }
+}
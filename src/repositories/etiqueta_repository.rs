@@ -0,0 +1,279 @@
+use crate::models::{normalizar_etiquetas, Etiqueta, EtiquetaTransaccion, TotalEtiqueta};
+use crate::{AppError, Result};
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+pub struct EtiquetaRepository {
+    pool: SqlitePool,
+}
+
+impl EtiquetaRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Todas las etiquetas con el número de transacciones activas que las usan
+    pub async fn get_all(&self) -> Result<Vec<Etiqueta>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT e.id, e.nombre,
+                COUNT(t.id) as "transacciones!: i64"
+            FROM etiquetas e
+            LEFT JOIN transaccion_etiquetas te ON te.etiqueta_id = e.id
+            LEFT JOIN transacciones t ON t.id = te.transaccion_id AND t.deleted_at IS NULL
+            GROUP BY e.id, e.nombre
+            ORDER BY e.nombre COLLATE NOCASE
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut etiquetas = Vec::new();
+        for row in rows {
+            etiquetas.push(Etiqueta {
+                id: Uuid::parse_str(&row.id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                nombre: row.nombre,
+                transacciones: row.transacciones,
+            });
+        }
+
+        Ok(etiquetas)
+    }
+
+    /// Todas las asignaciones de etiquetas, también las de transacciones en la papelera, para exportar
+    pub async fn get_asignaciones(&self) -> Result<Vec<EtiquetaTransaccion>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT te.transaccion_id, e.nombre
+            FROM transaccion_etiquetas te
+            JOIN etiquetas e ON e.id = te.etiqueta_id
+            ORDER BY te.transaccion_id, e.nombre COLLATE NOCASE
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut asignaciones = Vec::new();
+        for row in rows {
+            asignaciones.push(EtiquetaTransaccion {
+                transaccion_id: Uuid::parse_str(&row.transaccion_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                etiqueta: row.nombre,
+            });
+        }
+
+        Ok(asignaciones)
+    }
+
+    /// Etiquetas de una transacción, por orden alfabético
+    pub async fn get_by_transaccion(&self, transaccion_id: Uuid) -> Result<Vec<String>> {
+        let transaccion_id_str = transaccion_id.to_string();
+        let rows = sqlx::query!(
+            r#"
+            SELECT e.nombre
+            FROM transaccion_etiquetas te
+            JOIN etiquetas e ON e.id = te.etiqueta_id
+            WHERE te.transaccion_id = ?1
+            ORDER BY e.nombre COLLATE NOCASE
+            "#,
+            transaccion_id_str
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.nombre).collect())
+    }
+
+    /// Etiquetas de las transacciones activas de un retiro, agrupadas por transacción
+    pub async fn get_by_retiro(&self, retiro_id: Uuid) -> Result<HashMap<Uuid, Vec<String>>> {
+        let retiro_id_str = retiro_id.to_string();
+        let rows = sqlx::query!(
+            r#"
+            SELECT te.transaccion_id, e.nombre
+            FROM transaccion_etiquetas te
+            JOIN etiquetas e ON e.id = te.etiqueta_id
+            JOIN transacciones t ON t.id = te.transaccion_id
+            WHERE t.retiro_id = ?1 AND t.deleted_at IS NULL
+            ORDER BY e.nombre COLLATE NOCASE
+            "#,
+            retiro_id_str
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut por_transaccion: HashMap<Uuid, Vec<String>> = HashMap::new();
+        for row in rows {
+            let transaccion_id = Uuid::parse_str(&row.transaccion_id)
+                .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?;
+            por_transaccion
+                .entry(transaccion_id)
+                .or_default()
+                .push(row.nombre);
+        }
+
+        Ok(por_transaccion)
+    }
+
+    /// Añadir etiquetas a una transacción; las que no existen se crean.
+    /// Devuelve las etiquetas resultantes de la transacción.
+    pub async fn agregar(&self, transaccion_id: Uuid, etiquetas: &[String]) -> Result<Vec<String>> {
+        let etiquetas = normalizar_etiquetas(etiquetas)?;
+        self.validar_transaccion(transaccion_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+        for etiqueta in &etiquetas {
+            asignar(&mut tx, transaccion_id, etiqueta).await?;
+        }
+        tx.commit().await?;
+
+        self.get_by_transaccion(transaccion_id).await
+    }
+
+    /// Quitar etiquetas de una transacción.
+    /// Devuelve las etiquetas resultantes de la transacción.
+    pub async fn quitar(&self, transaccion_id: Uuid, etiquetas: &[String]) -> Result<Vec<String>> {
+        let etiquetas = normalizar_etiquetas(etiquetas)?;
+        self.validar_transaccion(transaccion_id).await?;
+
+        let transaccion_id_str = transaccion_id.to_string();
+        let mut tx = self.pool.begin().await?;
+        for etiqueta in &etiquetas {
+            sqlx::query!(
+                r#"
+                DELETE FROM transaccion_etiquetas
+                WHERE transaccion_id = ?1
+                  AND etiqueta_id = (SELECT id FROM etiquetas WHERE nombre = ?2)
+                "#,
+                transaccion_id_str,
+                etiqueta
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        borrar_sin_uso(&mut tx).await?;
+        tx.commit().await?;
+
+        self.get_by_transaccion(transaccion_id).await
+    }
+
+    /// Sustituir todas las etiquetas de una transacción
+    pub async fn reemplazar(
+        &self,
+        transaccion_id: Uuid,
+        etiquetas: &[String],
+    ) -> Result<Vec<String>> {
+        let etiquetas = normalizar_etiquetas(etiquetas)?;
+        self.validar_transaccion(transaccion_id).await?;
+
+        let transaccion_id_str = transaccion_id.to_string();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            "DELETE FROM transaccion_etiquetas WHERE transaccion_id = ?1",
+            transaccion_id_str
+        )
+        .execute(&mut *tx)
+        .await?;
+        for etiqueta in &etiquetas {
+            asignar(&mut tx, transaccion_id, etiqueta).await?;
+        }
+        borrar_sin_uso(&mut tx).await?;
+        tx.commit().await?;
+
+        self.get_by_transaccion(transaccion_id).await
+    }
+
+    /// Totales de ingresos y gastos por etiqueta de un retiro, o de todos si no se indica.
+    /// Ordenados de mayor a menor gasto.
+    pub async fn get_totales(&self, retiro_id: Option<Uuid>) -> Result<Vec<TotalEtiqueta>> {
+        let retiro_id_str = retiro_id.map(|id| id.to_string());
+        let rows = sqlx::query!(
+            r#"
+            SELECT e.nombre,
+                COALESCE(SUM(CASE WHEN t.tipo = 'Ingreso' THEN t.monto END), 0.0) as "ingresos!: f64",
+                COALESCE(SUM(CASE WHEN t.tipo = 'Gasto' THEN t.monto END), 0.0) as "gastos!: f64",
+                COUNT(*) as "transacciones!: i64"
+            FROM etiquetas e
+            JOIN transaccion_etiquetas te ON te.etiqueta_id = e.id
+            JOIN transacciones t ON t.id = te.transaccion_id
+            WHERE t.deleted_at IS NULL
+              AND (?1 IS NULL OR t.retiro_id = ?1)
+            GROUP BY e.id, e.nombre
+            ORDER BY 3 DESC, e.nombre COLLATE NOCASE
+            "#,
+            retiro_id_str
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| TotalEtiqueta {
+                etiqueta: row.nombre,
+                ingresos: row.ingresos,
+                gastos: row.gastos,
+                transacciones: row.transacciones,
+            })
+            .collect())
+    }
+
+    async fn validar_transaccion(&self, transaccion_id: Uuid) -> Result<()> {
+        let transaccion_id_str = transaccion_id.to_string();
+        let existe = sqlx::query!(
+            "SELECT id FROM transacciones WHERE id = ?1 AND deleted_at IS NULL",
+            transaccion_id_str
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match existe {
+            Some(_) => Ok(()),
+            None => Err(AppError::NotFound("Transacción".to_string())),
+        }
+    }
+}
+
+/// Asignar una etiqueta (ya normalizada) a una transacción, creándola si no existe.
+/// Se conserva la grafía con la que se creó la etiqueta.
+pub(crate) async fn asignar(
+    tx: &mut Transaction<'_, Sqlite>,
+    transaccion_id: Uuid,
+    etiqueta: &str,
+) -> Result<bool> {
+    let nuevo_id = Uuid::new_v4().to_string();
+    sqlx::query!(
+        "INSERT OR IGNORE INTO etiquetas (id, nombre) VALUES (?1, ?2)",
+        nuevo_id,
+        etiqueta
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    let transaccion_id_str = transaccion_id.to_string();
+    let result = sqlx::query!(
+        r#"
+        INSERT OR IGNORE INTO transaccion_etiquetas (transaccion_id, etiqueta_id)
+        SELECT ?1, id FROM etiquetas WHERE nombre = ?2
+        "#,
+        transaccion_id_str,
+        etiqueta
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Borrar las etiquetas que ya no tiene ninguna transacción
+async fn borrar_sin_uso(tx: &mut Transaction<'_, Sqlite>) -> Result<()> {
+    sqlx::query!(
+        r#"
+        DELETE FROM etiquetas
+        WHERE NOT EXISTS (SELECT 1 FROM transaccion_etiquetas te WHERE te.etiqueta_id = etiquetas.id)
+        "#
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,1196 @@
+use crate::models::{
+    huella_movimiento, normalizar_etiquetas, similitud_descripcion, AccionExtracto,
+    CandidatoExtracto, CreateTransaccion, DecisionExtracto, EstadoCandidato, EstadoConciliacion,
+    FormatoExtracto, InformeConciliacion, LineaExtracto, MovimientoExtracto, ResumenExtracto,
+    TipoTransaccion, Transaccion,
+};
+use crate::repositories::{
+    asignar, insertar_transaccion, parse_flexible_datetime, CuentaRepository, ReglaRepository,
+    TransaccionRepository,
+};
+use crate::{AppError, Result};
+use chrono::{NaiveDate, Utc};
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Opciones para preparar la importación de un extracto
+#[derive(Debug, Clone)]
+pub struct OpcionesExtracto {
+    pub retiro_id: Uuid,
+    /// Cuenta bancaria del extracto; también limita las coincidencias a sus transacciones
+    pub cuenta_id: Option<Uuid>,
+    /// Categoría de las transacciones nuevas de cada tipo
+    pub categoria_ingreso_id: Option<Uuid>,
+    pub categoria_gasto_id: Option<Uuid>,
+    /// Días de diferencia admitidos entre el movimiento y la transacción registrada
+    pub dias: u32,
+}
+
+pub struct ExtractoRepository {
+    pool: SqlitePool,
+}
+
+impl ExtractoRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Comparar los movimientos de un extracto con lo ya registrado en el retiro.
+    /// Cada transacción coincide como mucho con un movimiento: el mismo importe y tipo,
+    /// una fecha dentro del margen y, a igualdad, la descripción más parecida.
+    pub async fn preparar(
+        &self,
+        formato: FormatoExtracto,
+        movimientos: &[MovimientoExtracto],
+        opciones: &OpcionesExtracto,
+    ) -> Result<Vec<CandidatoExtracto>> {
+        self.validar_opciones(opciones).await?;
+
+        let importadas: HashMap<String, Option<Uuid>> = self
+            .get_lineas(None)
+            .await?
+            .into_iter()
+            .map(|linea| (linea.huella, linea.transaccion_id))
+            .collect();
+
+        // Transacciones que aún no corresponden a ninguna línea de extracto ni están cerradas
+        let registradas: Vec<Transaccion> = TransaccionRepository::new(self.pool.clone())
+            .get_by_retiro(opciones.retiro_id)
+            .await?
+            .into_iter()
+            .filter(|t| t.linea_extracto_id.is_none())
+            .filter(|t| t.conciliacion != EstadoConciliacion::Conciliada)
+            .filter(|t| {
+                opciones.cuenta_id.is_none()
+                    || t.cuenta_id.is_none()
+                    || t.cuenta_id == opciones.cuenta_id
+            })
+            .collect();
+
+        let reglas = ReglaRepository::new(self.pool.clone()).motor().await?;
+
+        let mut candidatos = Vec::new();
+        let mut ocurrencias: HashMap<String, usize> = HashMap::new();
+        for movimiento in movimientos {
+            let clave = huella_movimiento(opciones.cuenta_id, movimiento, 0);
+            let ocurrencia = ocurrencias.entry(clave).or_default();
+            let huella = huella_movimiento(opciones.cuenta_id, movimiento, *ocurrencia);
+            *ocurrencia += 1;
+
+            // Una regla que coincide tiene preferencia sobre la categoría por defecto del tipo
+            let tipo = movimiento.tipo();
+            let regla =
+                reglas.evaluar(&movimiento.descripcion, Some(movimiento.monto), Some(&tipo));
+            let categoria_id = match (regla, &tipo) {
+                (Some(regla), _) => Some(regla.categoria_id),
+                (None, TipoTransaccion::Ingreso) => opciones.categoria_ingreso_id,
+                (None, TipoTransaccion::Gasto) => opciones.categoria_gasto_id,
+            };
+            let (regla, etiquetas) = match regla {
+                Some(regla) => (Some(regla.nombre.clone()), regla.etiquetas.clone()),
+                None => (None, Vec::new()),
+            };
+            let propuesta = categoria_id.map(|categoria_id| CreateTransaccion {
+                retiro_id: opciones.retiro_id,
+                categoria_id,
+                tipo,
+                monto: movimiento.monto.abs(),
+                descripcion: movimiento.descripcion.clone(),
+                cuenta_id: opciones.cuenta_id,
+                metodo_pago: None,
+            });
+
+            let (estado, transaccion_id) = match importadas.get(&huella) {
+                Some(transaccion_id) => (EstadoCandidato::YaImportada, *transaccion_id),
+                None => (EstadoCandidato::Nueva, None),
+            };
+
+            candidatos.push(CandidatoExtracto {
+                retiro_id: opciones.retiro_id,
+                cuenta_id: opciones.cuenta_id,
+                formato,
+                fecha: movimiento.fecha,
+                monto: movimiento.monto,
+                descripcion: movimiento.descripcion.clone(),
+                referencia: movimiento.referencia.clone(),
+                huella,
+                estado,
+                transaccion_id,
+                puntuacion: None,
+                propuesta,
+                regla,
+                etiquetas,
+            });
+        }
+
+        // Todas las parejas posibles, de la más parecida a la menos
+        let mut parejas = Vec::new();
+        for (i, candidato) in candidatos.iter().enumerate() {
+            if candidato.estado != EstadoCandidato::Nueva {
+                continue;
+            }
+            for (j, transaccion) in registradas.iter().enumerate() {
+                if let Some(puntuacion) = puntuar(candidato, transaccion, opciones.dias) {
+                    parejas.push((puntuacion, i, j));
+                }
+            }
+        }
+        parejas.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let mut usadas = HashSet::new();
+        for (puntuacion, i, j) in parejas {
+            if candidatos[i].estado != EstadoCandidato::Nueva || usadas.contains(&j) {
+                continue;
+            }
+            usadas.insert(j);
+            candidatos[i].estado = EstadoCandidato::Coincide;
+            candidatos[i].transaccion_id = Some(registradas[j].id);
+            candidatos[i].puntuacion = Some((puntuacion * 100.0).round() / 100.0);
+        }
+
+        Ok(candidatos)
+    }
+
+    /// Aplicar las decisiones de la revisión en una única transacción SQL:
+    /// crear las transacciones nuevas y enlazar las líneas con las que ya existían.
+    /// Las líneas omitidas también se guardan: quedan como movimientos del banco sin enlazar.
+    pub async fn importar(&self, decisiones: Vec<DecisionExtracto>) -> Result<ResumenExtracto> {
+        let mut tx = self.pool.begin().await?;
+        let mut resumen = ResumenExtracto::default();
+
+        for decision in decisiones {
+            let candidato = decision.candidato;
+            let existente = sqlx::query!(
+                "SELECT id FROM lineas_extracto WHERE huella = ?1",
+                candidato.huella
+            )
+            .fetch_optional(&mut *tx)
+            .await?;
+            if candidato.estado == EstadoCandidato::YaImportada || existente.is_some() {
+                resumen.ya_importadas += 1;
+                continue;
+            }
+
+            match decision.accion {
+                AccionExtracto::Omitir => {
+                    insertar_linea(&mut tx, &candidato, None).await?;
+                    resumen.omitidas += 1;
+                }
+                AccionExtracto::Importar => {
+                    let propuesta = candidato.propuesta.clone().ok_or_else(|| {
+                        AppError::Validation(format!(
+                            "Falta la categoría de {} para '{}'",
+                            if candidato.monto >= 0.0 {
+                                "ingresos"
+                            } else {
+                                "gastos"
+                            },
+                            candidato.descripcion
+                        ))
+                    })?;
+                    propuesta
+                        .validate()
+                        .map_err(|e| AppError::Validation(e.to_string()))?;
+                    let mut transaccion = Transaccion::new(propuesta);
+                    transaccion.conciliacion = EstadoConciliacion::Punteada;
+                    // La fecha de una transacción es su created_at: se toma la del banco
+                    transaccion.created_at =
+                        candidato.fecha.and_hms_opt(0, 0, 0).unwrap().and_utc();
+                    insertar_transaccion(&mut tx, &transaccion).await?;
+                    for etiqueta in normalizar_etiquetas(&candidato.etiquetas)? {
+                        asignar(&mut tx, transaccion.id, &etiqueta).await?;
+                    }
+                    insertar_linea(&mut tx, &candidato, Some(transaccion.id)).await?;
+                    resumen.creadas += 1;
+                }
+                AccionExtracto::Vincular => {
+                    let transaccion_id = candidato
+                        .transaccion_id
+                        .filter(|_| candidato.estado == EstadoCandidato::Coincide)
+                        .ok_or_else(|| {
+                            AppError::Validation(format!(
+                                "'{}' no coincide con ninguna transacción registrada",
+                                candidato.descripcion
+                            ))
+                        })?;
+                    // La transacción pudo cambiar o enlazarse con otra línea desde la revisión
+                    comprobar_enlace(
+                        &mut tx,
+                        candidato.retiro_id,
+                        candidato.monto,
+                        transaccion_id,
+                    )
+                    .await?;
+                    insertar_linea(&mut tx, &candidato, Some(transaccion_id)).await?;
+                    puntear(&mut tx, transaccion_id).await?;
+                    resumen.vinculadas += 1;
+                }
+            }
+        }
+
+        tx.commit().await?;
+
+        Ok(resumen)
+    }
+
+    /// Líneas importadas, de la más reciente a la más antigua, opcionalmente de un retiro
+    pub async fn get_lineas(&self, retiro_id: Option<Uuid>) -> Result<Vec<LineaExtracto>> {
+        self.buscar_lineas(retiro_id, None).await
+    }
+
+    /// Obtener una línea de extracto por ID
+    pub async fn get_linea_by_id(&self, id: Uuid) -> Result<Option<LineaExtracto>> {
+        Ok(self.buscar_lineas(None, Some(id)).await?.into_iter().next())
+    }
+
+    /// Estado de la conciliación de un retiro: movimientos del banco sin transacción,
+    /// transacciones pendientes sin movimiento y totales de ambos lados.
+    /// Con `cuenta_id` solo se tienen en cuenta las líneas y transacciones de esa cuenta.
+    pub async fn informe_conciliacion(
+        &self,
+        retiro_id: Uuid,
+        cuenta_id: Option<Uuid>,
+    ) -> Result<InformeConciliacion> {
+        self.validar_retiro(retiro_id).await?;
+
+        let transacciones: Vec<Transaccion> = TransaccionRepository::new(self.pool.clone())
+            .get_by_retiro(retiro_id)
+            .await?
+            .into_iter()
+            .filter(|t| cuenta_id.is_none() || t.cuenta_id == cuenta_id)
+            .collect();
+        let lineas: Vec<LineaExtracto> = self
+            .get_lineas(Some(retiro_id))
+            .await?
+            .into_iter()
+            .filter(|l| cuenta_id.is_none() || l.cuenta_id == cuenta_id)
+            .collect();
+
+        let activas: HashSet<Uuid> = transacciones.iter().map(|t| t.id).collect();
+        let contar = |estado| {
+            transacciones
+                .iter()
+                .filter(|t| t.conciliacion == estado)
+                .count()
+        };
+        let total_extracto = lineas.iter().map(|l| l.monto).sum();
+        let total_libros = transacciones
+            .iter()
+            .map(|t| match t.tipo {
+                TipoTransaccion::Ingreso => t.monto,
+                TipoTransaccion::Gasto => -t.monto,
+            })
+            .sum();
+
+        Ok(InformeConciliacion {
+            retiro_id,
+            cuenta_id,
+            pendientes: contar(EstadoConciliacion::Pendiente),
+            punteadas: contar(EstadoConciliacion::Punteada),
+            conciliadas: contar(EstadoConciliacion::Conciliada),
+            total_extracto,
+            total_libros,
+            // Una línea cuya transacción está en la papelera vuelve a quedar sin enlazar
+            lineas_sin_transaccion: lineas
+                .into_iter()
+                .filter(|l| !l.transaccion_id.is_some_and(|id| activas.contains(&id)))
+                .collect(),
+            transacciones_sin_linea: transacciones
+                .into_iter()
+                .filter(|t| {
+                    t.linea_extracto_id.is_none() && t.conciliacion == EstadoConciliacion::Pendiente
+                })
+                .collect(),
+        })
+    }
+
+    /// Cerrar la conciliación: las transacciones punteadas del retiro pasan a conciliadas.
+    /// Devuelve cuántas se han conciliado.
+    pub async fn conciliar(&self, retiro_id: Uuid, cuenta_id: Option<Uuid>) -> Result<u64> {
+        self.validar_retiro(retiro_id).await?;
+
+        let retiro_id_str = retiro_id.to_string();
+        let cuenta_id_str = cuenta_id.map(|id| id.to_string());
+        let updated_at_str = Utc::now().to_rfc3339();
+        let result = sqlx::query!(
+            r#"
+            UPDATE transacciones SET conciliacion = 'Conciliada', updated_at = ?3
+            WHERE retiro_id = ?1 AND (?2 IS NULL OR cuenta_id = ?2)
+                AND conciliacion = 'Punteada' AND deleted_at IS NULL
+            "#,
+            retiro_id_str,
+            cuenta_id_str,
+            updated_at_str
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Enlazar a mano una línea sin transacción con una transacción del mismo retiro,
+    /// tipo e importe; la transacción queda punteada
+    pub async fn vincular(&self, linea_id: Uuid, transaccion_id: Uuid) -> Result<LineaExtracto> {
+        let linea = self
+            .get_linea_by_id(linea_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Línea de extracto".to_string()))?;
+        if let Some(actual) = linea.transaccion_id {
+            return Err(AppError::Validation(format!(
+                "La línea ya está enlazada con la transacción {}; desvincúlala antes",
+                actual
+            )));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        comprobar_enlace(&mut tx, linea.retiro_id, linea.monto, transaccion_id).await?;
+        let linea_id_str = linea_id.to_string();
+        let transaccion_id_str = transaccion_id.to_string();
+        let result = sqlx::query!(
+            "UPDATE lineas_extracto SET transaccion_id = ?2 WHERE id = ?1 AND transaccion_id IS NULL",
+            linea_id_str,
+            transaccion_id_str
+        )
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::Validation(
+                "La línea se ha enlazado mientras tanto; desvincúlala antes".to_string(),
+            ));
+        }
+        puntear(&mut tx, transaccion_id).await?;
+        tx.commit().await?;
+
+        Ok(LineaExtracto {
+            transaccion_id: Some(transaccion_id),
+            ..linea
+        })
+    }
+
+    /// Deshacer el enlace de una línea; su transacción vuelve a quedar pendiente.
+    /// Una transacción ya conciliada no se puede desvincular.
+    pub async fn desvincular(&self, linea_id: Uuid) -> Result<LineaExtracto> {
+        let linea = self
+            .get_linea_by_id(linea_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Línea de extracto".to_string()))?;
+        let Some(transaccion_id) = linea.transaccion_id else {
+            return Err(AppError::Validation(
+                "La línea no está enlazada con ninguna transacción".to_string(),
+            ));
+        };
+
+        let transaccion_id_str = transaccion_id.to_string();
+        let conciliada = sqlx::query!(
+            "SELECT id FROM transacciones WHERE id = ?1 AND conciliacion = 'Conciliada'",
+            transaccion_id_str
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        if conciliada.is_some() {
+            return Err(AppError::Validation(
+                "La transacción ya está conciliada; márcala como punteada antes de desvincularla"
+                    .to_string(),
+            ));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let linea_id_str = linea_id.to_string();
+        let updated_at_str = Utc::now().to_rfc3339();
+        sqlx::query!(
+            "UPDATE lineas_extracto SET transaccion_id = NULL WHERE id = ?1",
+            linea_id_str
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "UPDATE transacciones SET conciliacion = 'Pendiente', updated_at = ?2 WHERE id = ?1 AND conciliacion = 'Punteada'",
+            transaccion_id_str,
+            updated_at_str
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(LineaExtracto {
+            transaccion_id: None,
+            ..linea
+        })
+    }
+
+    async fn buscar_lineas(
+        &self,
+        retiro_id: Option<Uuid>,
+        id: Option<Uuid>,
+    ) -> Result<Vec<LineaExtracto>> {
+        let retiro_id_str = retiro_id.map(|id| id.to_string());
+        let id_str = id.map(|id| id.to_string());
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, retiro_id, cuenta_id, formato, fecha, monto, descripcion, referencia,
+                huella, transaccion_id, created_at
+            FROM lineas_extracto
+            WHERE (?1 IS NULL OR retiro_id = ?1) AND (?2 IS NULL OR id = ?2)
+            ORDER BY fecha DESC, created_at DESC
+            "#,
+            retiro_id_str,
+            id_str
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut lineas = Vec::new();
+        for row in rows {
+            lineas.push(LineaExtracto {
+                id: Uuid::parse_str(&row.id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                retiro_id: Uuid::parse_str(&row.retiro_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                cuenta_id: row
+                    .cuenta_id
+                    .map(|id| Uuid::parse_str(&id))
+                    .transpose()
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                formato: FormatoExtracto::parse(&row.formato)
+                    .ok_or_else(|| AppError::Internal("Invalid formato extracto".to_string()))?,
+                fecha: NaiveDate::parse_from_str(&row.fecha, "%Y-%m-%d")
+                    .map_err(|e| AppError::Internal(format!("Invalid date: {}", e)))?,
+                monto: row.monto,
+                descripcion: row.descripcion,
+                referencia: row.referencia,
+                huella: row.huella,
+                transaccion_id: row
+                    .transaccion_id
+                    .map(|id| Uuid::parse_str(&id))
+                    .transpose()
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                created_at: parse_flexible_datetime(&row.created_at)?,
+            });
+        }
+
+        Ok(lineas)
+    }
+
+    async fn validar_retiro(&self, retiro_id: Uuid) -> Result<()> {
+        let retiro_id_str = retiro_id.to_string();
+        let retiro = sqlx::query!(
+            "SELECT id FROM retiros WHERE id = ?1 AND deleted_at IS NULL",
+            retiro_id_str
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        if retiro.is_none() {
+            return Err(AppError::NotFound("Retiro".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Comprobar que el retiro, la cuenta y las categorías existen
+    async fn validar_opciones(&self, opciones: &OpcionesExtracto) -> Result<()> {
+        self.validar_retiro(opciones.retiro_id).await?;
+
+        if let Some(cuenta_id) = opciones.cuenta_id {
+            if CuentaRepository::new(self.pool.clone())
+                .get_by_id(cuenta_id)
+                .await?
+                .is_none()
+            {
+                return Err(AppError::NotFound("Cuenta".to_string()));
+            }
+        }
+
+        for (categoria_id, tipo) in [
+            (opciones.categoria_ingreso_id, "Ingreso"),
+            (opciones.categoria_gasto_id, "Gasto"),
+        ] {
+            let Some(categoria_id) = categoria_id else {
+                continue;
+            };
+            let categoria_id_str = categoria_id.to_string();
+            let categoria = sqlx::query!(
+                "SELECT tipo FROM categorias WHERE id = ?1 AND deleted_at IS NULL",
+                categoria_id_str
+            )
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Categoría".to_string()))?;
+            if categoria.tipo != tipo {
+                return Err(AppError::Validation(format!(
+                    "La categoría para los movimientos de tipo {} es de tipo {}",
+                    tipo, categoria.tipo
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Parecido entre un movimiento y una transacción registrada (de 0 a 1), o `None` si
+/// no pueden ser el mismo: distinto tipo o importe, o fechas demasiado separadas
+fn puntuar(candidato: &CandidatoExtracto, transaccion: &Transaccion, dias: u32) -> Option<f64> {
+    let mismo_tipo = matches!(
+        (candidato.monto >= 0.0, &transaccion.tipo),
+        (true, TipoTransaccion::Ingreso) | (false, TipoTransaccion::Gasto)
+    );
+    if !mismo_tipo || (candidato.monto.abs() - transaccion.monto).abs() >= 0.005 {
+        return None;
+    }
+
+    let diferencia = (transaccion.created_at.date_naive() - candidato.fecha)
+        .num_days()
+        .unsigned_abs();
+    if diferencia > u64::from(dias) {
+        return None;
+    }
+
+    let cercania = 1.0 - diferencia as f64 / (f64::from(dias) + 1.0);
+    Some((cercania + similitud_descripcion(&candidato.descripcion, &transaccion.descripcion)) / 2.0)
+}
+
+/// Comprobar que una línea del retiro `retiro_id` con importe `monto` se puede enlazar con la
+/// transacción: activa, del mismo retiro, del mismo tipo e importe y sin otra línea enlazada
+async fn comprobar_enlace(
+    tx: &mut Transaction<'_, Sqlite>,
+    retiro_id: Uuid,
+    monto: f64,
+    transaccion_id: Uuid,
+) -> Result<()> {
+    let transaccion_id_str = transaccion_id.to_string();
+    let transaccion = sqlx::query!(
+        r#"
+        SELECT retiro_id, tipo, monto,
+            (SELECT l.id FROM lineas_extracto l WHERE l.transaccion_id = transacciones.id) as "linea_extracto_id?: String"
+        FROM transacciones WHERE id = ?1 AND deleted_at IS NULL
+        "#,
+        transaccion_id_str
+    )
+    .fetch_optional(&mut **tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Transacción".to_string()))?;
+
+    if transaccion.retiro_id != retiro_id.to_string() {
+        return Err(AppError::Validation(
+            "La transacción es de otro retiro".to_string(),
+        ));
+    }
+    if let Some(otra) = transaccion.linea_extracto_id {
+        return Err(AppError::Validation(format!(
+            "La transacción ya está enlazada con la línea {}",
+            otra
+        )));
+    }
+    let importe = match transaccion.tipo.as_str() {
+        "Ingreso" => transaccion.monto,
+        "Gasto" => -transaccion.monto,
+        _ => return Err(AppError::Internal("Invalid tipo transaccion".to_string())),
+    };
+    if (importe - monto).abs() >= 0.005 {
+        return Err(AppError::Validation(format!(
+            "El importe no coincide: €{:.2} en el extracto y €{:.2} en la transacción",
+            monto, importe
+        )));
+    }
+
+    Ok(())
+}
+
+/// Marcar como punteada una transacción pendiente que se acaba de enlazar con el banco
+async fn puntear(tx: &mut Transaction<'_, Sqlite>, transaccion_id: Uuid) -> Result<()> {
+    let transaccion_id_str = transaccion_id.to_string();
+    let updated_at_str = Utc::now().to_rfc3339();
+    sqlx::query!(
+        "UPDATE transacciones SET conciliacion = 'Punteada', updated_at = ?2 WHERE id = ?1 AND conciliacion = 'Pendiente'",
+        transaccion_id_str,
+        updated_at_str
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+async fn insertar_linea(
+    tx: &mut Transaction<'_, Sqlite>,
+    candidato: &CandidatoExtracto,
+    transaccion_id: Option<Uuid>,
+) -> Result<()> {
+    let id_str = Uuid::new_v4().to_string();
+    let retiro_id_str = candidato.retiro_id.to_string();
+    let cuenta_id_str = candidato.cuenta_id.map(|id| id.to_string());
+    let formato_str = candidato.formato.to_string();
+    let fecha_str = candidato.fecha.format("%Y-%m-%d").to_string();
+    let transaccion_id_str = transaccion_id.map(|id| id.to_string());
+    let created_at_str = Utc::now().to_rfc3339();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO lineas_extracto (id, retiro_id, cuenta_id, formato, fecha, monto, descripcion, referencia, huella, transaccion_id, created_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+        "#,
+        id_str,
+        retiro_id_str,
+        cuenta_id_str,
+        formato_str,
+        fecha_str,
+        candidato.monto,
+        candidato.descripcion,
+        candidato.referencia,
+        candidato.huella,
+        transaccion_id_str,
+        created_at_str
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CreateCategoria, CreateCuenta, CreateRetiro, TipoCategoria, TipoCuenta};
+    use crate::repositories::{pool_de_prueba, CategoriaRepository, RetiroRepository};
+
+    /// Retiro con una categoría de ingresos y otra de gastos
+    struct Escenario {
+        pool: SqlitePool,
+        retiro_id: Uuid,
+        ingresos: Uuid,
+        gastos: Uuid,
+    }
+
+    async fn escenario() -> Escenario {
+        let pool = pool_de_prueba().await;
+        let retiro = RetiroRepository::new(pool.clone())
+            .create(CreateRetiro {
+                nombre: "Retiro de otoño".to_string(),
+                descripcion: None,
+                fecha_inicio: fecha(10).and_hms_opt(0, 0, 0).unwrap().and_utc(),
+                fecha_fin: fecha(14).and_hms_opt(0, 0, 0).unwrap().and_utc(),
+                ubicacion: None,
+                numero_participantes: 10,
+            })
+            .await
+            .unwrap();
+        let mut categorias = Vec::new();
+        for (nombre, tipo) in [
+            ("Inscripciones", TipoCategoria::Ingreso),
+            ("Comida", TipoCategoria::Gasto),
+        ] {
+            let categoria = CategoriaRepository::new(pool.clone())
+                .create(CreateCategoria {
+                    nombre: nombre.to_string(),
+                    tipo,
+                    color: "#FF0000".to_string(),
+                    padre_id: None,
+                })
+                .await
+                .unwrap();
+            categorias.push(categoria.id);
+        }
+
+        Escenario {
+            pool,
+            retiro_id: retiro.id,
+            ingresos: categorias[0],
+            gastos: categorias[1],
+        }
+    }
+
+    impl Escenario {
+        fn repo(&self) -> ExtractoRepository {
+            ExtractoRepository::new(self.pool.clone())
+        }
+
+        fn opciones(&self, dias: u32) -> OpcionesExtracto {
+            OpcionesExtracto {
+                retiro_id: self.retiro_id,
+                cuenta_id: None,
+                categoria_ingreso_id: Some(self.ingresos),
+                categoria_gasto_id: Some(self.gastos),
+                dias,
+            }
+        }
+
+        /// Registrar a mano una transacción del día indicado
+        async fn registrar(&self, monto: f64, descripcion: &str, dia: u32) -> Transaccion {
+            let transaccion = transaccion(self.retiro_id, self.gastos, monto, descripcion, dia);
+            let mut tx = self.pool.begin().await.unwrap();
+            insertar_transaccion(&mut tx, &transaccion).await.unwrap();
+            tx.commit().await.unwrap();
+            transaccion
+        }
+
+        async fn transaccion(&self, id: Uuid) -> Transaccion {
+            TransaccionRepository::new(self.pool.clone())
+                .get_by_id(id)
+                .await
+                .unwrap()
+                .unwrap()
+        }
+    }
+
+    fn fecha(dia: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 10, dia).unwrap()
+    }
+
+    /// Transacción del día indicado; un monto negativo es un gasto
+    fn transaccion(
+        retiro_id: Uuid,
+        categoria_id: Uuid,
+        monto: f64,
+        descripcion: &str,
+        dia: u32,
+    ) -> Transaccion {
+        let mut transaccion = Transaccion::new(CreateTransaccion {
+            retiro_id,
+            categoria_id,
+            tipo: if monto >= 0.0 {
+                TipoTransaccion::Ingreso
+            } else {
+                TipoTransaccion::Gasto
+            },
+            monto: monto.abs(),
+            descripcion: descripcion.to_string(),
+            cuenta_id: None,
+            metodo_pago: None,
+        });
+        transaccion.created_at = fecha(dia).and_hms_opt(12, 0, 0).unwrap().and_utc();
+        transaccion
+    }
+
+    fn movimiento(monto: f64, descripcion: &str, dia: u32) -> MovimientoExtracto {
+        MovimientoExtracto {
+            fecha: fecha(dia),
+            monto,
+            descripcion: descripcion.to_string(),
+            referencia: None,
+        }
+    }
+
+    fn candidato(monto: f64, descripcion: &str, dia: u32) -> CandidatoExtracto {
+        let movimiento = movimiento(monto, descripcion, dia);
+        CandidatoExtracto {
+            retiro_id: Uuid::new_v4(),
+            cuenta_id: None,
+            formato: FormatoExtracto::Norma43,
+            fecha: movimiento.fecha,
+            monto,
+            descripcion: movimiento.descripcion.clone(),
+            referencia: None,
+            huella: huella_movimiento(None, &movimiento, 0),
+            estado: EstadoCandidato::Nueva,
+            transaccion_id: None,
+            puntuacion: None,
+            propuesta: None,
+            regla: None,
+            etiquetas: Vec::new(),
+        }
+    }
+
+    fn decidir(
+        candidatos: &[CandidatoExtracto],
+        acciones: &[AccionExtracto],
+    ) -> Vec<DecisionExtracto> {
+        candidatos
+            .iter()
+            .cloned()
+            .zip(acciones.iter().copied())
+            .map(|(candidato, accion)| DecisionExtracto { candidato, accion })
+            .collect()
+    }
+
+    #[test]
+    fn puntua_segun_la_fecha_y_la_descripcion() {
+        let (retiro_id, categoria_id) = (Uuid::new_v4(), Uuid::new_v4());
+        let cena = transaccion(retiro_id, categoria_id, -30.0, "Cena en Casa Pepe", 12);
+
+        // Distinto tipo o importe, o fuera del margen de días: no pueden ser la misma
+        assert_eq!(
+            puntuar(&candidato(30.0, "Cena en Casa Pepe", 12), &cena, 2),
+            None
+        );
+        assert_eq!(
+            puntuar(&candidato(-30.5, "Cena en Casa Pepe", 12), &cena, 2),
+            None
+        );
+        assert_eq!(
+            puntuar(&candidato(-30.0, "Cena en Casa Pepe", 15), &cena, 2),
+            None
+        );
+
+        assert_eq!(
+            puntuar(&candidato(-30.0, "Cena en Casa Pepe", 12), &cena, 2),
+            Some(1.0)
+        );
+        let un_dia_despues = puntuar(&candidato(-30.0, "Cena en Casa Pepe", 13), &cena, 2).unwrap();
+        let otra_descripcion = puntuar(&candidato(-30.0, "TPV 4411", 12), &cena, 2).unwrap();
+        assert!(un_dia_despues < 1.0);
+        assert!(otra_descripcion < un_dia_despues);
+    }
+
+    #[tokio::test]
+    async fn cada_transaccion_coincide_con_un_solo_movimiento() {
+        let escenario = escenario().await;
+        let cafe = escenario.registrar(-2.5, "Café", 12).await;
+
+        let candidatos = escenario
+            .repo()
+            .preparar(
+                FormatoExtracto::Norma43,
+                &[movimiento(-2.5, "Café", 12), movimiento(-2.5, "Café", 12)],
+                &escenario.opciones(2),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(candidatos[0].estado, EstadoCandidato::Coincide);
+        assert_eq!(candidatos[0].transaccion_id, Some(cafe.id));
+        assert_eq!(candidatos[1].estado, EstadoCandidato::Nueva);
+        // Los dos movimientos idénticos tienen huellas distintas
+        assert_ne!(candidatos[0].huella, candidatos[1].huella);
+    }
+
+    #[tokio::test]
+    async fn empareja_por_la_descripcion_mas_parecida_dentro_del_margen() {
+        let escenario = escenario().await;
+        let farmacia = escenario.registrar(-12.0, "Farmacia Central", 12).await;
+        let panaderia = escenario.registrar(-12.0, "Panadería del Sol", 12).await;
+        let repo = escenario.repo();
+
+        let candidatos = repo
+            .preparar(
+                FormatoExtracto::Norma43,
+                &[movimiento(-12.0, "COMPRA PANADERÍA DEL SOL", 12)],
+                &escenario.opciones(2),
+            )
+            .await
+            .unwrap();
+        assert_eq!(candidatos[0].transaccion_id, Some(panaderia.id));
+        assert_ne!(candidatos[0].transaccion_id, Some(farmacia.id));
+
+        // A tres días solo coincide si el margen lo admite
+        let tres_dias_despues = [movimiento(-12.0, "Farmacia Central", 15)];
+        let candidatos = repo
+            .preparar(
+                FormatoExtracto::Norma43,
+                &tres_dias_despues,
+                &escenario.opciones(2),
+            )
+            .await
+            .unwrap();
+        assert_eq!(candidatos[0].estado, EstadoCandidato::Nueva);
+        let candidatos = repo
+            .preparar(
+                FormatoExtracto::Norma43,
+                &tres_dias_despues,
+                &escenario.opciones(3),
+            )
+            .await
+            .unwrap();
+        assert_eq!(candidatos[0].transaccion_id, Some(farmacia.id));
+    }
+
+    #[tokio::test]
+    async fn importa_vincula_y_omite_segun_la_decision() {
+        let escenario = escenario().await;
+        let cena = escenario.registrar(-30.0, "Cena", 12).await;
+        let repo = escenario.repo();
+        let movimientos = [
+            movimiento(-30.0, "Cena", 12),
+            movimiento(250.0, "Transferencia inscripción", 11),
+            movimiento(-4.0, "Comisión", 13),
+        ];
+
+        let candidatos = repo
+            .preparar(
+                FormatoExtracto::Norma43,
+                &movimientos,
+                &escenario.opciones(2),
+            )
+            .await
+            .unwrap();
+        let resumen = repo
+            .importar(decidir(
+                &candidatos,
+                &[
+                    AccionExtracto::Vincular,
+                    AccionExtracto::Importar,
+                    AccionExtracto::Omitir,
+                ],
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            (resumen.vinculadas, resumen.creadas, resumen.omitidas),
+            (1, 1, 1)
+        );
+
+        let cena = escenario.transaccion(cena.id).await;
+        assert_eq!(cena.conciliacion, EstadoConciliacion::Punteada);
+        assert!(cena.linea_extracto_id.is_some());
+
+        let lineas = repo.get_lineas(Some(escenario.retiro_id)).await.unwrap();
+        assert_eq!(lineas.len(), 3);
+        let inscripcion = lineas.iter().find(|l| l.monto == 250.0).unwrap();
+        let creada = escenario
+            .transaccion(inscripcion.transaccion_id.unwrap())
+            .await;
+        assert_eq!(creada.categoria_id, escenario.ingresos);
+        assert_eq!(creada.created_at.date_naive(), fecha(11));
+        assert_eq!(creada.conciliacion, EstadoConciliacion::Punteada);
+        let comision = lineas.iter().find(|l| l.monto == -4.0).unwrap();
+        assert_eq!(comision.transaccion_id, None);
+
+        // Volver a importar el mismo extracto no repite nada
+        let candidatos = repo
+            .preparar(
+                FormatoExtracto::Norma43,
+                &movimientos,
+                &escenario.opciones(2),
+            )
+            .await
+            .unwrap();
+        assert!(candidatos
+            .iter()
+            .all(|c| c.estado == EstadoCandidato::YaImportada));
+        let resumen = repo
+            .importar(decidir(&candidatos, &[AccionExtracto::Importar; 3]))
+            .await
+            .unwrap();
+        assert_eq!(resumen.ya_importadas, 3);
+        assert_eq!(repo.get_lineas(None).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn al_importar_se_vuelve_a_comprobar_el_enlace() {
+        let escenario = escenario().await;
+        let cena = escenario.registrar(-30.0, "Cena", 12).await;
+        let repo = escenario.repo();
+
+        // Dos revisiones preparadas a la vez proponen la misma transacción
+        let mut primera = movimiento(-30.0, "Cena", 12);
+        primera.referencia = Some("0001".to_string());
+        let mut segunda = movimiento(-30.0, "Cena", 12);
+        segunda.referencia = Some("0002".to_string());
+        let mut candidatos = Vec::new();
+        for movimiento in [primera, segunda] {
+            candidatos.extend(
+                repo.preparar(
+                    FormatoExtracto::Norma43,
+                    &[movimiento],
+                    &escenario.opciones(2),
+                )
+                .await
+                .unwrap(),
+            );
+        }
+        assert!(candidatos.iter().all(|c| c.transaccion_id == Some(cena.id)));
+
+        let mut alterado = candidatos[0].clone();
+        alterado.monto = -31.0;
+        assert!(repo
+            .importar(decidir(&[alterado], &[AccionExtracto::Vincular]))
+            .await
+            .is_err());
+
+        repo.importar(decidir(&candidatos[..1], &[AccionExtracto::Vincular]))
+            .await
+            .unwrap();
+        assert!(repo
+            .importar(decidir(&candidatos[1..], &[AccionExtracto::Vincular]))
+            .await
+            .is_err());
+        assert_eq!(repo.get_lineas(None).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn la_huella_distingue_la_cuenta() {
+        let escenario = escenario().await;
+        let repo = escenario.repo();
+        let mut cuentas = Vec::new();
+        for nombre in ["Banco", "Tarjeta"] {
+            let cuenta = CuentaRepository::new(escenario.pool.clone())
+                .create(CreateCuenta {
+                    nombre: nombre.to_string(),
+                    tipo: TipoCuenta::Banco,
+                    saldo_inicial: 0.0,
+                })
+                .await
+                .unwrap();
+            cuentas.push(cuenta.id);
+        }
+        let movimientos = [movimiento(-8.0, "Peaje", 12)];
+        let opciones = |cuenta_id| OpcionesExtracto {
+            cuenta_id: Some(cuenta_id),
+            ..escenario.opciones(2)
+        };
+
+        let candidatos = repo
+            .preparar(
+                FormatoExtracto::Norma43,
+                &movimientos,
+                &opciones(cuentas[0]),
+            )
+            .await
+            .unwrap();
+        repo.importar(decidir(&candidatos, &[AccionExtracto::Omitir]))
+            .await
+            .unwrap();
+
+        let misma_cuenta = repo
+            .preparar(
+                FormatoExtracto::Norma43,
+                &movimientos,
+                &opciones(cuentas[0]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(misma_cuenta[0].estado, EstadoCandidato::YaImportada);
+        let otra_cuenta = repo
+            .preparar(
+                FormatoExtracto::Norma43,
+                &movimientos,
+                &opciones(cuentas[1]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(otra_cuenta[0].estado, EstadoCandidato::Nueva);
+    }
+
+    /// Importar el extracto enlazando lo que coincide y omitiendo lo demás
+    async fn enlazar(escenario: &Escenario, movimientos: &[MovimientoExtracto]) {
+        let repo = escenario.repo();
+        let candidatos = repo
+            .preparar(
+                FormatoExtracto::Norma43,
+                movimientos,
+                &escenario.opciones(2),
+            )
+            .await
+            .unwrap();
+        let acciones: Vec<AccionExtracto> = candidatos
+            .iter()
+            .map(|c| match c.estado {
+                EstadoCandidato::Coincide => AccionExtracto::Vincular,
+                _ => AccionExtracto::Omitir,
+            })
+            .collect();
+        repo.importar(decidir(&candidatos, &acciones))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn conciliar_cierra_solo_las_transacciones_punteadas() {
+        let escenario = escenario().await;
+        let cena = escenario.registrar(-30.0, "Cena", 12).await;
+        let taxi = escenario.registrar(-15.0, "Taxi", 12).await;
+        enlazar(&escenario, &[movimiento(-30.0, "Cena", 12)]).await;
+        let repo = escenario.repo();
+
+        assert!(repo.conciliar(Uuid::new_v4(), None).await.is_err());
+        assert_eq!(
+            repo.conciliar(escenario.retiro_id, Some(Uuid::new_v4()))
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(repo.conciliar(escenario.retiro_id, None).await.unwrap(), 1);
+        assert_eq!(
+            escenario.transaccion(cena.id).await.conciliacion,
+            EstadoConciliacion::Conciliada
+        );
+        assert_eq!(
+            escenario.transaccion(taxi.id).await.conciliacion,
+            EstadoConciliacion::Pendiente
+        );
+        assert_eq!(repo.conciliar(escenario.retiro_id, None).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn desvincular_devuelve_la_transaccion_a_pendiente() {
+        let escenario = escenario().await;
+        let cena = escenario.registrar(-30.0, "Cena", 12).await;
+        let taxi = escenario.registrar(-15.0, "Taxi", 12).await;
+        enlazar(&escenario, &[movimiento(-30.0, "Cena", 12)]).await;
+        let repo = escenario.repo();
+        let linea = repo.get_lineas(None).await.unwrap().remove(0);
+
+        let linea = repo.desvincular(linea.id).await.unwrap();
+        assert_eq!(linea.transaccion_id, None);
+        let transaccion = escenario.transaccion(cena.id).await;
+        assert_eq!(transaccion.conciliacion, EstadoConciliacion::Pendiente);
+        assert_eq!(transaccion.linea_extracto_id, None);
+        assert!(repo.desvincular(linea.id).await.is_err());
+
+        // A mano solo se enlaza con una transacción del mismo importe
+        assert!(repo.vincular(linea.id, taxi.id).await.is_err());
+        repo.vincular(linea.id, cena.id).await.unwrap();
+        assert_eq!(
+            escenario.transaccion(cena.id).await.conciliacion,
+            EstadoConciliacion::Punteada
+        );
+
+        // Una transacción conciliada ya no se puede desvincular
+        repo.conciliar(escenario.retiro_id, None).await.unwrap();
+        assert!(repo.desvincular(linea.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn el_informe_compara_el_banco_con_los_libros() {
+        let escenario = escenario().await;
+        escenario.registrar(-30.0, "Cena", 12).await;
+        let taxi = escenario.registrar(-15.0, "Taxi", 12).await;
+        let hotel = escenario.registrar(-100.0, "Hotel", 11).await;
+        enlazar(
+            &escenario,
+            &[
+                movimiento(-30.0, "Cena", 12),
+                movimiento(-100.0, "Hotel", 11),
+                movimiento(250.0, "Transferencia inscripción", 10),
+            ],
+        )
+        .await;
+        // Al llevar su transacción a la papelera, la línea del hotel queda sin enlazar
+        TransaccionRepository::new(escenario.pool.clone())
+            .delete(hotel.id)
+            .await
+            .unwrap();
+
+        let informe = escenario
+            .repo()
+            .informe_conciliacion(escenario.retiro_id, None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            (informe.pendientes, informe.punteadas, informe.conciliadas),
+            (1, 1, 0)
+        );
+        assert_eq!(informe.total_extracto, 120.0);
+        assert_eq!(informe.total_libros, -45.0);
+        assert_eq!(informe.diferencia(), 165.0);
+        let mut sin_transaccion: Vec<f64> = informe
+            .lineas_sin_transaccion
+            .iter()
+            .map(|l| l.monto)
+            .collect();
+        sin_transaccion.sort_by(f64::total_cmp);
+        assert_eq!(sin_transaccion, vec![-100.0, 250.0]);
+        assert_eq!(informe.transacciones_sin_linea.len(), 1);
+        assert_eq!(informe.transacciones_sin_linea[0].id, taxi.id);
+
+        // Las líneas y transacciones de otra cuenta no cuentan
+        let informe = escenario
+            .repo()
+            .informe_conciliacion(escenario.retiro_id, Some(Uuid::new_v4()))
+            .await
+            .unwrap();
+        assert_eq!(informe.total_extracto, 0.0);
+        assert!(informe.transacciones_sin_linea.is_empty());
+    }
+}
@@ -1,7 +1,68 @@
+pub mod adjunto_repository;
+pub mod arqueo_repository;
 pub mod categoria_repository;
+pub mod cuenta_repository;
+pub mod dataset_repository;
+pub mod etiqueta_repository;
+pub mod extracto_repository;
+pub mod papelera_repository;
+pub mod plantilla_repository;
+pub mod regla_repository;
 pub mod retiro_repository;
 pub mod transaccion_repository;
 
+pub use adjunto_repository::*;
+pub use arqueo_repository::*;
 pub use categoria_repository::*;
+pub use cuenta_repository::*;
+pub use dataset_repository::*;
+pub use etiqueta_repository::*;
+pub use extracto_repository::*;
+pub use papelera_repository::*;
+pub use plantilla_repository::*;
+pub use regla_repository::*;
 pub use retiro_repository::*;
 pub use transaccion_repository::*;
+
+use crate::{AppError, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+/// Parsear fechas guardadas en RFC 3339 o en el formato datetime de SQLite
+pub(crate) fn parse_flexible_datetime(date_str: &str) -> Result<DateTime<Utc>> {
+    // Intentar RFC3339 primero
+    if let Ok(dt) = DateTime::parse_from_rfc3339(date_str) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+
+    // Intentar formato SQLite datetime: "YYYY-MM-DD HH:MM:SS"
+    if let Ok(naive_dt) = NaiveDateTime::parse_from_str(date_str, "%Y-%m-%d %H:%M:%S") {
+        return Ok(DateTime::from_naive_utc_and_offset(naive_dt, Utc));
+    }
+
+    // Intentar formato SQLite datetime con microsegundos: "YYYY-MM-DD HH:MM:SS.ffffff"
+    if let Ok(naive_dt) = NaiveDateTime::parse_from_str(date_str, "%Y-%m-%d %H:%M:%S%.f") {
+        return Ok(DateTime::from_naive_utc_and_offset(naive_dt, Utc));
+    }
+
+    Err(AppError::Internal(format!(
+        "Invalid date format: {}",
+        date_str
+    )))
+}
+
+/// Base de datos en memoria con las migraciones aplicadas, para los tests
+#[cfg(test)]
+pub(crate) async fn pool_de_prueba() -> sqlx::SqlitePool {
+    // Una sola conexión: cada conexión a `:memory:` abre una base de datos distinta
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("base de datos en memoria");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("migraciones");
+    pool
+}
+
@@ -0,0 +1,320 @@
+use crate::models::{ElementoPapelera, EnPapelera, ResumenVaciado, TipoElementoPapelera};
+use crate::repositories::parse_flexible_datetime;
+use crate::{AppError, Result};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// Acceso a los elementos eliminados (con `deleted_at`) de retiros, categorías y transacciones
+pub struct PapeleraRepository {
+    pool: SqlitePool,
+}
+
+impl PapeleraRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Listar la papelera, de lo más reciente a lo más antiguo.
+    /// Las transacciones eliminadas junto con su retiro se cuentan en el retiro y no se listan aparte.
+    pub async fn listar(&self) -> Result<Vec<ElementoPapelera>> {
+        let mut elementos = Vec::new();
+
+        let retiros = sqlx::query!(
+            r#"
+            SELECT r.id, r.nombre, r.deleted_at as "deleted_at!: String",
+                (SELECT COUNT(*) FROM transacciones t
+                 WHERE t.retiro_id = r.id AND t.deleted_at = r.deleted_at) as "transacciones!: i64"
+            FROM retiros r
+            WHERE r.deleted_at IS NOT NULL
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        for row in retiros {
+            elementos.push(ElementoPapelera {
+                id: Uuid::parse_str(&row.id).map_err(|e| AppError::Internal(e.to_string()))?,
+                tipo: TipoElementoPapelera::Retiro,
+                descripcion: row.nombre,
+                deleted_at: parse_flexible_datetime(&row.deleted_at)?,
+                transacciones: row.transacciones,
+            });
+        }
+
+        let categorias = sqlx::query!(
+            r#"SELECT id, nombre, deleted_at as "deleted_at!: String" FROM categorias WHERE deleted_at IS NOT NULL"#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        for row in categorias {
+            elementos.push(ElementoPapelera {
+                id: Uuid::parse_str(&row.id).map_err(|e| AppError::Internal(e.to_string()))?,
+                tipo: TipoElementoPapelera::Categoria,
+                descripcion: row.nombre,
+                deleted_at: parse_flexible_datetime(&row.deleted_at)?,
+                transacciones: 0,
+            });
+        }
+
+        let transacciones = sqlx::query!(
+            r#"
+            SELECT t.id, t.descripcion, t.deleted_at as "deleted_at!: String"
+            FROM transacciones t
+            JOIN retiros r ON r.id = t.retiro_id
+            WHERE t.deleted_at IS NOT NULL AND r.deleted_at IS NOT t.deleted_at
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        for row in transacciones {
+            elementos.push(ElementoPapelera {
+                id: Uuid::parse_str(&row.id).map_err(|e| AppError::Internal(e.to_string()))?,
+                tipo: TipoElementoPapelera::Transaccion,
+                descripcion: row.descripcion,
+                deleted_at: parse_flexible_datetime(&row.deleted_at)?,
+                transacciones: 0,
+            });
+        }
+
+        elementos.sort_by_key(|e| std::cmp::Reverse(e.deleted_at));
+        Ok(elementos)
+    }
+
+    /// Fecha de eliminación de cada fila en la papelera, también las transacciones
+    /// eliminadas junto con su retiro, para exportarlas
+    pub async fn get_fechas_eliminacion(&self) -> Result<Vec<EnPapelera>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT 'Retiro' as "tipo!: String", id as "id!: String", deleted_at as "deleted_at!: String"
+            FROM retiros WHERE deleted_at IS NOT NULL
+            UNION ALL
+            SELECT 'Categoria', id, deleted_at FROM categorias WHERE deleted_at IS NOT NULL
+            UNION ALL
+            SELECT 'Transaccion', id, deleted_at FROM transacciones WHERE deleted_at IS NOT NULL
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut fechas = Vec::new();
+        for row in rows {
+            fechas.push(EnPapelera {
+                tipo: match row.tipo.as_str() {
+                    "Retiro" => TipoElementoPapelera::Retiro,
+                    "Categoria" => TipoElementoPapelera::Categoria,
+                    _ => TipoElementoPapelera::Transaccion,
+                },
+                id: Uuid::parse_str(&row.id).map_err(|e| AppError::Internal(e.to_string()))?,
+                deleted_at: parse_flexible_datetime(&row.deleted_at)?,
+            });
+        }
+
+        Ok(fechas)
+    }
+
+    /// Restaurar un elemento de la papelera. Un retiro se restaura junto con las
+    /// transacciones que se eliminaron con él.
+    pub async fn restaurar(&self, id: Uuid) -> Result<Option<ElementoPapelera>> {
+        let elemento = match self.listar().await?.into_iter().find(|e| e.id == id) {
+            Some(elemento) => elemento,
+            None => return Ok(None),
+        };
+
+        match elemento.tipo {
+            TipoElementoPapelera::Retiro => self.restaurar_retiro(id).await?,
+            TipoElementoPapelera::Categoria => self.restaurar_categoria(id).await?,
+            TipoElementoPapelera::Transaccion => self.restaurar_transaccion(id).await?,
+        }
+
+        Ok(Some(elemento))
+    }
+
+    async fn restaurar_retiro(&self, id: Uuid) -> Result<()> {
+        let id_str = id.to_string();
+
+        // Las transacciones no pueden volver apuntando a una categoría eliminada
+        let categoria = sqlx::query!(
+            r#"
+            SELECT c.nombre
+            FROM transacciones t
+            JOIN retiros r ON r.id = t.retiro_id
+            JOIN categorias c ON c.id = t.categoria_id
+            WHERE t.retiro_id = ?1 AND t.deleted_at = r.deleted_at AND c.deleted_at IS NOT NULL
+            LIMIT 1
+            "#,
+            id_str
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        if let Some(categoria) = categoria {
+            return Err(AppError::Validation(format!(
+                "La categoría '{}' de sus transacciones está en la papelera; restáurala primero",
+                categoria.nombre
+            )));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            r#"
+            UPDATE transacciones SET deleted_at = NULL
+            WHERE retiro_id = ?1
+              AND deleted_at = (SELECT deleted_at FROM retiros WHERE id = ?1)
+            "#,
+            id_str
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!("UPDATE retiros SET deleted_at = NULL WHERE id = ?1", id_str)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn restaurar_categoria(&self, id: Uuid) -> Result<()> {
+        let id_str = id.to_string();
+
+        let row = sqlx::query!(
+            r#"
+            SELECT c.nombre, c.tipo,
+                (SELECT COUNT(*) FROM categorias p
+                 WHERE p.id = c.padre_id AND p.deleted_at IS NOT NULL) as "padre_eliminado!: i64",
+                (SELECT COUNT(*) FROM categorias o
+                 WHERE o.nombre = c.nombre COLLATE NOCASE AND o.tipo = c.tipo
+                   AND o.deleted_at IS NULL) as "duplicadas!: i64"
+            FROM categorias c
+            WHERE c.id = ?1
+            "#,
+            id_str
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        if row.padre_eliminado > 0 {
+            return Err(AppError::Validation(format!(
+                "La categoría padre de '{}' está en la papelera; restáurala primero",
+                row.nombre
+            )));
+        }
+        if row.duplicadas > 0 {
+            return Err(AppError::Duplicate(format!(
+                "Ya existe una categoría de tipo {} llamada '{}'",
+                row.tipo, row.nombre
+            )));
+        }
+
+        sqlx::query!("UPDATE categorias SET deleted_at = NULL WHERE id = ?1", id_str)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn restaurar_transaccion(&self, id: Uuid) -> Result<()> {
+        let id_str = id.to_string();
+
+        let row = sqlx::query!(
+            r#"
+            SELECT r.nombre as retiro, r.deleted_at IS NOT NULL as "retiro_eliminado!: bool",
+                c.nombre as categoria, c.deleted_at IS NOT NULL as "categoria_eliminada!: bool"
+            FROM transacciones t
+            JOIN retiros r ON r.id = t.retiro_id
+            JOIN categorias c ON c.id = t.categoria_id
+            WHERE t.id = ?1
+            "#,
+            id_str
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        if row.retiro_eliminado {
+            return Err(AppError::Validation(format!(
+                "El retiro '{}' está en la papelera; restáuralo primero",
+                row.retiro
+            )));
+        }
+        if row.categoria_eliminada {
+            return Err(AppError::Validation(format!(
+                "La categoría '{}' está en la papelera; restáurala primero",
+                row.categoria
+            )));
+        }
+
+        sqlx::query!("UPDATE transacciones SET deleted_at = NULL WHERE id = ?1", id_str)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Borrar definitivamente los elementos eliminados antes de `antes_de` (todos si es `None`).
+    /// Las categorías que aún usan transacciones, plantillas, reglas o subcategorías se conservan.
+    pub async fn vaciar(&self, antes_de: Option<DateTime<Utc>>) -> Result<ResumenVaciado> {
+        let antes_de_str = antes_de.map(|fecha| fecha.to_rfc3339());
+        let mut resumen = ResumenVaciado::default();
+        let mut tx = self.pool.begin().await?;
+
+        resumen.transacciones = sqlx::query!(
+            r#"
+            DELETE FROM transacciones
+            WHERE deleted_at IS NOT NULL
+              AND (?1 IS NULL OR julianday(deleted_at) <= julianday(?1))
+            "#,
+            antes_de_str
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        // El borrado en cascada se lleva las transacciones que quedaran del retiro
+        resumen.retiros = sqlx::query!(
+            r#"
+            DELETE FROM retiros
+            WHERE deleted_at IS NOT NULL
+              AND (?1 IS NULL OR julianday(deleted_at) <= julianday(?1))
+            "#,
+            antes_de_str
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        // Las subcategorías se borran antes que su padre, por eso se repite hasta que no quede nada
+        loop {
+            let borradas = sqlx::query!(
+                r#"
+                DELETE FROM categorias
+                WHERE deleted_at IS NOT NULL
+                  AND (?1 IS NULL OR julianday(deleted_at) <= julianday(?1))
+                  AND NOT EXISTS (SELECT 1 FROM transacciones t WHERE t.categoria_id = categorias.id)
+                  AND NOT EXISTS (SELECT 1 FROM plantillas_transaccion p WHERE p.categoria_id = categorias.id)
+                  AND NOT EXISTS (SELECT 1 FROM reglas_categoria r WHERE r.categoria_id = categorias.id)
+                  AND NOT EXISTS (SELECT 1 FROM categorias h WHERE h.padre_id = categorias.id)
+                "#,
+                antes_de_str
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+            if borradas == 0 {
+                break;
+            }
+            resumen.categorias += borradas;
+        }
+
+        tx.commit().await?;
+        Ok(resumen)
+    }
+
+    /// Purgar los elementos que llevan más de `dias` en la papelera (0 no purga nada)
+    pub async fn purgar_caducados(&self, dias: u32) -> Result<ResumenVaciado> {
+        if dias == 0 {
+            return Ok(ResumenVaciado::default());
+        }
+
+        self.vaciar(Some(Utc::now() - Duration::days(i64::from(dias))))
+            .await
+    }
+}
@@ -0,0 +1,354 @@
+use crate::models::{
+    CreatePlantillaTransaccion, PlantillaTransaccion, TipoTransaccion, Transaccion,
+};
+use crate::repositories::{insertar_transaccion, parse_flexible_datetime};
+use crate::{AppError, Result};
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use uuid::Uuid;
+use validator::Validate;
+
+pub struct PlantillaRepository {
+    pool: SqlitePool,
+}
+
+impl PlantillaRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Crear una nueva plantilla de transacción
+    pub async fn create(&self, data: CreatePlantillaTransaccion) -> Result<PlantillaTransaccion> {
+        // Validar el nombre y los datos de la transacción que generará
+        data.validate()
+            .map_err(|e| AppError::Validation(e.to_string()))?;
+        data.to_create_transaccion(Uuid::nil())
+            .validate()
+            .map_err(|e| AppError::Validation(e.to_string()))?;
+
+        self.validar_categoria(data.categoria_id, &data.tipo)
+            .await?;
+
+        let existe = sqlx::query!(
+            "SELECT COUNT(*) as count FROM plantillas_transaccion WHERE nombre = ?1",
+            data.nombre
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if existe.count > 0 {
+            return Err(AppError::Duplicate(format!(
+                "Ya existe una plantilla llamada '{}'",
+                data.nombre
+            )));
+        }
+
+        let plantilla = PlantillaTransaccion::new(data);
+
+        // Crear variables para evitar problemas de lifetime
+        let id_str = plantilla.id.to_string();
+        let categoria_id_str = plantilla.categoria_id.to_string();
+        let tipo_str = plantilla.tipo.to_string();
+        let created_at_str = plantilla.created_at.to_rfc3339();
+        let updated_at_str = plantilla.updated_at.to_rfc3339();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO plantillas_transaccion (id, nombre, categoria_id, tipo, monto, descripcion, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "#,
+            id_str,
+            plantilla.nombre,
+            categoria_id_str,
+            tipo_str,
+            plantilla.monto,
+            plantilla.descripcion,
+            created_at_str,
+            updated_at_str
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(plantilla)
+    }
+
+    /// Obtener una plantilla por ID
+    pub async fn get_by_id(&self, id: Uuid) -> Result<Option<PlantillaTransaccion>> {
+        let id_str = id.to_string();
+        let row = sqlx::query!(
+            "SELECT id, nombre, categoria_id, tipo, monto, descripcion, created_at, updated_at FROM plantillas_transaccion WHERE id = ?1",
+            id_str
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => {
+                let plantilla = PlantillaTransaccion {
+                    id: Uuid::parse_str(&row.id)
+                        .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                    nombre: row.nombre,
+                    categoria_id: Uuid::parse_str(&row.categoria_id)
+                        .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                    tipo: match row.tipo.as_str() {
+                        "Ingreso" => TipoTransaccion::Ingreso,
+                        "Gasto" => TipoTransaccion::Gasto,
+                        _ => {
+                            return Err(AppError::Internal("Invalid tipo transaccion".to_string()))
+                        }
+                    },
+                    monto: row.monto,
+                    descripcion: row.descripcion,
+                    created_at: parse_flexible_datetime(&row.created_at)?,
+                    updated_at: parse_flexible_datetime(&row.updated_at)?,
+                };
+                Ok(Some(plantilla))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Obtener todas las plantillas
+    pub async fn get_all(&self) -> Result<Vec<PlantillaTransaccion>> {
+        let rows = sqlx::query!(
+            "SELECT id, nombre, categoria_id, tipo, monto, descripcion, created_at, updated_at FROM plantillas_transaccion ORDER BY nombre"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut plantillas = Vec::new();
+        for row in rows {
+            let plantilla = PlantillaTransaccion {
+                id: Uuid::parse_str(&row.id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                nombre: row.nombre,
+                categoria_id: Uuid::parse_str(&row.categoria_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                tipo: match row.tipo.as_str() {
+                    "Ingreso" => TipoTransaccion::Ingreso,
+                    "Gasto" => TipoTransaccion::Gasto,
+                    _ => return Err(AppError::Internal("Invalid tipo transaccion".to_string())),
+                },
+                monto: row.monto,
+                descripcion: row.descripcion,
+                created_at: parse_flexible_datetime(&row.created_at)?,
+                updated_at: parse_flexible_datetime(&row.updated_at)?,
+            };
+            plantillas.push(plantilla);
+        }
+
+        Ok(plantillas)
+    }
+
+    /// Eliminar una plantilla
+    pub async fn delete(&self, id: Uuid) -> Result<bool> {
+        let id_str = id.to_string();
+        let result = sqlx::query!("DELETE FROM plantillas_transaccion WHERE id = ?1", id_str)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Crear en un retiro una transacción por cada plantilla indicada (todas si no se indica ninguna)
+    ///
+    /// Las transacciones se validan como `CreateTransaccion` y se insertan en una única
+    /// transacción SQL: si alguna falla no se crea ninguna.
+    pub async fn aplicar(
+        &self,
+        retiro_id: Uuid,
+        plantilla_ids: &[Uuid],
+    ) -> Result<Vec<Transaccion>> {
+        let retiro_id_str = retiro_id.to_string();
+        let retiro = sqlx::query!(
+            "SELECT id FROM retiros WHERE id = ?1 AND deleted_at IS NULL",
+            retiro_id_str
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        if retiro.is_none() {
+            return Err(AppError::NotFound("Retiro".to_string()));
+        }
+
+        let transacciones = self.transacciones(retiro_id, plantilla_ids).await?;
+        if transacciones.is_empty() {
+            return Err(AppError::Validation(
+                "No hay plantillas que aplicar".to_string(),
+            ));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        for transaccion in &transacciones {
+            insertar_transaccion(&mut tx, transaccion).await?;
+        }
+        tx.commit().await?;
+
+        Ok(transacciones)
+    }
+
+    /// Transacciones que generarían en un retiro las plantillas indicadas, validadas como
+    /// `CreateTransaccion`, sin guardarlas.
+    ///
+    /// Sin plantillas indicadas se usan todas las que tienen la categoría activa; indicar una
+    /// cuya categoría está archivada o en la papelera es un error.
+    pub async fn transacciones(
+        &self,
+        retiro_id: Uuid,
+        plantilla_ids: &[Uuid],
+    ) -> Result<Vec<Transaccion>> {
+        let categorias_activas: HashSet<String> =
+            sqlx::query!("SELECT id FROM categorias WHERE archivada = 0 AND deleted_at IS NULL")
+                .fetch_all(&self.pool)
+                .await?
+                .into_iter()
+                .map(|row| row.id)
+                .collect();
+        let activa = |plantilla: &PlantillaTransaccion| {
+            categorias_activas.contains(&plantilla.categoria_id.to_string())
+        };
+
+        let plantillas = if plantilla_ids.is_empty() {
+            self.get_all()
+                .await?
+                .into_iter()
+                .filter(|plantilla| activa(plantilla))
+                .collect()
+        } else {
+            let mut plantillas = Vec::new();
+            for id in plantilla_ids {
+                let plantilla = self
+                    .get_by_id(*id)
+                    .await?
+                    .ok_or_else(|| AppError::NotFound(format!("Plantilla {}", id)))?;
+                if !activa(&plantilla) {
+                    return Err(AppError::Validation(format!(
+                        "Plantilla '{}': su categoría está archivada o en la papelera",
+                        plantilla.nombre
+                    )));
+                }
+                plantillas.push(plantilla);
+            }
+            plantillas
+        };
+
+        let mut transacciones = Vec::new();
+        for plantilla in &plantillas {
+            let data = plantilla.to_create_transaccion(retiro_id);
+            data.validate().map_err(|e| {
+                AppError::Validation(format!("Plantilla '{}': {}", plantilla.nombre, e))
+            })?;
+            transacciones.push(Transaccion::new(data));
+        }
+
+        Ok(transacciones)
+    }
+
+    /// Comprobar que la categoría existe y es del mismo tipo que la plantilla
+    async fn validar_categoria(&self, categoria_id: Uuid, tipo: &TipoTransaccion) -> Result<()> {
+        let categoria_id_str = categoria_id.to_string();
+        let categoria = sqlx::query!(
+            "SELECT nombre, tipo FROM categorias WHERE id = ?1 AND deleted_at IS NULL",
+            categoria_id_str
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Categoría".to_string()))?;
+
+        if categoria.tipo != tipo.to_string() {
+            return Err(AppError::Validation(format!(
+                "La categoría '{}' es de tipo {} y la plantilla de tipo {}",
+                categoria.nombre, categoria.tipo, tipo
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CreateCategoria, CreateRetiro, TipoCategoria};
+    use crate::repositories::{
+        pool_de_prueba, CategoriaRepository, RetiroRepository, TransaccionRepository,
+    };
+    use chrono::Utc;
+
+    #[tokio::test]
+    async fn aplicar_rechaza_plantillas_de_categorias_archivadas() {
+        let pool = pool_de_prueba().await;
+        let retiro = RetiroRepository::new(pool.clone())
+            .create(CreateRetiro {
+                nombre: "Retiro de otoño".to_string(),
+                descripcion: None,
+                fecha_inicio: Utc::now(),
+                fecha_fin: Utc::now(),
+                ubicacion: None,
+                numero_participantes: 10,
+            })
+            .await
+            .unwrap();
+        let categorias = CategoriaRepository::new(pool.clone());
+        let mut plantillas = Vec::new();
+        for nombre in ["Seguro", "Material"] {
+            let categoria = categorias
+                .create(CreateCategoria {
+                    nombre: nombre.to_string(),
+                    tipo: TipoCategoria::Gasto,
+                    color: String::new(),
+                    padre_id: None,
+                })
+                .await
+                .unwrap();
+            let plantilla = PlantillaRepository::new(pool.clone())
+                .create(CreatePlantillaTransaccion {
+                    nombre: nombre.to_string(),
+                    categoria_id: categoria.id,
+                    tipo: TipoTransaccion::Gasto,
+                    monto: 50.0,
+                    descripcion: nombre.to_string(),
+                })
+                .await
+                .unwrap();
+            plantillas.push(plantilla);
+        }
+        categorias
+            .set_archivada(plantillas[1].categoria_id, true)
+            .await
+            .unwrap();
+        let repo = PlantillaRepository::new(pool.clone());
+
+        assert!(repo
+            .aplicar(retiro.id, &[plantillas[0].id, plantillas[1].id])
+            .await
+            .is_err());
+        let transacciones = TransaccionRepository::new(pool);
+        assert!(transacciones
+            .get_by_retiro(retiro.id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // Sin indicar plantillas se aplican solo las de categorías activas
+        let creadas = repo.aplicar(retiro.id, &[]).await.unwrap();
+        assert_eq!(creadas.len(), 1);
+        assert_eq!(creadas[0].descripcion, "Seguro");
+        assert_eq!(
+            transacciones.get_by_retiro(retiro.id).await.unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn valida_monto_y_descripcion() {
+        let plantilla = CreatePlantillaTransaccion {
+            nombre: "Fianza".to_string(),
+            categoria_id: Uuid::new_v4(),
+            tipo: TipoTransaccion::Gasto,
+            monto: 0.0,
+            descripcion: String::new(),
+        };
+        let errores = plantilla.validate().unwrap_err();
+        assert!(errores.field_errors().contains_key("monto"));
+        assert!(errores.field_errors().contains_key("descripcion"));
+    }
+}
@@ -0,0 +1,252 @@
+use crate::models::{
+    normalizar_etiquetas, CoincidenciaRegla, CreateReglaCategoria, MotorReglas, ReglaCategoria,
+    TipoTransaccion,
+};
+use crate::repositories::{parse_flexible_datetime, TransaccionRepository};
+use crate::{AppError, Result};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// Las etiquetas de una regla se guardan separadas por comas (no pueden contenerlas)
+fn parse_etiquetas(etiquetas: &str) -> Vec<String> {
+    etiquetas
+        .split(',')
+        .filter(|e| !e.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+pub struct ReglaRepository {
+    pool: SqlitePool,
+}
+
+impl ReglaRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Crear una nueva regla de categorización
+    pub async fn create(&self, mut data: CreateReglaCategoria) -> Result<ReglaCategoria> {
+        data.validar()?;
+        data.etiquetas = normalizar_etiquetas(&data.etiquetas)?;
+
+        let regla = ReglaCategoria::new(data);
+        self.insertar(&regla).await?;
+
+        Ok(regla)
+    }
+
+    /// Volver a guardar una regla eliminada con su ID original (al deshacer la eliminación)
+    pub async fn restaurar(&self, regla: &ReglaCategoria) -> Result<()> {
+        self.insertar(regla).await
+    }
+
+    async fn insertar(&self, regla: &ReglaCategoria) -> Result<()> {
+        self.validar_categoria(regla.categoria_id, &regla.tipo).await?;
+
+        let existe = sqlx::query!(
+            "SELECT COUNT(*) as count FROM reglas_categoria WHERE nombre = ?1",
+            regla.nombre
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if existe.count > 0 {
+            return Err(AppError::Duplicate(format!(
+                "Ya existe una regla llamada '{}'",
+                regla.nombre
+            )));
+        }
+
+        // Crear variables para evitar problemas de lifetime
+        let id_str = regla.id.to_string();
+        let categoria_id_str = regla.categoria_id.to_string();
+        let tipo_str = regla.tipo.to_string();
+        let etiquetas_str = regla.etiquetas.join(",");
+        let created_at_str = regla.created_at.to_rfc3339();
+        let updated_at_str = regla.updated_at.to_rfc3339();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO reglas_categoria (id, nombre, patron, monto_min, monto_max, tipo, categoria_id, etiquetas, prioridad, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+            "#,
+            id_str,
+            regla.nombre,
+            regla.patron,
+            regla.monto_min,
+            regla.monto_max,
+            tipo_str,
+            categoria_id_str,
+            etiquetas_str,
+            regla.prioridad,
+            created_at_str,
+            updated_at_str
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Obtener una regla por ID
+    pub async fn get_by_id(&self, id: Uuid) -> Result<Option<ReglaCategoria>> {
+        let id_str = id.to_string();
+        let row = sqlx::query!(
+            "SELECT id, nombre, patron, monto_min, monto_max, tipo, categoria_id, etiquetas, prioridad, created_at, updated_at FROM reglas_categoria WHERE id = ?1",
+            id_str
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(Some(ReglaCategoria {
+                id: Uuid::parse_str(&row.id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                nombre: row.nombre,
+                patron: row.patron,
+                monto_min: row.monto_min,
+                monto_max: row.monto_max,
+                tipo: match row.tipo.as_str() {
+                    "Ingreso" => TipoTransaccion::Ingreso,
+                    "Gasto" => TipoTransaccion::Gasto,
+                    _ => return Err(AppError::Internal("Invalid tipo transaccion".to_string())),
+                },
+                categoria_id: Uuid::parse_str(&row.categoria_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                etiquetas: parse_etiquetas(&row.etiquetas),
+                prioridad: row.prioridad,
+                created_at: parse_flexible_datetime(&row.created_at)?,
+                updated_at: parse_flexible_datetime(&row.updated_at)?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    /// Obtener todas las reglas, de mayor a menor prioridad
+    pub async fn get_all(&self) -> Result<Vec<ReglaCategoria>> {
+        let rows = sqlx::query!(
+            "SELECT id, nombre, patron, monto_min, monto_max, tipo, categoria_id, etiquetas, prioridad, created_at, updated_at FROM reglas_categoria ORDER BY prioridad DESC, nombre"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut reglas = Vec::new();
+        for row in rows {
+            reglas.push(ReglaCategoria {
+                id: Uuid::parse_str(&row.id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                nombre: row.nombre,
+                patron: row.patron,
+                monto_min: row.monto_min,
+                monto_max: row.monto_max,
+                tipo: match row.tipo.as_str() {
+                    "Ingreso" => TipoTransaccion::Ingreso,
+                    "Gasto" => TipoTransaccion::Gasto,
+                    _ => return Err(AppError::Internal("Invalid tipo transaccion".to_string())),
+                },
+                categoria_id: Uuid::parse_str(&row.categoria_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                etiquetas: parse_etiquetas(&row.etiquetas),
+                prioridad: row.prioridad,
+                created_at: parse_flexible_datetime(&row.created_at)?,
+                updated_at: parse_flexible_datetime(&row.updated_at)?,
+            });
+        }
+
+        Ok(reglas)
+    }
+
+    /// Eliminar una regla
+    pub async fn delete(&self, id: Uuid) -> Result<bool> {
+        let id_str = id.to_string();
+        let result = sqlx::query!("DELETE FROM reglas_categoria WHERE id = ?1", id_str)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Reglas listas para evaluar. Las que apuntan a una categoría en la papelera o
+    /// archivada se ignoran: no se deben crear transacciones nuevas en ella.
+    pub async fn motor(&self) -> Result<MotorReglas> {
+        let activas = sqlx::query!(
+            "SELECT id FROM categorias WHERE deleted_at IS NULL AND archivada = 0"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let activas: Vec<String> = activas.into_iter().map(|c| c.id).collect();
+
+        let reglas = self
+            .get_all()
+            .await?
+            .into_iter()
+            .filter(|regla| activas.contains(&regla.categoria_id.to_string()))
+            .collect();
+        MotorReglas::new(reglas)
+    }
+
+    /// Transacciones ya registradas (de un retiro o de todos) que cumplen la regla,
+    /// aunque luego se categorizaran a mano en otra categoría
+    pub async fn probar(
+        &self,
+        regla: &ReglaCategoria,
+        retiro_id: Option<Uuid>,
+    ) -> Result<Vec<CoincidenciaRegla>> {
+        let transaccion_repo = TransaccionRepository::new(self.pool.clone());
+        let transacciones = match retiro_id {
+            Some(retiro_id) => transaccion_repo.get_by_retiro(retiro_id).await?,
+            None => transaccion_repo.get_all().await?,
+        };
+
+        let motor = MotorReglas::new(vec![regla.clone()])?;
+        Ok(transacciones
+            .into_iter()
+            .filter(|t| {
+                motor
+                    .evaluar(&t.descripcion, Some(t.monto), Some(&t.tipo))
+                    .is_some()
+            })
+            .map(|t| CoincidenciaRegla {
+                transaccion_id: t.id,
+                retiro_id: t.retiro_id,
+                fecha: t.created_at,
+                tipo: t.tipo,
+                monto: t.monto,
+                descripcion: t.descripcion,
+                categoria_id: t.categoria_id,
+                misma_categoria: t.categoria_id == regla.categoria_id,
+            })
+            .collect())
+    }
+
+    /// Comprobar que la categoría existe y es del mismo tipo que la regla
+    async fn validar_categoria(&self, categoria_id: Uuid, tipo: &TipoTransaccion) -> Result<()> {
+        let categoria_id_str = categoria_id.to_string();
+        let categoria = sqlx::query!(
+            "SELECT nombre, tipo FROM categorias WHERE id = ?1 AND deleted_at IS NULL",
+            categoria_id_str
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Categoría".to_string()))?;
+
+        if categoria.tipo != tipo.to_string() {
+            return Err(AppError::Validation(format!(
+                "La categoría '{}' es de tipo {} y la regla de tipo {}",
+                categoria.nombre, categoria.tipo, tipo
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Traducir la violación del nombre único de las reglas a un error legible
+pub(crate) fn error_regla_duplicada(error: sqlx::Error, nombre: &str) -> AppError {
+    match &error {
+        sqlx::Error::Database(e) if e.is_unique_violation() => {
+            AppError::Duplicate(format!("Ya existe una regla llamada '{}'", nombre))
+        }
+        _ => AppError::Database(error),
+    }
+}
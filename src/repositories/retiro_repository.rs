@@ -1,37 +1,18 @@
-use crate::models::{CreateRetiro, EstadoRetiro, Retiro};
+use crate::models::{CreateRetiro, EstadoRetiro, Retiro, RetiroClonado};
+use crate::repositories::{insertar_transaccion, parse_flexible_datetime, PlantillaRepository};
 use crate::{AppError, Result};
-use chrono::{DateTime, NaiveDateTime, Utc};
-use sqlx::SqlitePool;
+use chrono::{DateTime, Months, Utc};
+use sqlx::{Sqlite, SqlitePool, Transaction};
 use uuid::Uuid;
 use validator::Validate;
 
+/// Separación por defecto entre ediciones de un mismo retiro (un semestre)
+pub const MESES_ENTRE_EDICIONES: u32 = 6;
+
 pub struct RetiroRepository {
     pool: SqlitePool,
 }
 
-// Función helper para parsear fechas en múltiples formatos
-fn parse_flexible_datetime(date_str: &str) -> Result<DateTime<Utc>> {
-    // Intentar RFC3339 primero
-    if let Ok(dt) = DateTime::parse_from_rfc3339(date_str) {
-        return Ok(dt.with_timezone(&Utc));
-    }
-
-    // Intentar formato SQLite datetime: "YYYY-MM-DD HH:MM:SS"
-    if let Ok(naive_dt) = NaiveDateTime::parse_from_str(date_str, "%Y-%m-%d %H:%M:%S") {
-        return Ok(DateTime::from_naive_utc_and_offset(naive_dt, Utc));
-    }
-
-    // Intentar formato SQLite datetime con microsegundos: "YYYY-MM-DD HH:MM:SS.ffffff"
-    if let Ok(naive_dt) = NaiveDateTime::parse_from_str(date_str, "%Y-%m-%d %H:%M:%S%.f") {
-        return Ok(DateTime::from_naive_utc_and_offset(naive_dt, Utc));
-    }
-
-    Err(AppError::Internal(format!(
-        "Invalid date format: {}",
-        date_str
-    )))
-}
-
 impl RetiroRepository {
     pub fn new(pool: SqlitePool) -> Self {
         Self { pool }
@@ -45,32 +26,9 @@ impl RetiroRepository {
 
         let retiro = Retiro::new(data);
 
-        // Crear variables para evitar problemas de lifetime
-        let id_str = retiro.id.to_string();
-        let estado_str = retiro.estado.to_string();
-        let fecha_inicio_str = retiro.fecha_inicio.to_rfc3339();
-        let fecha_fin_str = retiro.fecha_fin.to_rfc3339();
-        let created_at_str = retiro.created_at.to_rfc3339();
-        let updated_at_str = retiro.updated_at.to_rfc3339();
-
-        sqlx::query!(
-            r#"
-            INSERT INTO retiros (id, nombre, descripcion, fecha_inicio, fecha_fin, ubicacion, numero_participantes, estado, created_at, updated_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
-            "#,
-            id_str,
-            retiro.nombre,
-            retiro.descripcion,
-            fecha_inicio_str,
-            fecha_fin_str,
-            retiro.ubicacion,
-            retiro.numero_participantes,
-            estado_str,
-            created_at_str,
-            updated_at_str
-        )
-        .execute(&self.pool)
-        .await?;
+        let mut tx = self.pool.begin().await?;
+        insertar_retiro(&mut tx, &retiro).await?;
+        tx.commit().await?;
 
         Ok(retiro)
     }
@@ -79,7 +37,7 @@ impl RetiroRepository {
     pub async fn get_by_id(&self, id: Uuid) -> Result<Option<Retiro>> {
         let id_str = id.to_string();
         let row = sqlx::query!(
-            "SELECT id, nombre, descripcion, fecha_inicio, fecha_fin, ubicacion, numero_participantes, estado, created_at, updated_at FROM retiros WHERE id = ?1",
+            "SELECT id, nombre, descripcion, fecha_inicio, fecha_fin, ubicacion, numero_participantes, estado, created_at, updated_at FROM retiros WHERE id = ?1 AND deleted_at IS NULL",
             id_str
         )
         .fetch_optional(&self.pool)
@@ -113,6 +71,40 @@ impl RetiroRepository {
 
     /// Obtener todos los retiros
     pub async fn get_all(&self) -> Result<Vec<Retiro>> {
+        let rows = sqlx::query!(
+            "SELECT id, nombre, descripcion, fecha_inicio, fecha_fin, ubicacion, numero_participantes, estado, created_at, updated_at FROM retiros WHERE deleted_at IS NULL ORDER BY fecha_inicio DESC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut retiros = Vec::new();
+        for row in rows {
+            let retiro = Retiro {
+                id: Uuid::parse_str(&row.id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                nombre: row.nombre,
+                descripcion: row.descripcion,
+                fecha_inicio: parse_flexible_datetime(&row.fecha_inicio)?,
+                fecha_fin: parse_flexible_datetime(&row.fecha_fin)?,
+                ubicacion: row.ubicacion,
+                numero_participantes: row.numero_participantes as i32,
+                estado: match row.estado.as_str() {
+                    "Planificacion" => EstadoRetiro::Planificacion,
+                    "Activo" => EstadoRetiro::Activo,
+                    "Finalizado" => EstadoRetiro::Finalizado,
+                    _ => return Err(AppError::Internal("Invalid estado retiro".to_string())),
+                },
+                created_at: parse_flexible_datetime(&row.created_at)?,
+                updated_at: parse_flexible_datetime(&row.updated_at)?,
+            };
+            retiros.push(retiro);
+        }
+
+        Ok(retiros)
+    }
+
+    /// Obtener todos los retiros, también los de la papelera (para exportar)
+    pub async fn get_all_incluyendo_eliminados(&self) -> Result<Vec<Retiro>> {
         let rows = sqlx::query!(
             "SELECT id, nombre, descripcion, fecha_inicio, fecha_fin, ubicacion, numero_participantes, estado, created_at, updated_at FROM retiros ORDER BY fecha_inicio DESC"
         )
@@ -149,7 +141,7 @@ impl RetiroRepository {
     pub async fn get_by_estado(&self, estado: EstadoRetiro) -> Result<Vec<Retiro>> {
         let estado_str = estado.to_string();
         let rows = sqlx::query!(
-            "SELECT id, nombre, descripcion, fecha_inicio, fecha_fin, ubicacion, numero_participantes, estado, created_at, updated_at FROM retiros WHERE estado = ?1 ORDER BY fecha_inicio DESC",
+            "SELECT id, nombre, descripcion, fecha_inicio, fecha_fin, ubicacion, numero_participantes, estado, created_at, updated_at FROM retiros WHERE estado = ?1 AND deleted_at IS NULL ORDER BY fecha_inicio DESC",
             estado_str
         )
         .fetch_all(&self.pool)
@@ -196,7 +188,7 @@ impl RetiroRepository {
             r#"
             UPDATE retiros 
             SET nombre = ?1, descripcion = ?2, fecha_inicio = ?3, fecha_fin = ?4, ubicacion = ?5, numero_participantes = ?6, updated_at = ?7
-            WHERE id = ?8
+            WHERE id = ?8 AND deleted_at IS NULL
             "#,
             data.nombre,
             data.descripcion,
@@ -232,7 +224,7 @@ impl RetiroRepository {
             r#"
             UPDATE retiros 
             SET estado = ?1, updated_at = ?2
-            WHERE id = ?3
+            WHERE id = ?3 AND deleted_at IS NULL
             "#,
             estado_str,
             updated_at_str,
@@ -249,21 +241,111 @@ impl RetiroRepository {
         self.get_by_id(id).await
     }
 
-    /// Eliminar un retiro
+    /// Clonar un retiro como plantilla de la siguiente edición
+    ///
+    /// El nuevo retiro empieza en `fecha_inicio` (por defecto un semestre después del
+    /// original), conserva la duración, la descripción, la ubicación y el número de
+    /// participantes, y queda en estado Planificación.
+    ///
+    /// Las transacciones del original no se copian: son movimientos reales y contarían en el
+    /// balance, en los saldos de las cuentas y en los arqueos de la nueva edición. Con
+    /// `con_plantillas` se crean las transacciones de las plantillas cuya categoría sigue
+    /// activa, igual que al aplicarlas. Todo se crea en una única transacción SQL.
+    pub async fn clonar(
+        &self,
+        id: Uuid,
+        nombre: Option<String>,
+        fecha_inicio: Option<DateTime<Utc>>,
+        con_plantillas: bool,
+    ) -> Result<Option<RetiroClonado>> {
+        let original = match self.get_by_id(id).await? {
+            Some(retiro) => retiro,
+            None => return Ok(None),
+        };
+
+        let fecha_inicio = match fecha_inicio {
+            Some(fecha) => fecha,
+            None => original
+                .fecha_inicio
+                .checked_add_months(Months::new(MESES_ENTRE_EDICIONES))
+                .ok_or_else(|| {
+                    AppError::Validation("Fecha de inicio fuera de rango".to_string())
+                })?,
+        };
+        let fecha_fin = fecha_inicio + (original.fecha_fin - original.fecha_inicio);
+
+        let nombre = nombre
+            .unwrap_or_else(|| format!("{} ({})", original.nombre, fecha_inicio.format("%m/%Y")));
+
+        let data = CreateRetiro {
+            nombre,
+            descripcion: original.descripcion,
+            fecha_inicio,
+            fecha_fin,
+            ubicacion: original.ubicacion,
+            numero_participantes: original.numero_participantes,
+        };
+        data.validate()
+            .map_err(|e| AppError::Validation(e.to_string()))?;
+        let retiro = Retiro::new(data);
+
+        let desde_plantillas = if con_plantillas {
+            PlantillaRepository::new(self.pool.clone())
+                .transacciones(retiro.id, &[])
+                .await?
+        } else {
+            Vec::new()
+        };
+
+        let mut tx = self.pool.begin().await?;
+        insertar_retiro(&mut tx, &retiro).await?;
+        for transaccion in &desde_plantillas {
+            insertar_transaccion(&mut tx, transaccion).await?;
+        }
+        tx.commit().await?;
+
+        Ok(Some(RetiroClonado {
+            retiro,
+            desde_plantillas,
+        }))
+    }
+
+    /// Enviar un retiro a la papelera junto con sus transacciones
     pub async fn delete(&self, id: Uuid) -> Result<bool> {
         let id_str = id.to_string();
-        let result = sqlx::query!("DELETE FROM retiros WHERE id = ?1", id_str)
-            .execute(&self.pool)
-            .await?;
+        // Misma marca de tiempo en el retiro y sus transacciones para restaurarlos juntos
+        let deleted_at_str = Utc::now().to_rfc3339();
 
-        Ok(result.rows_affected() > 0)
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query!(
+            "UPDATE retiros SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL",
+            id_str,
+            deleted_at_str
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query!(
+            "UPDATE transacciones SET deleted_at = ?2 WHERE retiro_id = ?1 AND deleted_at IS NULL",
+            id_str,
+            deleted_at_str
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(true)
     }
 
     /// Contar retiros por estado
     pub async fn count_by_estado(&self, estado: EstadoRetiro) -> Result<i64> {
         let estado_str = estado.to_string();
         let row = sqlx::query!(
-            "SELECT COUNT(*) as count FROM retiros WHERE estado = ?1",
+            "SELECT COUNT(*) as count FROM retiros WHERE estado = ?1 AND deleted_at IS NULL",
             estado_str
         )
         .fetch_one(&self.pool)
@@ -280,7 +362,7 @@ impl RetiroRepository {
     /// Obtener total de participantes de todos los retiros
     pub async fn get_total_participantes(&self) -> Result<i64> {
         let row = sqlx::query!(
-            "SELECT COALESCE(SUM(numero_participantes), 0) as total FROM retiros"
+            "SELECT COALESCE(SUM(numero_participantes), 0) as total FROM retiros WHERE deleted_at IS NULL"
         )
         .fetch_one(&self.pool)
         .await?;
@@ -291,7 +373,7 @@ impl RetiroRepository {
     /// Obtener retiros finalizados recientes (últimos N)
     pub async fn get_finalizados_recientes(&self, limit: i32) -> Result<Vec<Retiro>> {
         let rows = sqlx::query!(
-            "SELECT id, nombre, descripcion, fecha_inicio, fecha_fin, ubicacion, numero_participantes, estado, created_at, updated_at FROM retiros WHERE estado = 'Finalizado' ORDER BY fecha_fin DESC LIMIT ?1",
+            "SELECT id, nombre, descripcion, fecha_inicio, fecha_fin, ubicacion, numero_participantes, estado, created_at, updated_at FROM retiros WHERE estado = 'Finalizado' AND deleted_at IS NULL ORDER BY fecha_fin DESC LIMIT ?1",
             limit
         )
         .fetch_all(&self.pool)
@@ -322,7 +404,7 @@ impl RetiroRepository {
     pub async fn search_by_name(&self, query: &str) -> Result<Vec<Retiro>> {
         let search_pattern = format!("%{}%", query);
         let rows = sqlx::query!(
-            "SELECT id, nombre, descripcion, fecha_inicio, fecha_fin, ubicacion, numero_participantes, estado, created_at, updated_at FROM retiros WHERE nombre LIKE ?1 ORDER BY fecha_inicio DESC",
+            "SELECT id, nombre, descripcion, fecha_inicio, fecha_fin, ubicacion, numero_participantes, estado, created_at, updated_at FROM retiros WHERE nombre LIKE ?1 AND deleted_at IS NULL ORDER BY fecha_inicio DESC",
             search_pattern
         )
         .fetch_all(&self.pool)
@@ -354,3 +436,146 @@ impl RetiroRepository {
         Ok(retiros)
     }
 }
+
+async fn insertar_retiro(tx: &mut Transaction<'_, Sqlite>, retiro: &Retiro) -> Result<()> {
+    // Crear variables para evitar problemas de lifetime
+    let id_str = retiro.id.to_string();
+    let estado_str = retiro.estado.to_string();
+    let fecha_inicio_str = retiro.fecha_inicio.to_rfc3339();
+    let fecha_fin_str = retiro.fecha_fin.to_rfc3339();
+    let created_at_str = retiro.created_at.to_rfc3339();
+    let updated_at_str = retiro.updated_at.to_rfc3339();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO retiros (id, nombre, descripcion, fecha_inicio, fecha_fin, ubicacion, numero_participantes, estado, created_at, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+        "#,
+        id_str,
+        retiro.nombre,
+        retiro.descripcion,
+        fecha_inicio_str,
+        fecha_fin_str,
+        retiro.ubicacion,
+        retiro.numero_participantes,
+        estado_str,
+        created_at_str,
+        updated_at_str
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        CreateCategoria, CreatePlantillaTransaccion, CreateTransaccion, TipoCategoria,
+        TipoTransaccion,
+    };
+    use crate::repositories::{pool_de_prueba, CategoriaRepository, TransaccionRepository};
+    use chrono::TimeZone;
+
+    async fn categoria(pool: &SqlitePool, nombre: &str) -> Uuid {
+        CategoriaRepository::new(pool.clone())
+            .create(CreateCategoria {
+                nombre: nombre.to_string(),
+                tipo: TipoCategoria::Gasto,
+                color: String::new(),
+                padre_id: None,
+            })
+            .await
+            .unwrap()
+            .id
+    }
+
+    async fn plantilla(pool: &SqlitePool, nombre: &str, categoria_id: Uuid, monto: f64) {
+        PlantillaRepository::new(pool.clone())
+            .create(CreatePlantillaTransaccion {
+                nombre: nombre.to_string(),
+                categoria_id,
+                tipo: TipoTransaccion::Gasto,
+                monto,
+                descripcion: nombre.to_string(),
+            })
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn clonar_no_copia_las_transacciones_reales() {
+        let pool = pool_de_prueba().await;
+        let repo = RetiroRepository::new(pool.clone());
+        let original = repo
+            .create(CreateRetiro {
+                nombre: "Retiro de otoño".to_string(),
+                descripcion: None,
+                fecha_inicio: Utc.with_ymd_and_hms(2026, 10, 12, 0, 0, 0).unwrap(),
+                fecha_fin: Utc.with_ymd_and_hms(2026, 10, 14, 0, 0, 0).unwrap(),
+                ubicacion: Some("Montserrat".to_string()),
+                numero_participantes: 20,
+            })
+            .await
+            .unwrap();
+        let comida = categoria(&pool, "Comida").await;
+        let seguro = categoria(&pool, "Seguro").await;
+        let material = categoria(&pool, "Material").await;
+        let transacciones = TransaccionRepository::new(pool.clone());
+        transacciones
+            .create(CreateTransaccion {
+                retiro_id: original.id,
+                categoria_id: comida,
+                tipo: TipoTransaccion::Gasto,
+                monto: 300.0,
+                descripcion: "Compra del desayuno".to_string(),
+                cuenta_id: None,
+                metodo_pago: None,
+            })
+            .await
+            .unwrap();
+        plantilla(&pool, "Seguro", seguro, 80.0).await;
+        plantilla(&pool, "Material", material, 25.0).await;
+        CategoriaRepository::new(pool.clone())
+            .set_archivada(material, true)
+            .await
+            .unwrap();
+
+        let clon = repo
+            .clonar(original.id, None, None, true)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(matches!(clon.retiro.estado, EstadoRetiro::Planificacion));
+        assert_eq!(
+            clon.retiro.fecha_inicio,
+            Utc.with_ymd_and_hms(2027, 4, 12, 0, 0, 0).unwrap()
+        );
+        assert_eq!(clon.retiro.ubicacion.as_deref(), Some("Montserrat"));
+        // Solo la plantilla con categoría activa; nada de la edición anterior
+        assert_eq!(clon.desde_plantillas.len(), 1);
+        let nuevas = transacciones.get_by_retiro(clon.retiro.id).await.unwrap();
+        assert_eq!(nuevas.len(), 1);
+        assert_eq!(nuevas[0].categoria_id, seguro);
+        assert_eq!(
+            transacciones
+                .calculate_balance(clon.retiro.id, None)
+                .await
+                .unwrap(),
+            -80.0
+        );
+
+        let sin_plantillas = repo
+            .clonar(original.id, Some("Otra edición".to_string()), None, false)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(transacciones
+            .get_by_retiro(sin_plantillas.retiro.id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
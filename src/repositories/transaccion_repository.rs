@@ -1,31 +1,30 @@
-use crate::models::{CreateTransaccion, TipoTransaccion, Transaccion};
+use crate::models::{
+    arbol_categorias, antecesores, CreateTransaccion, EstadoConciliacion, MetodoPago,
+    TipoTransaccion, TotalCategoria, Transaccion,
+};
+use crate::repositories::{parse_flexible_datetime, CategoriaRepository, EtiquetaRepository};
 use crate::{AppError, Result};
-use chrono::{DateTime, NaiveDateTime, Utc};
-use sqlx::SqlitePool;
+use chrono::{Datelike, Days, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, Sqlite, SqlitePool, Transaction};
+use std::collections::HashMap;
 use uuid::Uuid;
 use validator::Validate;
 
-// Función helper para parsear fechas en múltiples formatos
-fn parse_flexible_datetime(date_str: &str) -> Result<DateTime<Utc>> {
-    // Intentar RFC3339 primero
-    if let Ok(dt) = DateTime::parse_from_rfc3339(date_str) {
-        return Ok(dt.with_timezone(&Utc));
-    }
-
-    // Intentar formato SQLite datetime: "YYYY-MM-DD HH:MM:SS"
-    if let Ok(naive_dt) = NaiveDateTime::parse_from_str(date_str, "%Y-%m-%d %H:%M:%S") {
-        return Ok(DateTime::from_naive_utc_and_offset(naive_dt, Utc));
-    }
-
-    // Intentar formato SQLite datetime con microsegundos: "YYYY-MM-DD HH:MM:SS.ffffff"
-    if let Ok(naive_dt) = NaiveDateTime::parse_from_str(date_str, "%Y-%m-%d %H:%M:%S%.f") {
-        return Ok(DateTime::from_naive_utc_and_offset(naive_dt, Utc));
-    }
+// Función helper para leer el método de pago guardado como texto
+fn parse_metodo_pago(valor: Option<String>) -> Result<Option<MetodoPago>> {
+    valor
+        .map(|v| {
+            MetodoPago::parse(&v)
+                .ok_or_else(|| AppError::Internal(format!("Invalid metodo de pago: {}", v)))
+        })
+        .transpose()
+}
 
-    Err(AppError::Internal(format!(
-        "Invalid date format: {}",
-        date_str
-    )))
+// Función helper para leer el estado de conciliación guardado como texto
+fn parse_conciliacion(valor: &str) -> Result<EstadoConciliacion> {
+    EstadoConciliacion::parse(valor)
+        .ok_or_else(|| AppError::Internal(format!("Invalid estado de conciliacion: {}", valor)))
 }
 
 pub struct TransaccionRepository {
@@ -43,6 +42,36 @@ impl TransaccionRepository {
         data.validate()
             .map_err(|e| AppError::Validation(e.to_string()))?;
 
+        // El retiro y la categoría no pueden estar en la papelera
+        let retiro_id_str = data.retiro_id.to_string();
+        let categoria_id_str = data.categoria_id.to_string();
+        let existentes = sqlx::query!(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM retiros WHERE id = ?1 AND deleted_at IS NULL) as "retiros!: i64",
+                (SELECT COUNT(*) FROM categorias WHERE id = ?2 AND deleted_at IS NULL) as "categorias!: i64"
+            "#,
+            retiro_id_str,
+            categoria_id_str
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        if existentes.retiros == 0 {
+            return Err(AppError::NotFound("Retiro".to_string()));
+        }
+        if existentes.categorias == 0 {
+            return Err(AppError::NotFound("Categoría".to_string()));
+        }
+        if let Some(cuenta_id) = data.cuenta_id {
+            let cuenta_id_str = cuenta_id.to_string();
+            let cuenta = sqlx::query!("SELECT id FROM cuentas WHERE id = ?1", cuenta_id_str)
+                .fetch_optional(&self.pool)
+                .await?;
+            if cuenta.is_none() {
+                return Err(AppError::NotFound("Cuenta".to_string()));
+            }
+        }
+
         let transaccion = Transaccion::new(data);
 
         // Crear variables para evitar problemas de lifetime
@@ -50,13 +79,15 @@ impl TransaccionRepository {
         let retiro_id_str = transaccion.retiro_id.to_string();
         let categoria_id_str = transaccion.categoria_id.to_string();
         let tipo_str = transaccion.tipo.to_string();
+        let cuenta_id_str = transaccion.cuenta_id.map(|id| id.to_string());
+        let metodo_pago_str = transaccion.metodo_pago.map(|m| m.to_string());
         let created_at_str = transaccion.created_at.to_rfc3339();
         let updated_at_str = transaccion.updated_at.to_rfc3339();
 
         sqlx::query!(
             r#"
-            INSERT INTO transacciones (id, retiro_id, categoria_id, tipo, monto, descripcion, created_at, updated_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            INSERT INTO transacciones (id, retiro_id, categoria_id, tipo, monto, descripcion, cuenta_id, metodo_pago, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
             "#,
             id_str,
             retiro_id_str,
@@ -64,6 +95,8 @@ impl TransaccionRepository {
             tipo_str,
             transaccion.monto,
             transaccion.descripcion,
+            cuenta_id_str,
+            metodo_pago_str,
             created_at_str,
             updated_at_str
         )
@@ -77,7 +110,7 @@ impl TransaccionRepository {
     pub async fn get_by_id(&self, id: Uuid) -> Result<Option<Transaccion>> {
         let id_str = id.to_string();
         let row = sqlx::query!(
-            "SELECT id, retiro_id, categoria_id, tipo, monto, descripcion, created_at, updated_at FROM transacciones WHERE id = ?1",
+            r#"SELECT id, retiro_id, categoria_id, tipo, monto, descripcion, cuenta_id, metodo_pago, conciliacion, (SELECT l.id FROM lineas_extracto l WHERE l.transaccion_id = transacciones.id) as "linea_extracto_id?: String", created_at, updated_at FROM transacciones WHERE id = ?1 AND deleted_at IS NULL"#,
             id_str
         )
         .fetch_optional(&self.pool)
@@ -101,6 +134,18 @@ impl TransaccionRepository {
                     },
                     monto: row.monto,
                     descripcion: row.descripcion,
+                    cuenta_id: row
+                        .cuenta_id
+                        .map(|id| Uuid::parse_str(&id))
+                        .transpose()
+                        .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                    metodo_pago: parse_metodo_pago(row.metodo_pago)?,
+                    conciliacion: parse_conciliacion(&row.conciliacion)?,
+                    linea_extracto_id: row
+                        .linea_extracto_id
+                        .map(|id| Uuid::parse_str(&id))
+                        .transpose()
+                        .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
                     created_at: parse_flexible_datetime(&row.created_at)?,
                     updated_at: parse_flexible_datetime(&row.updated_at)?,
                 };
@@ -114,7 +159,7 @@ impl TransaccionRepository {
     pub async fn get_by_retiro(&self, retiro_id: Uuid) -> Result<Vec<Transaccion>> {
         let retiro_id_str = retiro_id.to_string();
         let rows = sqlx::query!(
-            "SELECT id, retiro_id, categoria_id, tipo, monto, descripcion, created_at, updated_at FROM transacciones WHERE retiro_id = ?1 ORDER BY created_at DESC",
+            r#"SELECT id, retiro_id, categoria_id, tipo, monto, descripcion, cuenta_id, metodo_pago, conciliacion, (SELECT l.id FROM lineas_extracto l WHERE l.transaccion_id = transacciones.id) as "linea_extracto_id?: String", created_at, updated_at FROM transacciones WHERE retiro_id = ?1 AND deleted_at IS NULL ORDER BY created_at DESC"#,
             retiro_id_str
         )
         .fetch_all(&self.pool)
@@ -136,6 +181,133 @@ impl TransaccionRepository {
                 },
                 monto: row.monto,
                 descripcion: row.descripcion,
+                cuenta_id: row
+                    .cuenta_id
+                    .map(|id| Uuid::parse_str(&id))
+                    .transpose()
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                metodo_pago: parse_metodo_pago(row.metodo_pago)?,
+                conciliacion: parse_conciliacion(&row.conciliacion)?,
+                linea_extracto_id: row
+                    .linea_extracto_id
+                    .map(|id| Uuid::parse_str(&id))
+                    .transpose()
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                created_at: parse_flexible_datetime(&row.created_at)?,
+                updated_at: parse_flexible_datetime(&row.updated_at)?,
+            };
+            transacciones.push(transaccion);
+        }
+        Ok(transacciones)
+    }
+
+    /// Obtener las transacciones de un retiro que tienen todas las etiquetas indicadas
+    /// (sin distinguir mayúsculas). Sin etiquetas equivale a `get_by_retiro`.
+    pub async fn get_by_retiro_con_etiquetas(
+        &self,
+        retiro_id: Uuid,
+        etiquetas: &[String],
+    ) -> Result<Vec<Transaccion>> {
+        let transacciones = self.get_by_retiro(retiro_id).await?;
+        if etiquetas.is_empty() {
+            return Ok(transacciones);
+        }
+
+        let por_transaccion = EtiquetaRepository::new(self.pool.clone())
+            .get_by_retiro(retiro_id)
+            .await?;
+        Ok(transacciones
+            .into_iter()
+            .filter(|t| {
+                por_transaccion.get(&t.id).is_some_and(|propias| {
+                    etiquetas
+                        .iter()
+                        .all(|e| propias.iter().any(|p| p.eq_ignore_ascii_case(e.trim())))
+                })
+            })
+            .collect())
+    }
+
+    /// Obtener todas las transacciones
+    pub async fn get_all(&self) -> Result<Vec<Transaccion>> {
+        let rows = sqlx::query!(
+            r#"SELECT id, retiro_id, categoria_id, tipo, monto, descripcion, cuenta_id, metodo_pago, conciliacion, (SELECT l.id FROM lineas_extracto l WHERE l.transaccion_id = transacciones.id) as "linea_extracto_id?: String", created_at, updated_at FROM transacciones WHERE deleted_at IS NULL ORDER BY created_at, id"#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut transacciones = Vec::new();
+        for row in rows {
+            let transaccion = Transaccion {
+                id: Uuid::parse_str(&row.id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                retiro_id: Uuid::parse_str(&row.retiro_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                categoria_id: Uuid::parse_str(&row.categoria_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                tipo: match row.tipo.as_str() {
+                    "Ingreso" => TipoTransaccion::Ingreso,
+                    "Gasto" => TipoTransaccion::Gasto,
+                    _ => return Err(AppError::Internal("Invalid tipo transaccion".to_string())),
+                },
+                monto: row.monto,
+                descripcion: row.descripcion,
+                cuenta_id: row
+                    .cuenta_id
+                    .map(|id| Uuid::parse_str(&id))
+                    .transpose()
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                metodo_pago: parse_metodo_pago(row.metodo_pago)?,
+                conciliacion: parse_conciliacion(&row.conciliacion)?,
+                linea_extracto_id: row
+                    .linea_extracto_id
+                    .map(|id| Uuid::parse_str(&id))
+                    .transpose()
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                created_at: parse_flexible_datetime(&row.created_at)?,
+                updated_at: parse_flexible_datetime(&row.updated_at)?,
+            };
+            transacciones.push(transaccion);
+        }
+        Ok(transacciones)
+    }
+
+    /// Obtener todas las transacciones, también las de la papelera (para exportar)
+    pub async fn get_all_incluyendo_eliminadas(&self) -> Result<Vec<Transaccion>> {
+        let rows = sqlx::query!(
+            r#"SELECT id, retiro_id, categoria_id, tipo, monto, descripcion, cuenta_id, metodo_pago, conciliacion, (SELECT l.id FROM lineas_extracto l WHERE l.transaccion_id = transacciones.id) as "linea_extracto_id?: String", created_at, updated_at FROM transacciones ORDER BY created_at, id"#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut transacciones = Vec::new();
+        for row in rows {
+            let transaccion = Transaccion {
+                id: Uuid::parse_str(&row.id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                retiro_id: Uuid::parse_str(&row.retiro_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                categoria_id: Uuid::parse_str(&row.categoria_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                tipo: match row.tipo.as_str() {
+                    "Ingreso" => TipoTransaccion::Ingreso,
+                    "Gasto" => TipoTransaccion::Gasto,
+                    _ => return Err(AppError::Internal("Invalid tipo transaccion".to_string())),
+                },
+                monto: row.monto,
+                descripcion: row.descripcion,
+                cuenta_id: row
+                    .cuenta_id
+                    .map(|id| Uuid::parse_str(&id))
+                    .transpose()
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                metodo_pago: parse_metodo_pago(row.metodo_pago)?,
+                conciliacion: parse_conciliacion(&row.conciliacion)?,
+                linea_extracto_id: row
+                    .linea_extracto_id
+                    .map(|id| Uuid::parse_str(&id))
+                    .transpose()
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
                 created_at: parse_flexible_datetime(&row.created_at)?,
                 updated_at: parse_flexible_datetime(&row.updated_at)?,
             };
@@ -144,24 +316,68 @@ impl TransaccionRepository {
         Ok(transacciones)
     }
 
-    /// Eliminar una transacción
+    /// Enviar una transacción a la papelera
     pub async fn delete(&self, id: Uuid) -> Result<bool> {
         let id_str = id.to_string();
-        let result = sqlx::query!("DELETE FROM transacciones WHERE id = ?1", id_str)
-            .execute(&self.pool)
-            .await?;
+        let deleted_at_str = Utc::now().to_rfc3339();
+        let result = sqlx::query!(
+            "UPDATE transacciones SET deleted_at = ?2 WHERE id = ?1 AND deleted_at IS NULL",
+            id_str,
+            deleted_at_str
+        )
+        .execute(&self.pool)
+        .await?;
 
         Ok(result.rows_affected() > 0)
     }
 
-    /// Calcular balance por tipo de transacción (opcional)
+    /// Cambiar a mano el estado de conciliación de una transacción (p. ej. puntear un pago
+    /// en efectivo). Una transacción enlazada con una línea de extracto no puede quedar pendiente.
+    pub async fn set_conciliacion(
+        &self,
+        id: Uuid,
+        estado: EstadoConciliacion,
+    ) -> Result<Transaccion> {
+        let transaccion = self
+            .get_by_id(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Transacción".to_string()))?;
+        if estado == EstadoConciliacion::Pendiente && transaccion.linea_extracto_id.is_some() {
+            return Err(AppError::Validation(
+                "La transacción está enlazada con una línea de extracto; desvincúlala antes de dejarla pendiente"
+                    .to_string(),
+            ));
+        }
+
+        let id_str = id.to_string();
+        let estado_str = estado.to_string();
+        let updated_at = Utc::now();
+        let updated_at_str = updated_at.to_rfc3339();
+        sqlx::query!(
+            "UPDATE transacciones SET conciliacion = ?2, updated_at = ?3 WHERE id = ?1",
+            id_str,
+            estado_str,
+            updated_at_str
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(Transaccion {
+            conciliacion: estado,
+            updated_at,
+            ..transaccion
+        })
+    }
+
+    /// Calcular balance por tipo de transacción (opcional).
+    /// Las transferencias entre cuentas no son ingresos ni gastos y no se incluyen.
     pub async fn calculate_balance(&self, retiro_id: Uuid, tipo: Option<TipoTransaccion>) -> Result<f64> {
         let retiro_id_str = retiro_id.to_string();
 
         match tipo {
             Some(TipoTransaccion::Ingreso) => {
                 let row = sqlx::query!(
-                    "SELECT COALESCE(SUM(monto), 0) as total FROM transacciones WHERE retiro_id = ?1 AND tipo = 'Ingreso'",
+                    "SELECT COALESCE(SUM(monto), 0) as total FROM transacciones WHERE retiro_id = ?1 AND tipo = 'Ingreso' AND deleted_at IS NULL",
                     retiro_id_str
                 )
                 .fetch_one(&self.pool)
@@ -170,7 +386,7 @@ impl TransaccionRepository {
             },
             Some(TipoTransaccion::Gasto) => {
                 let row = sqlx::query!(
-                    "SELECT COALESCE(SUM(monto), 0) as total FROM transacciones WHERE retiro_id = ?1 AND tipo = 'Gasto'",
+                    "SELECT COALESCE(SUM(monto), 0) as total FROM transacciones WHERE retiro_id = ?1 AND tipo = 'Gasto' AND deleted_at IS NULL",
                     retiro_id_str
                 )
                 .fetch_one(&self.pool)
@@ -180,14 +396,14 @@ impl TransaccionRepository {
             None => {
                 // Calcular balance total (ingresos - gastos)
         let ingresos_row = sqlx::query!(
-            "SELECT COALESCE(SUM(monto), 0) as total FROM transacciones WHERE retiro_id = ?1 AND tipo = 'Ingreso'",
+            "SELECT COALESCE(SUM(monto), 0) as total FROM transacciones WHERE retiro_id = ?1 AND tipo = 'Ingreso' AND deleted_at IS NULL",
             retiro_id_str
         )
         .fetch_one(&self.pool)
         .await?;
 
         let gastos_row = sqlx::query!(
-            "SELECT COALESCE(SUM(monto), 0) as total FROM transacciones WHERE retiro_id = ?1 AND tipo = 'Gasto'",
+            "SELECT COALESCE(SUM(monto), 0) as total FROM transacciones WHERE retiro_id = ?1 AND tipo = 'Gasto' AND deleted_at IS NULL",
                     retiro_id_str
                 )
                 .fetch_one(&self.pool)
@@ -198,11 +414,72 @@ impl TransaccionRepository {
         }
     }
 
+    /// Obtener el resumen financiero de un retiro
+    pub async fn get_financial_summary(&self, retiro_id: Uuid) -> Result<FinancialSummary> {
+        self.get_financial_summaries(&[retiro_id])
+            .await?
+            .pop()
+            .ok_or_else(|| AppError::Internal("Resumen financiero no calculado".to_string()))
+    }
+
+    /// Obtener el resumen financiero de varios retiros con una sola consulta agrupada.
+    /// Devuelve un resumen por cada ID solicitado, en el mismo orden (con ceros si no hay transacciones).
+    pub async fn get_financial_summaries(&self, retiro_ids: &[Uuid]) -> Result<Vec<FinancialSummary>> {
+        if retiro_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids: Vec<String> = retiro_ids.iter().map(|id| id.to_string()).collect();
+        let ids_json = serde_json::to_string(&ids)
+            .map_err(|e| AppError::Internal(format!("Error serializando IDs: {}", e)))?;
+
+        // query! no puede inferir las columnas de json_each, así que se usa la versión en tiempo de ejecución
+        let rows = sqlx::query(
+            r#"
+            SELECT 
+                ids.value as retiro_id,
+                COALESCE(SUM(CASE WHEN t.tipo = 'Ingreso' THEN t.monto ELSE 0.0 END), 0.0) as total_ingresos,
+                COALESCE(SUM(CASE WHEN t.tipo = 'Gasto' THEN t.monto ELSE 0.0 END), 0.0) as total_gastos,
+                COUNT(CASE WHEN t.tipo = 'Ingreso' THEN 1 END) as count_ingresos,
+                COUNT(CASE WHEN t.tipo = 'Gasto' THEN 1 END) as count_gastos
+            FROM json_each(?1) ids
+            LEFT JOIN transacciones t ON t.retiro_id = ids.value AND t.deleted_at IS NULL
+            GROUP BY ids.key, ids.value
+            ORDER BY ids.key
+            "#,
+        )
+        .bind(ids_json)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut resumenes = Vec::new();
+        for row in rows {
+            let retiro_id: String = row.try_get("retiro_id")?;
+            let total_ingresos: f64 = row.try_get("total_ingresos")?;
+            let total_gastos: f64 = row.try_get("total_gastos")?;
+            let count_ingresos: i64 = row.try_get("count_ingresos")?;
+            let count_gastos: i64 = row.try_get("count_gastos")?;
+
+            resumenes.push(FinancialSummary {
+                retiro_id: Uuid::parse_str(&retiro_id)
+                    .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?,
+                total_ingresos,
+                total_gastos,
+                balance: total_ingresos - total_gastos,
+                count_ingresos: count_ingresos as i32,
+                count_gastos: count_gastos as i32,
+                total_transacciones: (count_ingresos + count_gastos) as i32,
+            });
+        }
+
+        Ok(resumenes)
+    }
+
     /// Contar transacciones por retiro
     pub async fn count_by_retiro(&self, retiro_id: Uuid) -> Result<i64> {
         let retiro_id_str = retiro_id.to_string();
         let row = sqlx::query!(
-            "SELECT COUNT(*) as count FROM transacciones WHERE retiro_id = ?1",
+            "SELECT COUNT(*) as count FROM transacciones WHERE retiro_id = ?1 AND deleted_at IS NULL",
             retiro_id_str
         )
         .fetch_one(&self.pool)
@@ -215,21 +492,21 @@ impl TransaccionRepository {
     pub async fn calculate_global_balance(&self) -> Result<(f64, f64, i64)> {
         // Total ingresos
         let ingresos_row = sqlx::query!(
-            "SELECT COALESCE(SUM(monto), 0) as total FROM transacciones WHERE tipo = 'Ingreso'"
+            "SELECT COALESCE(SUM(monto), 0) as total FROM transacciones WHERE tipo = 'Ingreso' AND deleted_at IS NULL"
         )
         .fetch_one(&self.pool)
         .await?;
 
         // Total gastos
         let gastos_row = sqlx::query!(
-            "SELECT COALESCE(SUM(monto), 0) as total FROM transacciones WHERE tipo = 'Gasto'"
+            "SELECT COALESCE(SUM(monto), 0) as total FROM transacciones WHERE tipo = 'Gasto' AND deleted_at IS NULL"
         )
         .fetch_one(&self.pool)
         .await?;
 
         // Total transacciones
         let count_row = sqlx::query!(
-            "SELECT COUNT(*) as count FROM transacciones"
+            "SELECT COUNT(*) as count FROM transacciones WHERE deleted_at IS NULL"
         )
         .fetch_one(&self.pool)
         .await?;
@@ -241,36 +518,86 @@ impl TransaccionRepository {
         ))
     }
 
-    /// Obtener top categorías de gastos (por monto total)
+    /// Obtener top categorías de gastos (por monto total, sumando sus subcategorías)
     pub async fn get_top_categorias_gastos(&self, limit: i32) -> Result<Vec<(String, String, f64)>> {
+        let gasto = TipoTransaccion::Gasto.to_string();
+        let mut totales: Vec<_> = self
+            .totales_en_arbol(None, Some(gasto))
+            .await?
+            .into_iter()
+            .filter(|t| t.nivel == 0 && t.total_acumulado > 0.0)
+            .collect();
+        totales.sort_by(|a, b| b.total_acumulado.total_cmp(&a.total_acumulado));
+
+        Ok(totales
+            .into_iter()
+            .take(limit.max(0) as usize)
+            .map(|t| (t.nombre, t.color, t.total_acumulado))
+            .collect())
+    }
+
+    /// Totales por categoría en orden de árbol, con el total propio y el acumulado
+    /// de cada categoría con sus subcategorías (de un retiro o de todos)
+    pub async fn get_totales_por_categoria(
+        &self,
+        retiro_id: Option<Uuid>,
+    ) -> Result<Vec<TotalCategoria>> {
+        self.totales_en_arbol(retiro_id, None).await
+    }
+
+    async fn totales_en_arbol(
+        &self,
+        retiro_id: Option<Uuid>,
+        tipo: Option<String>,
+    ) -> Result<Vec<TotalCategoria>> {
+        let retiro_id_str = retiro_id.map(|id| id.to_string());
         let rows = sqlx::query!(
             r#"
-            SELECT 
-                c.nombre,
-                c.color,
-                COALESCE(SUM(t.monto), 0) as total 
-            FROM transacciones t
-            INNER JOIN categorias c ON t.categoria_id = c.id
-            WHERE t.tipo = 'Gasto' 
-            GROUP BY c.id, c.nombre, c.color
-            ORDER BY total DESC 
-            LIMIT ?1
+            SELECT categoria_id, COALESCE(SUM(monto), 0) as "total!: f64"
+            FROM transacciones
+            WHERE (?1 IS NULL OR retiro_id = ?1) AND (?2 IS NULL OR tipo = ?2)
+              AND deleted_at IS NULL
+            GROUP BY categoria_id
             "#,
-            limit
+            retiro_id_str,
+            tipo
         )
         .fetch_all(&self.pool)
         .await?;
 
-        let mut resultados = Vec::new();
+        let mut propios = HashMap::new();
         for row in rows {
-            resultados.push((
-                row.nombre,
-                row.color,
-                row.total as f64,
-            ));
+            let categoria_id = Uuid::parse_str(&row.categoria_id)
+                .map_err(|e| AppError::Internal(format!("Invalid UUID: {}", e)))?;
+            propios.insert(categoria_id, row.total);
         }
 
-        Ok(resultados)
+        // Cada total se suma a su categoría y a todos sus antecesores
+        let categorias = CategoriaRepository::new(self.pool.clone())
+            .get_all_incluyendo_archivadas()
+            .await?;
+        let mut acumulados: HashMap<Uuid, f64> = HashMap::new();
+        for (categoria_id, total) in &propios {
+            *acumulados.entry(*categoria_id).or_default() += total;
+            for antecesor in antecesores(*categoria_id, &categorias) {
+                *acumulados.entry(antecesor).or_default() += total;
+            }
+        }
+
+        Ok(arbol_categorias(&categorias)
+            .into_iter()
+            .map(|(nivel, c)| TotalCategoria {
+                categoria_id: c.id,
+                nombre: c.nombre.clone(),
+                tipo: c.tipo.clone(),
+                color: c.color.clone(),
+                padre_id: c.padre_id,
+                archivada: c.archivada,
+                nivel,
+                total_propio: propios.get(&c.id).copied().unwrap_or(0.0),
+                total_acumulado: acumulados.get(&c.id).copied().unwrap_or(0.0),
+            })
+            .collect())
     }
 
     /// Calcular estadísticas por retiro (para comparativas)
@@ -286,6 +613,7 @@ impl TransaccionRepository {
                     COALESCE(SUM(CASE WHEN tipo = 'Ingreso' THEN monto ELSE 0 END), 0) - 
                     COALESCE(SUM(CASE WHEN tipo = 'Gasto' THEN monto ELSE 0 END), 0) as balance
                 FROM transacciones
+                WHERE deleted_at IS NULL
                 GROUP BY retiro_id
             ) as balances
             "#
@@ -303,7 +631,7 @@ impl TransaccionRepository {
                     retiro_id,
                     COALESCE(SUM(monto), 0) as total_ingresos
                 FROM transacciones
-                WHERE tipo = 'Ingreso'
+                WHERE tipo = 'Ingreso' AND deleted_at IS NULL
                 GROUP BY retiro_id
             ) as ingresos_por_retiro
             "#
@@ -322,7 +650,7 @@ impl TransaccionRepository {
                     retiro_id,
                     COALESCE(SUM(monto), 0) as total_gastos
                 FROM transacciones
-                WHERE tipo = 'Gasto'
+                WHERE tipo = 'Gasto' AND deleted_at IS NULL
                 GROUP BY retiro_id
             ) as gastos_por_retiro
             "#
@@ -337,10 +665,163 @@ impl TransaccionRepository {
             gastos_promedio_row.count_retiros as i32,
         ))
     }
+
+    /// Obtener el flujo de caja agrupado por periodo (día/semana/mes) con acumulados.
+    /// Se puede filtrar por retiro, por rango de fechas o por ambos.
+    pub async fn get_flujo_caja(
+        &self,
+        retiro_id: Option<Uuid>,
+        periodo: PeriodoFlujo,
+        desde: Option<NaiveDate>,
+        hasta: Option<NaiveDate>,
+    ) -> Result<Vec<PuntoFlujoCaja>> {
+        let retiro_id_str = retiro_id.map(|id| id.to_string());
+        let periodo_str = periodo.to_string();
+        let desde_str = desde.map(|d| d.format("%Y-%m-%d").to_string());
+        let hasta_str = hasta.map(|d| d.format("%Y-%m-%d").to_string());
+
+        // El inicio de cada periodo se calcula en SQLite: la semana empieza en lunes
+        let rows = sqlx::query!(
+            r#"
+            SELECT 
+                CASE ?2
+                    WHEN 'Dia' THEN date(created_at)
+                    WHEN 'Semana' THEN date(created_at, 'weekday 0', '-6 days')
+                    ELSE date(created_at, 'start of month')
+                END as "periodo!: String",
+                COALESCE(SUM(CASE WHEN tipo = 'Ingreso' THEN monto ELSE 0 END), 0) as "ingresos!: f64",
+                COALESCE(SUM(CASE WHEN tipo = 'Gasto' THEN monto ELSE 0 END), 0) as "gastos!: f64",
+                COUNT(*) as "transacciones!: i64"
+            FROM transacciones
+            WHERE (?1 IS NULL OR retiro_id = ?1)
+              AND deleted_at IS NULL
+              AND (?3 IS NULL OR date(created_at) >= ?3)
+              AND (?4 IS NULL OR date(created_at) <= ?4)
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+            retiro_id_str,
+            periodo_str,
+            desde_str,
+            hasta_str
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut buckets = Vec::new();
+        for row in rows {
+            let inicio = NaiveDate::parse_from_str(&row.periodo, "%Y-%m-%d")
+                .map_err(|e| AppError::Internal(format!("Invalid periodo: {}", e)))?;
+            buckets.push((inicio, row.ingresos, row.gastos, row.transacciones));
+        }
+
+        // Con `desde`/`hasta` la serie cubre el rango completo aunque haya periodos vacíos
+        let primero = desde
+            .map(|d| periodo.inicio(d))
+            .or_else(|| buckets.first().map(|b| b.0));
+        let ultimo = hasta
+            .map(|h| periodo.inicio(h))
+            .or_else(|| buckets.last().map(|b| b.0))
+            .or(primero);
+        let (primero, ultimo) = match (primero, ultimo) {
+            (Some(primero), Some(ultimo)) => (primero, ultimo),
+            _ => return Ok(Vec::new()),
+        };
+
+        // Los acumulados parten de lo registrado antes de `desde`
+        let previo = sqlx::query!(
+            r#"
+            SELECT 
+                COALESCE(SUM(CASE WHEN tipo = 'Ingreso' THEN monto ELSE 0 END), 0) as "ingresos!: f64",
+                COALESCE(SUM(CASE WHEN tipo = 'Gasto' THEN monto ELSE 0 END), 0) as "gastos!: f64"
+            FROM transacciones
+            WHERE (?1 IS NULL OR retiro_id = ?1)
+              AND deleted_at IS NULL
+              AND ?2 IS NOT NULL
+              AND date(created_at) < ?2
+            "#,
+            retiro_id_str,
+            desde_str
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        // Rellenar los periodos sin movimientos para que la serie sea continua
+        let mut serie = Vec::new();
+        let mut ingresos_acumulados = previo.ingresos;
+        let mut gastos_acumulados = previo.gastos;
+        let mut datos = buckets.into_iter().peekable();
+        let mut actual = primero;
+        while actual <= ultimo {
+            let (ingresos, gastos, transacciones) = match datos.peek() {
+                Some((inicio, ..)) if *inicio == actual => {
+                    let (_, ingresos, gastos, transacciones) = datos.next().unwrap();
+                    (ingresos, gastos, transacciones)
+                }
+                _ => (0.0, 0.0, 0),
+            };
+
+            ingresos_acumulados += ingresos;
+            gastos_acumulados += gastos;
+
+            serie.push(PuntoFlujoCaja {
+                periodo: actual,
+                ingresos,
+                gastos,
+                transacciones,
+                ingresos_acumulados,
+                gastos_acumulados,
+                balance: ingresos_acumulados - gastos_acumulados,
+            });
+
+            actual = periodo.siguiente(actual)?;
+        }
+
+        Ok(serie)
+    }
+}
+
+/// Insertar una transacción ya construida dentro de una transacción SQL abierta
+pub(crate) async fn insertar_transaccion(
+    tx: &mut Transaction<'_, Sqlite>,
+    transaccion: &Transaccion,
+) -> Result<()> {
+    // Crear variables para evitar problemas de lifetime
+    let id_str = transaccion.id.to_string();
+    let retiro_id_str = transaccion.retiro_id.to_string();
+    let categoria_id_str = transaccion.categoria_id.to_string();
+    let tipo_str = transaccion.tipo.to_string();
+    let cuenta_id_str = transaccion.cuenta_id.map(|id| id.to_string());
+    let metodo_pago_str = transaccion.metodo_pago.map(|m| m.to_string());
+    let conciliacion_str = transaccion.conciliacion.to_string();
+    let created_at_str = transaccion.created_at.to_rfc3339();
+    let updated_at_str = transaccion.updated_at.to_rfc3339();
+
+    sqlx::query!(
+        r#"
+        INSERT INTO transacciones (id, retiro_id, categoria_id, tipo, monto, descripcion, cuenta_id, metodo_pago, conciliacion, created_at, updated_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+        "#,
+        id_str,
+        retiro_id_str,
+        categoria_id_str,
+        tipo_str,
+        transaccion.monto,
+        transaccion.descripcion,
+        cuenta_id_str,
+        metodo_pago_str,
+        conciliacion_str,
+        created_at_str,
+        updated_at_str
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
 }
 
 /// Estructura para resumen financiero
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FinancialSummary {
     pub retiro_id: Uuid,
     pub total_ingresos: f64,
@@ -350,3 +831,257 @@ pub struct FinancialSummary {
     pub count_gastos: i32,
     pub total_transacciones: i32,
 }
+
+/// Granularidad de agrupación para el flujo de caja
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum PeriodoFlujo {
+    Dia,
+    Semana,
+    Mes,
+}
+
+impl PeriodoFlujo {
+    /// Inicio del periodo que contiene `fecha`, igual que lo agrupa SQLite
+    fn inicio(&self, fecha: NaiveDate) -> NaiveDate {
+        match self {
+            PeriodoFlujo::Dia => fecha,
+            PeriodoFlujo::Semana => {
+                fecha - Days::new(fecha.weekday().num_days_from_monday() as u64)
+            }
+            PeriodoFlujo::Mes => fecha.with_day(1).unwrap_or(fecha),
+        }
+    }
+
+    /// Inicio del periodo siguiente a `inicio`
+    fn siguiente(&self, inicio: NaiveDate) -> Result<NaiveDate> {
+        let siguiente = match self {
+            PeriodoFlujo::Dia => inicio.checked_add_days(Days::new(1)),
+            PeriodoFlujo::Semana => inicio.checked_add_days(Days::new(7)),
+            PeriodoFlujo::Mes => inicio.checked_add_months(Months::new(1)),
+        };
+
+        siguiente.ok_or_else(|| AppError::Internal("Fecha fuera de rango".to_string()))
+    }
+}
+
+// Implementar Display para facilitar la conversión a string
+impl std::fmt::Display for PeriodoFlujo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PeriodoFlujo::Dia => write!(f, "Dia"),
+            PeriodoFlujo::Semana => write!(f, "Semana"),
+            PeriodoFlujo::Mes => write!(f, "Mes"),
+        }
+    }
+}
+
+/// Punto de la serie temporal de flujo de caja
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PuntoFlujoCaja {
+    /// Fecha de inicio del periodo
+    pub periodo: NaiveDate,
+    pub ingresos: f64,
+    pub gastos: f64,
+    pub transacciones: i64,
+    pub ingresos_acumulados: f64,
+    pub gastos_acumulados: f64,
+    /// Balance acumulado hasta el final del periodo
+    pub balance: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CreateCategoria, CreateRetiro, TipoCategoria};
+    use crate::repositories::{pool_de_prueba, RetiroRepository};
+    use chrono::TimeZone;
+
+    /// Crear una transacción con la fecha indicada (las de `create` llevan la fecha actual)
+    async fn transaccion_en(
+        pool: &SqlitePool,
+        retiro_id: Uuid,
+        categoria_id: Uuid,
+        tipo: TipoTransaccion,
+        monto: f64,
+        fecha: (u32, u32),
+    ) -> Transaccion {
+        let mut transaccion = Transaccion::new(CreateTransaccion {
+            retiro_id,
+            categoria_id,
+            tipo,
+            monto,
+            descripcion: "Movimiento".to_string(),
+            cuenta_id: None,
+            metodo_pago: None,
+        });
+        transaccion.created_at = Utc
+            .with_ymd_and_hms(2026, fecha.0, fecha.1, 22, 30, 0)
+            .unwrap();
+
+        let mut tx = pool.begin().await.unwrap();
+        insertar_transaccion(&mut tx, &transaccion).await.unwrap();
+        tx.commit().await.unwrap();
+        transaccion
+    }
+
+    #[tokio::test]
+    async fn agrupa_el_flujo_de_caja_por_periodo() {
+        let pool = pool_de_prueba().await;
+        let retiro = RetiroRepository::new(pool.clone())
+            .create(CreateRetiro {
+                nombre: "Retiro de otoño".to_string(),
+                descripcion: None,
+                fecha_inicio: Utc.with_ymd_and_hms(2026, 10, 12, 0, 0, 0).unwrap(),
+                fecha_fin: Utc.with_ymd_and_hms(2026, 10, 30, 0, 0, 0).unwrap(),
+                ubicacion: None,
+                numero_participantes: 10,
+            })
+            .await
+            .unwrap();
+        let categorias = CategoriaRepository::new(pool.clone());
+        let cuotas = categorias
+            .create(CreateCategoria {
+                nombre: "Cuotas".to_string(),
+                tipo: TipoCategoria::Ingreso,
+                color: String::new(),
+                padre_id: None,
+            })
+            .await
+            .unwrap();
+        let comida = categorias
+            .create(CreateCategoria {
+                nombre: "Comida".to_string(),
+                tipo: TipoCategoria::Gasto,
+                color: String::new(),
+                padre_id: None,
+            })
+            .await
+            .unwrap();
+
+        // Miércoles 14 y domingo 18 caen en la semana del lunes 12; la del 19 queda vacía
+        transaccion_en(
+            &pool,
+            retiro.id,
+            cuotas.id,
+            TipoTransaccion::Ingreso,
+            100.0,
+            (10, 14),
+        )
+        .await;
+        transaccion_en(
+            &pool,
+            retiro.id,
+            comida.id,
+            TipoTransaccion::Gasto,
+            30.0,
+            (10, 18),
+        )
+        .await;
+        transaccion_en(
+            &pool,
+            retiro.id,
+            comida.id,
+            TipoTransaccion::Gasto,
+            20.0,
+            (10, 27),
+        )
+        .await;
+        let eliminada = transaccion_en(
+            &pool,
+            retiro.id,
+            comida.id,
+            TipoTransaccion::Gasto,
+            500.0,
+            (10, 14),
+        )
+        .await;
+        let repo = TransaccionRepository::new(pool.clone());
+        repo.delete(eliminada.id).await.unwrap();
+
+        let semanas = repo
+            .get_flujo_caja(Some(retiro.id), PeriodoFlujo::Semana, None, None)
+            .await
+            .unwrap();
+        let resumen: Vec<_> = semanas
+            .iter()
+            .map(|p| {
+                (
+                    p.periodo.to_string(),
+                    p.ingresos,
+                    p.gastos,
+                    p.transacciones,
+                    p.balance,
+                )
+            })
+            .collect();
+        assert_eq!(
+            resumen,
+            vec![
+                ("2026-10-12".to_string(), 100.0, 30.0, 2, 70.0),
+                ("2026-10-19".to_string(), 0.0, 0.0, 0, 70.0),
+                ("2026-10-26".to_string(), 0.0, 20.0, 1, 50.0),
+            ]
+        );
+
+        let meses = repo
+            .get_flujo_caja(Some(retiro.id), PeriodoFlujo::Mes, None, None)
+            .await
+            .unwrap();
+        assert_eq!(meses.len(), 1);
+        assert_eq!(
+            meses[0].periodo,
+            NaiveDate::from_ymd_opt(2026, 10, 1).unwrap()
+        );
+        assert_eq!(meses[0].transacciones, 3);
+
+        let dias = repo
+            .get_flujo_caja(
+                None,
+                PeriodoFlujo::Dia,
+                NaiveDate::from_ymd_opt(2026, 10, 15),
+                NaiveDate::from_ymd_opt(2026, 10, 27),
+            )
+            .await
+            .unwrap();
+        assert_eq!(dias.len(), 13, "del 15 al 27, con los días vacíos rellenos");
+        assert_eq!(
+            dias[0].periodo,
+            NaiveDate::from_ymd_opt(2026, 10, 15).unwrap()
+        );
+        assert_eq!(dias[0].balance, 100.0, "parte del ingreso del día 14");
+        assert_eq!(dias[3].gastos, 30.0);
+        assert_eq!(dias.last().unwrap().balance, 50.0);
+
+        // Un rango sin movimientos sigue mostrando el saldo acumulado
+        let semanas = repo
+            .get_flujo_caja(
+                Some(retiro.id),
+                PeriodoFlujo::Semana,
+                NaiveDate::from_ymd_opt(2026, 11, 4),
+                NaiveDate::from_ymd_opt(2026, 11, 15),
+            )
+            .await
+            .unwrap();
+        let resumen: Vec<_> = semanas
+            .iter()
+            .map(|p| (p.periodo.to_string(), p.transacciones, p.balance))
+            .collect();
+        assert_eq!(
+            resumen,
+            vec![
+                ("2026-11-02".to_string(), 0, 50.0),
+                ("2026-11-09".to_string(), 0, 50.0),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn flujo_de_caja_vacio() {
+        let repo = TransaccionRepository::new(pool_de_prueba().await);
+        let serie = repo
+            .get_flujo_caja(None, PeriodoFlujo::Mes, None, None)
+            .await
+            .unwrap();
+        assert!(serie.is_empty());
+    }
+}
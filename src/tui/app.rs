@@ -0,0 +1,600 @@
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::widgets::TableState;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::{
+    Categoria, CreateCategoria, CreateTransaccion, EstadoRetiro, Retiro, TipoCategoria,
+    TipoTransaccion, Transaccion,
+};
+use crate::repositories::{
+    CategoriaRepository, FinancialSummary, RetiroRepository, TransaccionRepository,
+};
+use crate::{AppError, Result};
+
+/// Pestañas de la interfaz, en el mismo orden que las secciones de la app desktop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    Dashboard,
+    Retiros,
+    Transacciones,
+    Categorias,
+}
+
+impl Tab {
+    pub const ALL: [Tab; 4] = [
+        Tab::Dashboard,
+        Tab::Retiros,
+        Tab::Transacciones,
+        Tab::Categorias,
+    ];
+
+    pub fn titulo(self) -> &'static str {
+        match self {
+            Tab::Dashboard => "Dashboard",
+            Tab::Retiros => "Retiros",
+            Tab::Transacciones => "Transacciones",
+            Tab::Categorias => "Categorías",
+        }
+    }
+
+    fn index(self) -> usize {
+        Tab::ALL.iter().position(|t| *t == self).unwrap_or(0)
+    }
+}
+
+/// Formulario de nueva transacción
+pub struct TransaccionForm {
+    pub campo: usize,
+    pub tipo: TipoTransaccion,
+    /// Índice dentro de las categorías del tipo seleccionado
+    pub categoria: usize,
+    pub monto: String,
+    pub descripcion: String,
+}
+
+impl TransaccionForm {
+    pub const CAMPOS: [&'static str; 4] = ["Tipo", "Categoría", "Monto (€)", "Descripción"];
+}
+
+/// Formulario de creación/edición de categoría
+pub struct CategoriaForm {
+    pub id: Option<Uuid>,
+    pub campo: usize,
+    pub nombre: String,
+    pub tipo: TipoCategoria,
+    pub color: String,
+    /// Se conserva al editar; las subcategorías se gestionan desde el CLI
+    pub padre_id: Option<Uuid>,
+}
+
+impl CategoriaForm {
+    pub const CAMPOS: [&'static str; 3] = ["Nombre", "Tipo", "Color (#RRGGBB)"];
+}
+
+/// Elemento pendiente de confirmar su eliminación
+pub enum Eliminacion {
+    Transaccion(Uuid),
+    Categoria(Uuid),
+}
+
+pub enum Modo {
+    Normal,
+    NuevaTransaccion(TransaccionForm),
+    EditarCategoria(CategoriaForm),
+    Confirmar(Eliminacion, String),
+}
+
+/// Estado de la interfaz de terminal
+pub struct App {
+    retiro_repo: RetiroRepository,
+    categoria_repo: CategoriaRepository,
+    transaccion_repo: TransaccionRepository,
+
+    pub tab: Tab,
+    pub modo: Modo,
+    pub mensaje: Option<(String, bool)>,
+    pub salir: bool,
+
+    pub retiros: Vec<Retiro>,
+    pub retiros_state: TableState,
+    pub categorias: Vec<Categoria>,
+    pub categorias_state: TableState,
+    pub transacciones: Vec<Transaccion>,
+    pub transacciones_state: TableState,
+
+    /// Retiro cuyas transacciones se muestran (por defecto el primer retiro activo)
+    pub retiro_actual: Option<Uuid>,
+    pub resumen_actual: Option<FinancialSummary>,
+    pub resumen_seleccionado: Option<FinancialSummary>,
+    pub balance_global: (f64, f64, i64),
+    pub top_categorias: Vec<(String, String, f64)>,
+}
+
+impl App {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            retiro_repo: RetiroRepository::new(pool.clone()),
+            categoria_repo: CategoriaRepository::new(pool.clone()),
+            transaccion_repo: TransaccionRepository::new(pool),
+            tab: Tab::Dashboard,
+            modo: Modo::Normal,
+            mensaje: None,
+            salir: false,
+            retiros: Vec::new(),
+            retiros_state: TableState::default(),
+            categorias: Vec::new(),
+            categorias_state: TableState::default(),
+            transacciones: Vec::new(),
+            transacciones_state: TableState::default(),
+            retiro_actual: None,
+            resumen_actual: None,
+            resumen_seleccionado: None,
+            balance_global: (0.0, 0.0, 0),
+            top_categorias: Vec::new(),
+        }
+    }
+
+    /// Recargar todos los datos desde los repositorios
+    pub async fn refresh(&mut self) -> Result<()> {
+        self.retiros = self.retiro_repo.get_all().await?;
+        // Las archivadas se cargan para mostrar el histórico, pero no se ofrecen en los formularios
+        self.categorias = self.categoria_repo.get_all_incluyendo_archivadas().await?;
+        self.balance_global = self.transaccion_repo.calculate_global_balance().await?;
+        self.top_categorias = self.transaccion_repo.get_top_categorias_gastos(5).await?;
+
+        // Conservar el retiro actual si sigue existiendo; si no, usar el primero activo
+        let sigue_existiendo = self
+            .retiro_actual
+            .map(|id| self.retiros.iter().any(|r| r.id == id))
+            .unwrap_or(false);
+        if !sigue_existiendo {
+            self.retiro_actual = self
+                .retiros
+                .iter()
+                .find(|r| matches!(r.estado, EstadoRetiro::Activo))
+                .or_else(|| self.retiros.first())
+                .map(|r| r.id);
+        }
+
+        clamp_selection(&mut self.retiros_state, self.retiros.len());
+        clamp_selection(&mut self.categorias_state, self.categorias.len());
+
+        self.refresh_transacciones().await?;
+        self.refresh_resumen_seleccionado().await
+    }
+
+    async fn refresh_transacciones(&mut self) -> Result<()> {
+        match self.retiro_actual {
+            Some(retiro_id) => {
+                self.transacciones = self.transaccion_repo.get_by_retiro(retiro_id).await?;
+                self.resumen_actual = Some(
+                    self.transaccion_repo
+                        .get_financial_summary(retiro_id)
+                        .await?,
+                );
+            }
+            None => {
+                self.transacciones.clear();
+                self.resumen_actual = None;
+            }
+        }
+
+        clamp_selection(&mut self.transacciones_state, self.transacciones.len());
+        Ok(())
+    }
+
+    async fn refresh_resumen_seleccionado(&mut self) -> Result<()> {
+        self.resumen_seleccionado = match self.retiro_seleccionado() {
+            Some(retiro) => {
+                let id = retiro.id;
+                Some(self.transaccion_repo.get_financial_summary(id).await?)
+            }
+            None => None,
+        };
+        Ok(())
+    }
+
+    pub fn retiro_seleccionado(&self) -> Option<&Retiro> {
+        self.retiros_state
+            .selected()
+            .and_then(|i| self.retiros.get(i))
+    }
+
+    pub fn retiro(&self, id: Uuid) -> Option<&Retiro> {
+        self.retiros.iter().find(|r| r.id == id)
+    }
+
+    pub fn categoria(&self, id: Uuid) -> Option<&Categoria> {
+        self.categorias.iter().find(|c| c.id == id)
+    }
+
+    /// Categorías disponibles (no archivadas) para un tipo de transacción
+    pub fn categorias_de(&self, tipo: &TipoTransaccion) -> Vec<&Categoria> {
+        self.categorias
+            .iter()
+            .filter(|c| !c.archivada && c.tipo.to_string() == tipo.to_string())
+            .collect()
+    }
+
+    pub fn tab_index(&self) -> usize {
+        self.tab.index()
+    }
+
+    /// Procesar una tecla; los errores se muestran en la barra de estado
+    pub async fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.salir = true;
+            return;
+        }
+
+        let resultado = match self.modo {
+            Modo::Normal => self.handle_normal(key).await,
+            Modo::NuevaTransaccion(_) => self.handle_transaccion_form(key).await,
+            Modo::EditarCategoria(_) => self.handle_categoria_form(key).await,
+            Modo::Confirmar(..) => self.handle_confirmar(key).await,
+        };
+
+        if let Err(e) = resultado {
+            self.mensaje = Some((e.to_string(), true));
+        }
+    }
+
+    async fn handle_normal(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.salir = true,
+            KeyCode::Char('1') => self.tab = Tab::Dashboard,
+            KeyCode::Char('2') => self.tab = Tab::Retiros,
+            KeyCode::Char('3') => self.tab = Tab::Transacciones,
+            KeyCode::Char('4') => self.tab = Tab::Categorias,
+            KeyCode::Tab => self.tab = Tab::ALL[(self.tab.index() + 1) % Tab::ALL.len()],
+            KeyCode::BackTab => {
+                self.tab = Tab::ALL[(self.tab.index() + Tab::ALL.len() - 1) % Tab::ALL.len()]
+            }
+            KeyCode::Char('r') => {
+                self.refresh().await?;
+                self.mensaje = Some(("Datos actualizados".to_string(), false));
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.mover(1).await?,
+            KeyCode::Up | KeyCode::Char('k') => self.mover(-1).await?,
+            _ => match self.tab {
+                Tab::Dashboard => {}
+                Tab::Retiros => self.handle_retiros(key).await?,
+                Tab::Transacciones => self.handle_transacciones(key).await?,
+                Tab::Categorias => self.handle_categorias(key),
+            },
+        }
+        Ok(())
+    }
+
+    async fn mover(&mut self, delta: isize) -> Result<()> {
+        match self.tab {
+            Tab::Dashboard => {}
+            Tab::Retiros => {
+                move_selection(&mut self.retiros_state, self.retiros.len(), delta);
+                self.refresh_resumen_seleccionado().await?;
+            }
+            Tab::Transacciones => move_selection(
+                &mut self.transacciones_state,
+                self.transacciones.len(),
+                delta,
+            ),
+            Tab::Categorias => {
+                move_selection(&mut self.categorias_state, self.categorias.len(), delta)
+            }
+        }
+        Ok(())
+    }
+
+    async fn handle_retiros(&mut self, key: KeyEvent) -> Result<()> {
+        let (id, nombre, estado) = match self.retiro_seleccionado() {
+            Some(retiro) => (retiro.id, retiro.nombre.clone(), retiro.estado.clone()),
+            None => return Ok(()),
+        };
+
+        match key.code {
+            // Usar el retiro seleccionado en la pestaña de transacciones
+            KeyCode::Enter => {
+                self.retiro_actual = Some(id);
+                self.refresh_transacciones().await?;
+                self.tab = Tab::Transacciones;
+            }
+            // Avanzar el estado: Planificación → Activo → Finalizado → Planificación
+            KeyCode::Char('s') => {
+                let nuevo_estado = match estado {
+                    EstadoRetiro::Planificacion => EstadoRetiro::Activo,
+                    EstadoRetiro::Activo => EstadoRetiro::Finalizado,
+                    EstadoRetiro::Finalizado => EstadoRetiro::Planificacion,
+                };
+                self.retiro_repo
+                    .update_estado(id, nuevo_estado.clone())
+                    .await?
+                    .ok_or_else(|| AppError::NotFound("Retiro".to_string()))?;
+                self.refresh().await?;
+                self.mensaje = Some((format!("{} → {}", nombre, nuevo_estado), false));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn handle_transacciones(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Char('n') => {
+                if self.retiro_actual.is_none() {
+                    return Err(AppError::Validation(
+                        "Primero selecciona un retiro en la pestaña Retiros".to_string(),
+                    ));
+                }
+                self.modo = Modo::NuevaTransaccion(TransaccionForm {
+                    campo: 0,
+                    tipo: TipoTransaccion::Gasto,
+                    categoria: 0,
+                    monto: String::new(),
+                    descripcion: String::new(),
+                });
+            }
+            KeyCode::Char('d') => {
+                let seleccionada = self
+                    .transacciones_state
+                    .selected()
+                    .and_then(|i| self.transacciones.get(i));
+                if let Some(transaccion) = seleccionada {
+                    let descripcion = format!(
+                        "¿Eliminar la transacción '{}' de €{:.2}?",
+                        transaccion.descripcion, transaccion.monto
+                    );
+                    self.modo =
+                        Modo::Confirmar(Eliminacion::Transaccion(transaccion.id), descripcion);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_categorias(&mut self, key: KeyEvent) {
+        let seleccionada = self
+            .categorias_state
+            .selected()
+            .and_then(|i| self.categorias.get(i));
+
+        match key.code {
+            KeyCode::Char('n') => {
+                self.modo = Modo::EditarCategoria(CategoriaForm {
+                    id: None,
+                    campo: 0,
+                    nombre: String::new(),
+                    tipo: TipoCategoria::Gasto,
+                    color: "#".to_string(),
+                    padre_id: None,
+                });
+            }
+            KeyCode::Char('e') | KeyCode::Enter => {
+                if let Some(categoria) = seleccionada {
+                    self.modo = Modo::EditarCategoria(CategoriaForm {
+                        id: Some(categoria.id),
+                        campo: 0,
+                        nombre: categoria.nombre.clone(),
+                        tipo: categoria.tipo.clone(),
+                        color: categoria.color.clone(),
+                        padre_id: categoria.padre_id,
+                    });
+                }
+            }
+            KeyCode::Char('d') => {
+                if let Some(categoria) = seleccionada {
+                    let descripcion = format!("¿Eliminar la categoría '{}'?", categoria.nombre);
+                    self.modo = Modo::Confirmar(Eliminacion::Categoria(categoria.id), descripcion);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    async fn handle_transaccion_form(&mut self, key: KeyEvent) -> Result<()> {
+        let num_categorias = match &self.modo {
+            Modo::NuevaTransaccion(form) => self.categorias_de(&form.tipo).len(),
+            _ => return Ok(()),
+        };
+        let form = match &mut self.modo {
+            Modo::NuevaTransaccion(form) => form,
+            _ => return Ok(()),
+        };
+
+        match key.code {
+            KeyCode::Esc => self.modo = Modo::Normal,
+            KeyCode::Tab | KeyCode::Down => {
+                form.campo = (form.campo + 1) % TransaccionForm::CAMPOS.len()
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                form.campo =
+                    (form.campo + TransaccionForm::CAMPOS.len() - 1) % TransaccionForm::CAMPOS.len()
+            }
+            KeyCode::Left | KeyCode::Right if form.campo == 0 => {
+                form.tipo = match form.tipo {
+                    TipoTransaccion::Ingreso => TipoTransaccion::Gasto,
+                    TipoTransaccion::Gasto => TipoTransaccion::Ingreso,
+                };
+                form.categoria = 0;
+            }
+            KeyCode::Right if form.campo == 1 && num_categorias > 0 => {
+                form.categoria = (form.categoria + 1) % num_categorias
+            }
+            KeyCode::Left if form.campo == 1 && num_categorias > 0 => {
+                form.categoria = (form.categoria + num_categorias - 1) % num_categorias
+            }
+            KeyCode::Backspace => {
+                match form.campo {
+                    2 => form.monto.pop(),
+                    3 => form.descripcion.pop(),
+                    _ => None,
+                };
+            }
+            KeyCode::Char(c) => match form.campo {
+                2 if c.is_ascii_digit() || c == '.' || c == ',' => form.monto.push(c),
+                3 => form.descripcion.push(c),
+                _ => {}
+            },
+            KeyCode::Enter => self.guardar_transaccion().await?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn guardar_transaccion(&mut self) -> Result<()> {
+        let form = match &self.modo {
+            Modo::NuevaTransaccion(form) => form,
+            _ => return Ok(()),
+        };
+
+        let retiro_id = self
+            .retiro_actual
+            .ok_or_else(|| AppError::Validation("No hay retiro seleccionado".to_string()))?;
+        let categoria_id = self
+            .categorias_de(&form.tipo)
+            .get(form.categoria)
+            .map(|c| c.id)
+            .ok_or_else(|| {
+                AppError::Validation(format!("No hay categorías de tipo {}", form.tipo))
+            })?;
+        let monto: f64 = form
+            .monto
+            .replace(',', ".")
+            .parse()
+            .map_err(|_| AppError::Validation("Monto inválido".to_string()))?;
+
+        // La validación (monto mínimo, longitud de descripción) la aplica el repositorio
+        let transaccion = self
+            .transaccion_repo
+            .create(CreateTransaccion {
+                retiro_id,
+                categoria_id,
+                tipo: form.tipo.clone(),
+                monto,
+                descripcion: form.descripcion.trim().to_string(),
+                cuenta_id: None,
+                metodo_pago: None,
+            })
+            .await?;
+
+        self.modo = Modo::Normal;
+        self.refresh().await?;
+        self.mensaje = Some((
+            format!("Transacción creada: €{:.2}", transaccion.monto),
+            false,
+        ));
+        Ok(())
+    }
+
+    async fn handle_categoria_form(&mut self, key: KeyEvent) -> Result<()> {
+        let form = match &mut self.modo {
+            Modo::EditarCategoria(form) => form,
+            _ => return Ok(()),
+        };
+
+        match key.code {
+            KeyCode::Esc => self.modo = Modo::Normal,
+            KeyCode::Tab | KeyCode::Down => {
+                form.campo = (form.campo + 1) % CategoriaForm::CAMPOS.len()
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                form.campo =
+                    (form.campo + CategoriaForm::CAMPOS.len() - 1) % CategoriaForm::CAMPOS.len()
+            }
+            KeyCode::Left | KeyCode::Right if form.campo == 1 => {
+                form.tipo = match form.tipo {
+                    TipoCategoria::Ingreso => TipoCategoria::Gasto,
+                    TipoCategoria::Gasto => TipoCategoria::Ingreso,
+                };
+            }
+            KeyCode::Backspace => {
+                match form.campo {
+                    0 => form.nombre.pop(),
+                    2 => form.color.pop(),
+                    _ => None,
+                };
+            }
+            KeyCode::Char(c) => match form.campo {
+                0 => form.nombre.push(c),
+                2 if c.is_ascii_hexdigit() || c == '#' => form.color.push(c),
+                _ => {}
+            },
+            KeyCode::Enter => self.guardar_categoria().await?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn guardar_categoria(&mut self) -> Result<()> {
+        let form = match &self.modo {
+            Modo::EditarCategoria(form) => form,
+            _ => return Ok(()),
+        };
+
+        let data = CreateCategoria {
+            nombre: form.nombre.trim().to_string(),
+            tipo: form.tipo.clone(),
+            color: form.color.to_uppercase(),
+            padre_id: form.padre_id,
+        };
+
+        let categoria = match form.id {
+            Some(id) => self
+                .categoria_repo
+                .update(id, data)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Categoría".to_string()))?,
+            None => self.categoria_repo.create(data).await?,
+        };
+
+        self.modo = Modo::Normal;
+        self.refresh().await?;
+        self.mensaje = Some((format!("Categoría guardada: {}", categoria.nombre), false));
+        Ok(())
+    }
+
+    async fn handle_confirmar(&mut self, key: KeyEvent) -> Result<()> {
+        let modo = std::mem::replace(&mut self.modo, Modo::Normal);
+        let eliminacion = match modo {
+            Modo::Confirmar(eliminacion, _) => eliminacion,
+            _ => return Ok(()),
+        };
+
+        if !matches!(key.code, KeyCode::Char('s') | KeyCode::Char('y')) {
+            self.mensaje = Some(("Eliminación cancelada".to_string(), false));
+            return Ok(());
+        }
+
+        let eliminado = match eliminacion {
+            Eliminacion::Transaccion(id) => self.transaccion_repo.delete(id).await?,
+            Eliminacion::Categoria(id) => self.categoria_repo.delete(id).await?,
+        };
+
+        self.refresh().await?;
+        self.mensaje = Some(if eliminado {
+            ("Enviado a la papelera".to_string(), false)
+        } else {
+            ("No se pudo eliminar".to_string(), true)
+        });
+        Ok(())
+    }
+}
+
+fn clamp_selection(state: &mut TableState, len: usize) {
+    if len == 0 {
+        state.select(None);
+    } else {
+        let actual = state.selected().unwrap_or(0);
+        state.select(Some(actual.min(len - 1)));
+    }
+}
+
+fn move_selection(state: &mut TableState, len: usize, delta: isize) {
+    if len == 0 {
+        return;
+    }
+    let actual = state.selected().unwrap_or(0) as isize;
+    let nuevo = (actual + delta).rem_euclid(len as isize) as usize;
+    state.select(Some(nuevo));
+}
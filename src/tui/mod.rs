@@ -0,0 +1,45 @@
+pub mod app;
+pub mod ui;
+
+use std::time::Duration;
+
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+
+use crate::database::Database;
+use crate::{AppError, Result};
+
+pub use app::App;
+
+/// Ejecutar la interfaz de terminal a pantalla completa sobre la base de datos indicada
+pub async fn run_tui(database_url: &str) -> Result<()> {
+    let db = Database::new(database_url).await?;
+    let mut app = App::new(db.pool().clone());
+    app.refresh().await?;
+
+    let mut terminal = ratatui::init();
+    let resultado = run_loop(&mut terminal, &mut app).await;
+    ratatui::restore();
+    resultado
+}
+
+async fn run_loop(terminal: &mut ratatui::DefaultTerminal, app: &mut App) -> Result<()> {
+    while !app.salir {
+        terminal
+            .draw(|frame| ui::draw(frame, app))
+            .map_err(terminal_error)?;
+
+        if event::poll(Duration::from_millis(250)).map_err(terminal_error)? {
+            if let Event::Key(key) = event::read().map_err(terminal_error)? {
+                if key.kind == KeyEventKind::Press {
+                    app.mensaje = None;
+                    app.handle_key(key).await;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn terminal_error(e: std::io::Error) -> AppError {
+    AppError::Internal(format!("Terminal error: {}", e))
+}
@@ -0,0 +1,417 @@
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Cell, Clear, Paragraph, Row, Table, Tabs};
+use ratatui::Frame;
+
+use crate::models::{EstadoRetiro, TipoTransaccion};
+use crate::repositories::FinancialSummary;
+
+use super::app::{App, CategoriaForm, Modo, Tab, TransaccionForm};
+
+/// Dibujar la interfaz completa
+pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [cabecera, cuerpo, estado] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let titulos = Tab::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, tab)| format!("{} {}", i + 1, tab.titulo()));
+    frame.render_widget(
+        Tabs::new(titulos)
+            .block(Block::bordered().title(" 🏔️  Gestión de Retiros "))
+            .select(app.tab_index())
+            .highlight_style(
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        cabecera,
+    );
+
+    match app.tab {
+        Tab::Dashboard => draw_dashboard(frame, app, cuerpo),
+        Tab::Retiros => draw_retiros(frame, app, cuerpo),
+        Tab::Transacciones => draw_transacciones(frame, app, cuerpo),
+        Tab::Categorias => draw_categorias(frame, app, cuerpo),
+    }
+
+    draw_barra_estado(frame, app, estado);
+
+    match &app.modo {
+        Modo::Normal => {}
+        Modo::NuevaTransaccion(form) => draw_transaccion_form(frame, app, form),
+        Modo::EditarCategoria(form) => draw_categoria_form(frame, form),
+        Modo::Confirmar(_, pregunta) => draw_confirmacion(frame, pregunta),
+    }
+}
+
+fn draw_dashboard(frame: &mut Frame, app: &App, area: Rect) {
+    let [arriba, abajo] = Layout::vertical([Constraint::Length(9), Constraint::Min(0)]).areas(area);
+    let [activo, global] =
+        Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(arriba);
+
+    // Retiro actual
+    let titulo = match app.retiro_actual.and_then(|id| app.retiro(id)) {
+        Some(retiro) => format!(" 🎯 {} ({}) ", retiro.nombre, retiro.estado),
+        None => " 🎯 Sin retiro seleccionado ".to_string(),
+    };
+    let lineas = match &app.resumen_actual {
+        Some(resumen) => resumen_lines(resumen),
+        None => vec![Line::from("No hay retiros. Crea uno con 'retiro crear'.").dim()],
+    };
+    frame.render_widget(
+        Paragraph::new(lineas).block(Block::bordered().title(titulo)),
+        activo,
+    );
+
+    // Resumen global
+    let (ingresos, gastos, transacciones) = app.balance_global;
+    let activos = app
+        .retiros
+        .iter()
+        .filter(|r| matches!(r.estado, EstadoRetiro::Activo))
+        .count();
+    let lineas = vec![
+        Line::from(format!(
+            "Retiros: {} ({} activos)",
+            app.retiros.len(),
+            activos
+        )),
+        Line::from(format!("Transacciones: {}", transacciones)),
+        Line::from(vec![
+            "Ingresos: ".into(),
+            format!("€{:.2}", ingresos).green(),
+        ]),
+        Line::from(vec!["Gastos:   ".into(), format!("€{:.2}", gastos).red()]),
+        Line::from(vec!["Balance:  ".into(), balance_span(ingresos - gastos)]),
+    ];
+    frame.render_widget(
+        Paragraph::new(lineas).block(Block::bordered().title(" 📊 Global ")),
+        global,
+    );
+
+    // Top categorías de gasto
+    let rows = app.top_categorias.iter().map(|(nombre, color, total)| {
+        Row::new(vec![
+            Cell::from("■").style(Style::default().fg(parse_color(color))),
+            Cell::from(nombre.clone()),
+            Cell::from(format!("€{:.2}", total)),
+        ])
+    });
+    frame.render_widget(
+        Table::new(
+            rows,
+            [
+                Constraint::Length(2),
+                Constraint::Min(20),
+                Constraint::Length(14),
+            ],
+        )
+        .header(Row::new(vec!["", "Categoría", "Total"]).bold())
+        .block(Block::bordered().title(" 💸 Top categorías de gasto ")),
+        abajo,
+    );
+}
+
+fn draw_retiros(frame: &mut Frame, app: &mut App, area: Rect) {
+    let [lista, detalle] =
+        Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(area);
+
+    let actual = app.retiro_actual;
+    let rows: Vec<Row> = app
+        .retiros
+        .iter()
+        .map(|r| {
+            let marca = if Some(r.id) == actual { "▶" } else { " " };
+            Row::new(vec![
+                Cell::from(marca),
+                Cell::from(r.nombre.clone()),
+                Cell::from(r.estado.to_string()).style(estado_style(&r.estado)),
+                Cell::from(r.fecha_inicio.format("%d/%m/%Y").to_string()),
+                Cell::from(r.numero_participantes.to_string()),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(1),
+            Constraint::Min(16),
+            Constraint::Length(13),
+            Constraint::Length(10),
+            Constraint::Length(5),
+        ],
+    )
+    .header(Row::new(vec!["", "Nombre", "Estado", "Inicio", "👥"]).bold())
+    .row_highlight_style(highlight())
+    .block(
+        Block::bordered()
+            .title(" 🏔️  Retiros ")
+            .title_bottom(" Enter: usar · s: cambiar estado "),
+    );
+    frame.render_stateful_widget(table, lista, &mut app.retiros_state);
+
+    let lineas = match (app.retiro_seleccionado(), &app.resumen_seleccionado) {
+        (Some(retiro), Some(resumen)) => {
+            let mut lineas = vec![
+                Line::from(retiro.nombre.clone()).bold(),
+                Line::from(format!(
+                    "{} - {}",
+                    retiro.fecha_inicio.format("%d/%m/%Y"),
+                    retiro.fecha_fin.format("%d/%m/%Y")
+                )),
+                Line::from(retiro.ubicacion.clone().unwrap_or_default()).dim(),
+                Line::from(""),
+            ];
+            lineas.extend(resumen_lines(resumen));
+            lineas
+        }
+        _ => vec![Line::from("Sin retiros").dim()],
+    };
+    frame.render_widget(
+        Paragraph::new(lineas).block(Block::bordered().title(" Detalle ")),
+        detalle,
+    );
+}
+
+fn draw_transacciones(frame: &mut Frame, app: &mut App, area: Rect) {
+    let titulo = match app.retiro_actual.and_then(|id| app.retiro(id)) {
+        Some(retiro) => match &app.resumen_actual {
+            Some(resumen) => format!(" 💰 {} · balance €{:.2} ", retiro.nombre, resumen.balance),
+            None => format!(" 💰 {} ", retiro.nombre),
+        },
+        None => " 💰 Transacciones ".to_string(),
+    };
+
+    let rows: Vec<Row> = app
+        .transacciones
+        .iter()
+        .map(|t| {
+            let (signo, style) = match t.tipo {
+                TipoTransaccion::Ingreso => ("+", Style::default().fg(Color::Green)),
+                TipoTransaccion::Gasto => ("-", Style::default().fg(Color::Red)),
+            };
+            let categoria = app
+                .categoria(t.categoria_id)
+                .map(|c| c.nombre.clone())
+                .unwrap_or_else(|| "?".to_string());
+            Row::new(vec![
+                Cell::from(t.created_at.format("%d/%m/%Y").to_string()),
+                Cell::from(categoria),
+                Cell::from(t.descripcion.clone()),
+                Cell::from(format!("{}€{:.2}", signo, t.monto)).style(style),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(10),
+            Constraint::Length(18),
+            Constraint::Min(20),
+            Constraint::Length(12),
+        ],
+    )
+    .header(Row::new(vec!["Fecha", "Categoría", "Descripción", "Monto"]).bold())
+    .row_highlight_style(highlight())
+    .block(
+        Block::bordered()
+            .title(titulo)
+            .title_bottom(" n: nueva · d: eliminar "),
+    );
+    frame.render_stateful_widget(table, area, &mut app.transacciones_state);
+}
+
+fn draw_categorias(frame: &mut Frame, app: &mut App, area: Rect) {
+    let rows: Vec<Row> = app
+        .categorias
+        .iter()
+        .map(|c| {
+            let fila = Row::new(vec![
+                Cell::from("■").style(Style::default().fg(parse_color(&c.color))),
+                Cell::from(c.nombre.clone()),
+                Cell::from(c.tipo.to_string()),
+                Cell::from(c.color.clone()),
+                Cell::from(if c.archivada { "archivada" } else { "" }),
+            ]);
+            if c.archivada {
+                fila.dim()
+            } else {
+                fila
+            }
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(2),
+            Constraint::Min(20),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(10),
+        ],
+    )
+    .header(Row::new(vec!["", "Nombre", "Tipo", "Color", ""]).bold())
+    .row_highlight_style(highlight())
+    .block(
+        Block::bordered()
+            .title(" 🏷️  Categorías ")
+            .title_bottom(" n: nueva · e: editar · d: eliminar "),
+    );
+    frame.render_stateful_widget(table, area, &mut app.categorias_state);
+}
+
+fn draw_barra_estado(frame: &mut Frame, app: &App, area: Rect) {
+    let linea = match &app.mensaje {
+        Some((mensaje, true)) => Line::from(format!(" ❌ {}", mensaje)).red(),
+        Some((mensaje, false)) => Line::from(format!(" ✅ {}", mensaje)).green(),
+        None => Line::from(" 1-4/Tab: pestañas · ↑↓/jk: mover · r: recargar · q: salir").dim(),
+    };
+    frame.render_widget(Paragraph::new(linea), area);
+}
+
+fn draw_transaccion_form(frame: &mut Frame, app: &App, form: &TransaccionForm) {
+    let categoria = app
+        .categorias_de(&form.tipo)
+        .get(form.categoria)
+        .map(|c| c.nombre.clone())
+        .unwrap_or_else(|| "(sin categorías)".to_string());
+    let valores = [
+        format!("◀ {} ▶", form.tipo),
+        format!("◀ {} ▶", categoria),
+        form.monto.clone(),
+        form.descripcion.clone(),
+    ];
+    draw_form(
+        frame,
+        " Nueva transacción ",
+        &TransaccionForm::CAMPOS,
+        &valores,
+        form.campo,
+    );
+}
+
+fn draw_categoria_form(frame: &mut Frame, form: &CategoriaForm) {
+    let titulo = if form.id.is_some() {
+        " Editar categoría "
+    } else {
+        " Nueva categoría "
+    };
+    let valores = [
+        form.nombre.clone(),
+        format!("◀ {} ▶", form.tipo),
+        form.color.clone(),
+    ];
+    draw_form(frame, titulo, &CategoriaForm::CAMPOS, &valores, form.campo);
+}
+
+fn draw_form(frame: &mut Frame, titulo: &str, campos: &[&str], valores: &[String], activo: usize) {
+    let area = centered(frame.area(), 60, campos.len() as u16 + 4);
+    let mut lineas: Vec<Line> = campos
+        .iter()
+        .zip(valores)
+        .enumerate()
+        .map(|(i, (campo, valor))| {
+            let etiqueta = Span::from(format!("{:<16}", campo));
+            if i == activo {
+                Line::from(vec![etiqueta.bold(), format!("{}▏", valor).yellow()])
+            } else {
+                Line::from(vec![etiqueta, valor.clone().into()])
+            }
+        })
+        .collect();
+    lineas.push(Line::from(""));
+    lineas.push(Line::from("Tab: siguiente · ←→: elegir · Enter: guardar · Esc: cancelar").dim());
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lineas).block(Block::bordered().title(titulo.to_string()).yellow()),
+        area,
+    );
+}
+
+fn draw_confirmacion(frame: &mut Frame, pregunta: &str) {
+    let area = centered(frame.area(), 60, 4);
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(pregunta.to_string()),
+            Line::from("s: confirmar · cualquier otra tecla: cancelar").dim(),
+        ])
+        .block(Block::bordered().title(" ⚠️  Confirmar ").red()),
+        area,
+    );
+}
+
+fn resumen_lines(resumen: &FinancialSummary) -> Vec<Line<'static>> {
+    vec![
+        Line::from(vec![
+            "Ingresos: ".into(),
+            format!("€{:.2}", resumen.total_ingresos).green(),
+            format!(" ({})", resumen.count_ingresos).dim(),
+        ]),
+        Line::from(vec![
+            "Gastos:   ".into(),
+            format!("€{:.2}", resumen.total_gastos).red(),
+            format!(" ({})", resumen.count_gastos).dim(),
+        ]),
+        Line::from(vec!["Balance:  ".into(), balance_span(resumen.balance)]),
+    ]
+}
+
+fn balance_span(balance: f64) -> Span<'static> {
+    let span = Span::from(format!("€{:.2}", balance)).bold();
+    if balance >= 0.0 {
+        span.green()
+    } else {
+        span.red()
+    }
+}
+
+fn estado_style(estado: &EstadoRetiro) -> Style {
+    match estado {
+        EstadoRetiro::Planificacion => Style::default().fg(Color::Yellow),
+        EstadoRetiro::Activo => Style::default().fg(Color::Green),
+        EstadoRetiro::Finalizado => Style::default().fg(Color::Blue),
+    }
+}
+
+fn highlight() -> Style {
+    Style::default()
+        .bg(Color::DarkGray)
+        .add_modifier(Modifier::BOLD)
+}
+
+/// Convertir un color "#RRGGBB" a un color de terminal
+fn parse_color(hex: &str) -> Color {
+    let hex = hex.trim_start_matches('#');
+    if hex.len() != 6 {
+        return Color::Reset;
+    }
+    match (
+        u8::from_str_radix(&hex[0..2], 16),
+        u8::from_str_radix(&hex[2..4], 16),
+        u8::from_str_radix(&hex[4..6], 16),
+    ) {
+        (Ok(r), Ok(g), Ok(b)) => Color::Rgb(r, g, b),
+        _ => Color::Reset,
+    }
+}
+
+fn centered(area: Rect, ancho: u16, alto: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Length(ancho)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(alto)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}